@@ -0,0 +1,15 @@
+// one light's soft falloff in its color, added over the darkness by GlowMaterial's blend
+@group(1) @binding(0)
+var<uniform> color: vec4<f32>;
+@group(1) @binding(1)
+var glow: texture_2d<f32>;
+@group(1) @binding(2)
+var glow_sampler: sampler;
+
+@fragment
+fn fragment(
+    #import bevy_sprite::mesh2d_vertex_output
+) -> @location(0) vec4<f32> {
+    let falloff = textureSample(glow, glow_sampler, uv).a;
+    return vec4<f32>(color.rgb, color.a * falloff);
+}
@@ -0,0 +1,22 @@
+// the game camera's frame drawn over the window, with its color channels pulled apart toward
+// the edges and white mixed in after a big hit
+@group(1) @binding(0)
+var frame: texture_2d<f32>;
+@group(1) @binding(1)
+var frame_sampler: sampler;
+// x is how far red and blue split at the edges of the screen in uv, y is how much white
+@group(1) @binding(2)
+var<uniform> effect: vec4<f32>;
+
+@fragment
+fn fragment(
+    #import bevy_sprite::mesh2d_vertex_output
+) -> @location(0) vec4<f32> {
+    let split = (uv - vec2<f32>(0.5, 0.5)) * effect.x;
+    let color = vec3<f32>(
+        textureSample(frame, frame_sampler, uv + split).r,
+        textureSample(frame, frame_sampler, uv).g,
+        textureSample(frame, frame_sampler, uv - split).b,
+    );
+    return vec4<f32>(mix(color, vec3<f32>(1.0, 1.0, 1.0), effect.y), 1.0);
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::loading::GameAssets;
+use crate::menu::{despawn_screen, screen_root, spawn_button, spawn_title, MenuButton};
+use crate::pickups::ScoreGem;
+use crate::settings::{spawn_slider_row, Settings, SliderLabel};
+use crate::shield::ShieldPickup;
+use crate::{Enemy, GameState, HeldItem, Weapon};
+
+// how much bigger an outline is than what it sits behind
+const OUTLINE_SCALE: f32 = 1.3;
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum PaletteChoice {
+    Standard,
+    // keeps reds and greens apart from anything that matters
+    RedGreen,
+    BlueYellow,
+    HighContrast,
+}
+
+pub const PALETTES: [PaletteChoice; 4] = [
+    PaletteChoice::Standard,
+    PaletteChoice::RedGreen,
+    PaletteChoice::BlueYellow,
+    PaletteChoice::HighContrast,
+];
+
+impl PaletteChoice {
+    pub fn name(self) -> &'static str {
+        match self {
+            PaletteChoice::Standard => "Standard",
+            PaletteChoice::RedGreen => "Red-green safe",
+            PaletteChoice::BlueYellow => "Blue-yellow safe",
+            PaletteChoice::HighContrast => "High contrast",
+        }
+    }
+}
+
+// what charger windups and off screen hit arcs flash in
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum WarningColor {
+    Red,
+    Orange,
+    Magenta,
+    Yellow,
+}
+
+pub const WARNING_COLORS: [WarningColor; 4] = [
+    WarningColor::Red,
+    WarningColor::Orange,
+    WarningColor::Magenta,
+    WarningColor::Yellow,
+];
+
+impl WarningColor {
+    pub fn name(self) -> &'static str {
+        match self {
+            WarningColor::Red => "Red",
+            WarningColor::Orange => "Orange",
+            WarningColor::Magenta => "Magenta",
+            WarningColor::Yellow => "Yellow",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            WarningColor::Red => Color::RED,
+            WarningColor::Orange => Color::rgb(1.0, 0.5, 0.0),
+            WarningColor::Magenta => Color::FUCHSIA,
+            WarningColor::Yellow => Color::YELLOW,
+        }
+    }
+}
+
+// steps through a list of options, wrapping at both ends
+pub fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0) as isize;
+    let len = options.len() as isize;
+    options[(index + step).rem_euclid(len) as usize]
+}
+
+// every color that tells things apart in play, rebuilt whenever the settings change
+pub struct Palette {
+    pub danger: Color,
+    pub stunned: Color,
+    pub gem: Color,
+    pub shield: Color,
+    pub uncommon: Color,
+    pub rare: Color,
+    pub epic: Color,
+    pub outline: Color,
+}
+
+impl Palette {
+    pub fn new(choice: PaletteChoice, warning: WarningColor) -> Self {
+        let danger = warning.color();
+        match choice {
+            PaletteChoice::Standard => Palette {
+                danger,
+                stunned: Color::GRAY,
+                gem: Color::GOLD,
+                shield: Color::rgb(0.3, 0.7, 1.0),
+                uncommon: Color::rgb(0.4, 0.9, 0.4),
+                rare: Color::rgb(0.3, 0.6, 1.0),
+                epic: Color::rgb(0.8, 0.4, 1.0),
+                outline: Color::WHITE,
+            },
+            // blues, oranges and yellows from the okabe-ito set
+            PaletteChoice::RedGreen => Palette {
+                danger,
+                stunned: Color::GRAY,
+                gem: Color::rgb(0.9, 0.6, 0.0),
+                shield: Color::rgb(0.35, 0.7, 0.9),
+                uncommon: Color::rgb(0.35, 0.7, 0.9),
+                rare: Color::rgb(0.95, 0.9, 0.25),
+                epic: Color::rgb(0.8, 0.6, 0.7),
+                outline: Color::WHITE,
+            },
+            PaletteChoice::BlueYellow => Palette {
+                danger,
+                stunned: Color::GRAY,
+                gem: Color::rgb(1.0, 0.55, 0.75),
+                shield: Color::rgb(0.0, 0.8, 0.8),
+                uncommon: Color::rgb(0.4, 0.9, 0.4),
+                rare: Color::rgb(0.0, 0.8, 0.8),
+                epic: Color::rgb(1.0, 0.4, 0.7),
+                outline: Color::WHITE,
+            },
+            PaletteChoice::HighContrast => Palette {
+                danger,
+                stunned: Color::rgb(0.3, 0.3, 0.3),
+                gem: Color::WHITE,
+                shield: Color::CYAN,
+                uncommon: Color::GREEN,
+                rare: Color::CYAN,
+                epic: Color::FUCHSIA,
+                outline: Color::YELLOW,
+            },
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::new(PaletteChoice::Standard, WarningColor::Red)
+    }
+}
+
+// a solid colored copy of an enemy or pickup drawn just behind it
+#[derive(Component)]
+pub struct Outline;
+
+// white-on-transparent copies of sprite textures, tinted to the outline color when drawn
+#[derive(Default)]
+pub struct Silhouettes {
+    images: HashMap<Handle<Image>, Handle<Image>>,
+    atlases: HashMap<Handle<TextureAtlas>, Handle<TextureAtlas>>,
+}
+
+impl Silhouettes {
+    fn image(&mut self, source: &Handle<Image>, images: &mut Assets<Image>) -> Handle<Image> {
+        if let Some(silhouette) = self.images.get(source) {
+            return silhouette.clone();
+        }
+        let mut silhouette = match images.get(source) {
+            Some(image) => image.clone(),
+            None => return source.clone(),
+        };
+        // every texture here is 8 bit rgba, only alpha is kept
+        silhouette.data.chunks_mut(4).for_each(|pixel| {
+            pixel[..3].iter_mut().for_each(|channel| *channel = 255);
+        });
+        let handle = images.add(silhouette);
+        self.images.insert(source.clone(), handle.clone());
+        handle
+    }
+
+    fn atlas(
+        &mut self,
+        source: &Handle<TextureAtlas>,
+        atlases: &mut Assets<TextureAtlas>,
+        images: &mut Assets<Image>,
+    ) -> Handle<TextureAtlas> {
+        if let Some(silhouette) = self.atlases.get(source) {
+            return silhouette.clone();
+        }
+        let mut silhouette = match atlases.get(source) {
+            Some(atlas) => atlas.clone(),
+            None => return source.clone(),
+        };
+        silhouette.texture = self.image(&silhouette.texture, images);
+        let handle = atlases.add(silhouette);
+        self.atlases.insert(source.clone(), handle.clone());
+        handle
+    }
+}
+
+#[derive(Component)]
+pub struct AccessibilityScreen;
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Palette>()
+            .init_resource::<Silhouettes>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Accessibility)
+                    .with_system(create_accessibility_menu),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Accessibility)
+                    .with_system(despawn_screen::<AccessibilityScreen>),
+            )
+            .add_system(apply_palette)
+            .add_system(add_outlines.after(apply_palette))
+            .add_system(follow_animation.after(add_outlines));
+    }
+}
+
+pub fn create_accessibility_menu(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.9)))
+        .insert(AccessibilityScreen)
+        .insert(Name::new("Accessibility Menu"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Accessibility");
+            spawn_slider_row(parent, &font_handle, SliderLabel::Palette);
+            spawn_slider_row(parent, &font_handle, SliderLabel::WarningColor);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Outlines);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Shake);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Flash);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Assist);
+            spawn_button(parent, &font_handle, "Back", MenuButton::Back);
+        });
+}
+
+pub fn apply_palette(settings: Res<Settings>, mut palette: ResMut<Palette>) {
+    if settings.is_changed() {
+        *palette = Palette::new(settings.palette, settings.warning_color);
+    }
+}
+
+// things that matter in a fight get an outline, added as they spawn or all at once when the
+// setting is turned on or the outline color changes
+#[allow(clippy::too_many_arguments)]
+pub fn add_outlines(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    palette: Res<Palette>,
+    mut applied: Local<Option<(bool, Color)>>,
+    mut silhouettes: ResMut<Silhouettes>,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    outlines: Query<Entity, With<Outline>>,
+    animated: Query<(
+        Entity,
+        &Handle<TextureAtlas>,
+        &TextureAtlasSprite,
+        ChangeTrackers<Enemy>,
+    )>,
+    still: Query<
+        (Entity, &Handle<Image>, &Sprite, ChangeTrackers<Sprite>),
+        (
+            Or<(With<ScoreGem>, With<ShieldPickup>, With<Weapon>)>,
+            Without<HeldItem>,
+        ),
+    >,
+) {
+    // the volume sliders and the rest of the settings leave the outlines alone
+    let wanted = (settings.outlines, palette.outline);
+    let refresh = *applied != Some(wanted);
+    *applied = Some(wanted);
+    if refresh {
+        outlines.iter().for_each(|outline| {
+            commands.entity(outline).despawn_recursive();
+        });
+    }
+    if !settings.outlines {
+        return;
+    }
+    animated
+        .iter()
+        .filter(|(_, _, _, tracker)| tracker.is_added() || refresh)
+        .for_each(|(entity, atlas, sprite, _)| {
+            let atlas = silhouettes.atlas(atlas, &mut atlases, &mut images);
+            commands.entity(entity).with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteSheetBundle {
+                        texture_atlas: atlas,
+                        sprite: TextureAtlasSprite {
+                            color: palette.outline,
+                            index: sprite.index,
+                            flip_x: sprite.flip_x,
+                            ..default()
+                        },
+                        transform: Transform {
+                            translation: Vec3::new(0.0, 0.0, -0.1),
+                            scale: Vec3::splat(OUTLINE_SCALE),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(Outline);
+            });
+        });
+    still
+        .iter()
+        .filter(|(_, _, _, tracker)| tracker.is_added() || refresh)
+        .for_each(|(entity, texture, sprite, _)| {
+            let texture = silhouettes.image(texture, &mut images);
+            commands.entity(entity).with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        texture,
+                        sprite: Sprite {
+                            color: palette.outline,
+                            custom_size: sprite.custom_size.map(|size| size * OUTLINE_SCALE),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, -0.1),
+                        ..default()
+                    })
+                    .insert(Outline);
+            });
+        });
+}
+
+// keeps an outline on the same frame as the sprite it's wrapped around
+pub fn follow_animation(
+    sprites: Query<&TextureAtlasSprite, Without<Outline>>,
+    mut outlines: Query<(&Parent, &mut TextureAtlasSprite), With<Outline>>,
+) {
+    outlines.iter_mut().for_each(|(parent, mut outline)| {
+        if let Ok(sprite) = sprites.get(parent.get()) {
+            outline.index = sprite.index;
+            outline.flip_x = sprite.flip_x;
+        }
+    });
+}
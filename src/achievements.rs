@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::RealClock;
+use crate::loading::GameAssets;
+use crate::menu::{despawn_screen, screen_root, spawn_button, spawn_title, MenuButton};
+use crate::modes::RunConfig;
+use crate::save::SaveData;
+use crate::stats::RunStats;
+use crate::{Behavior, GameState, Score};
+
+const TOAST_SECONDS: f32 = 3.0;
+const TOAST_SLIDE: f32 = 0.3;
+const TOAST_WIDTH: f32 = 300.0;
+const LOCKED_COLOR: Color = Color::rgb(0.4, 0.4, 0.4);
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Achievement {
+    JumperHunter,
+    Survivor,
+    HighScorer,
+    Untouchable,
+}
+
+const ALL_ACHIEVEMENTS: [Achievement; 4] = [
+    Achievement::JumperHunter,
+    Achievement::Survivor,
+    Achievement::HighScorer,
+    Achievement::Untouchable,
+];
+
+fn achievement_text(achievement: Achievement) -> (&'static str, &'static str) {
+    match achievement {
+        Achievement::JumperHunter => ("Jumper Hunter", "Kill 100 jumpers"),
+        Achievement::Survivor => ("Survivor", "Survive 5 minutes in one run"),
+        Achievement::HighScorer => ("High Scorer", "Reach 10,000 score"),
+        Achievement::Untouchable => ("Untouchable", "Last 2 minutes without taking a hit"),
+    }
+}
+
+fn is_earned(achievement: Achievement, stats: &RunStats, score: &Score, save: &SaveData) -> bool {
+    match achievement {
+        Achievement::JumperHunter => {
+            save.jumpers_killed as usize + stats.kills.get(Behavior::Jumper) >= 100
+        }
+        Achievement::Survivor => stats.survived >= 300.0,
+        Achievement::HighScorer => score.score >= 10_000,
+        Achievement::Untouchable => stats.survived >= 120.0 && stats.hits_taken == 0,
+    }
+}
+
+pub struct AchievementUnlocked(pub Achievement);
+
+#[derive(Component)]
+pub struct Toast {
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct AchievementsScreen;
+
+pub struct AchievementPlugin;
+
+impl Plugin for AchievementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AchievementUnlocked>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(evaluate_achievements),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Achievements).with_system(create_achievements_page),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Achievements)
+                    .with_system(despawn_screen::<AchievementsScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(bank_run_stats))
+            .add_system(show_achievement_toasts)
+            .add_system(slide_toasts);
+    }
+}
+
+pub fn evaluate_achievements(
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    run_config: Res<RunConfig>,
+    mut save: ResMut<SaveData>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    if !run_config.mode.keeps_scores() {
+        return;
+    }
+    let earned: Vec<Achievement> = ALL_ACHIEVEMENTS
+        .iter()
+        .copied()
+        .filter(|achievement| !save.achievements.contains(achievement))
+        .filter(|achievement| is_earned(*achievement, &stats, &score, &save))
+        .collect();
+    if earned.is_empty() {
+        return;
+    }
+    earned.iter().for_each(|achievement| {
+        save.achievements.push(*achievement);
+        unlocked.send(AchievementUnlocked(*achievement));
+    });
+    save.save();
+}
+
+// lifetime counters only move forward once a run ends in a game over, like the coins do
+pub fn bank_run_stats(
+    stats: Res<RunStats>,
+    run_config: Res<RunConfig>,
+    mut save: ResMut<SaveData>,
+) {
+    if !run_config.mode.keeps_scores() {
+        return;
+    }
+    save.jumpers_killed += stats.kills.get(Behavior::Jumper) as u32;
+    save.save();
+}
+
+pub fn show_achievement_toasts(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut unlocked: EventReader<AchievementUnlocked>,
+    toasts: Query<(), With<Toast>>,
+) {
+    let mut stacked = toasts.iter().count();
+    unlocked
+        .iter()
+        .for_each(|AchievementUnlocked(achievement)| {
+            let (name, description) = achievement_text(*achievement);
+            spawn_toast(&mut commands, &assets.font, name, description, stacked);
+            stacked += 1;
+        });
+}
+
+// anything else worth a corner notice borrows the same slide in card
+pub fn spawn_toast(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    title: &str,
+    body: &str,
+    stacked: usize,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0 + stacked as f32 * 70.0),
+                    right: Val::Px(-TOAST_WIDTH),
+                    ..default()
+                },
+                size: Size::new(Val::Px(TOAST_WIDTH), Val::Px(60.0)),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Toast {
+            timer: Timer::from_seconds(TOAST_SECONDS, false),
+        })
+        .insert(Name::new("Toast"))
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_sections(vec![
+                    TextSection::new(
+                        format!("{}\n", title),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 22.,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    TextSection::new(
+                        body,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                ]),
+                ..default()
+            });
+        });
+}
+
+// slides in from the right edge, sits for a moment, then slides back out
+pub fn slide_toasts(
+    mut commands: Commands,
+    mut toasts: Query<(Entity, &mut Toast, &mut Style)>,
+    clock: Res<RealClock>,
+) {
+    toasts
+        .iter_mut()
+        .for_each(|(entity, mut toast, mut style)| {
+            toast.timer.tick(clock.delta());
+            if toast.timer.finished() {
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+            let elapsed = toast.timer.elapsed_secs();
+            let shown = (elapsed / TOAST_SLIDE)
+                .min((TOAST_SECONDS - elapsed) / TOAST_SLIDE)
+                .clamp(0.0, 1.0);
+            style.position.right = Val::Px(10.0 - (1.0 - shown) * (TOAST_WIDTH + 10.0));
+        });
+}
+
+pub fn create_achievements_page(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    save: Res<SaveData>,
+) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.9)))
+        .insert(AchievementsScreen)
+        .insert(Name::new("Achievements"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Achievements");
+            ALL_ACHIEVEMENTS.iter().for_each(|achievement| {
+                let (name, description) = achievement_text(*achievement);
+                let color = if save.achievements.contains(achievement) {
+                    Color::GOLD
+                } else {
+                    LOCKED_COLOR
+                };
+                parent.spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        format!("{} - {}", name, description),
+                        TextStyle {
+                            font: font_handle.clone(),
+                            font_size: 24.,
+                            color,
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::all(Val::Px(6.0)),
+                        ..default()
+                    },
+                    ..default()
+                });
+            });
+            spawn_button(parent, &font_handle, "Back", MenuButton::Back);
+        });
+}
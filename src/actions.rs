@@ -0,0 +1,268 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+#[cfg(feature = "debug")]
+use crate::console::Console;
+use crate::modes::{GameMode, RunConfig};
+use crate::touch::TouchControls;
+
+// how far the left stick has to lean before it counts as a direction
+const STICK_DEADZONE: f32 = 0.5;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Dash,
+    Fire,
+    Grab,
+    BulletTime,
+    SkipTutorial,
+    Pause,
+}
+
+const ACTIONS: [Action; 9] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Jump,
+    Action::Dash,
+    Action::Fire,
+    Action::Grab,
+    Action::BulletTime,
+    Action::SkipTutorial,
+    Action::Pause,
+];
+
+// whichever was touched last, prompts are drawn for it
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub enum Device {
+    #[default]
+    Keyboard,
+    Gamepad,
+    Touch,
+}
+
+// every binding folded down to what the game cares about, read once at the start of the frame
+#[derive(Default)]
+pub struct Actions {
+    pressed: [bool; ACTIONS.len()],
+    just_pressed: [bool; ACTIONS.len()],
+    // sticks and fingers have no just_pressed of their own, so edges come from last frame
+    virtual_held: [bool; ACTIONS.len()],
+}
+
+impl Actions {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed[action as usize]
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed[action as usize]
+    }
+
+    // held and just pressed, one bit per action, the way a replay stores a frame
+    pub fn bits(&self) -> (u16, u16) {
+        let pack = |flags: &[bool; ACTIONS.len()]| {
+            flags
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (index, on)| bits | (u16::from(*on) << index))
+        };
+        (pack(&self.pressed), pack(&self.just_pressed))
+    }
+
+    pub fn set_bits(&mut self, held: u16, just_pressed: u16) {
+        ACTIONS.iter().for_each(|action| {
+            let index = *action as usize;
+            self.pressed[index] = held & (1 << index) != 0;
+            self.just_pressed[index] = just_pressed & (1 << index) != 0;
+        });
+    }
+}
+
+// a bit of text that shows the glyph for an action, kept in step with the last used device
+#[derive(Component)]
+pub struct ActionPrompt {
+    pub action: Action,
+    pub label: &'static str,
+}
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Actions>()
+            .init_resource::<Device>()
+            .add_system_to_stage(CoreStage::PreUpdate, read_actions.after(InputSystem))
+            .add_system(update_action_prompts);
+    }
+}
+
+// the mouse belongs to the spawning player in versus mode, so firing moves to Return
+fn keyboard_binding(action: Action, versus: bool) -> (&'static [KeyCode], &'static [MouseButton]) {
+    match action {
+        Action::MoveLeft => (&[KeyCode::A], &[]),
+        Action::MoveRight => (&[KeyCode::D], &[]),
+        Action::Jump => (&[KeyCode::Space], &[]),
+        // double tapping a direction dashes too, that's worked out where the player moves
+        Action::Dash => (&[KeyCode::LShift], &[]),
+        Action::Fire if versus => (&[KeyCode::Return], &[]),
+        Action::Fire => (&[], &[MouseButton::Left]),
+        Action::Grab => (&[KeyCode::E], &[]),
+        Action::BulletTime => (&[KeyCode::Q], &[MouseButton::Right]),
+        Action::SkipTutorial => (&[KeyCode::Tab], &[]),
+        Action::Pause => (&[KeyCode::Escape], &[]),
+    }
+}
+
+fn gamepad_binding(action: Action, versus: bool) -> &'static [GamepadButtonType] {
+    match action {
+        Action::MoveLeft => &[GamepadButtonType::DPadLeft],
+        Action::MoveRight => &[GamepadButtonType::DPadRight],
+        Action::Jump => &[GamepadButtonType::South],
+        Action::Dash => &[GamepadButtonType::East],
+        Action::Fire if versus => &[],
+        Action::Fire => &[GamepadButtonType::RightTrigger2],
+        Action::Grab => &[GamepadButtonType::West],
+        Action::BulletTime => &[GamepadButtonType::LeftTrigger2],
+        Action::SkipTutorial => &[GamepadButtonType::Select],
+        Action::Pause => &[GamepadButtonType::Start],
+    }
+}
+
+// the glyph drawn wherever the game tells the player what to press, versus moves firing like
+// the bindings do
+pub fn prompt(action: Action, device: Device, versus: bool) -> &'static str {
+    match device {
+        Device::Keyboard => match action {
+            Action::MoveLeft => "[A]",
+            Action::MoveRight => "[D]",
+            Action::Jump => "[Space]",
+            Action::Dash => "[Shift]",
+            Action::Fire if versus => "[Enter]",
+            Action::Fire => "(LMB)",
+            Action::Grab => "[E]",
+            Action::BulletTime => "[Q]",
+            Action::SkipTutorial => "[Tab]",
+            Action::Pause => "[Esc]",
+        },
+        Device::Gamepad => match action {
+            Action::MoveLeft => "(<)",
+            Action::MoveRight => "(>)",
+            Action::Jump => "(A)",
+            Action::Dash => "(B)",
+            // the pad has no fire button in versus, the keyboard's Return is the only one
+            Action::Fire if versus => "[Enter]",
+            Action::Fire => "(RT)",
+            Action::Grab => "(X)",
+            Action::BulletTime => "(LT)",
+            Action::SkipTutorial => "(Back)",
+            Action::Pause => "(Start)",
+        },
+        Device::Touch => match action {
+            Action::MoveLeft | Action::MoveRight => "(Stick)",
+            Action::Jump => "(Jump)",
+            Action::Dash => "(Dash)",
+            Action::Fire => "(Right thumb)",
+            Action::Grab => "(Grab)",
+            Action::BulletTime => "(Slow)",
+            Action::SkipTutorial => "Tap here",
+            Action::Pause => "(||)",
+        },
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn read_actions(
+    mut actions: ResMut<Actions>,
+    mut device: ResMut<Device>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    pad_buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    run_config: Res<RunConfig>,
+    touch: Res<TouchControls>,
+    #[cfg(feature = "debug")] console: Option<Res<Console>>,
+) {
+    // whatever is typed into the console is meant for it, not the player
+    #[cfg(feature = "debug")]
+    if console.map_or(false, |console| console.is_open()) {
+        actions.pressed = Default::default();
+        actions.just_pressed = Default::default();
+        return;
+    }
+    let versus = run_config.mode == GameMode::Versus;
+    let stick = |axis: GamepadAxisType| {
+        gamepads
+            .iter()
+            .filter_map(|gamepad| axes.get(GamepadAxis::new(*gamepad, axis)))
+            .find(|value| value.abs() > STICK_DEADZONE)
+            .unwrap_or(0.0)
+    };
+    let stick_x = stick(GamepadAxisType::LeftStickX);
+    let mut used_keyboard = false;
+    let mut used_gamepad = false;
+    ACTIONS.iter().for_each(|action| {
+        let index = *action as usize;
+        let (key_codes, mouse_buttons) = keyboard_binding(*action, versus);
+        // browsers turn taps into clicks as well, the thumb area does the firing instead
+        let mouse_buttons = if touch.enabled { &[] } else { mouse_buttons };
+        let key_pressed = keys.any_pressed(key_codes.iter().copied())
+            || buttons.any_pressed(mouse_buttons.iter().copied());
+        let key_just_pressed = keys.any_just_pressed(key_codes.iter().copied())
+            || buttons.any_just_pressed(mouse_buttons.iter().copied());
+        let pad_pressed = gamepads.iter().any(|gamepad| {
+            gamepad_binding(*action, versus)
+                .iter()
+                .any(|button| pad_buttons.pressed(GamepadButton::new(*gamepad, *button)))
+        }) || match action {
+            Action::MoveLeft => stick_x < 0.0,
+            Action::MoveRight => stick_x > 0.0,
+            _ => false,
+        };
+        let virtual_pressed = pad_pressed || touch.pressed(*action);
+        let virtual_just_pressed = virtual_pressed && !actions.virtual_held[index];
+        actions.pressed[index] = key_pressed || virtual_pressed;
+        actions.just_pressed[index] = key_just_pressed || virtual_just_pressed;
+        actions.virtual_held[index] = virtual_pressed;
+        used_keyboard |= key_just_pressed;
+        used_gamepad |= pad_pressed && virtual_just_pressed;
+    });
+    // aiming counts too, otherwise the prompts would lag behind whoever picked up the mouse
+    used_keyboard |= keys.get_just_pressed().next().is_some()
+        || (!touch.enabled && buttons.get_just_pressed().next().is_some());
+    used_gamepad |= pad_buttons.get_just_pressed().next().is_some()
+        || stick(GamepadAxisType::RightStickX) != 0.0
+        || stick(GamepadAxisType::RightStickY) != 0.0;
+    let latest = match (touch.touched, used_keyboard, used_gamepad) {
+        (true, _, _) => Device::Touch,
+        (false, true, false) => Device::Keyboard,
+        (false, false, true) => Device::Gamepad,
+        _ => return,
+    };
+    // only written on a switch so anything drawing prompts can wait for is_changed
+    if *device != latest {
+        *device = latest;
+    }
+}
+
+pub fn update_action_prompts(
+    device: Res<Device>,
+    run_config: Res<RunConfig>,
+    mut prompts: Query<(&mut Text, &ActionPrompt)>,
+    new_prompts: Query<(), Added<ActionPrompt>>,
+) {
+    if !device.is_changed() && new_prompts.is_empty() {
+        return;
+    }
+    let versus = run_config.mode == GameMode::Versus;
+    prompts.iter_mut().for_each(|(mut text, action_prompt)| {
+        text.sections[0].value = format!(
+            "{} {}",
+            prompt(action_prompt.action, *device, versus),
+            action_prompt.label
+        );
+    });
+}
@@ -0,0 +1,128 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use libm::{atan2f, cosf, sinf};
+
+use crate::clock::GameClock;
+use crate::crosshair::AimPoint;
+use crate::modes::{GameMode, RunConfig};
+use crate::schedule::GameplaySet;
+use crate::settings::Settings;
+use crate::touch::TouchControls;
+use crate::{aim_held_item, Enemy, GameState, HeldItem, Player};
+
+const STICK_DEADZONE: f32 = 0.25;
+// higher follows the stick faster, lower smooths out more jitter
+const STICK_SMOOTHING: f32 = 14.0;
+// enemies inside this half angle and range pull the aim
+const ASSIST_CONE: f32 = 0.35;
+const ASSIST_RANGE: f32 = 220.0;
+// share of the gap closed at full strength
+const ASSIST_MAX_PULL: f32 = 0.6;
+// how far out the crosshair sits while the stick is aiming
+const CROSSHAIR_DISTANCE: f32 = 60.0;
+
+// the right stick owns the aim until the mouse moves again
+#[derive(Default)]
+pub struct GamepadAim {
+    pub active: bool,
+    smoothed: Vec2,
+}
+
+pub struct AimPlugin;
+
+impl Plugin for AimPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GamepadAim>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(aim_with_gamepad.label(GameplaySet::Presentation)),
+        );
+    }
+}
+
+pub fn aim_with_gamepad(
+    mut aim: ResMut<GamepadAim>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut cursor_moves: EventReader<CursorMoved>,
+    mut players: Query<&mut Player>,
+    mut held_items: Query<&mut Transform, With<HeldItem>>,
+    enemies: Query<&Transform, (With<Enemy>, Without<HeldItem>)>,
+    settings: Res<Settings>,
+    clock: Res<GameClock>,
+    run_config: Res<RunConfig>,
+    touch: Res<TouchControls>,
+    mut aim_point: ResMut<AimPoint>,
+) {
+    if run_config.mode == GameMode::Versus {
+        return;
+    }
+    // browsers can report taps as cursor moves, those shouldn't hand the aim back to the mouse
+    if cursor_moves.iter().count() > 0 && !touch.enabled {
+        aim.active = false;
+    }
+    // a dragging right thumb steers the same way the stick does, aim assist and all
+    let stick = if touch.aim != Vec2::ZERO {
+        touch.aim
+    } else {
+        gamepads
+            .iter()
+            .next()
+            .copied()
+            .map_or(Vec2::ZERO, |gamepad| {
+                Vec2::new(
+                    axes.get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickX))
+                        .unwrap_or(0.0),
+                    axes.get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickY))
+                        .unwrap_or(0.0),
+                )
+            })
+    };
+    if stick.length() > STICK_DEADZONE {
+        aim.active = true;
+    } else if aim.active {
+        // let go of the stick, keep pointing where it was
+        return;
+    }
+    if !aim.active {
+        return;
+    }
+    let blend = (STICK_SMOOTHING * clock.unscaled_delta().as_secs_f32()).min(1.0);
+    aim.smoothed = aim.smoothed.lerp(stick, blend);
+    if let Some(mut player) = players.iter_mut().next() {
+        let stick_angle = atan2f(aim.smoothed.y, aim.smoothed.x);
+        let origin = player.location.truncate();
+        let pull = enemies
+            .iter()
+            .map(|trans| trans.translation.truncate() - origin)
+            .filter(|offset| offset.length() <= ASSIST_RANGE)
+            .map(|offset| {
+                (
+                    offset.length(),
+                    angle_between(stick_angle, atan2f(offset.y, offset.x)),
+                )
+            })
+            .filter(|(_, gap)| gap.abs() <= ASSIST_CONE)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(0.0, |(_, gap)| gap * settings.aim_assist * ASSIST_MAX_PULL);
+        player.looking_at = stick_angle + pull;
+        aim_point.0 = origin
+            + Vec2::new(cosf(player.looking_at), sinf(player.looking_at)) * CROSSHAIR_DISTANCE;
+        if let Some(mut held_item) = held_items.iter_mut().next() {
+            let angle = player.looking_at;
+            aim_held_item(&mut held_item, &player, angle, 5.0);
+        }
+    }
+}
+
+// signed gap from one angle to another, wrapped into -PI..PI
+fn angle_between(from: f32, to: f32) -> f32 {
+    let gap = (to - from) % (2.0 * PI);
+    if gap > PI {
+        gap - 2.0 * PI
+    } else if gap < -PI {
+        gap + 2.0 * PI
+    } else {
+        gap
+    }
+}
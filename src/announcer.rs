@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::combat::DeathEvent;
+use crate::director::WaveStarted;
+use crate::loading::GameAssets;
+use crate::modes::RunConfig;
+use crate::save::SaveData;
+use crate::settings::Settings;
+use crate::{GameState, ResetGame, Score};
+
+// room left between lines so they never talk over each other
+const LINE_SECONDS: f32 = 1.5;
+// lines waiting past this many are dropped, lowest priority first
+const MAX_QUEUED: usize = 3;
+// kills closer together than this count toward a multi kill
+const MULTI_KILL_SECONDS: f32 = 0.6;
+
+// ordered by priority, a later line jumps the queue ahead of an earlier one
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnnouncerLine {
+    Wave,
+    DoubleKill,
+    TripleKill,
+    NewHighScore,
+}
+
+pub const ALL_LINES: [AnnouncerLine; 4] = [
+    AnnouncerLine::Wave,
+    AnnouncerLine::DoubleKill,
+    AnnouncerLine::TripleKill,
+    AnnouncerLine::NewHighScore,
+];
+
+pub fn line_to_asset(line: AnnouncerLine) -> &'static str {
+    match line {
+        AnnouncerLine::Wave => "sounds/announcer/Wave.ogg",
+        AnnouncerLine::DoubleKill => "sounds/announcer/DoubleKill.ogg",
+        AnnouncerLine::TripleKill => "sounds/announcer/TripleKill.ogg",
+        AnnouncerLine::NewHighScore => "sounds/announcer/NewHighScore.ogg",
+    }
+}
+
+// send one of these to have the announcer say a line when it gets a chance
+pub struct Announce(pub AnnouncerLine);
+
+pub struct Announcer {
+    queue: Vec<AnnouncerLine>,
+    speaking: Timer,
+    streak: u32,
+    streak_timer: Timer,
+    beat_best: bool,
+}
+
+impl Default for Announcer {
+    fn default() -> Self {
+        let mut speaking = Timer::from_seconds(LINE_SECONDS, false);
+        // start finished so the first line plays straight away
+        speaking.tick(speaking.duration());
+        Announcer {
+            queue: Vec::new(),
+            speaking,
+            streak: 0,
+            streak_timer: Timer::from_seconds(MULTI_KILL_SECONDS, false),
+            beat_best: false,
+        }
+    }
+}
+
+impl Announcer {
+    fn push(&mut self, line: AnnouncerLine) {
+        if self.queue.contains(&line) {
+            return;
+        }
+        self.queue.push(line);
+        self.queue.sort();
+        if self.queue.len() > MAX_QUEUED {
+            self.queue.remove(0);
+        }
+    }
+}
+
+pub struct AnnouncerPlugin;
+
+impl Plugin for AnnouncerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Announcer>()
+            .add_event::<Announce>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(announce_waves)
+                    .with_system(announce_multi_kills)
+                    .with_system(announce_high_score)
+                    .with_system(
+                        speak_lines
+                            .after(announce_waves)
+                            .after(announce_multi_kills)
+                            .after(announce_high_score),
+                    ),
+            )
+            .add_system(reset_announcer);
+    }
+}
+
+pub fn announce_waves(mut waves: EventReader<WaveStarted>, mut lines: EventWriter<Announce>) {
+    waves
+        .iter()
+        .for_each(|_| lines.send(Announce(AnnouncerLine::Wave)));
+}
+
+pub fn announce_multi_kills(
+    mut deaths: EventReader<DeathEvent>,
+    mut announcer: ResMut<Announcer>,
+    mut lines: EventWriter<Announce>,
+    clock: Res<GameClock>,
+) {
+    announcer.streak_timer.tick(clock.delta());
+    deaths
+        .iter()
+        .filter(|death| death.enemy().is_some())
+        .for_each(|_| {
+            announcer.streak = if announcer.streak_timer.finished() {
+                1
+            } else {
+                announcer.streak + 1
+            };
+            announcer.streak_timer.reset();
+            match announcer.streak {
+                2 => lines.send(Announce(AnnouncerLine::DoubleKill)),
+                3 => lines.send(Announce(AnnouncerLine::TripleKill)),
+                _default => {}
+            }
+        });
+}
+
+// only once a run, and not on the very first run on a board when there's nothing to beat
+pub fn announce_high_score(
+    score: Res<Score>,
+    save: Res<SaveData>,
+    run_config: Res<RunConfig>,
+    mut announcer: ResMut<Announcer>,
+    mut lines: EventWriter<Announce>,
+) {
+    if announcer.beat_best {
+        return;
+    }
+    let best = save.best_score(&run_config.leaderboard_bucket());
+    if best == 0 || score.score <= best {
+        return;
+    }
+    announcer.beat_best = true;
+    lines.send(Announce(AnnouncerLine::NewHighScore));
+}
+
+pub fn speak_lines(
+    mut announcer: ResMut<Announcer>,
+    mut lines: EventReader<Announce>,
+    assets: Res<GameAssets>,
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    clock: Res<GameClock>,
+) {
+    lines.iter().for_each(|line| announcer.push(line.0));
+    // turned off in settings, lines are heard and then forgotten
+    if !settings.announcer {
+        announcer.queue.clear();
+        return;
+    }
+    announcer.speaking.tick(clock.delta());
+    if !announcer.speaking.finished() {
+        return;
+    }
+    if let Some(line) = announcer.queue.pop() {
+        audio.play_with_settings(
+            assets.announcer_line(line),
+            PlaybackSettings {
+                repeat: false,
+                volume: settings.sfx,
+                speed: 1.0,
+            },
+        );
+        announcer.speaking.reset();
+    }
+}
+
+pub fn reset_announcer(mut announcer: ResMut<Announcer>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *announcer = Announcer::default();
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::modes::RunConfig;
+use crate::settings::Settings;
+use crate::spawning::SpawnRules;
+use crate::ResetGame;
+
+// spare lives an assisted run starts with, spent before the run would end
+const ASSIST_LIVES: u8 = 2;
+// half the usual number of enemies on screen at once
+const ASSIST_SOFT_CAP: usize = 20;
+// how fast difficulty climbs compared to a regular run
+pub const ASSIST_RAMP: f32 = 0.6;
+
+#[derive(Default)]
+pub struct Assist {
+    lives: u8,
+}
+
+impl Assist {
+    // true when a spare life was there to spend
+    pub fn spend_life(&mut self) -> bool {
+        if self.lives == 0 {
+            return false;
+        }
+        self.lives -= 1;
+        true
+    }
+}
+
+pub struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Assist>().add_system(lock_in_assist);
+    }
+}
+
+// assist is decided as a run starts so flipping the setting mid run can't help a scored run
+pub fn lock_in_assist(
+    mut resets: EventReader<ResetGame>,
+    settings: Res<Settings>,
+    mut run_config: ResMut<RunConfig>,
+    mut rules: ResMut<SpawnRules>,
+    mut assist: ResMut<Assist>,
+) {
+    if resets.iter().count() == 0 && !run_config.is_added() {
+        return;
+    }
+    run_config.assisted = settings.assist;
+    rules.soft_cap = if settings.assist {
+        ASSIST_SOFT_CAP
+    } else {
+        SpawnRules::default().soft_cap
+    };
+    assist.lives = if settings.assist { ASSIST_LIVES } else { 0 };
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::clock::GameClock;
+use crate::depth::RenderLayer;
+use crate::{GameState, Layers, RunEntity, SpawnWeapon};
+
+const BEACON_COLOR: Color = Color::rgba(1.0, 1.0, 0.6, 0.35);
+// where the floor is if the ray somehow misses it
+const FALLBACK_GROUND: f32 = -106.0;
+
+// light pillar and landing mark for a weapon drop, follows whatever is currently standing in for
+// the drop (the warning, then the weapon) and fades out once that's gone
+#[derive(Component)]
+pub struct Beacon {
+    pub source: Entity,
+    fade: Option<Timer>,
+}
+
+pub struct BeaconPlugin;
+
+impl Plugin for BeaconPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing).with_system(place_weapon_beacons),
+        )
+        // after update so a weapon spawned this frame already exists when its beacon looks for it
+        .add_system_to_stage(CoreStage::PostUpdate, fade_weapon_beacons);
+    }
+}
+
+pub fn place_weapon_beacons(
+    mut commands: Commands,
+    physics_world: PhysicsWorld,
+    warnings: Query<(Entity, &SpawnWeapon), Added<SpawnWeapon>>,
+) {
+    warnings.iter().for_each(|(warning, spawn)| {
+        let start = spawn.position;
+        let ground = physics_world
+            .ray_cast_with_filter(
+                start,
+                Vec3::new(0.0, -500.0, 0.0),
+                true,
+                CollisionLayers::new(Layers::Player, Layers::World),
+                |_| true,
+            )
+            .map_or(FALLBACK_GROUND, |hit| hit.collision_point.y);
+        let height = (start.y - ground).max(0.0);
+        commands
+            .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+                start.x,
+                ground,
+                // behind the floor, so the beam rises out of the ground
+                RenderLayer::Background.z() - 0.5,
+            )))
+            .insert(Beacon {
+                source: warning,
+                fade: None,
+            })
+            .insert(Name::new("Weapon Beacon"))
+            .insert(RunEntity)
+            .with_children(|parent| {
+                parent.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: BEACON_COLOR,
+                        custom_size: Some(Vec2::new(4.0, height)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, height / 2.0, 0.0),
+                    ..default()
+                });
+                parent.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: BEACON_COLOR,
+                        custom_size: Some(Vec2::new(16.0, 2.0)),
+                        ..default()
+                    },
+                    ..default()
+                });
+            });
+    });
+}
+
+pub fn fade_weapon_beacons(
+    mut commands: Commands,
+    mut beacons: Query<(Entity, &mut Beacon, &Children)>,
+    mut sprites: Query<&mut Sprite>,
+    existing: Query<()>,
+    clock: Res<GameClock>,
+) {
+    beacons
+        .iter_mut()
+        .for_each(|(entity, mut beacon, children)| {
+            if beacon.fade.is_none() && existing.get(beacon.source).is_err() {
+                beacon.fade = Some(Timer::from_seconds(0.3, false));
+            }
+            if let Some(fade) = &mut beacon.fade {
+                fade.tick(clock.delta());
+                if fade.finished() {
+                    commands.entity(entity).despawn_recursive();
+                    return;
+                }
+                let alpha = BEACON_COLOR.a() * (1.0 - fade.percent());
+                children.iter().for_each(|child| {
+                    if let Ok(mut sprite) = sprites.get_mut(*child) {
+                        sprite.color.set_a(alpha);
+                    }
+                });
+            }
+        });
+}
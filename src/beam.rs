@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use heron::rapier_plugin::PhysicsWorld;
+use libm::{atan2f, cosf, sinf};
+
+use crate::actions::Actions;
+use crate::clock::GameClock;
+use crate::collision::collision_layers;
+use crate::combat::{airborne, Combatant, DamageEvent, DeathEvent};
+use crate::depth::RenderLayer;
+use crate::schedule::GameplaySet;
+use crate::weapons::weapon_stats;
+use crate::{trigger_input, Enemy, GameState, HeldItem, Layers, Player, Weapon, Weapons};
+
+const BEAM_RANGE: f32 = 400.0;
+const BEAM_WIDTH: f32 = 2.0;
+// seconds of beam from cold before it cuts out, and how long a full bar takes to bleed off
+const HEAT_SECONDS: f32 = 2.0;
+const COOL_SECONDS: f32 = 3.0;
+const BEAM_COLOR: Color = Color::rgba(1.0, 0.35, 0.45, 0.9);
+const HOT_COLOR: Color = Color::rgb(1.0, 0.25, 0.2);
+
+// heat on a held laser and the damage soaking into whatever it's resting on
+#[derive(Component, Default)]
+pub struct BeamEmitter {
+    heat: f32,
+    // locked out until it's cooled all the way down
+    overheated: bool,
+    end: Option<Vec3>,
+    target: Option<Entity>,
+    scorch: f32,
+}
+
+#[derive(Component)]
+pub struct BeamSprite;
+
+pub struct BeamPlugin;
+
+impl Plugin for BeamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(create_beam_sprite).add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(arm_beam_weapons)
+                .with_system(fire_beam.label(GameplaySet::Simulation))
+                .with_system(draw_beam.label(GameplaySet::Presentation)),
+        );
+    }
+}
+
+pub fn create_beam_sprite(mut commands: Commands) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: BEAM_COLOR,
+                custom_size: Some(Vec2::ONE),
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(BeamSprite)
+        .insert(Name::new("Beam"));
+}
+
+pub fn arm_beam_weapons(
+    mut commands: Commands,
+    held_items: Query<(Entity, &Weapon), Added<HeldItem>>,
+) {
+    held_items
+        .iter()
+        .filter(|(_, weapon)| matches!(weapon.asset, Weapons::Laser))
+        .for_each(|(entity, _)| {
+            commands.entity(entity).insert(BeamEmitter::default());
+        });
+}
+
+pub fn fire_beam(
+    mut commands: Commands,
+    mut emitters: Query<(&Transform, &Weapon, &mut BeamEmitter), With<HeldItem>>,
+    mut enemies: Query<(&Transform, &mut Enemy, Option<&Velocity>), Without<HeldItem>>,
+    players: Query<&Player>,
+    physics: PhysicsWorld,
+    actions: Res<Actions>,
+    clock: Res<GameClock>,
+    mut damage: EventWriter<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+) {
+    let looking_at = match players.iter().next() {
+        Some(player) => player.looking_at,
+        None => return,
+    };
+    let (_, held) = trigger_input(&actions);
+    let delta = clock.delta_seconds();
+    emitters
+        .iter_mut()
+        .for_each(|(trans, weapon, mut emitter)| {
+            if !held || emitter.overheated {
+                emitter.heat = (emitter.heat - delta / COOL_SECONDS).max(0.0);
+                if emitter.heat == 0.0 {
+                    emitter.overheated = false;
+                }
+                emitter.end = None;
+                emitter.target = None;
+                emitter.scorch = 0.0;
+                return;
+            }
+            emitter.heat += delta / HEAT_SECONDS;
+            if emitter.heat >= 1.0 {
+                emitter.heat = 1.0;
+                emitter.overheated = true;
+            }
+            // stops on the first wall or enemy along the aim
+            let start = trans.translation;
+            let ray = Vec3::new(cosf(looking_at), sinf(looking_at), 0.0) * BEAM_RANGE;
+            let hit = physics.ray_cast_with_filter(
+                start,
+                ray,
+                true,
+                collision_layers(Layers::Projectiles),
+                |_| true,
+            );
+            emitter.end = Some(hit.map_or(start + ray, |hit| hit.collision_point));
+            let target = hit
+                .map(|hit| hit.entity)
+                .filter(|entity| enemies.get(*entity).is_ok());
+            if target != emitter.target {
+                emitter.target = target;
+                emitter.scorch = 0.0;
+            }
+            let target = match target {
+                Some(target) => target,
+                None => return,
+            };
+            // the damage stat is per second, it lands a point at a time
+            emitter.scorch += weapon_stats(weapon.asset).damage as f32 * delta;
+            if emitter.scorch < 1.0 {
+                return;
+            }
+            emitter.scorch -= 1.0;
+            if let Ok((enemy_trans, mut enemy, velocity)) = enemies.get_mut(target) {
+                let victim = Combatant::Enemy(enemy.asset);
+                enemy.health -= 1;
+                damage.send(DamageEvent {
+                    target,
+                    victim,
+                    amount: 1,
+                    position: enemy_trans.translation,
+                    absorbed: false,
+                    source: None,
+                    by_player: true,
+                });
+                if enemy.health <= 0 {
+                    deaths.send(DeathEvent {
+                        entity: target,
+                        victim,
+                        position: enemy_trans.translation,
+                        weapon: Some(weapon.asset),
+                        airborne: airborne(velocity),
+                        thrown: false,
+                    });
+                    commands.entity(target).despawn_recursive();
+                    emitter.target = None;
+                }
+            }
+        });
+}
+
+// the gun glows hotter as it heats up
+pub fn draw_beam(
+    mut emitters: Query<(&Transform, &BeamEmitter, &mut Sprite), With<HeldItem>>,
+    mut beams: Query<(&mut Transform, &mut Visibility), (With<BeamSprite>, Without<HeldItem>)>,
+) {
+    let mut segment = None;
+    emitters
+        .iter_mut()
+        .for_each(|(trans, emitter, mut sprite)| {
+            let glow = Vec4::from(Color::WHITE).lerp(Vec4::from(HOT_COLOR), emitter.heat);
+            sprite.color = Color::from(glow);
+            segment = emitter.end.map(|end| (trans.translation, end));
+        });
+    beams
+        .iter_mut()
+        .for_each(|(mut trans, mut visibility)| match segment {
+            Some((start, end)) => {
+                let span = (end - start).truncate();
+                visibility.is_visible = true;
+                trans.translation = ((start + end) / 2.0)
+                    .truncate()
+                    .extend(RenderLayer::Projectiles.z());
+                trans.rotation = Quat::from_rotation_z(atan2f(span.y, span.x));
+                trans.scale = Vec3::new(span.length(), BEAM_WIDTH, 1.0);
+            }
+            None => visibility.is_visible = false,
+        });
+}
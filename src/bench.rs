@@ -0,0 +1,190 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::collision::CollisionRules;
+use crate::loading::AtlasRegistry;
+use crate::modes::{GameRng, RunConfig};
+use crate::particles::{spawn_burst, Particle};
+use crate::patterns::{fire_pattern, ProjectileKind, ShotPattern};
+use crate::transition::Transition;
+use crate::{
+    spawn_enemy_warning, Enemy, EnemyBullet, GameState, GodMode, Player, ResetGame, SpawnEnemy,
+    ALL_BEHAVIORS,
+};
+
+// same scene on every machine and every build, so two reports can be compared
+const BENCH_SEED: u64 = 0xBE4C_0000_2022;
+const ENEMY_TARGET: usize = 200;
+const PROJECTILE_TARGET: usize = 500;
+const PARTICLE_TARGET: usize = 1500;
+// frames before the scene has filled up aren't counted
+const WARMUP_SECONDS: f32 = 3.0;
+const DEFAULT_SECONDS: f32 = 30.0;
+// spread the top ups over a few frames instead of one huge spike
+const MAX_SPAWNS_PER_FRAME: usize = 50;
+const BENCH_SHOT: ShotPattern = ShotPattern {
+    kind: ProjectileKind::Bullet,
+    count: 1,
+    spread: 0.0,
+    speed: 40.0,
+};
+
+// --bench fills the arena and keeps it full, timing frames until it quits on its own.
+// --bench-seconds <n> changes how long it measures for
+pub struct Bench {
+    seconds: f32,
+    elapsed: f32,
+    rng: GameRng,
+    frame_times: Vec<f32>,
+}
+
+impl Bench {
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        if !args.iter().any(|arg| arg == "--bench") {
+            return None;
+        }
+        let seconds = args
+            .iter()
+            .position(|arg| arg == "--bench-seconds")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|seconds| seconds.parse::<f32>().ok())
+            .unwrap_or(DEFAULT_SECONDS);
+        Some(Bench {
+            seconds,
+            elapsed: 0.0,
+            rng: GameRng(StdRng::seed_from_u64(BENCH_SEED)),
+            frame_times: Vec::new(),
+        })
+    }
+}
+
+pub struct BenchPlugin;
+
+impl Plugin for BenchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(fix_bench_seed)
+            // the bench runs as whoever played last
+            .add_system_set(SystemSet::on_enter(GameState::Profiles).with_system(start_bench))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(protect_bench_player)
+                    .with_system(fill_bench_scene)
+                    .with_system(time_bench_frames),
+            );
+    }
+}
+
+// the run's own rng drives spawns and drops, pinning it keeps the scene the same every time
+pub fn fix_bench_seed(mut run_config: ResMut<RunConfig>) {
+    run_config.seed = BENCH_SEED;
+    run_config.fixed_seed = true;
+}
+
+// straight past the menu and into a run
+pub fn start_bench(mut resets: EventWriter<ResetGame>, mut transition: ResMut<Transition>) {
+    resets.send(ResetGame);
+    transition.fade_to(GameState::Playing);
+}
+
+// a dead player would end the bench early
+pub fn protect_bench_player(
+    mut commands: Commands,
+    players: Query<Entity, (With<Player>, Without<GodMode>)>,
+) {
+    players.iter().for_each(|player| {
+        commands.entity(player).insert(GodMode);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn fill_bench_scene(
+    mut commands: Commands,
+    mut bench: ResMut<Bench>,
+    atlases: Res<AtlasRegistry>,
+    rules: Res<CollisionRules>,
+    players: Query<&Player>,
+    enemies: Query<(), Or<(With<Enemy>, With<SpawnEnemy>)>>,
+    projectiles: Query<(), With<EnemyBullet>>,
+    particles: Query<(), With<Particle>>,
+) {
+    let target = players
+        .iter()
+        .next()
+        .map_or(Vec3::ZERO, |player| player.location);
+    let enemies = enemies.iter().count();
+    (enemies..ENEMY_TARGET.min(enemies + MAX_SPAWNS_PER_FRAME)).for_each(|i| {
+        let x = bench.rng.gen_range(-200.0..200.0);
+        spawn_enemy_warning(
+            &mut commands,
+            &atlases,
+            ALL_BEHAVIORS[i % ALL_BEHAVIORS.len()],
+            x,
+        );
+    });
+    let projectiles = projectiles.iter().count();
+    (projectiles..PROJECTILE_TARGET.min(projectiles + MAX_SPAWNS_PER_FRAME)).for_each(|_| {
+        let origin = Vec3::new(
+            bench.rng.gen_range(-200.0..200.0),
+            bench.rng.gen_range(-80.0..120.0),
+            0.0,
+        );
+        fire_pattern(&mut commands, &atlases, &rules, origin, target, BENCH_SHOT);
+    });
+    let particles = particles.iter().count();
+    if particles < PARTICLE_TARGET {
+        let position = Vec3::new(
+            bench.rng.gen_range(-200.0..200.0),
+            bench.rng.gen_range(-80.0..120.0),
+            1.0,
+        );
+        spawn_burst(
+            &mut commands,
+            &mut bench.rng,
+            position,
+            Color::ORANGE_RED,
+            MAX_SPAWNS_PER_FRAME,
+            60.0,
+        );
+    }
+}
+
+// real frame time, bullet time and pauses don't make a frame any cheaper
+pub fn time_bench_frames(
+    mut bench: ResMut<Bench>,
+    time: Res<Time>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let delta = time.delta_seconds();
+    bench.elapsed += delta;
+    if bench.elapsed < WARMUP_SECONDS {
+        return;
+    }
+    bench.frame_times.push(delta * 1000.0);
+    if bench.elapsed < WARMUP_SECONDS + bench.seconds {
+        return;
+    }
+    report(&mut bench.frame_times);
+    exit.send(AppExit);
+}
+
+fn report(frame_times: &mut [f32]) {
+    if frame_times.is_empty() {
+        return;
+    }
+    frame_times.sort_by(|a, b| a.total_cmp(b));
+    let count = frame_times.len();
+    let average = frame_times.iter().sum::<f32>() / count as f32;
+    let percentile = |p: f32| frame_times[((count - 1) as f32 * p).round() as usize];
+    println!("bench: {} frames", count);
+    println!(
+        "bench: average {:.2} ms ({:.0} fps)",
+        average,
+        1000.0 / average
+    );
+    println!("bench: 95th percentile {:.2} ms", percentile(0.95));
+    println!("bench: 99th percentile {:.2} ms", percentile(0.99));
+    println!("bench: worst {:.2} ms", frame_times[count - 1]);
+}
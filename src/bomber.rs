@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use crate::explosions::ExplosionEvent;
+use crate::loading::GameAssets;
+use crate::schedule::{FixedGameplayStage, FixedStep};
+use crate::settings::Settings;
+use crate::{GameState, GravityData, Player};
+
+const CHASE_SPEED: f32 = 45.0;
+// the fuse lights once the player is this close
+const FUSE_RANGE: f32 = 40.0;
+const FUSE_SECONDS: f32 = 1.2;
+const BLAST_RADIUS: f32 = 50.0;
+const FUSE_COLOR: Color = Color::rgb(1.0, 0.2, 0.1);
+
+// a short run at the player, bombers chain these back to back
+#[derive(Component)]
+pub struct Chase {
+    timer: Timer,
+}
+
+impl Default for Chase {
+    fn default() -> Self {
+        Chase {
+            timer: Timer::from_seconds(1.0, false),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BomberFuse {
+    timer: Timer,
+    lit: bool,
+}
+
+pub struct BomberPlugin;
+
+impl Plugin for BomberPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set_to_stage(
+            FixedGameplayStage,
+            SystemSet::on_update(GameState::Playing)
+                .with_system(handle_chases)
+                .with_system(burn_bomber_fuses),
+        );
+    }
+}
+
+pub fn handle_chases(
+    mut commands: Commands,
+    mut chasers: Query<(Entity, &mut Transform, &mut Chase)>,
+    players: Query<&Player>,
+    step: Res<FixedStep>,
+    gravity: Res<GravityData>,
+) {
+    let player = match players.iter().next() {
+        Some(player) => player.location,
+        None => return,
+    };
+    chasers
+        .iter_mut()
+        .for_each(|(entity, mut trans, mut chase)| {
+            chase.timer.tick(step.delta());
+            if trans.translation.truncate().distance(player.truncate()) < FUSE_RANGE {
+                commands
+                    .entity(entity)
+                    .remove::<Chase>()
+                    .insert(BomberFuse {
+                        timer: Timer::from_seconds(FUSE_SECONDS, false),
+                        lit: false,
+                    });
+                return;
+            }
+            if chase.timer.finished() {
+                commands.entity(entity).remove::<Chase>();
+                return;
+            }
+            let toward = (player.x - trans.translation.x).signum();
+            trans.translation.x +=
+                toward * CHASE_SPEED * gravity.enemy_speed * step.delta_seconds();
+        });
+}
+
+// blinks and beeps faster and faster, then goes off
+pub fn burn_bomber_fuses(
+    mut commands: Commands,
+    mut bombers: Query<(Entity, &Transform, &mut BomberFuse, &mut TextureAtlasSprite)>,
+    step: Res<FixedStep>,
+    mut explosions: EventWriter<ExplosionEvent>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+) {
+    bombers
+        .iter_mut()
+        .for_each(|(entity, trans, mut fuse, mut sprite)| {
+            fuse.timer.tick(step.delta());
+            if fuse.timer.finished() {
+                explosions.send(ExplosionEvent {
+                    position: trans.translation,
+                    radius: BLAST_RADIUS,
+                    hurts_player: true,
+                    weapon: None,
+                });
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+            let burnt = fuse.timer.percent();
+            let lit = (burnt * burnt * 8.0).fract() < 0.5;
+            if lit && !fuse.lit {
+                audio.play_with_settings(
+                    assets.beep.clone(),
+                    PlaybackSettings {
+                        repeat: false,
+                        volume: settings.sfx,
+                        speed: 1.0,
+                    },
+                );
+            }
+            fuse.lit = lit;
+            sprite.color = if lit { FUSE_COLOR } else { Color::WHITE };
+        });
+}
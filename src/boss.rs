@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::combat::{Combatant, DamageEvent, DeathEvent};
+use crate::director::WaveStarted;
+use crate::explosions::ExplosionEvent;
+use crate::impacts::Projectile;
+use crate::loading::{AtlasRegistry, GameAssets};
+use crate::modes::{GameMode, RunConfig};
+use crate::prestige::BossDefeated;
+use crate::schedule::GameplaySet;
+use crate::sequence::{Sequence, Sequencer};
+use crate::tween::{Ease, Tween, TweenTarget};
+use crate::weapons::weapon_stats;
+use crate::{spawn_enemy_warning, Behavior, Bullet, Enemy, GameState, Player};
+
+// every this many waves a boss is called in instead of waiting for the next one to clear
+const BOSS_EVERY: u32 = 5;
+const BOSS_HEALTH: i8 = 15;
+const BOSS_SCALE: f32 = 2.0;
+// a blast nobody's weapon set off takes a bigger bite than a single bullet
+const BLAST_DAMAGE: i8 = 3;
+// half the size of a regular enemy's collider
+const ENEMY_HALF_SIZE: f32 = 7.5;
+
+// an oversized charger, regular spawns hold off for as long as one is alive or on its way
+#[derive(Component)]
+pub struct Boss;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(call_in_boss)
+                .with_system(start_boss_intro)
+                .with_system(wound_boss.after(GameplaySet::CollisionResponse)),
+        );
+    }
+}
+
+pub fn call_in_boss(
+    mut commands: Commands,
+    mut waves: EventReader<WaveStarted>,
+    bosses: Query<(), With<Boss>>,
+    players: Query<&Player>,
+    atlases: Res<AtlasRegistry>,
+    run_config: Res<RunConfig>,
+) {
+    let due = waves.iter().any(|WaveStarted(wave)| wave % BOSS_EVERY == 0);
+    if !due || !bosses.is_empty() || run_config.mode == GameMode::Versus {
+        return;
+    }
+    // comes in on the far side from the player
+    let x = match players.iter().next() {
+        Some(player) if player.location.x > 0.0 => -180.0,
+        _default => 180.0,
+    };
+    let warning = spawn_enemy_warning(&mut commands, &atlases, Behavior::Charger, x);
+    commands.entity(warning).insert(Boss);
+}
+
+pub fn start_boss_intro(
+    mut commands: Commands,
+    mut bosses: Query<(Entity, &mut Transform, &mut Enemy), Added<Boss>>,
+    mut sequencer: ResMut<Sequencer>,
+    sequences: Res<Assets<Sequence>>,
+    assets: Res<GameAssets>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let (entity, mut trans, mut enemy) = match bosses.iter_mut().next() {
+        Some(boss) => boss,
+        None => return,
+    };
+    enemy.health = BOSS_HEALTH;
+    // grows into its full size during the intro, lifted so the bigger body clears the floor
+    trans.translation.y += ENEMY_HALF_SIZE * (BOSS_SCALE - 1.0);
+    commands
+        .entity(entity)
+        .insert(CollisionShape::Cuboid {
+            border_radius: None,
+            half_extends: Vec3::new(ENEMY_HALF_SIZE, ENEMY_HALF_SIZE, 0.0) * BOSS_SCALE,
+        })
+        .insert(
+            Tween::new(1.2, Ease::OutCubic)
+                .with(TweenTarget::Scale(Vec3::ONE, Vec3::splat(BOSS_SCALE)))
+                .real_time(),
+        );
+    let focus = trans.translation.truncate();
+    sequencer.play(&sequences, &assets.boss_intro, focus, &mut state);
+}
+
+// bullets and blasts only chip away at a boss, regular enemies go down to the first one. each
+// takes off its weapon's damage
+pub fn wound_boss(
+    mut commands: Commands,
+    mut bosses: Query<(Entity, &Transform, &mut Enemy), With<Boss>>,
+    bullets: Query<(Entity, &Collisions, &Transform, Option<&Projectile>), With<Bullet>>,
+    mut explosions: EventReader<ExplosionEvent>,
+    mut damage: EventWriter<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut defeated: EventWriter<BossDefeated>,
+) {
+    let blasts: Vec<&ExplosionEvent> = explosions.iter().collect();
+    bosses.iter_mut().for_each(|(entity, trans, mut enemy)| {
+        let victim = Combatant::Enemy(enemy.asset);
+        let mut hits: Vec<(i8, Vec3, bool)> = bullets
+            .iter()
+            .filter(|(_, collisions, ..)| collisions.entities().any(|other| other == entity))
+            .map(|(bullet, _, bullet_trans, projectile)| {
+                // gone on contact, or it would hit again every frame it overlaps
+                commands.entity(bullet).despawn_recursive();
+                (
+                    projectile.map_or(1, Projectile::damage),
+                    bullet_trans.translation,
+                    projectile.is_some(),
+                )
+            })
+            .collect();
+        hits.extend(
+            blasts
+                .iter()
+                .filter(|blast| blast.reaches(trans.translation))
+                .map(|blast| {
+                    let amount = blast
+                        .weapon
+                        .map_or(BLAST_DAMAGE, |weapon| weapon_stats(weapon).damage);
+                    (amount, blast.position, blast.weapon.is_some())
+                }),
+        );
+        if hits.is_empty() {
+            return;
+        }
+        hits.iter().for_each(|(amount, source, by_player)| {
+            enemy.health -= amount;
+            damage.send(DamageEvent {
+                target: entity,
+                victim,
+                amount: *amount,
+                position: trans.translation,
+                absorbed: false,
+                source: Some(*source),
+                by_player: *by_player,
+            });
+        });
+        if enemy.health <= 0 {
+            // worn down over many hits, there's no single shot to credit for style
+            deaths.send(DeathEvent {
+                entity,
+                victim,
+                position: trans.translation,
+                weapon: None,
+                airborne: false,
+                thrown: false,
+            });
+            defeated.send(BossDefeated);
+            commands.entity(entity).despawn_recursive();
+        }
+    });
+}
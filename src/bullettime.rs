@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::actions::{Action, Actions};
+use crate::clock::GameClock;
+use crate::combat::DeathEvent;
+use crate::{GameState, GravityData, Player, ResetGame};
+
+const SLOW_SCALE: f32 = 0.3;
+const REFILL_PER_KILL: f32 = 0.1;
+
+pub struct BulletTime {
+    meter: f32,
+    timer: Timer,
+    active: bool,
+    // whatever the clock ran at before, the debug console can have it sped up or slowed down
+    restore: f32,
+}
+
+impl Default for BulletTime {
+    fn default() -> Self {
+        BulletTime {
+            meter: 1.0,
+            timer: Timer::from_seconds(2.0, false),
+            active: false,
+            restore: 1.0,
+        }
+    }
+}
+
+impl BulletTime {
+    // how much slower the world runs than the player. the player keeps real time, so its
+    // physics velocity is this much bigger than how fast it actually moves
+    pub fn player_scale(&self) -> f32 {
+        if self.active {
+            SLOW_SCALE
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BulletTimeMeter;
+
+pub struct BulletTimePlugin;
+
+impl Plugin for BulletTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BulletTime>()
+            .add_startup_system(create_bullet_time_meter)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(activate_bullet_time)
+                    .with_system(tick_bullet_time)
+                    .with_system(refill_bullet_time),
+            )
+            .add_system(update_bullet_time_meter)
+            .add_system(reset_bullet_time);
+    }
+}
+
+pub fn activate_bullet_time(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut clock: ResMut<GameClock>,
+    gravity: Res<GravityData>,
+    mut players: Query<(Entity, &mut Velocity), With<Player>>,
+) {
+    if !actions.just_pressed(Action::BulletTime) {
+        return;
+    }
+    if bullet_time.active || bullet_time.meter < 1.0 {
+        return;
+    }
+    bullet_time.active = true;
+    bullet_time.meter = 0.0;
+    bullet_time.timer.reset();
+    bullet_time.restore = clock.scale();
+    clock.set_scale(bullet_time.restore * SLOW_SCALE);
+    // physics steps slower for everyone, so the player's speed is scaled back up to match and
+    // gravity topped up until it pulls on the player at the usual rate
+    let catch_up = (1.0 / (SLOW_SCALE * SLOW_SCALE) - 1.0) * gravity.gravity;
+    players.iter_mut().for_each(|(entity, mut velocity)| {
+        velocity.linear /= SLOW_SCALE;
+        commands
+            .entity(entity)
+            .insert(Acceleration::from_linear(catch_up));
+    });
+}
+
+pub fn tick_bullet_time(
+    mut commands: Commands,
+    mut bullet_time: ResMut<BulletTime>,
+    mut clock: ResMut<GameClock>,
+    mut players: Query<(Entity, &mut Velocity), With<Player>>,
+) {
+    if !bullet_time.active {
+        return;
+    }
+    // runs on real time, otherwise the slowdown would stretch itself out
+    bullet_time.timer.tick(clock.unscaled_delta());
+    if bullet_time.timer.finished() {
+        bullet_time.active = false;
+        clock.set_scale(bullet_time.restore);
+        players.iter_mut().for_each(|(entity, mut velocity)| {
+            velocity.linear *= SLOW_SCALE;
+            commands.entity(entity).remove::<Acceleration>();
+        });
+    }
+}
+
+pub fn refill_bullet_time(
+    mut bullet_time: ResMut<BulletTime>,
+    mut deaths: EventReader<DeathEvent>,
+) {
+    let count = deaths.iter().filter_map(DeathEvent::enemy).count();
+    if count == 0 || bullet_time.active {
+        return;
+    }
+    bullet_time.meter = (bullet_time.meter + REFILL_PER_KILL * count as f32).min(1.0);
+}
+
+pub fn create_bullet_time_meter(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..default()
+                },
+                size: Size::new(Val::Px(120.0), Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Name::new("Bullet Time Meter"))
+        .with_children(|bar| {
+            bar.spawn_bundle(NodeBundle {
+                color: UiColor(Color::rgb(0.9, 0.8, 0.2)),
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(BulletTimeMeter);
+        });
+}
+
+pub fn update_bullet_time_meter(
+    bullet_time: Res<BulletTime>,
+    mut meters: Query<&mut Style, With<BulletTimeMeter>>,
+) {
+    if !bullet_time.is_changed() {
+        return;
+    }
+    // while active the bar drains with the remaining slowdown instead of showing the meter
+    let fill = if bullet_time.active {
+        1.0 - bullet_time.timer.percent()
+    } else {
+        bullet_time.meter
+    };
+    meters.iter_mut().for_each(|mut style| {
+        style.size.width = Val::Percent(fill * 100.0);
+    });
+}
+
+pub fn reset_bullet_time(
+    mut bullet_time: ResMut<BulletTime>,
+    mut clock: ResMut<GameClock>,
+    mut resets: EventReader<ResetGame>,
+) {
+    if resets.iter().count() > 0 {
+        // a restart mid slowdown hands the clock back, the player is respawned anyway
+        if bullet_time.active {
+            clock.set_scale(bullet_time.restore);
+        }
+        *bullet_time = BulletTime::default();
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::num::NonZeroU32;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{
+    Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+    ImageDataLayout, MapMode, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::render::texture::BevyDefault;
+use bevy::render::{RenderApp, RenderStage};
+use bevy::tasks::IoTaskPool;
+use bevy::ui::UiCameraConfig;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, RgbaImage};
+
+use crate::clock::RealClock;
+use crate::MyCamera;
+
+const CAPTURE_DIR: &str = "captures";
+const BYTES_PER_PIXEL: u32 = 4;
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+// held down to record, let go to save
+const CLIP_KEY: KeyCode = KeyCode::F11;
+const CLIP_FPS: f32 = 15.0;
+// only the tail end of a long hold is kept
+const CLIP_SECONDS: f32 = 5.0;
+// clip frames are shrunk by this much so a few seconds of them fit in memory
+const CLIP_DOWNSCALE: u32 = 2;
+
+// a second camera that mirrors the game camera into an image, only switched on for the frame
+// being captured. the render world copies that image back out so it can be written to disk
+#[derive(Component)]
+pub struct CaptureCamera;
+
+#[derive(Clone)]
+pub struct CaptureTarget {
+    image: Handle<Image>,
+    armed: bool,
+    // what this frame's capture is for, it travels with the pixels back to the app
+    kinds: Vec<CaptureKind>,
+}
+
+impl ExtractResource for CaptureTarget {
+    type Source = CaptureTarget;
+
+    fn extract_resource(source: &Self::Source) -> Self {
+        source.clone()
+    }
+}
+
+// what a capture is wanted for, the next frame that comes back is handed to it
+#[derive(Copy, Clone)]
+pub enum CaptureKind {
+    Photo,
+    Screenshot,
+    ClipFrame,
+}
+
+impl CaptureKind {
+    // photos are of the scene, the rest show the game as it's being played
+    fn shows_hud(&self) -> bool {
+        !matches!(self, CaptureKind::Photo)
+    }
+}
+
+#[derive(Default)]
+pub struct CaptureRequests {
+    pending: Vec<CaptureKind>,
+}
+
+impl CaptureRequests {
+    pub fn request(&mut self, kind: CaptureKind) {
+        self.pending.push(kind);
+    }
+}
+
+// rgba8 pixels, rows tightly packed
+#[derive(Clone)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    kinds: Vec<CaptureKind>,
+}
+
+struct FrameSender(Mutex<Sender<CapturedFrame>>);
+
+// a copy the gpu hasn't finished yet. mapping is asked for without waiting on it, the queue
+// submit of a later frame finishes it and the pixels are picked up then
+struct Readback {
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    padded_row: u32,
+    format: TextureFormat,
+    kinds: Vec<CaptureKind>,
+    mapped: Mutex<Receiver<bool>>,
+}
+
+#[derive(Default)]
+struct PendingReadbacks(Vec<Readback>);
+
+struct FrameReceiver(Mutex<Receiver<CapturedFrame>>);
+
+#[derive(Default)]
+pub struct ClipRecorder {
+    recording: bool,
+    // already shrunk, see CLIP_DOWNSCALE
+    frames: VecDeque<RgbaImage>,
+    since_frame: f32,
+}
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = channel();
+        app.init_resource::<CaptureRequests>()
+            .init_resource::<ClipRecorder>()
+            .insert_resource(FrameReceiver(Mutex::new(receiver)))
+            .add_plugin(ExtractResourcePlugin::<CaptureTarget>::default())
+            .add_startup_system(create_capture_camera)
+            .add_system_to_stage(CoreStage::PostUpdate, arm_capture)
+            .add_system(capture_hotkeys)
+            .add_system(receive_frames.after(capture_hotkeys));
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(FrameSender(Mutex::new(sender)))
+                .init_resource::<PendingReadbacks>()
+                .add_system_to_stage(RenderStage::Cleanup, read_back_capture)
+                .add_system_to_stage(
+                    RenderStage::Cleanup,
+                    collect_readbacks.after(read_back_capture),
+                );
+        }
+    }
+}
+
+fn capture_image(width: u32, height: u32) -> Image {
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("capture"),
+            size,
+            dimension: TextureDimension::D2,
+            // the 2d pipelines only draw to bevy's default format, bgra on most desktops.
+            // readbacks are put into rgba order before anything is encoded, see to_rgba
+            format: TextureFormat::bevy_default(),
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+pub fn create_capture_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    windows: Res<Windows>,
+) {
+    let (width, height) = windows.get_primary().map_or((1, 1), |window| {
+        (
+            window.physical_width().max(1),
+            window.physical_height().max(1),
+        )
+    });
+    let image = images.add(capture_image(width, height));
+    commands
+        .spawn_bundle(Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(image.clone()),
+                is_active: false,
+                // drawn before the game camera so the window's camera has the last word
+                priority: -1,
+                ..default()
+            },
+            ..default()
+        })
+        // switched per capture, see CaptureKind::shows_hud
+        .insert(UiCameraConfig { show_ui: false })
+        .insert(CaptureCamera)
+        .insert(Name::new("Capture Camera"));
+    commands.insert_resource(CaptureTarget {
+        image,
+        armed: false,
+        kinds: Vec::new(),
+    });
+}
+
+// switches the capture camera on for exactly one frame whenever something asked for a capture
+pub fn arm_capture(
+    mut requests: ResMut<CaptureRequests>,
+    mut target: ResMut<CaptureTarget>,
+    mut images: ResMut<Assets<Image>>,
+    windows: Res<Windows>,
+    game_cameras: Query<(&Transform, &OrthographicProjection), With<MyCamera>>,
+    mut capture_cameras: Query<
+        (
+            &mut Camera,
+            &mut Transform,
+            &mut OrthographicProjection,
+            &mut UiCameraConfig,
+        ),
+        (With<CaptureCamera>, Without<MyCamera>),
+    >,
+) {
+    let kinds = std::mem::take(&mut requests.pending);
+    let armed = !kinds.is_empty();
+    let shows_hud = kinds.iter().any(CaptureKind::shows_hud);
+    // left alone while idle, the render world picks it up on change
+    if armed || target.armed {
+        target.armed = armed;
+        target.kinds = kinds;
+    }
+    let (mut camera, mut trans, mut projection, mut ui) = match capture_cameras.iter_mut().next() {
+        Some(camera) => camera,
+        None => return,
+    };
+    camera.is_active = armed;
+    if !armed {
+        return;
+    }
+    ui.show_ui = shows_hud;
+    if let Some((game_trans, game_projection)) = game_cameras.iter().next() {
+        *trans = *game_trans;
+        *projection = game_projection.clone();
+    }
+    // follows the window so captures come out at the size the game is being played at
+    if let (Some(window), Some(image)) = (windows.get_primary(), images.get_mut(&target.image)) {
+        let size = image.texture_descriptor.size;
+        if size.width != window.physical_width() || size.height != window.physical_height() {
+            image.resize(Extent3d {
+                width: window.physical_width().max(1),
+                height: window.physical_height().max(1),
+                depth_or_array_layers: 1,
+            });
+        }
+    }
+}
+
+// the window's format is bgra on most desktops, the encoders all want rgba
+fn to_rgba(format: TextureFormat, pixels: &mut [u8]) {
+    if matches!(
+        format,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+    ) {
+        pixels
+            .chunks_exact_mut(BYTES_PER_PIXEL as usize)
+            .for_each(|pixel| pixel.swap(0, 2));
+    }
+}
+
+// copies the capture image into a buffer the cpu can read, see Readback
+fn read_back_capture(
+    target: Option<Res<CaptureTarget>>,
+    images: Res<RenderAssets<Image>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    mut pending: ResMut<PendingReadbacks>,
+) {
+    let target = match target.filter(|target| target.armed) {
+        Some(target) => target,
+        None => return,
+    };
+    let image = match images.get(&target.image) {
+        Some(image) => image,
+        None => return,
+    };
+    let width = image.size.x as u32;
+    let height = image.size.y as u32;
+    // rows in a copy have to start on an alignment boundary, the padding is cut off afterwards
+    let row = width * BYTES_PER_PIXEL;
+    let padded_row = (row + COPY_BYTES_PER_ROW_ALIGNMENT - 1) / COPY_BYTES_PER_ROW_ALIGNMENT
+        * COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("capture readback"),
+        size: (padded_row * height) as u64,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("capture readback"),
+    });
+    encoder.copy_texture_to_buffer(
+        image.texture.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_row),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit([encoder.finish()]);
+    let (mapped_sender, mapped) = channel();
+    buffer.slice(..).map_async(MapMode::Read, move |result| {
+        let _ = mapped_sender.send(result.is_ok());
+    });
+    pending.0.push(Readback {
+        buffer,
+        width,
+        height,
+        padded_row,
+        format: image.texture_format,
+        kinds: target.kinds.clone(),
+        mapped: Mutex::new(mapped),
+    });
+}
+
+// ships every finished copy back to the app, the rest wait for another frame
+fn collect_readbacks(mut pending: ResMut<PendingReadbacks>, sender: Res<FrameSender>) {
+    pending.0.retain(|readback| {
+        let mapped = match readback.mapped.lock().ok().map(|mapped| mapped.try_recv()) {
+            Some(Ok(mapped)) => mapped,
+            Some(Err(TryRecvError::Empty)) => return true,
+            _default => false,
+        };
+        if !mapped {
+            warn!("a capture could not be read back");
+            return false;
+        }
+        let row = (readback.width * BYTES_PER_PIXEL) as usize;
+        let mut pixels: Vec<u8> = readback
+            .buffer
+            .slice(..)
+            .get_mapped_range()
+            .chunks(readback.padded_row as usize)
+            .flat_map(|padded| padded[..row].to_vec())
+            .collect();
+        readback.buffer.unmap();
+        to_rgba(readback.format, &mut pixels);
+        if let Ok(sender) = sender.0.lock() {
+            let _ = sender.send(CapturedFrame {
+                width: readback.width,
+                height: readback.height,
+                pixels,
+                kinds: readback.kinds.clone(),
+            });
+        }
+        false
+    });
+}
+
+fn capture_path(prefix: &str, extension: &str) -> String {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    format!("{}/{}-{}.{}", CAPTURE_DIR, prefix, stamp, extension)
+}
+
+// encoding happens off the main thread, a big png takes long enough to drop frames
+fn save_png(frame: CapturedFrame, prefix: &'static str) {
+    IoTaskPool::get()
+        .spawn(async move {
+            let path = capture_path(prefix, "png");
+            let result = fs::create_dir_all(CAPTURE_DIR)
+                .map_err(|err| err.to_string())
+                .and_then(|_| {
+                    image::save_buffer(
+                        &path,
+                        &frame.pixels,
+                        frame.width,
+                        frame.height,
+                        image::ColorType::Rgba8,
+                    )
+                    .map_err(|err| err.to_string())
+                });
+            match result {
+                Ok(()) => info!("saved {}", path),
+                Err(err) => warn!("could not save {}: {}", path, err),
+            }
+        })
+        .detach();
+}
+
+fn shrink(frame: &CapturedFrame) -> Option<RgbaImage> {
+    let full = RgbaImage::from_raw(frame.width, frame.height, frame.pixels.clone())?;
+    Some(image::imageops::resize(
+        &full,
+        (frame.width / CLIP_DOWNSCALE).max(1),
+        (frame.height / CLIP_DOWNSCALE).max(1),
+        FilterType::Nearest,
+    ))
+}
+
+// the whole clip is encoded on a task, a few seconds of gif can take longer than that to write
+fn save_gif(frames: Vec<RgbaImage>) {
+    IoTaskPool::get()
+        .spawn(async move {
+            let path = capture_path("clip", "gif");
+            let delay = Delay::from_numer_denom_ms(1000, CLIP_FPS as u32);
+            let result = fs::create_dir_all(CAPTURE_DIR)
+                .and_then(|_| File::create(&path))
+                .map_err(|err| err.to_string())
+                .and_then(|file| {
+                    let mut encoder = GifEncoder::new(file);
+                    encoder
+                        .set_repeat(Repeat::Infinite)
+                        .map_err(|err| err.to_string())?;
+                    encoder
+                        .encode_frames(
+                            frames
+                                .into_iter()
+                                .map(|image| Frame::from_parts(image, 0, 0, delay)),
+                        )
+                        .map_err(|err| err.to_string())
+                });
+            match result {
+                Ok(()) => info!("saved {}", path),
+                Err(err) => warn!("could not save {}: {}", path, err),
+            }
+        })
+        .detach();
+}
+
+pub fn capture_hotkeys(
+    keys: Res<Input<KeyCode>>,
+    mut requests: ResMut<CaptureRequests>,
+    mut recorder: ResMut<ClipRecorder>,
+    clock: Res<RealClock>,
+) {
+    if keys.just_pressed(SCREENSHOT_KEY) {
+        requests.request(CaptureKind::Screenshot);
+    }
+    if keys.just_released(CLIP_KEY) && recorder.recording {
+        recorder.recording = false;
+        let frames: Vec<RgbaImage> = recorder.frames.drain(..).collect();
+        if !frames.is_empty() {
+            save_gif(frames);
+        }
+    }
+    if !keys.pressed(CLIP_KEY) {
+        return;
+    }
+    if !recorder.recording {
+        recorder.recording = true;
+        recorder.frames.clear();
+        // first frame goes out straight away
+        recorder.since_frame = 1.0 / CLIP_FPS;
+    }
+    recorder.since_frame += clock.delta_seconds();
+    if recorder.since_frame >= 1.0 / CLIP_FPS {
+        recorder.since_frame = 0.0;
+        requests.request(CaptureKind::ClipFrame);
+    }
+}
+
+pub fn receive_frames(receiver: Res<FrameReceiver>, mut recorder: ResMut<ClipRecorder>) {
+    let frames: Vec<CapturedFrame> = match receiver.0.lock() {
+        Ok(receiver) => receiver.try_iter().collect(),
+        Err(_) => return,
+    };
+    frames.into_iter().for_each(|frame| {
+        frame.kinds.iter().for_each(|kind| match kind {
+            CaptureKind::Photo => save_png(frame.clone(), "photo"),
+            CaptureKind::Screenshot => save_png(frame.clone(), "screenshot"),
+            // a frame that lands after the key was let go missed the clip
+            CaptureKind::ClipFrame if recorder.recording => {
+                if let Some(shrunk) = shrink(&frame) {
+                    recorder.frames.push_back(shrunk);
+                }
+                if recorder.frames.len() > (CLIP_FPS * CLIP_SECONDS) as usize {
+                    recorder.frames.pop_front();
+                }
+            }
+            CaptureKind::ClipFrame => {}
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // pure red as the gpu hands it back from a bgra target
+    const BGRA_RED: [u8; 4] = [0, 0, 255, 255];
+    const RGBA_RED: [u8; 4] = [255, 0, 0, 255];
+
+    fn readback(format: TextureFormat, pixel: [u8; 4]) -> CapturedFrame {
+        let mut pixels = pixel.repeat(4);
+        to_rgba(format, &mut pixels);
+        CapturedFrame {
+            width: 2,
+            height: 2,
+            pixels,
+            kinds: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn bgra_readbacks_stay_red_in_photos_and_clips() {
+        let frame = readback(TextureFormat::Bgra8UnormSrgb, BGRA_RED);
+        assert_eq!(frame.pixels[..4], RGBA_RED);
+        let clip_frame = shrink(&frame).expect("a 2x2 frame shrinks to 1x1");
+        assert_eq!(clip_frame.get_pixel(0, 0).0, RGBA_RED);
+    }
+
+    #[test]
+    fn rgba_readbacks_are_left_alone() {
+        let frame = readback(TextureFormat::Rgba8UnormSrgb, RGBA_RED);
+        assert_eq!(frame.pixels[..4], RGBA_RED);
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use serde::Deserialize;
+
+use crate::loading::GameAssets;
+use crate::menu::{spawn_small_button, MenuButton};
+use crate::settings::Settings;
+use crate::{GameState, Weapons};
+
+// every selectable character, in menu order
+pub const CHARACTER_FILES: [&str; 3] = [
+    "characters/runner.character.json",
+    "characters/scout.character.json",
+    "characters/heavy.character.json",
+];
+
+// a character's look and stat block, loaded from assets/characters
+#[derive(Clone, Deserialize, TypeUuid)]
+#[uuid = "5d3f6a1e-2b7c-4f0e-9a41-8c2d7e6b1f09"]
+pub struct CharacterDef {
+    pub name: String,
+    sprite: String,
+    // multiplier on the walking top speed
+    pub move_speed: f32,
+    // multiplier on the tuned jump height
+    pub jump_scale: f32,
+    pub dash_cooldown: f32,
+    pub starting_weapon: Option<Weapons>,
+    #[serde(skip)]
+    pub texture: Handle<Image>,
+}
+
+impl Default for CharacterDef {
+    fn default() -> Self {
+        CharacterDef {
+            name: String::from("Runner"),
+            sprite: String::from("images/Character.png"),
+            move_speed: 1.0,
+            jump_scale: 1.0,
+            dash_cooldown: 0.0,
+            starting_weapon: None,
+            texture: Handle::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CharacterDefLoader;
+
+impl AssetLoader for CharacterDefLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let mut def: CharacterDef = serde_json::from_slice(bytes)?;
+            def.texture = load_context.get_handle(def.sprite.as_str());
+            let sprite = def.sprite.clone();
+            load_context
+                .set_default_asset(LoadedAsset::new(def).with_dependency(sprite.as_str().into()));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["character.json"]
+    }
+}
+
+// the menu pick, or the built in runner if its file never loaded
+pub fn selected_character(
+    settings: &Settings,
+    assets: &GameAssets,
+    defs: &Assets<CharacterDef>,
+) -> CharacterDef {
+    assets
+        .characters
+        .get(settings.character)
+        .and_then(|handle| defs.get(handle))
+        .cloned()
+        .unwrap_or_else(|| CharacterDef {
+            texture: assets.character.clone(),
+            ..default()
+        })
+}
+
+#[derive(Component)]
+pub struct CharacterLabel;
+
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<CharacterDef>()
+            .init_asset_loader::<CharacterDefLoader>()
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu).with_system(update_character_label),
+            );
+    }
+}
+
+pub fn spawn_character_select(parent: &mut ChildBuilder, font: &Handle<Font>) {
+    parent
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_small_button(row, font, "<", MenuButton::PreviousCharacter);
+            row.spawn_bundle(TextBundle {
+                text: Text::from_sections(vec![
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 30.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.,
+                            color: Color::GRAY,
+                        },
+                    ),
+                ]),
+                style: Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(CharacterLabel);
+            spawn_small_button(row, font, ">", MenuButton::NextCharacter);
+        });
+}
+
+pub fn update_character_label(
+    settings: Res<Settings>,
+    assets: Res<GameAssets>,
+    defs: Res<Assets<CharacterDef>>,
+    mut labels: Query<&mut Text, With<CharacterLabel>>,
+    new_labels: Query<(), Added<CharacterLabel>>,
+) {
+    if !settings.is_changed() && new_labels.is_empty() {
+        return;
+    }
+    let def = selected_character(&settings, &assets, &defs);
+    labels.iter_mut().for_each(|mut text| {
+        text.sections[0].value = format!("{}\n", def.name);
+        text.sections[1].value = format!(
+            "speed {:.1}  jump {:.1}  dash cd {:.1}s",
+            def.move_speed, def.jump_scale, def.dash_cooldown
+        );
+    });
+}
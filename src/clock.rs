@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::GravityData;
+
+// gameplay time: bevy's Time keeps running while paused, so every gameplay timer ticks off
+// this instead and the physics scale follows it
+pub struct GameClock {
+    scale: f32,
+    paused: bool,
+    delta: Duration,
+    unscaled: Duration,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock {
+            scale: 1.0,
+            paused: false,
+            delta: Duration::ZERO,
+            unscaled: Duration::ZERO,
+        }
+    }
+}
+
+impl GameClock {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    // real frame time that still stops while paused, for player actions that
+    // shouldn't slow down with the rest of the world
+    pub fn unscaled_delta(&self) -> Duration {
+        self.unscaled
+    }
+
+    pub fn scale(&self) -> f32 {
+        if self.paused {
+            0.0
+        } else {
+            self.scale
+        }
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+}
+
+// wall time for menus, toasts and hud animation, it never pauses or slows with bullet time
+#[derive(Default)]
+pub struct RealClock {
+    delta: Duration,
+}
+
+impl RealClock {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+}
+
+pub fn tick_real_clock(time: Res<Time>, mut clock: ResMut<RealClock>) {
+    clock.delta = time.delta();
+}
+
+pub fn tick_game_clock(
+    time: Res<Time>,
+    mut clock: ResMut<GameClock>,
+    mut physics_time: ResMut<PhysicsTime>,
+    gravity: Res<GravityData>,
+) {
+    clock.delta = time.delta().mul_f32(clock.scale());
+    clock.unscaled = if clock.paused {
+        Duration::ZERO
+    } else {
+        time.delta()
+    };
+    physics_time.set_scale(clock.scale() * gravity.phys_time);
+}
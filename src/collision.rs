@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::combat::{Combatant, DeathEvent, Slain};
+use crate::{Enemy, EnemyBullet, GameState, Layers};
+
+const ALL_LAYERS: [Layers; 9] = [
+    Layers::World,
+    Layers::Player,
+    Layers::Enemies,
+    Layers::Weapons,
+    Layers::Projectiles,
+    Layers::Debris,
+    Layers::EnemyProjectiles,
+    Layers::Hazard,
+    Layers::Thrown,
+];
+
+// every pair of layers that touch, order doesn't matter. anything not listed passes through
+const INTERACTIONS: [(Layers, Layers); 13] = [
+    (Layers::World, Layers::Player),
+    (Layers::World, Layers::Enemies),
+    (Layers::World, Layers::Weapons),
+    (Layers::World, Layers::Debris),
+    (Layers::World, Layers::Projectiles),
+    (Layers::World, Layers::Thrown),
+    (Layers::Player, Layers::Enemies),
+    (Layers::Player, Layers::Weapons),
+    (Layers::Player, Layers::EnemyProjectiles),
+    (Layers::Player, Layers::Hazard),
+    (Layers::Enemies, Layers::Projectiles),
+    (Layers::Enemies, Layers::Hazard),
+    (Layers::Enemies, Layers::Thrown),
+];
+
+fn interacts(a: Layers, b: Layers) -> bool {
+    INTERACTIONS
+        .iter()
+        .any(|(first, second)| (*first == a && *second == b) || (*first == b && *second == a))
+}
+
+// the one place a collider's group and mask come from
+pub fn collision_layers(group: Layers) -> CollisionLayers {
+    ALL_LAYERS
+        .iter()
+        .filter(|other| interacts(group, **other))
+        .fold(
+            CollisionLayers::none().with_group(group),
+            |layers, other| layers.with_mask(*other),
+        )
+}
+
+// run-wide collision rules, --friendly-fire lets enemy bullets hit other enemies
+pub struct CollisionRules {
+    pub enemy_friendly_fire: bool,
+}
+
+impl CollisionRules {
+    pub fn from_args() -> Self {
+        CollisionRules {
+            enemy_friendly_fire: std::env::args().any(|arg| arg == "--friendly-fire"),
+        }
+    }
+
+    // the matrix plus whatever the rules switch on
+    pub fn layers(&self, group: Layers) -> CollisionLayers {
+        let layers = collision_layers(group);
+        match group {
+            Layers::Enemies if self.enemy_friendly_fire => {
+                layers.with_mask(Layers::EnemyProjectiles)
+            }
+            Layers::EnemyProjectiles if self.enemy_friendly_fire => {
+                layers.with_mask(Layers::Enemies)
+            }
+            _ => layers,
+        }
+    }
+}
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CollisionRules::from_args())
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(enemy_bullets_hit_enemies),
+            );
+    }
+}
+
+// only ever sees a collision when friendly fire is on, the layers filter it out otherwise
+pub fn enemy_bullets_hit_enemies(
+    mut commands: Commands,
+    bullets: Query<(Entity, &Collisions), With<EnemyBullet>>,
+    enemies: Query<(&Transform, &Enemy)>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut slain: ResMut<Slain>,
+) {
+    bullets.iter().for_each(|(bullet, collisions)| {
+        collisions.entities().for_each(|other| {
+            if let Ok((trans, enemy)) = enemies.get(other) {
+                commands.entity(other).despawn_recursive();
+                commands.entity(bullet).despawn_recursive();
+                if !slain.claim(other) {
+                    return;
+                }
+                deaths.send(DeathEvent {
+                    entity: other,
+                    victim: Combatant::Enemy(enemy.asset),
+                    position: trans.translation,
+                    weapon: None,
+                    airborne: false,
+                    thrown: false,
+                });
+            }
+        });
+    });
+}
@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use rand::Rng;
+
+use crate::assist::Assist;
+use crate::killzone::PLAYER_SPAWN;
+use crate::loading::GameAssets;
+use crate::materials::PhysicsMaterials;
+use crate::modes::GameRng;
+use crate::particles::spawn_burst;
+use crate::sequence::{Sequence, Sequencer};
+use crate::shop::enemy_drop;
+use crate::transition::Transition;
+use crate::{
+    spawn_weapon_pickup, Behavior, GameState, Invulnerable, Player, Weapons, PLAYER_MAX_HEALTH,
+};
+
+// who was on the receiving end of a hit or a death
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Combatant {
+    Player,
+    Enemy(Behavior),
+}
+
+// sent for every hit that lands, even the ones a shield soaks up
+pub struct DamageEvent {
+    pub target: Entity,
+    pub victim: Combatant,
+    pub amount: i8,
+    pub position: Vec3,
+    pub absorbed: bool,
+    // where the hit came from, when that's known
+    pub source: Option<Vec3>,
+    // the player's own shots, blasts and thrown guns, not enemies, hazards or falling drops
+    pub by_player: bool,
+}
+
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub victim: Combatant,
+    pub position: Vec3,
+    // the player's weapon behind the kill, when it came from one
+    pub weapon: Option<Weapons>,
+    // the victim was off the ground when it went down
+    pub airborne: bool,
+    // the weapon hit by being thrown at the victim, not by firing it
+    pub thrown: bool,
+}
+
+impl DeathEvent {
+    pub fn enemy(&self) -> Option<Behavior> {
+        match self.victim {
+            Combatant::Enemy(behavior) => Some(behavior),
+            Combatant::Player => None,
+        }
+    }
+
+    // the weapon behind an enemy kill, none for the player's death or enemies killing enemies
+    pub fn player_weapon(&self) -> Option<Weapons> {
+        self.enemy().and(self.weapon)
+    }
+}
+
+// enemies already killed this frame. despawns only land at the end of the stage, so five
+// shotgun pellets or a bullet and a blast can all still find the same enemy
+#[derive(Default)]
+pub struct Slain(HashSet<Entity>);
+
+impl Slain {
+    // true the first time an entity is claimed this frame, whoever claims it sends its death
+    pub fn claim(&mut self, entity: Entity) -> bool {
+        self.0.insert(entity)
+    }
+}
+
+pub fn forget_slain(mut slain: ResMut<Slain>) {
+    slain.0.clear();
+}
+
+// rising or falling this fast means nothing is holding it up
+const AIRBORNE_SPEED: f32 = 30.0;
+
+pub fn airborne(velocity: Option<&Velocity>) -> bool {
+    velocity.map_or(false, |velocity| velocity.linear.y.abs() > AIRBORNE_SPEED)
+}
+
+impl DamageEvent {
+    pub fn hit_player(&self) -> bool {
+        self.victim == Combatant::Player
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum PickupKind {
+    Weapon(Weapons),
+    ScoreGem(i64),
+    Shield,
+}
+
+// sent once per pickup, whatever the physics reports for the overlap
+pub struct PickupEvent {
+    pub kind: PickupKind,
+    pub position: Vec3,
+}
+
+pub struct WeaponFiredEvent {
+    pub weapon: Weapons,
+    pub position: Vec3,
+    pub angle: f32,
+}
+
+// the player went down for good, a spare life soaking up the death doesn't send one
+pub struct PlayerDefeated {
+    pub position: Vec3,
+}
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DeathEvent>()
+            .add_event::<PickupEvent>()
+            .add_event::<WeaponFiredEvent>()
+            .add_event::<PlayerDefeated>()
+            .init_resource::<Slain>()
+            .add_system_to_stage(CoreStage::First, forget_slain)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(burst_on_death)
+                    .with_system(drop_enemy_weapons)
+                    .with_system(end_run_on_player_death),
+            );
+    }
+}
+
+pub fn burst_on_death(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    mut rng: ResMut<GameRng>,
+) {
+    deaths.iter().for_each(|death| {
+        if death.enemy().is_some() {
+            spawn_burst(
+                &mut commands,
+                &mut rng,
+                death.position,
+                Color::ORANGE_RED,
+                8,
+                60.0,
+            );
+        }
+    });
+}
+
+pub fn drop_enemy_weapons(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    mut rng: ResMut<GameRng>,
+    assets: Res<GameAssets>,
+    materials: Res<PhysicsMaterials>,
+) {
+    deaths.iter().for_each(|death| {
+        let drop = death.enemy().and_then(enemy_drop);
+        if let Some((weapon, chance)) = drop {
+            if rng.gen_bool(chance) {
+                spawn_weapon_pickup(&mut commands, &assets, &materials, death.position, weapon);
+            }
+        }
+    });
+}
+
+// takes a heart off the player and sends the death when it was the last one, otherwise the
+// player gets a moment to recover. true when the player went down
+pub fn wound_player(
+    commands: &mut Commands,
+    deaths: &mut EventWriter<DeathEvent>,
+    entity: Entity,
+    player: &mut Player,
+    position: Vec3,
+) -> bool {
+    player.health -= 1;
+    if player.health > 0 {
+        commands
+            .entity(entity)
+            .insert(Invulnerable(Timer::from_seconds(1.0, false)));
+        return false;
+    }
+    deaths.send(DeathEvent {
+        entity,
+        victim: Combatant::Player,
+        position,
+        weapon: None,
+        airborne: false,
+        thrown: false,
+    });
+    true
+}
+
+// back at the spawn point standing still, after a fall or a spent spare life
+pub fn return_to_spawn(player: &mut Player, trans: &mut Transform, velocity: &mut Velocity) {
+    player.location = PLAYER_SPAWN;
+    trans.translation = PLAYER_SPAWN.truncate().extend(trans.translation.z);
+    *velocity = Velocity::default();
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn end_run_on_player_death(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    mut transition: ResMut<Transition>,
+    mut assist: ResMut<Assist>,
+    mut players: Query<(&mut Player, &mut Transform, &mut Velocity)>,
+    mut sequencer: ResMut<Sequencer>,
+    sequences: Res<Assets<Sequence>>,
+    assets: Res<GameAssets>,
+    mut state: ResMut<State<GameState>>,
+    mut defeated: EventWriter<PlayerDefeated>,
+) {
+    deaths.iter().for_each(|death| {
+        if death.victim != Combatant::Player {
+            return;
+        }
+        // an assisted run spends a spare life and starts over from the spawn point
+        if let Ok((mut player, mut trans, mut velocity)) = players.get_mut(death.entity) {
+            if assist.spend_life() {
+                player.health = PLAYER_MAX_HEALTH;
+                return_to_spawn(&mut player, &mut trans, &mut velocity);
+                commands
+                    .entity(death.entity)
+                    .insert(Invulnerable(Timer::from_seconds(2.0, false)));
+                return;
+            }
+        }
+        commands.entity(death.entity).despawn_recursive();
+        defeated.send(PlayerDefeated {
+            position: death.position,
+        });
+        // lingers on where the player went down first, straight to the screen without it
+        let focus = death.position.truncate();
+        if sequencer.play(&sequences, &assets.game_over, focus, &mut state) {
+            sequencer.then(GameState::GameOver);
+        } else {
+            transition.fade_to(GameState::GameOver);
+        }
+    });
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::clock::GameClock;
+use crate::loading::{AtlasRegistry, GameAssets};
+use crate::materials::PhysicsMaterials;
+use crate::schedule::GameplaySet;
+use crate::spawning::SpawnRules;
+use crate::{
+    equip_weapon, spawn_enemy_warning, Behavior, DifficultyTimer, GameState, GodMode, HeldItem,
+    Player, Weapon, Weapons,
+};
+
+const MAX_LOG_LINES: usize = 8;
+
+// everything the console can ask the rest of the game to do
+pub enum ConsoleCommand {
+    Spawn(Behavior, u32),
+    Give(Weapons),
+    SetDifficulty(i64),
+    God,
+    TimeScale(f32),
+}
+
+#[derive(Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    log: Vec<String>,
+}
+
+impl Console {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_event::<ConsoleCommand>()
+            .add_startup_system(create_console)
+            .add_system(toggle_console)
+            .add_system(type_in_console.after(toggle_console))
+            .add_system(apply_cheats)
+            .add_system(update_console_text)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .label(GameplaySet::Input)
+                    .with_system(spawn_console_enemies)
+                    .with_system(give_console_weapon),
+            );
+    }
+}
+
+fn parse_behavior(name: &str) -> Option<Behavior> {
+    match name {
+        "walker" => Some(Behavior::Walker),
+        "jumper" => Some(Behavior::Jumper),
+        "shooter" => Some(Behavior::Shooter),
+        "burst" => Some(Behavior::BurstShooter),
+        "charger" => Some(Behavior::Charger),
+        "summoner" => Some(Behavior::Summoner),
+        "shielded" => Some(Behavior::Shielded),
+        "bomber" => Some(Behavior::Bomber),
+        _default => None,
+    }
+}
+
+fn parse_weapon(name: &str) -> Option<Weapons> {
+    match name {
+        "base" => Some(Weapons::Base),
+        "rocket" => Some(Weapons::Rocket),
+        "sniper" => Some(Weapons::Sniper),
+        "shotgun" => Some(Weapons::Shotgun),
+        "rock" => Some(Weapons::Rock),
+        "airplane" => Some(Weapons::Airplane),
+        "enemygun" => Some(Weapons::EnemyGun),
+        "homing" => Some(Weapons::HomingRocket),
+        "laser" => Some(Weapons::Laser),
+        "grenade" => Some(Weapons::Grenade),
+        "smg" => Some(Weapons::Smg),
+        _default => None,
+    }
+}
+
+fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let lowered = line.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    match words.as_slice() {
+        ["spawn", name] => parse_behavior(name)
+            .map(|behavior| ConsoleCommand::Spawn(behavior, 1))
+            .ok_or(format!("unknown enemy '{}'", name)),
+        ["spawn", name, count] => {
+            let behavior = parse_behavior(name).ok_or(format!("unknown enemy '{}'", name))?;
+            let count: u32 = count
+                .parse()
+                .map_err(|_| format!("bad count '{}'", count))?;
+            // a whole screen's worth at most, every warning is spawned in the one frame
+            let cap = SpawnRules::default().soft_cap as u32;
+            Ok(ConsoleCommand::Spawn(behavior, count.min(cap)))
+        }
+        ["give", name] => parse_weapon(name)
+            .map(ConsoleCommand::Give)
+            .ok_or(format!("unknown weapon '{}'", name)),
+        ["set", "difficulty", value] => value
+            .parse()
+            .map(ConsoleCommand::SetDifficulty)
+            .map_err(|_| format!("bad difficulty '{}'", value)),
+        ["god"] => Ok(ConsoleCommand::God),
+        ["timescale", value] => value
+            .parse()
+            .map(ConsoleCommand::TimeScale)
+            .map_err(|_| format!("bad timescale '{}'", value)),
+        [] => Err(String::new()),
+        _default => Err(format!("unknown command '{}'", line)),
+    }
+}
+
+pub fn create_console(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 16.,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(60.0),
+                    left: Val::Px(5.0),
+                    ..default()
+                },
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(ConsoleText)
+        .insert(Name::new("Console"));
+}
+
+pub fn toggle_console(
+    keys: Res<Input<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut texts: Query<&mut Visibility, With<ConsoleText>>,
+) {
+    if !keys.just_pressed(KeyCode::Grave) {
+        return;
+    }
+    console.open = !console.open;
+    console.input.clear();
+    texts.iter_mut().for_each(|mut visibility| {
+        visibility.is_visible = console.open;
+    });
+}
+
+pub fn type_in_console(
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+    mut commands: EventWriter<ConsoleCommand>,
+) {
+    if !console.open {
+        characters.clear();
+        return;
+    }
+    characters.iter().for_each(|received| {
+        // the backtick that opened the console shouldn't end up in the input
+        if !received.char.is_control() && received.char != '`' {
+            console.input.push(received.char);
+        }
+    });
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        match parse_command(&line) {
+            Ok(command) => {
+                console.log.push(format!("> {}", line));
+                commands.send(command);
+            }
+            Err(error) if error.is_empty() => {}
+            Err(error) => console.log.push(error),
+        }
+        let overflow = console.log.len().saturating_sub(MAX_LOG_LINES);
+        console.log.drain(..overflow);
+    }
+}
+
+pub fn apply_cheats(
+    mut commands: Commands,
+    mut console_commands: EventReader<ConsoleCommand>,
+    mut difficulty: ResMut<DifficultyTimer>,
+    mut clock: ResMut<GameClock>,
+    players: Query<(Entity, Option<&GodMode>), With<Player>>,
+) {
+    console_commands.iter().for_each(|command| match command {
+        ConsoleCommand::SetDifficulty(value) => difficulty.difficulty = (*value).max(1),
+        ConsoleCommand::TimeScale(scale) => clock.set_scale(scale.max(0.0)),
+        ConsoleCommand::God => players.iter().for_each(|(player, god)| {
+            if god.is_some() {
+                commands.entity(player).remove::<GodMode>();
+            } else {
+                commands.entity(player).insert(GodMode);
+            }
+        }),
+        // spawning and weapons go through the gameplay helpers below
+        ConsoleCommand::Spawn(..) | ConsoleCommand::Give(_) => {}
+    });
+}
+
+// a cheat draws from its own rng, the run's seeded one stays in step with its replay
+pub fn spawn_console_enemies(
+    mut commands: Commands,
+    atlases: Res<AtlasRegistry>,
+    mut console_commands: EventReader<ConsoleCommand>,
+) {
+    let mut rng = rand::thread_rng();
+    console_commands.iter().for_each(|command| {
+        if let ConsoleCommand::Spawn(behavior, count) = command {
+            for _i in 0..*count {
+                let random_x = rng.gen_range(-200.0..200.0) as f32;
+                spawn_enemy_warning(&mut commands, &atlases, *behavior, random_x);
+            }
+        }
+    });
+}
+
+pub fn give_console_weapon(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    materials: Res<PhysicsMaterials>,
+    mut console_commands: EventReader<ConsoleCommand>,
+    player_query: Query<&Player>,
+    query_held_item: Query<(Entity, &Weapon), With<HeldItem>>,
+) {
+    console_commands.iter().for_each(|command| {
+        if let ConsoleCommand::Give(weapon) = command {
+            if let Some(player) = player_query.iter().next() {
+                equip_weapon(
+                    &mut commands,
+                    &assets,
+                    &materials,
+                    player,
+                    &query_held_item,
+                    *weapon,
+                );
+            }
+        }
+    });
+}
+
+pub fn update_console_text(console: Res<Console>, mut texts: Query<&mut Text, With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+    let mut lines = console.log.join("\n");
+    lines += &format!("\n> {}_", console.input);
+    texts.iter_mut().for_each(|mut text| {
+        text.sections[0].value = lines.clone();
+    });
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use libm::{cosf, sinf};
+
+use crate::depth::RenderLayer;
+use crate::modes::{GameMode, RunConfig};
+use crate::settings::Settings;
+use crate::{GameState, HeldItem, Player, Weapon, Weapons};
+
+const AIM_DOTS: usize = 20;
+const AIM_DOT_SPACING: f32 = 8.0;
+
+// where the player is aiming in world space, written by whichever input owns the aim
+#[derive(Default)]
+pub struct AimPoint(pub Vec2);
+
+#[derive(Component)]
+pub struct Crosshair;
+
+#[derive(Component)]
+pub struct AimDot(usize);
+
+pub struct CrosshairPlugin;
+
+impl Plugin for CrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AimPoint>()
+            .add_startup_system(create_crosshair)
+            .add_system(update_crosshair)
+            .add_system(update_aim_line)
+            .add_system(hide_system_cursor);
+    }
+}
+
+pub fn create_crosshair(mut commands: Commands) {
+    commands
+        .spawn_bundle(SpatialBundle::default())
+        .insert(Crosshair)
+        .insert(Name::new("Crosshair"))
+        .with_children(|parent| {
+            [Vec2::new(7.0, 1.0), Vec2::new(1.0, 7.0)]
+                .iter()
+                .for_each(|size| {
+                    parent.spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(1.0, 1.0, 1.0, 0.9),
+                            custom_size: Some(*size),
+                            ..default()
+                        },
+                        ..default()
+                    });
+                });
+        });
+    (0..AIM_DOTS).for_each(|index| {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 0.2, 0.2, 0.35),
+                    custom_size: Some(Vec2::splat(1.0)),
+                    ..default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(AimDot(index))
+            .insert(Name::new("Aim Dot"));
+    });
+}
+
+pub fn update_crosshair(
+    aim: Res<AimPoint>,
+    state: Res<State<GameState>>,
+    run_config: Res<RunConfig>,
+    mut crosshairs: Query<(&mut Transform, &mut Visibility), With<Crosshair>>,
+) {
+    // versus aims with keys and the mouse player spawns, neither has a point to mark
+    let playing = *state.current() == GameState::Playing && run_config.mode != GameMode::Versus;
+    crosshairs
+        .iter_mut()
+        .for_each(|(mut trans, mut visibility)| {
+            visibility.is_visible = playing;
+            trans.translation = aim.0.extend(RenderLayer::Overlay.z());
+        });
+}
+
+// the sniper gets a dotted line out of the barrel so long shots can be lined up
+pub fn update_aim_line(
+    state: Res<State<GameState>>,
+    players: Query<&Player>,
+    held_items: Query<(&Transform, &Weapon), With<HeldItem>>,
+    mut dots: Query<(&AimDot, &mut Transform, &mut Visibility), Without<HeldItem>>,
+) {
+    let playing = *state.current() == GameState::Playing;
+    let sniper = held_items
+        .iter()
+        .find(|(_, weapon)| matches!(weapon.asset, Weapons::Sniper));
+    let line = match (playing, sniper, players.iter().next()) {
+        (true, Some((held_trans, _)), Some(player)) => Some((held_trans, player.looking_at)),
+        _ => None,
+    };
+    dots.iter_mut()
+        .for_each(|(dot, mut trans, mut visibility)| {
+            visibility.is_visible = line.is_some();
+            if let Some((held_trans, angle)) = line {
+                let distance = (dot.0 + 1) as f32 * AIM_DOT_SPACING;
+                trans.translation = Vec3::new(
+                    held_trans.translation.x + cosf(angle) * distance,
+                    held_trans.translation.y + sinf(angle) * distance,
+                    RenderLayer::Overlay.z(),
+                );
+            }
+        });
+}
+
+pub fn hide_system_cursor(
+    settings: Res<Settings>,
+    state: Res<State<GameState>>,
+    mut windows: ResMut<Windows>,
+) {
+    if !settings.is_changed() && !state.is_changed() {
+        return;
+    }
+    let hidden = settings.hide_cursor && *state.current() == GameState::Playing;
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_cursor_visibility(!hidden);
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::director::Director;
+use crate::loading::GameAssets;
+use crate::particles::Particle;
+use crate::{
+    Bullet, Despawner, DifficultyTimer, Enemy, EnemyBullet, EnemyTimer, SpawnEnemy, SpawnWeapon,
+    Weapon, WeaponSpawns,
+};
+
+pub const ENEMY_COUNT: DiagnosticId =
+    DiagnosticId::from_u128(0x6e0b_7f04_4a3c_4d2e_9a51_3c8e_1f20_a001);
+pub const BULLET_COUNT: DiagnosticId =
+    DiagnosticId::from_u128(0x6e0b_7f04_4a3c_4d2e_9a51_3c8e_1f20_a002);
+pub const PARTICLE_COUNT: DiagnosticId =
+    DiagnosticId::from_u128(0x6e0b_7f04_4a3c_4d2e_9a51_3c8e_1f20_a003);
+
+#[derive(Default)]
+pub struct DebugOverlay {
+    visible: bool,
+}
+
+#[derive(Component)]
+pub struct DebugText;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin::default())
+            .init_resource::<DebugOverlay>()
+            .add_startup_system(setup_diagnostics)
+            .add_startup_system(create_debug_overlay)
+            .add_system(measure_entities)
+            .add_system(toggle_debug_overlay)
+            .add_system(update_debug_overlay);
+    }
+}
+
+pub fn setup_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(ENEMY_COUNT, "enemies", 20));
+    diagnostics.add(Diagnostic::new(BULLET_COUNT, "bullets", 20));
+    diagnostics.add(Diagnostic::new(PARTICLE_COUNT, "particles", 20));
+}
+
+pub fn measure_entities(
+    mut diagnostics: ResMut<Diagnostics>,
+    enemies: Query<&Enemy>,
+    bullets: Query<&Bullet>,
+    enemy_bullets: Query<&EnemyBullet>,
+    traces: Query<&Despawner>,
+    particles: Query<&Particle>,
+) {
+    diagnostics.add_measurement(ENEMY_COUNT, enemies.iter().len() as f64);
+    diagnostics.add_measurement(
+        BULLET_COUNT,
+        (bullets.iter().len() + enemy_bullets.iter().len()) as f64,
+    );
+    diagnostics.add_measurement(
+        PARTICLE_COUNT,
+        (traces.iter().len() + particles.iter().len()) as f64,
+    );
+}
+
+pub fn create_debug_overlay(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 14.,
+                    color: Color::YELLOW,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(5.0),
+                    left: Val::Px(5.0),
+                    ..default()
+                },
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(DebugText)
+        .insert(Name::new("Debug Overlay"));
+}
+
+pub fn toggle_debug_overlay(
+    keys: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut texts: Query<&mut Visibility, With<DebugText>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+    overlay.visible = !overlay.visible;
+    texts.iter_mut().for_each(|mut visibility| {
+        visibility.is_visible = overlay.visible;
+    });
+}
+
+fn diagnostic_value(diagnostics: &Diagnostics, id: DiagnosticId) -> f64 {
+    diagnostics
+        .get(id)
+        .and_then(|diagnostic| diagnostic.value())
+        .unwrap_or(0.0)
+}
+
+fn timer_line(name: &str, timer: &Timer) -> String {
+    format!(
+        "{}: {:.2}/{:.2}s\n",
+        name,
+        timer.elapsed_secs(),
+        timer.duration().as_secs_f32()
+    )
+}
+
+pub fn update_debug_overlay(
+    overlay: Res<DebugOverlay>,
+    diagnostics: Res<Diagnostics>,
+    difficulty: Res<DifficultyTimer>,
+    enemy_timer: Res<EnemyTimer>,
+    director: Res<Director>,
+    weapon_spawns: Res<WeaponSpawns>,
+    all: Query<Entity>,
+    warnings: Query<&SpawnEnemy>,
+    weapon_warnings: Query<&SpawnWeapon>,
+    weapons: Query<&Weapon>,
+    mut texts: Query<&mut Text, With<DebugText>>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.average())
+        .unwrap_or(0.0);
+    let mut report = format!("FPS: {:.0}\n", fps);
+    report += &format!("Entities: {}\n", all.iter().len());
+    report += &format!(
+        "Enemies: {:.0}\n",
+        diagnostic_value(&diagnostics, ENEMY_COUNT)
+    );
+    report += &format!(
+        "Bullets: {:.0}\n",
+        diagnostic_value(&diagnostics, BULLET_COUNT)
+    );
+    report += &format!(
+        "Particles: {:.0}\n",
+        diagnostic_value(&diagnostics, PARTICLE_COUNT)
+    );
+    report += &format!("Enemy warnings: {}\n", warnings.iter().len());
+    report += &format!("Weapon warnings: {}\n", weapon_warnings.iter().len());
+    report += &format!("Weapons: {}\n", weapons.iter().len());
+    report += &format!("Difficulty: {}\n", difficulty.difficulty);
+    report += &timer_line("Difficulty timer", &difficulty.timer);
+    report += &format!(
+        "Director: {:?} stress {:.2}\n",
+        director.phase(),
+        director.stress()
+    );
+    report += &format!(
+        "Spawn interval: {:.2}s\n",
+        enemy_timer.timer.duration().as_secs_f32()
+    );
+    report += &timer_line("Weapon timer", &weapon_spawns.timer);
+    texts.iter_mut().for_each(|mut text| {
+        text.sections[0].value = report.clone();
+    });
+}
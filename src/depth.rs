@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+// how far apart the layers sit. anything that needs to stand out inside its own layer can step
+// up or down by less than this without crossing into the next one
+const LAYER_SPACING: f32 = 10.0;
+
+// every world sprite is drawn on one of these, back to front. spawns take their depth from
+// here instead of picking a z, so a bullet can't end up behind the floor again
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum RenderLayer {
+    Background,
+    World,
+    Pickups,
+    Enemies,
+    Player,
+    Projectiles,
+    Particles,
+    // the ambient darkness and the glows over it
+    Lighting,
+    // crosshair, markers and popups drawn in the world but read like ui
+    Overlay,
+}
+
+impl RenderLayer {
+    pub fn z(self) -> f32 {
+        self as u8 as f32 * LAYER_SPACING
+    }
+
+    // a spawn position on this layer
+    pub fn at(self, position: Vec2) -> Vec3 {
+        position.extend(self.z())
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::clock::GameClock;
+use crate::combat::DamageEvent;
+use crate::spawning::SpawnRules;
+use crate::{Enemy, GameState, HeldItem, Player, ResetGame};
+
+const STRESS_PER_HIT: f32 = 0.35;
+const STRESS_DECAY: f32 = 0.08;
+const UNARMED_STRESS: f32 = 0.15;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum Phase {
+    BuildUp,
+    Peak,
+    Relax,
+}
+
+// paces the run like Left 4 Dead's director: push until the player is sweating, hold the peak
+// for a bit, then back off and let them breathe before building up again
+pub struct Director {
+    phase: Phase,
+    phase_timer: Timer,
+    stress: f32,
+    spawn_timer: Timer,
+    wave: u32,
+}
+
+// sent as the director pushes into a peak, each one is a new wave
+pub struct WaveStarted(pub u32);
+
+// sent when a wave's peak runs out with the player still standing
+pub struct WaveCleared(pub u32);
+
+impl Default for Director {
+    fn default() -> Self {
+        Director {
+            phase: Phase::BuildUp,
+            phase_timer: Timer::from_seconds(25.0, false),
+            stress: 0.0,
+            spawn_timer: Timer::from_seconds(2.0, true),
+            wave: 0,
+        }
+    }
+}
+
+impl Director {
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn wave(&self) -> u32 {
+        self.wave
+    }
+
+    pub fn stress(&self) -> f32 {
+        self.stress
+    }
+
+    fn enter(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.phase_timer = Timer::from_seconds(
+            match phase {
+                Phase::BuildUp => 25.0,
+                Phase::Peak => 8.0,
+                Phase::Relax => 6.0,
+            },
+            false,
+        );
+    }
+
+    // stretches the difficulty's spawn interval to fit the phase, true when a wave is due
+    pub fn spawn_ready(&mut self, delta: Duration, base: Duration) -> bool {
+        let multiplier = match self.phase {
+            Phase::BuildUp => 1.0,
+            Phase::Peak => 0.6,
+            Phase::Relax => 3.0,
+        };
+        let interval = base.mul_f32(multiplier);
+        if self.spawn_timer.duration() != interval {
+            self.spawn_timer.set_duration(interval);
+        }
+        self.spawn_timer.tick(delta);
+        self.spawn_timer.just_finished()
+    }
+}
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Director>()
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCleared>()
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(update_director))
+            .add_system(reset_director);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_director(
+    mut director: ResMut<Director>,
+    mut damage: EventReader<DamageEvent>,
+    rules: Res<SpawnRules>,
+    players: Query<&Player>,
+    enemies: Query<(&Enemy, &Transform)>,
+    held_items: Query<(), With<HeldItem>>,
+    clock: Res<GameClock>,
+    mut waves: EventWriter<WaveStarted>,
+    mut cleared: EventWriter<WaveCleared>,
+) {
+    let hit_count = damage.iter().filter(|hit| hit.hit_player()).count();
+    // proximity and low health set a floor, hits spike it and it bleeds off over time
+    let pressure = rules.pressure(players.iter().next(), &enemies);
+    let unarmed = if held_items.is_empty() {
+        UNARMED_STRESS
+    } else {
+        0.0
+    };
+    let decayed = director.stress - STRESS_DECAY * clock.delta_seconds();
+    director.stress = (decayed + STRESS_PER_HIT * hit_count as f32)
+        .max(pressure * 0.8 + unarmed)
+        .clamp(0.0, 1.0);
+
+    director.phase_timer.tick(clock.delta());
+    let next = match director.phase {
+        Phase::BuildUp if director.stress > 0.8 || director.phase_timer.finished() => {
+            Some(Phase::Peak)
+        }
+        Phase::Peak if director.phase_timer.finished() => Some(Phase::Relax),
+        Phase::Relax if director.phase_timer.finished() && director.stress < 0.3 => {
+            Some(Phase::BuildUp)
+        }
+        _default => None,
+    };
+    if let Some(phase) = next {
+        director.enter(phase);
+        match phase {
+            Phase::Peak => {
+                director.wave += 1;
+                waves.send(WaveStarted(director.wave));
+            }
+            Phase::Relax => cleared.send(WaveCleared(director.wave)),
+            Phase::BuildUp => {}
+        }
+    }
+}
+
+pub fn reset_director(mut director: ResMut<Director>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *director = Director::default();
+    }
+}
@@ -0,0 +1,266 @@
+use bevy::asset::HandleId;
+use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::render::texture::ImageSampler;
+use bevy::ui::UiScale;
+use bevy::window::{WindowMode, WindowResized};
+
+use crate::loading::GameAssets;
+use crate::menu::{despawn_screen, screen_root, spawn_button, spawn_title, MenuButton};
+use crate::settings::{spawn_slider_row, Settings, SliderLabel};
+use crate::smoothing::{shift, smooth_bodies, unsmooth_bodies};
+use crate::{GameState, MyCamera};
+
+// logical window sizes offered in the display menu
+pub const WINDOW_SIZES: [(u32, u32); 4] = [(960, 540), (1280, 720), (1600, 900), (1920, 1080)];
+// world units from the bottom of the view to the top, what the camera was always built around
+const VIEW_HEIGHT: f32 = 250.0;
+// the shape the arena is laid out for, letterboxing holds the view to it
+const VIEW_ASPECT: f32 = 16.0 / 9.0;
+pub const MIN_UI_SCALE: f32 = 0.75;
+pub const MAX_UI_SCALE: f32 = 1.5;
+
+// world units covered by one screen pixel, kept in step with the camera by fit_camera
+pub struct PixelGrid(f32);
+
+impl Default for PixelGrid {
+    fn default() -> Self {
+        PixelGrid(1.0)
+    }
+}
+
+// how far each drawn position was nudged onto the grid this frame, taken back off before the
+// next one the same way smoothing does
+#[derive(Default)]
+pub struct PixelSnaps(Vec<(Entity, Vec3)>);
+
+#[derive(Component)]
+pub struct DisplayScreen;
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Display).with_system(create_display_menu),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Display).with_system(despawn_screen::<DisplayScreen>),
+        )
+        .add_system(toggle_fullscreen)
+        .add_system(apply_window.after(toggle_fullscreen))
+        .add_system(fit_camera.after(apply_window))
+        .add_system(apply_ui_scale)
+        .init_resource::<PixelGrid>()
+        .init_resource::<PixelSnaps>()
+        .add_system(apply_crisp_pixels)
+        .add_system_to_stage(CoreStage::Last, snap_to_pixels.after(smooth_bodies))
+        .add_system_to_stage(CoreStage::First, unsnap_pixels.before(unsmooth_bodies));
+    }
+}
+
+pub fn create_display_menu(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.9)))
+        .insert(DisplayScreen)
+        .insert(Name::new("Display Menu"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Display");
+            spawn_slider_row(parent, &font_handle, SliderLabel::Fullscreen);
+            spawn_slider_row(parent, &font_handle, SliderLabel::WindowSize);
+            spawn_slider_row(parent, &font_handle, SliderLabel::PixelPerfect);
+            spawn_slider_row(parent, &font_handle, SliderLabel::CrispPixels);
+            spawn_slider_row(parent, &font_handle, SliderLabel::PixelHud);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Letterbox);
+            spawn_slider_row(parent, &font_handle, SliderLabel::UiScale);
+            spawn_button(parent, &font_handle, "Back", MenuButton::Back);
+        });
+}
+
+pub fn toggle_fullscreen(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    let alt = keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]);
+    if alt && keys.just_pressed(KeyCode::Return) {
+        settings.fullscreen = !settings.fullscreen;
+    }
+}
+
+pub fn apply_window(mut settings: ResMut<Settings>, mut windows: ResMut<Windows>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let window = match windows.get_primary_mut() {
+        Some(window) => window,
+        None => return,
+    };
+    if settings.fullscreen {
+        if window.mode() != WindowMode::BorderlessFullscreen {
+            // remembered so leaving fullscreen puts the window back where it was
+            if let Some(position) = window.position() {
+                settings.window_position = Some((position.x, position.y));
+            }
+            window.set_mode(WindowMode::BorderlessFullscreen);
+        }
+        return;
+    }
+    if window.mode() != WindowMode::Windowed {
+        window.set_mode(WindowMode::Windowed);
+        if let Some((x, y)) = settings.window_position {
+            window.set_position(IVec2::new(x, y));
+        }
+    }
+    let (width, height) = WINDOW_SIZES[settings.window_size.min(WINDOW_SIZES.len() - 1)];
+    if window.width() as u32 != width || window.height() as u32 != height {
+        window.set_resolution(width as f32, height as f32);
+    }
+}
+
+// every ui node and font size is laid out in pixels, bevy multiplies them all by this
+pub fn apply_ui_scale(settings: Res<Settings>, mut ui_scale: ResMut<UiScale>) {
+    let scale = settings.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE) as f64;
+    if settings.is_changed() && ui_scale.scale != scale {
+        ui_scale.scale = scale;
+    }
+}
+
+// whole multiples of the view height, never less than one
+fn pixel_scale(physical_height: u32) -> u32 {
+    ((physical_height as f32 / VIEW_HEIGHT) as u32).max(1)
+}
+
+// the view is rebuilt from the window every time either one changes, so any size shows the
+// same slice of the arena instead of cropping into it
+pub fn fit_camera(
+    settings: Res<Settings>,
+    mut resized: EventReader<WindowResized>,
+    windows: Res<Windows>,
+    mut grid: ResMut<PixelGrid>,
+    mut cameras: Query<(&mut Camera, &mut OrthographicProjection), With<MyCamera>>,
+    new_cameras: Query<(), Added<MyCamera>>,
+) {
+    let resized = resized.iter().count() > 0;
+    if !resized && !settings.is_changed() && new_cameras.is_empty() {
+        return;
+    }
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let physical = UVec2::new(window.physical_width(), window.physical_height());
+    let scale = pixel_scale(physical.y);
+    let view = match (settings.letterbox, settings.pixel_perfect) {
+        (false, _) => physical,
+        // the biggest whole multiple of the view that fits
+        (true, true) => {
+            let height = VIEW_HEIGHT as u32 * scale;
+            UVec2::new((height as f32 * VIEW_ASPECT) as u32, height).min(physical)
+        }
+        (true, false) => {
+            let width = (physical.y as f32 * VIEW_ASPECT) as u32;
+            if width <= physical.x {
+                UVec2::new(width, physical.y)
+            } else {
+                UVec2::new(physical.x, (physical.x as f32 / VIEW_ASPECT) as u32)
+            }
+        }
+    };
+    cameras.iter_mut().for_each(|(mut camera, mut projection)| {
+        camera.viewport = if view == physical {
+            None
+        } else {
+            Some(Viewport {
+                physical_position: (physical - view) / 2,
+                physical_size: view,
+                ..default()
+            })
+        };
+        grid.0 = if settings.pixel_perfect {
+            1.0 / scale as f32
+        } else {
+            VIEW_HEIGHT / view.y as f32
+        };
+        if settings.pixel_perfect {
+            // the camera draws into the post-process image, whose pixels are all real ones,
+            // so this keeps each world unit a whole number of them
+            projection.scaling_mode = ScalingMode::WindowSize;
+            projection.scale = 1.0 / scale as f32;
+        } else {
+            projection.scaling_mode = ScalingMode::FixedVertical(1.);
+            projection.scale = VIEW_HEIGHT;
+        }
+    });
+}
+
+// images load with the nearest sampler set in main. turning crisp pixels off swaps every loaded
+// image to linear, and anything loaded after that follows
+pub fn apply_crisp_pixels(
+    settings: Res<Settings>,
+    mut applied: Local<Option<bool>>,
+    mut events: EventReader<AssetEvent<Image>>,
+    assets: Option<Res<GameAssets>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let switched = *applied != Some(settings.crisp_pixels);
+    *applied = Some(settings.crisp_pixels);
+    let ids: Vec<HandleId> = if switched {
+        images.ids().collect()
+    } else if !settings.crisp_pixels {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                AssetEvent::Created { handle } => Some(handle.id),
+                _default => None,
+            })
+            .collect()
+    } else {
+        return;
+    };
+    ids.into_iter()
+        // the pixel score digits stay blocky either way
+        .filter(|id| {
+            assets
+                .as_ref()
+                .map_or(true, |assets| !assets.is_digit(&Handle::weak(*id)))
+        })
+        .for_each(|id| {
+            if let Some(image) = images.get_mut(&Handle::weak(id)) {
+                image.sampler_descriptor = if settings.crisp_pixels {
+                    ImageSampler::nearest()
+                } else {
+                    ImageSampler::linear()
+                };
+            }
+        });
+}
+
+// rounds every drawn sprite and the camera onto the screen pixel grid after smoothing has
+// placed them, so art never lands between pixels and shimmers as it moves
+pub fn snap_to_pixels(
+    settings: Res<Settings>,
+    grid: Res<PixelGrid>,
+    mut snaps: ResMut<PixelSnaps>,
+    mut drawn: Query<
+        (Entity, &mut GlobalTransform),
+        Or<(With<Sprite>, With<TextureAtlasSprite>, With<MyCamera>)>,
+    >,
+) {
+    if !settings.crisp_pixels || grid.0 <= 0.0 {
+        return;
+    }
+    drawn.iter_mut().for_each(|(entity, mut global)| {
+        let position = global.translation().truncate();
+        let offset = ((position / grid.0).round() * grid.0 - position).extend(0.0);
+        if offset != Vec3::ZERO {
+            shift(&mut global, offset);
+            snaps.0.push((entity, offset));
+        }
+    });
+}
+
+pub fn unsnap_pixels(mut snaps: ResMut<PixelSnaps>, mut globals: Query<&mut GlobalTransform>) {
+    snaps.0.drain(..).for_each(|(entity, offset)| {
+        if let Ok(mut global) = globals.get_mut(entity) {
+            shift(&mut global, -offset);
+        }
+    });
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::boss::Boss;
+use crate::combat::{airborne, Combatant, DamageEvent, DeathEvent, Slain};
+use crate::modes::GameRng;
+use crate::particles::spawn_burst;
+use crate::schedule::GameplaySet;
+use crate::weapons::weapon_stats;
+use crate::{Enemy, GameState, Weapons};
+
+// anything that blows up sends one of these, enemies inside the radius die
+pub struct ExplosionEvent {
+    pub position: Vec3,
+    pub radius: f32,
+    // enemy blasts catch the player too, hurt_player checks for these
+    pub hurts_player: bool,
+    // what set it off, when it was one of the player's
+    pub weapon: Option<Weapons>,
+}
+
+impl ExplosionEvent {
+    pub fn reaches(&self, position: Vec3) -> bool {
+        self.position.truncate().distance(position.truncate()) <= self.radius
+    }
+}
+
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExplosionEvent>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(detonate.after(GameplaySet::CollisionResponse)),
+        );
+    }
+}
+
+pub fn detonate(
+    mut commands: Commands,
+    mut explosions: EventReader<ExplosionEvent>,
+    enemies: Query<(Entity, &Transform, &Enemy, Option<&Velocity>), Without<Boss>>,
+    mut damage: EventWriter<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut slain: ResMut<Slain>,
+    mut rng: ResMut<GameRng>,
+) {
+    explosions.iter().for_each(|explosion| {
+        let position = explosion.position;
+        spawn_burst(&mut commands, &mut rng, position, Color::ORANGE, 16, 120.0);
+        enemies
+            .iter()
+            .filter(|(_, trans, ..)| explosion.reaches(trans.translation))
+            .for_each(|(entity, trans, enemy, velocity)| {
+                let victim = Combatant::Enemy(enemy.asset);
+                damage.send(DamageEvent {
+                    target: entity,
+                    victim,
+                    amount: explosion
+                        .weapon
+                        .map_or(1, |weapon| weapon_stats(weapon).damage),
+                    position: trans.translation,
+                    absorbed: false,
+                    source: Some(position),
+                    by_player: explosion.weapon.is_some(),
+                });
+                if !slain.claim(entity) {
+                    return;
+                }
+                deaths.send(DeathEvent {
+                    entity,
+                    victim,
+                    position: trans.translation,
+                    weapon: explosion.weapon,
+                    airborne: airborne(velocity),
+                    thrown: false,
+                });
+                commands.entity(entity).despawn_recursive();
+            });
+    });
+}
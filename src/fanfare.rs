@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use crate::clock::RealClock;
+use crate::loading::GameAssets;
+use crate::settings::Settings;
+use crate::tween::{Ease, Tween, TweenTarget};
+use crate::{GameState, ResetGame, Score, ScoreLabel};
+
+const MILESTONES: [i64; 3] = [1_000, 5_000, 10_000];
+const BANNER_SECONDS: f32 = 2.0;
+
+#[derive(Default)]
+pub struct ScoreFanfare {
+    last_score: i64,
+    milestones_reached: usize,
+}
+
+#[derive(Component)]
+pub struct MilestoneBanner(Timer);
+
+pub struct FanfarePlugin;
+
+impl Plugin for FanfarePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScoreFanfare>()
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(celebrate_score))
+            .add_system(expire_banners)
+            .add_system(reset_fanfare);
+    }
+}
+
+pub fn celebrate_score(
+    mut commands: Commands,
+    score: Res<Score>,
+    mut fanfare: ResMut<ScoreFanfare>,
+    mut labels: Query<&mut Tween, With<ScoreLabel>>,
+    assets: Res<GameAssets>,
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+) {
+    if !score.is_changed() {
+        return;
+    }
+    if score.score > fanfare.last_score {
+        labels.iter_mut().for_each(|mut tween| tween.restart());
+    }
+    fanfare.last_score = score.score;
+    let reached = MILESTONES
+        .iter()
+        .take_while(|milestone| score.score >= **milestone)
+        .count();
+    if reached <= fanfare.milestones_reached {
+        return;
+    }
+    fanfare.milestones_reached = reached;
+    spawn_banner(&mut commands, &assets, MILESTONES[reached - 1]);
+    audio.play_with_settings(
+        assets.milestone.clone(),
+        PlaybackSettings {
+            repeat: false,
+            volume: settings.sfx,
+            speed: 1.0,
+        },
+    );
+}
+
+fn spawn_banner(commands: &mut Commands, assets: &GameAssets, milestone: i64) {
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(MilestoneBanner(Timer::from_seconds(BANNER_SECONDS, false)))
+        .insert(Name::new("Milestone Banner"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        format!("{} POINTS!", milestone),
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 72.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(
+                    Tween::new(0.6, Ease::OutCubic)
+                        .with(TweenTarget::Scale(Vec3::splat(1.8), Vec3::ONE))
+                        .with(TweenTarget::TextColor(Color::GOLD, Color::WHITE))
+                        .real_time(),
+                );
+        });
+}
+
+pub fn expire_banners(
+    mut commands: Commands,
+    mut banners: Query<(Entity, &mut MilestoneBanner)>,
+    clock: Res<RealClock>,
+) {
+    banners.iter_mut().for_each(|(entity, mut banner)| {
+        banner.0.tick(clock.delta());
+        if banner.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    });
+}
+
+pub fn reset_fanfare(mut fanfare: ResMut<ScoreFanfare>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *fanfare = ScoreFanfare::default();
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use bevy::render::texture::ImageSampler;
+use bevy::window::WindowScaleFactorChanged;
+
+use crate::display::{MAX_UI_SCALE, MIN_UI_SCALE};
+use crate::loading::GameAssets;
+use crate::settings::Settings;
+use crate::{Score, ScoreLabel, ScoreParent};
+
+// size of each sprite in images/digits, in source pixels
+const DIGIT_WIDTH: f32 = 3.0;
+const DIGIT_HEIGHT: f32 = 5.0;
+// about the height of the figures in the 40px score text they stand in for
+const HUD_DIGIT_HEIGHT: f32 = 30.0;
+const MAX_DIGITS: usize = 12;
+
+// what a piece of ui text is for. each role has a base size, fitted to the ui scale so the
+// glyphs land on whole screen pixels
+#[derive(Component, Copy, Clone)]
+pub enum TextRole {
+    Title,
+    Label,
+    Hud,
+}
+
+impl TextRole {
+    pub fn base_size(self) -> f32 {
+        match self {
+            TextRole::Title => 60.,
+            TextRole::Label => 30.,
+            TextRole::Hud => 40.,
+        }
+    }
+}
+
+// the score drawn from the digit sprites instead of the font, when the setting is on
+#[derive(Component)]
+pub struct PixelScore;
+
+#[derive(Component)]
+pub struct PixelDigit(usize);
+
+pub struct FontPlugin;
+
+impl Plugin for FontPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(fit_text_sizes)
+            .add_system(crisp_digit_images)
+            .add_system(create_pixel_score)
+            .add_system(update_pixel_score.after(create_pixel_score));
+    }
+}
+
+// physical pixels per logical ui pixel, counting both the monitor and the ui scale setting
+fn render_scale(windows: &Windows, settings: &Settings) -> f32 {
+    let window = windows
+        .get_primary()
+        .map_or(1.0, |window| window.scale_factor() as f32);
+    window * settings.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+}
+
+// text is rasterized at its size times the render scale, and a fractional pixel size smears
+// every edge of every glyph
+pub fn snap_font_size(base: f32, scale: f32) -> f32 {
+    (base * scale).round().max(1.0) / scale
+}
+
+pub fn fit_text_sizes(
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    mut rescaled: EventReader<WindowScaleFactorChanged>,
+    new_texts: Query<(), Added<TextRole>>,
+    mut texts: Query<(&mut Text, &TextRole)>,
+) {
+    let rescaled = rescaled.iter().count() > 0;
+    if !rescaled && !settings.is_changed() && new_texts.is_empty() {
+        return;
+    }
+    let scale = render_scale(&windows, &settings);
+    texts.iter_mut().for_each(|(mut text, role)| {
+        let size = snap_font_size(role.base_size(), scale);
+        text.sections
+            .iter_mut()
+            .for_each(|section| section.style.font_size = size);
+    });
+}
+
+// the digits are a handful of pixels each and only read when every one stays a hard square
+pub fn crisp_digit_images(
+    mut events: EventReader<AssetEvent<Image>>,
+    assets: Option<Res<GameAssets>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
+    events.iter().for_each(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle }
+            if assets.is_digit(handle) =>
+        {
+            // setting it counts as a modification, only touch images that still need it
+            let needs_sampler = images.get(handle).map_or(false, |image| {
+                matches!(image.sampler_descriptor, ImageSampler::Default)
+            });
+            if needs_sampler {
+                if let Some(image) = images.get_mut(handle) {
+                    image.sampler_descriptor = ImageSampler::nearest();
+                }
+            }
+        }
+        _default => {}
+    });
+}
+
+pub fn create_pixel_score(
+    mut commands: Commands,
+    assets: Option<Res<GameAssets>>,
+    parents: Query<Entity, With<ScoreParent>>,
+    rows: Query<(), With<PixelScore>>,
+) {
+    if !rows.is_empty() {
+        return;
+    }
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
+    parents.iter().for_each(|parent| {
+        commands.entity(parent).with_children(|score_parent| {
+            score_parent
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(Color::NONE),
+                    style: Style {
+                        display: Display::None,
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(PixelScore)
+                .insert(Name::new("Pixel Score"))
+                .with_children(|row| {
+                    (0..MAX_DIGITS).for_each(|index| {
+                        row.spawn_bundle(ImageBundle {
+                            image: UiImage(assets.digit(0)),
+                            style: Style {
+                                display: Display::None,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .insert(PixelDigit(index));
+                    });
+                });
+        });
+    });
+}
+
+// swaps the score text for the digit sprites. if any sprite failed to load the text stays,
+// so a missing file never leaves the hud without a score
+#[allow(clippy::too_many_arguments)]
+pub fn update_pixel_score(
+    score: Res<Score>,
+    settings: Res<Settings>,
+    assets: Option<Res<GameAssets>>,
+    images: Res<Assets<Image>>,
+    windows: Res<Windows>,
+    new_digits: Query<(), Added<PixelDigit>>,
+    mut labels: Query<&mut Style, With<ScoreLabel>>,
+    mut rows: Query<&mut Style, (With<PixelScore>, Without<ScoreLabel>)>,
+    mut digits: Query<
+        (&PixelDigit, &mut UiImage, &mut Style),
+        (Without<PixelScore>, Without<ScoreLabel>),
+    >,
+) {
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
+    let changed = score.is_changed() || settings.is_changed() || images.is_changed();
+    if !changed && new_digits.is_empty() {
+        return;
+    }
+    let loaded = (0..10).all(|digit| images.get(&assets.digit(digit)).is_some());
+    let pixel_hud = settings.pixel_hud && loaded;
+    labels.iter_mut().for_each(|mut style| {
+        style.display = if pixel_hud {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    });
+    rows.iter_mut().for_each(|mut style| {
+        style.display = if pixel_hud {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    });
+    if !pixel_hud {
+        return;
+    }
+    let values: Vec<usize> = score
+        .score
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|digit| digit as usize)
+        .collect();
+    // each source pixel covers a whole number of screen pixels, whatever the ui scale
+    let scale = render_scale(&windows, &settings);
+    let pixel = (HUD_DIGIT_HEIGHT / DIGIT_HEIGHT * scale).round().max(1.0) / scale;
+    digits
+        .iter_mut()
+        .for_each(|(digit, mut image, mut style)| match values.get(digit.0) {
+            Some(value) => {
+                image.0 = assets.digit(*value);
+                style.display = Display::Flex;
+                style.size = Size::new(Val::Px(DIGIT_WIDTH * pixel), Val::Px(DIGIT_HEIGHT * pixel));
+                style.margin =
+                    UiRect::new(Val::Px(0.0), Val::Px(pixel), Val::Px(0.0), Val::Px(0.0));
+            }
+            None => style.display = Display::None,
+        });
+}
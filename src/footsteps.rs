@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use rand::Rng;
+
+use crate::clock::GameClock;
+use crate::loading::GameAssets;
+use crate::modes::CosmeticRng;
+use crate::schedule::GameplaySet;
+use crate::settings::Settings;
+use crate::{Dashing, GameState, Layers, Player};
+
+// slower than this is shuffling, not running
+const STEP_SPEED: f32 = 20.0;
+// seconds between steps while running
+const STEP_SECONDS: f32 = 0.28;
+const STEP_VOLUME: f32 = 0.4;
+// each step is pitched a little differently so a run doesn't sound like a metronome
+const STEP_PITCH_SPREAD: f32 = 0.12;
+
+#[derive(Component)]
+pub struct Footsteps {
+    timer: Timer,
+}
+
+impl Default for Footsteps {
+    fn default() -> Self {
+        Footsteps {
+            timer: Timer::from_seconds(STEP_SECONDS, true),
+        }
+    }
+}
+
+pub struct FootstepPlugin;
+
+impl Plugin for FootstepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(track_footsteps)
+                .with_system(step.label(GameplaySet::Presentation))
+                .with_system(whoosh.label(GameplaySet::Presentation)),
+        );
+    }
+}
+
+pub fn track_footsteps(mut commands: Commands, players: Query<Entity, Added<Player>>) {
+    players.iter().for_each(|entity| {
+        commands.entity(entity).insert(Footsteps::default());
+    });
+}
+
+pub fn step(
+    mut walkers: Query<(&Velocity, &Collisions, &mut Footsteps), Without<Dashing>>,
+    layers: Query<&CollisionLayers>,
+    mut rng: ResMut<CosmeticRng>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+    clock: Res<GameClock>,
+) {
+    walkers
+        .iter_mut()
+        .for_each(|(velocity, collisions, mut footsteps)| {
+            let speed = velocity.linear.x.abs();
+            let grounded = collisions.entities().any(|other| {
+                layers
+                    .get(other)
+                    .map_or(false, |layers| layers.contains_group(Layers::World))
+            });
+            if !grounded || speed < STEP_SPEED {
+                // the first step lands as soon as they start moving again
+                let ready = footsteps.timer.duration();
+                footsteps.timer.set_elapsed(ready);
+                return;
+            }
+            footsteps.timer.tick(clock.delta());
+            if !footsteps.timer.just_finished() {
+                return;
+            }
+            let pitch = 1.0 + rng.gen_range(-STEP_PITCH_SPREAD..STEP_PITCH_SPREAD);
+            audio.play_with_settings(
+                assets.footstep.clone(),
+                PlaybackSettings {
+                    repeat: false,
+                    volume: settings.sfx * STEP_VOLUME,
+                    speed: pitch,
+                },
+            );
+        });
+}
+
+pub fn whoosh(
+    dashers: Query<(), Added<Dashing>>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+) {
+    if dashers.is_empty() {
+        return;
+    }
+    audio.play_with_settings(
+        assets.dash.clone(),
+        PlaybackSettings {
+            repeat: false,
+            volume: settings.sfx,
+            speed: 1.0,
+        },
+    );
+}
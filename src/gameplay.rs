@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use heron::{prelude::*, PhysicsSteps};
+
+use crate::accessibility::Palette;
+use crate::actions::ActionsPlugin;
+use crate::assist::AssistPlugin;
+use crate::beacon::BeaconPlugin;
+use crate::beam::BeamPlugin;
+use crate::bomber::BomberPlugin;
+use crate::boss::BossPlugin;
+use crate::bullettime::BulletTimePlugin;
+use crate::clock::{tick_game_clock, tick_real_clock, GameClock, RealClock};
+use crate::collision::CollisionPlugin;
+use crate::combat::CombatPlugin;
+use crate::crosshair::AimPoint;
+use crate::director::DirectorPlugin;
+use crate::explosions::ExplosionPlugin;
+use crate::grenade::GrenadePlugin;
+use crate::hazards::HazardPlugin;
+use crate::homing::HomingPlugin;
+use crate::impacts::ImpactPlugin;
+use crate::killzone::KillZonePlugin;
+use crate::materials::MaterialsPlugin;
+use crate::modes::ModePlugin;
+use crate::navigation::NavigationPlugin;
+use crate::patterns::PatternPlugin;
+use crate::perks::PerkPlugin;
+use crate::pickups::PickupPlugin;
+use crate::prestige::PrestigePlugin;
+use crate::schedule::{FixedGameplayStage, GameplaySet, SchedulePlugin};
+use crate::scoreattack::ScoreAttackPlugin;
+use crate::scoring::ScoringPlugin;
+use crate::sequence::SequencePlugin;
+use crate::shield::ShieldPlugin;
+use crate::shielded::ShieldedPlugin;
+use crate::spatial::SpatialSound;
+use crate::spawning::SpawningPlugin;
+use crate::stats::StatsPlugin;
+use crate::style::StylePlugin;
+use crate::summoner::SummonerPlugin;
+use crate::sweep::SweepPlugin;
+use crate::tokens::AttackTokenPlugin;
+use crate::touch::TouchControls;
+use crate::transition::TransitionPlugin;
+use crate::{
+    apply_gravity_data, create_borders, equip_picked_up_weapon, fire_weapon, grab_weapon,
+    handle_bullet_collision, handle_chargers, handle_despawner, handle_invulnerable,
+    handle_jumpers, handle_shooter, handle_slides, handle_thrown_weapon_collision, hurt_player,
+    move_enemies, move_player, reset_world, spawn_enemies, spawn_warned, spawn_warned_enemy,
+    tick_timers, DashTimer, DifficultyTimer, EnemyTimer, GameState, GravityData, ResetGame, Score,
+    WeaponSpawns,
+};
+
+// everything a run needs to play out, shared by the game, the headless checks and raced
+// ghosts so none of them can drift from the others. nothing in here needs a window, a renderer
+// or a sound device. whoever adds it brings the assets, settings, save, run config and state
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(PhysicsPlugin::default())
+            .insert_resource(Gravity::from(Vec3::new(0.0, -70.1, 0.0)))
+            .insert_resource(PhysicsTime::new(1.))
+            .insert_resource(PhysicsSteps::from_steps_per_seconds(30.))
+            .add_plugin(SchedulePlugin)
+            .add_plugin(ActionsPlugin)
+            .add_plugin(CombatPlugin)
+            .add_plugin(ScoringPlugin)
+            .add_plugin(StylePlugin)
+            .add_plugin(CollisionPlugin)
+            .add_plugin(ImpactPlugin)
+            .add_plugin(TransitionPlugin)
+            .add_plugin(HomingPlugin)
+            .add_plugin(BeamPlugin)
+            .add_plugin(ExplosionPlugin)
+            .add_plugin(GrenadePlugin)
+            .add_plugin(SummonerPlugin)
+            .add_plugin(ShieldedPlugin)
+            .add_plugin(BomberPlugin)
+            .add_plugin(PatternPlugin)
+            .add_plugin(AttackTokenPlugin)
+            .add_plugin(NavigationPlugin)
+            .add_plugin(BossPlugin)
+            .add_plugin(SequencePlugin)
+            .add_plugin(AssistPlugin)
+            .add_plugin(ModePlugin)
+            .add_plugin(ScoreAttackPlugin)
+            .add_plugin(PrestigePlugin)
+            .add_plugin(SpawningPlugin)
+            .add_plugin(MaterialsPlugin)
+            .add_plugin(SweepPlugin)
+            .add_plugin(ShieldPlugin)
+            .add_plugin(BulletTimePlugin)
+            .add_plugin(DirectorPlugin)
+            .add_plugin(HazardPlugin)
+            .add_plugin(KillZonePlugin)
+            .add_plugin(PickupPlugin)
+            .add_plugin(BeaconPlugin)
+            .add_plugin(PerkPlugin)
+            .add_plugin(StatsPlugin)
+            .init_resource::<GameClock>()
+            .init_resource::<RealClock>()
+            .add_system_to_stage(CoreStage::PreUpdate, tick_game_clock)
+            .add_system_to_stage(CoreStage::PreUpdate, tick_real_clock)
+            .add_event::<ResetGame>()
+            // enemies shooting send these, only the game has anything to play them on
+            .add_event::<SpatialSound>()
+            .init_resource::<DifficultyTimer>()
+            .init_resource::<EnemyTimer>()
+            .init_resource::<GravityData>()
+            .init_resource::<WeaponSpawns>()
+            .init_resource::<Score>()
+            .init_resource::<DashTimer>()
+            // the touch, aim and palette plugins fill these in when there's a screen
+            .init_resource::<TouchControls>()
+            .init_resource::<AimPoint>()
+            .init_resource::<Palette>()
+            .add_startup_system(create_borders)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .label(GameplaySet::Input)
+                    .with_system(fire_weapon)
+                    .with_system(move_player)
+                    .with_system(grab_weapon),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .label(GameplaySet::Simulation)
+                    .after(GameplaySet::Input)
+                    .with_system(tick_timers)
+                    .with_system(spawn_enemies)
+                    .with_system(equip_picked_up_weapon)
+                    .with_system(spawn_warned)
+                    .with_system(spawn_warned_enemy),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .label(GameplaySet::CollisionResponse)
+                    .after(GameplaySet::Simulation)
+                    .with_system(handle_bullet_collision)
+                    .with_system(handle_thrown_weapon_collision)
+                    .with_system(hurt_player),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .label(GameplaySet::Presentation)
+                    .after(GameplaySet::CollisionResponse)
+                    .with_system(handle_invulnerable),
+            )
+            .add_system_set_to_stage(
+                FixedGameplayStage,
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(move_enemies)
+                    .with_system(handle_slides)
+                    .with_system(handle_jumpers)
+                    .with_system(handle_shooter)
+                    .with_system(handle_chargers),
+            )
+            .add_system(reset_world)
+            .add_system(apply_gravity_data)
+            .add_system(handle_despawner.after(GameplaySet::CollisionResponse));
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use libm::{cosf, sinf};
+use rand::Rng;
+
+use crate::clock::GameClock;
+use crate::collision::collision_layers;
+use crate::combat::DeathEvent;
+use crate::depth::RenderLayer;
+use crate::materials::{MaterialClass, PhysicsMaterials};
+use crate::modes::CosmeticRng;
+use crate::schedule::GameplaySet;
+use crate::{GameState, Layers, RunEntity};
+
+const GIB_COLORS: [Color; 3] = [
+    Color::rgb(0.55, 0.05, 0.05),
+    Color::rgb(0.4, 0.02, 0.04),
+    Color::rgb(0.7, 0.15, 0.1),
+];
+// below this a gib counts as resting
+const SETTLE_SPEED: f32 = 4.0;
+// how long a gib has to rest before it's baked into a decal
+const SETTLE_SECONDS: f32 = 0.3;
+// a gib stuck rattling against something gets baked anyway
+const GIB_LIFETIME: f32 = 3.0;
+// past this the oldest decals are cleared away, so a long run stays cheap
+const MAX_DECALS: usize = 150;
+
+// a chunk thrown out when an enemy dies, it bounces about until it settles
+#[derive(Component)]
+pub struct Gib {
+    settle: Timer,
+    lifetime: Timer,
+}
+
+// what's left of a gib once it's come to rest, a plain sprite with no body. the number is its
+// place in line, lowest goes first when there are too many
+#[derive(Component)]
+pub struct Decal(u64);
+
+#[derive(Default)]
+pub struct Decals {
+    next: u64,
+}
+
+pub struct GibPlugin;
+
+impl Plugin for GibPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Decals>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .label(GameplaySet::Presentation)
+                .after(GameplaySet::CollisionResponse)
+                .with_system(spawn_gibs)
+                .with_system(settle_gibs)
+                .with_system(trim_decals.after(settle_gibs)),
+        );
+    }
+}
+
+pub fn spawn_gibs(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    mut rng: ResMut<CosmeticRng>,
+    materials: Res<PhysicsMaterials>,
+) {
+    deaths
+        .iter()
+        .filter(|death| death.enemy().is_some())
+        .for_each(|death| {
+            let count = rng.gen_range(3..=5);
+            for _i in 0..count {
+                // thrown up and out, never straight down into the floor
+                let angle = rng.gen_range(0.2..std::f32::consts::PI - 0.2);
+                let speed = rng.gen_range(40.0..110.0);
+                let size = rng.gen_range(2.0..4.0);
+                let color = GIB_COLORS[rng.gen_range(0..GIB_COLORS.len())];
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color,
+                            custom_size: Some(Vec2::splat(size)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            RenderLayer::Particles.at(death.position.truncate()),
+                        ),
+                        ..default()
+                    })
+                    .insert(RigidBody::Dynamic)
+                    .insert(CollisionShape::Cuboid {
+                        half_extends: Vec3::new(size / 2.0, size / 2.0, 0.0),
+                        border_radius: None,
+                    })
+                    .insert(materials.get(MaterialClass::Gib))
+                    .insert(collision_layers(Layers::Debris))
+                    .insert(Velocity {
+                        linear: Vec3::new(cosf(angle) * speed, sinf(angle) * speed, 0.0),
+                        angular: AxisAngle::new(Vec3::Z, rng.gen_range(-8.0..8.0)),
+                    })
+                    .insert(Gib {
+                        settle: Timer::from_seconds(SETTLE_SECONDS, false),
+                        lifetime: Timer::from_seconds(GIB_LIFETIME, false),
+                    })
+                    .insert(Name::new("Gib"))
+                    .insert(RunEntity);
+            }
+        });
+}
+
+// swaps each gib that has come to rest for a decal where it lies
+pub fn settle_gibs(
+    mut commands: Commands,
+    mut decals: ResMut<Decals>,
+    clock: Res<GameClock>,
+    mut gibs: Query<(Entity, &mut Gib, &Velocity, &Transform, &Sprite)>,
+) {
+    gibs.iter_mut()
+        .for_each(|(entity, mut gib, velocity, trans, sprite)| {
+            gib.lifetime.tick(clock.delta());
+            if velocity.linear.length() < SETTLE_SPEED {
+                gib.settle.tick(clock.delta());
+            } else {
+                gib.settle.reset();
+            }
+            if !gib.settle.finished() && !gib.lifetime.finished() {
+                return;
+            }
+            commands.entity(entity).despawn();
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: sprite.clone(),
+                    transform: Transform {
+                        // just in front of the level it's lying on
+                        translation: trans
+                            .translation
+                            .truncate()
+                            .extend(RenderLayer::World.z() + 1.0),
+                        rotation: trans.rotation,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(Decal(decals.next))
+                .insert(Name::new("Decal"))
+                .insert(RunEntity);
+            decals.next += 1;
+        });
+}
+
+pub fn trim_decals(mut commands: Commands, decals: Query<(Entity, &Decal)>) {
+    let count = decals.iter().count();
+    if count <= MAX_DECALS {
+        return;
+    }
+    let mut oldest: Vec<(u64, Entity)> = decals
+        .iter()
+        .map(|(entity, decal)| (decal.0, entity))
+        .collect();
+    oldest.sort_unstable();
+    oldest
+        .iter()
+        .take(count - MAX_DECALS)
+        .for_each(|(_, entity)| commands.entity(*entity).despawn());
+}
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use libm::{cosf, sinf};
+
+use crate::actions::Actions;
+use crate::clock::GameClock;
+use crate::collision::collision_layers;
+use crate::depth::RenderLayer;
+use crate::explosions::ExplosionEvent;
+use crate::loading::GameAssets;
+use crate::materials::{MaterialClass, PhysicsMaterials};
+use crate::schedule::GameplaySet;
+use crate::{trigger_input, GameState, HeldItem, Layers, Player, RunEntity, Weapon, Weapons};
+
+const FUSE_SECONDS: f32 = 2.5;
+const GRENADE_BLAST_RADIUS: f32 = 48.0;
+const THROW_SPEED: f32 = 180.0;
+// a little extra lift so even a flat throw arcs
+const THROW_LIFT: f32 = 60.0;
+const FUSE_COLOR: Color = Color::rgb(1.0, 0.3, 0.2);
+
+// a held grenade, the fuse starts burning on the press and it leaves the hand on release
+#[derive(Component, Default)]
+pub struct Cooking {
+    fuse: Option<Timer>,
+}
+
+// a thrown grenade, carries whatever was left of the fuse
+#[derive(Component)]
+pub struct Fuse(Timer);
+
+pub struct GrenadePlugin;
+
+impl Plugin for GrenadePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(arm_grenades)
+                .with_system(cook_grenades.label(GameplaySet::Simulation))
+                .with_system(burn_fuses.label(GameplaySet::Simulation)),
+        );
+    }
+}
+
+pub fn arm_grenades(mut commands: Commands, held_items: Query<(Entity, &Weapon), Added<HeldItem>>) {
+    held_items
+        .iter()
+        .filter(|(_, weapon)| matches!(weapon.asset, Weapons::Grenade))
+        .for_each(|(entity, _)| {
+            commands.entity(entity).insert(Cooking::default());
+        });
+}
+
+// blinks faster the closer the fuse gets to the end
+fn blink(sprite: &mut Sprite, fuse: &Timer) {
+    let burnt = fuse.percent();
+    let lit = (burnt * burnt * 12.0).fract() < 0.5;
+    sprite.color = if lit { FUSE_COLOR } else { Color::WHITE };
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn cook_grenades(
+    mut commands: Commands,
+    mut held_items: Query<(Entity, &Transform, &mut Cooking, &mut Sprite), With<HeldItem>>,
+    players: Query<&Player>,
+    assets: Res<GameAssets>,
+    materials: Res<PhysicsMaterials>,
+    actions: Res<Actions>,
+    clock: Res<GameClock>,
+    mut explosions: EventWriter<ExplosionEvent>,
+) {
+    let looking_at = match players.iter().next() {
+        Some(player) => player.looking_at,
+        None => return,
+    };
+    let (pressed, held) = trigger_input(&actions);
+    held_items
+        .iter_mut()
+        .for_each(|(entity, trans, mut cooking, mut sprite)| {
+            if cooking.fuse.is_none() && pressed {
+                cooking.fuse = Some(Timer::from_seconds(FUSE_SECONDS, false));
+            }
+            let fuse = match cooking.fuse.as_mut() {
+                Some(fuse) => fuse,
+                None => return,
+            };
+            fuse.tick(clock.delta());
+            blink(&mut sprite, fuse);
+            // held too long, it goes off in the hand
+            if fuse.finished() {
+                explosions.send(ExplosionEvent {
+                    position: trans.translation,
+                    radius: GRENADE_BLAST_RADIUS,
+                    hurts_player: false,
+                    weapon: Some(Weapons::Grenade),
+                });
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+            if held {
+                return;
+            }
+            commands
+                .spawn_bundle(SpriteBundle {
+                    texture: assets.weapon(Weapons::Grenade),
+                    transform: Transform::from_translation(
+                        RenderLayer::Projectiles.at(trans.translation.truncate()),
+                    ),
+                    ..default()
+                })
+                .insert(RigidBody::Dynamic)
+                .insert(CollisionShape::Sphere { radius: 3.0 })
+                .insert(materials.get(MaterialClass::Grenade))
+                .insert(collision_layers(Layers::Debris))
+                .insert(Velocity::from_linear(Vec3::new(
+                    cosf(looking_at) * THROW_SPEED,
+                    sinf(looking_at) * THROW_SPEED + THROW_LIFT,
+                    0.0,
+                )))
+                .insert(Fuse(fuse.clone()))
+                .insert(Name::new("Grenade"))
+                .insert(RunEntity);
+            commands.entity(entity).despawn_recursive();
+        });
+}
+
+pub fn burn_fuses(
+    mut commands: Commands,
+    mut grenades: Query<(Entity, &Transform, &mut Fuse, &mut Sprite)>,
+    clock: Res<GameClock>,
+    mut explosions: EventWriter<ExplosionEvent>,
+) {
+    grenades
+        .iter_mut()
+        .for_each(|(entity, trans, mut fuse, mut sprite)| {
+            fuse.0.tick(clock.delta());
+            blink(&mut sprite, &fuse.0);
+            if fuse.0.finished() {
+                explosions.send(ExplosionEvent {
+                    position: trans.translation,
+                    radius: GRENADE_BLAST_RADIUS,
+                    hurts_player: false,
+                    weapon: Some(Weapons::Grenade),
+                });
+                commands.entity(entity).despawn_recursive();
+            }
+        });
+}
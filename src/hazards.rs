@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use rand::Rng;
+
+use crate::clock::GameClock;
+use crate::collision::collision_layers;
+use crate::depth::RenderLayer;
+use crate::loading::GameAssets;
+use crate::modes::GameRng;
+use crate::{Despawner, Enemy, GameState, GravityData, Layers, ResetGame, RunEntity};
+
+const WARNING_SECONDS: f32 = 3.0;
+const ACTIVE_SECONDS: f32 = 15.0;
+const CALM_SECONDS: f32 = 60.0;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum HazardKind {
+    Meteors,
+    Lava,
+    LowGravity,
+}
+
+fn hazard_name(kind: HazardKind) -> &'static str {
+    match kind {
+        HazardKind::Meteors => "Meteor shower",
+        HazardKind::Lava => "Lava flow",
+        HazardKind::LowGravity => "Low gravity",
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CycleStage {
+    Calm,
+    Warning(HazardKind),
+    Active(HazardKind),
+}
+
+pub struct HazardCycle {
+    stage: CycleStage,
+    timer: Timer,
+    meteor_timer: Timer,
+}
+
+impl Default for HazardCycle {
+    fn default() -> Self {
+        HazardCycle {
+            stage: CycleStage::Calm,
+            timer: Timer::from_seconds(CALM_SECONDS, false),
+            meteor_timer: Timer::from_seconds(0.4, true),
+        }
+    }
+}
+
+// anything carrying this hurts the player and kills enemies it touches
+#[derive(Component)]
+pub struct Hazard;
+
+#[derive(Component)]
+pub struct HazardLabel;
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HazardCycle>()
+            .add_startup_system(create_hazard_label)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(advance_hazard_cycle)
+                    .with_system(rain_meteors)
+                    .with_system(hazards_hit_enemies),
+            )
+            .add_system(update_hazard_label)
+            .add_system(reset_hazards);
+    }
+}
+
+fn hazard_collider(size: Vec2) -> (CollisionShape, CollisionLayers) {
+    (
+        CollisionShape::Cuboid {
+            half_extends: size.extend(0.0) / 2.0,
+            border_radius: None,
+        },
+        collision_layers(Layers::Hazard),
+    )
+}
+
+fn spawn_lava(commands: &mut Commands, rng: &mut GameRng) {
+    let side = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+    let size = Vec2::new(220.0, 8.0);
+    let (shape, layers) = hazard_collider(size);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 0.35, 0.0, 0.85),
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(
+                RenderLayer::World.at(Vec2::new(side * 110.0, -104.0)),
+            ),
+            ..default()
+        })
+        .insert(RigidBody::Sensor)
+        .insert(shape)
+        .insert(layers)
+        .insert(Collisions::default())
+        .insert(Hazard)
+        .insert(Despawner(Timer::from_seconds(ACTIVE_SECONDS, false)))
+        .insert(Name::new("Lava"))
+        .insert(RunEntity);
+}
+
+fn set_low_gravity(gravity: &mut GravityData, on: bool) {
+    gravity.gravity *= if on { 0.5 } else { 2.0 };
+}
+
+pub fn advance_hazard_cycle(
+    mut commands: Commands,
+    mut cycle: ResMut<HazardCycle>,
+    mut gravity: ResMut<GravityData>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<GameClock>,
+) {
+    cycle.timer.tick(clock.delta());
+    if !cycle.timer.finished() {
+        return;
+    }
+    let (stage, seconds) = match cycle.stage {
+        CycleStage::Calm => {
+            let kind = match rng.gen_range(0..3) {
+                0 => HazardKind::Meteors,
+                1 => HazardKind::Lava,
+                _default => HazardKind::LowGravity,
+            };
+            (CycleStage::Warning(kind), WARNING_SECONDS)
+        }
+        CycleStage::Warning(kind) => {
+            match kind {
+                HazardKind::Lava => spawn_lava(&mut commands, &mut rng),
+                HazardKind::LowGravity => set_low_gravity(&mut gravity, true),
+                HazardKind::Meteors => {}
+            }
+            (CycleStage::Active(kind), ACTIVE_SECONDS)
+        }
+        CycleStage::Active(kind) => {
+            if kind == HazardKind::LowGravity {
+                set_low_gravity(&mut gravity, false);
+            }
+            (CycleStage::Calm, CALM_SECONDS)
+        }
+    };
+    cycle.stage = stage;
+    cycle.timer = Timer::from_seconds(seconds, false);
+}
+
+pub fn rain_meteors(
+    mut commands: Commands,
+    mut cycle: ResMut<HazardCycle>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<GameClock>,
+) {
+    if cycle.stage != CycleStage::Active(HazardKind::Meteors) {
+        return;
+    }
+    cycle.meteor_timer.tick(clock.delta());
+    if !cycle.meteor_timer.just_finished() {
+        return;
+    }
+    let random_x = rng.gen_range(-200.0..200.0) as f32;
+    let size = Vec2::new(6.0, 6.0);
+    let (shape, layers) = hazard_collider(size);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.9, 0.4, 0.1),
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(
+                RenderLayer::Projectiles.at(Vec2::new(random_x, 130.0)),
+            ),
+            ..default()
+        })
+        .insert(RigidBody::Sensor)
+        .insert(shape)
+        .insert(layers)
+        .insert(Velocity::from_linear(Vec3::new(
+            rng.gen_range(-20.0..20.0),
+            -160.0,
+            0.0,
+        )))
+        .insert(Collisions::default())
+        .insert(Hazard)
+        .insert(Despawner(Timer::from_seconds(2.0, false)))
+        .insert(Name::new("Meteor"))
+        .insert(RunEntity);
+}
+
+pub fn hazards_hit_enemies(
+    mut commands: Commands,
+    hazards: Query<&Collisions, With<Hazard>>,
+    enemies: Query<Entity, With<Enemy>>,
+) {
+    hazards.iter().for_each(|collisions| {
+        collisions.entities().for_each(|other| {
+            if enemies.get(other).is_ok() {
+                commands.entity(other).despawn_recursive();
+            }
+        });
+    });
+}
+
+pub fn create_hazard_label(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 24.,
+                    color: Color::ORANGE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(70.0),
+                    left: Val::Percent(40.0),
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        })
+        .insert(HazardLabel)
+        .insert(Name::new("Hazard Label"));
+}
+
+pub fn update_hazard_label(
+    cycle: Res<HazardCycle>,
+    mut labels: Query<&mut Text, With<HazardLabel>>,
+) {
+    if !cycle.is_changed() {
+        return;
+    }
+    let remaining = cycle.timer.duration().as_secs_f32() - cycle.timer.elapsed_secs();
+    let message = match cycle.stage {
+        CycleStage::Calm => String::new(),
+        CycleStage::Warning(kind) => {
+            format!("{} in {:.0}!", hazard_name(kind), remaining.ceil())
+        }
+        CycleStage::Active(kind) => format!("{} {:.0}s", hazard_name(kind), remaining.ceil()),
+    };
+    labels.iter_mut().for_each(|mut label| {
+        label.sections[0].value = message.clone();
+    });
+}
+
+pub fn reset_hazards(
+    mut cycle: ResMut<HazardCycle>,
+    mut gravity: ResMut<GravityData>,
+    mut resets: EventReader<ResetGame>,
+) {
+    if resets.iter().count() == 0 {
+        return;
+    }
+    if cycle.stage == CycleStage::Active(HazardKind::LowGravity) {
+        set_low_gravity(&mut gravity, false);
+    }
+    *cycle = HazardCycle::default();
+}
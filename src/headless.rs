@@ -0,0 +1,304 @@
+use std::time::{Duration, Instant};
+
+use bevy::app::ScheduleRunnerSettings;
+use bevy::asset::AssetPlugin;
+use bevy::core::CorePlugin;
+use bevy::ecs::event::{Events, ManualEventReader};
+use bevy::hierarchy::HierarchyPlugin;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::transform::TransformPlugin;
+use heron::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::characters::CharacterDef;
+use crate::collision::{collision_layers, CollisionRules};
+use crate::combat::{Combatant, DeathEvent};
+use crate::director::Director;
+use crate::gameplay::GameplayPlugin;
+use crate::impacts::Projectile;
+use crate::loading::{AtlasRegistry, GameAssets};
+use crate::modes::{GameMode, Modifiers, RunConfig};
+use crate::save::SaveData;
+use crate::settings::Settings;
+use crate::spawning::SpawnRules;
+use crate::transition::Transition;
+use crate::{
+    Bullet, Despawner, Enemy, EnemyBullet, GameState, Layers, Player, ResetGame, RunEntity, Score,
+    SpawnEnemy, Weapons,
+};
+
+// the same fights every time, so a failure can be run again and looked at
+const CHECK_SEED: u64 = 0x4EAD_1E55_2022;
+const TICK_SECONDS: f64 = 1.0 / 30.0;
+// three minutes of game time, long enough for the director to cycle and a few runs to end
+const CHECK_TICKS: u32 = 30 * 180;
+const KILL_EVERY_TICKS: u32 = 15;
+const HIT_PLAYER_EVERY_TICKS: u32 = 90;
+// how long the harness sits on a dead player before starting the next run, the fade to the
+// game over screen has to be done by then
+const RESTART_TICKS: u32 = 30;
+
+// --headless-check drives the gameplay core with no window, renderer or files, throwing
+// synthetic input and fights at it and checking the rules below hold after every tick. the
+// game is a binary, so tests/headless.rs runs it this way and reads the exit code
+pub fn run_checks() -> Result<(), String> {
+    let mut app = headless_app();
+    let mut rng = StdRng::seed_from_u64(CHECK_SEED);
+    let mut deaths = ManualEventReader::<DeathEvent>::default();
+    let mut checks = Invariants::default();
+    let start = Instant::now();
+    app.world.send_event(ResetGame);
+    for tick in 0..CHECK_TICKS {
+        let reset = checks.dead_for >= RESTART_TICKS;
+        if reset {
+            // the same way the retry button does it
+            app.world.send_event(ResetGame);
+            app.world
+                .resource_mut::<Transition>()
+                .fade_to(GameState::Playing);
+        }
+        press_random_keys(&mut app, &mut rng);
+        if tick % KILL_EVERY_TICKS == 0 {
+            shoot_an_enemy(&mut app, &mut rng);
+        }
+        if tick % HIT_PLAYER_EVERY_TICKS == 0 {
+            hit_the_player(&mut app);
+        }
+        app.world
+            .resource_mut::<Time>()
+            .update_with_instant(start + Duration::from_secs_f64(tick as f64 * TICK_SECONDS));
+        app.update();
+        checks
+            .check(&mut app, &mut deaths, reset)
+            .map_err(|err| format!("tick {}: {}", tick, err))?;
+    }
+    if checks.runs_ended == 0 {
+        return Err(String::from(
+            "the player never died, the fights aren't reaching them",
+        ));
+    }
+    if checks.waves_started == 0 {
+        return Err(String::from("the director never started a wave"));
+    }
+    if checks.best_score <= 0 {
+        return Err(String::from("no run scored anything"));
+    }
+    Ok(())
+}
+
+// a standard run on the check seed, so every check fights the same fights
+fn headless_app() -> App {
+    simulation_app(RunConfig {
+        mode: GameMode::Standard,
+        seed: CHECK_SEED,
+        fixed_seed: true,
+        day: 0,
+        modifiers: Modifiers::default(),
+        new_game_plus: false,
+        assisted: false,
+    })
+}
+
+// the game's own gameplay plugin, with placeholders for everything that draws, plays sound or
+// reads files. the checks here and raced ghosts both run on it
+pub fn simulation_app(run_config: RunConfig) -> App {
+    let mut app = App::new();
+    // MinimalPlugins minus its time plugin, which would put the wall clock back over the time
+    // the harness steps by hand every tick
+    app.insert_resource(ScheduleRunnerSettings::run_once())
+        .add_plugin(CorePlugin::default())
+        .init_resource::<Time>()
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(AssetPlugin)
+        .add_asset::<Image>()
+        .add_asset::<TextureAtlas>()
+        .add_asset::<Font>()
+        .add_asset::<AudioSource>()
+        .add_asset::<AudioSink>()
+        .add_asset::<CharacterDef>()
+        .init_resource::<Audio>()
+        .insert_resource(run_config.rng())
+        .insert_resource(run_config)
+        .insert_resource(GameAssets::placeholder())
+        .insert_resource(AtlasRegistry::placeholder())
+        .insert_resource(Settings::default())
+        .insert_resource(SaveData::default())
+        .add_state(GameState::Playing)
+        .add_plugin(GameplayPlugin);
+    app
+}
+
+// what has to hold after every tick, whatever the player and enemies got up to
+#[derive(Default)]
+struct Invariants {
+    score: i64,
+    player_deaths: usize,
+    runs_ended: usize,
+    // ticks since the player went down, counting stops once the next run starts
+    dead_for: u32,
+    wave: u32,
+    // across every run, so the end of the check can tell the game got anywhere at all
+    waves_started: u32,
+    best_score: i64,
+}
+
+impl Invariants {
+    fn check(
+        &mut self,
+        app: &mut App,
+        deaths: &mut ManualEventReader<DeathEvent>,
+        reset: bool,
+    ) -> Result<(), String> {
+        if reset {
+            *self = Invariants {
+                runs_ended: self.runs_ended,
+                waves_started: self.waves_started,
+                best_score: self.best_score,
+                ..default()
+            };
+        }
+        let world = &mut app.world;
+        let cap = world.resource::<SpawnRules>().soft_cap;
+        let enemies = world
+            .query_filtered::<(), Or<(With<Enemy>, With<SpawnEnemy>)>>()
+            .iter(world)
+            .count();
+        if enemies > cap {
+            return Err(format!(
+                "{} enemies and warnings, the cap is {}",
+                enemies, cap
+            ));
+        }
+        let score = world.resource::<Score>().score;
+        if score < self.score {
+            return Err(format!("score went down from {} to {}", self.score, score));
+        }
+        self.score = score;
+        self.best_score = self.best_score.max(score);
+        let wave = world.resource::<Director>().wave();
+        if wave < self.wave {
+            return Err(format!("the wave went back from {} to {}", self.wave, wave));
+        }
+        self.waves_started += wave - self.wave;
+        self.wave = wave;
+        let player_deaths = deaths
+            .iter(world.resource::<Events<DeathEvent>>())
+            .filter(|death| death.victim == Combatant::Player)
+            .count();
+        self.player_deaths += player_deaths;
+        if self.player_deaths > 1 {
+            return Err(format!(
+                "the player died {} times in one run",
+                self.player_deaths
+            ));
+        }
+        let players = world.query::<&Player>().iter(world).count();
+        match (self.player_deaths, players) {
+            (0, 1) => {}
+            (1, 0) => {
+                if self.dead_for == 0 {
+                    self.runs_ended += 1;
+                }
+                self.dead_for += 1;
+                let state = world.resource::<State<GameState>>().current();
+                if self.dead_for == RESTART_TICKS && *state != GameState::GameOver {
+                    return Err(format!(
+                        "still on {:?} {} ticks after the player died",
+                        state, RESTART_TICKS
+                    ));
+                }
+            }
+            // despawning happens through commands, so the body is still there on the tick it dies
+            (1, 1) if player_deaths == 1 => {}
+            (deaths, players) => {
+                return Err(format!(
+                    "{} players alive after {} deaths this run",
+                    players, deaths
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+fn press_random_keys(app: &mut App, rng: &mut StdRng) {
+    let mut events = app.world.resource_mut::<Events<KeyboardInput>>();
+    // 1 picks the first perk whenever a choice comes up
+    [
+        KeyCode::A,
+        KeyCode::D,
+        KeyCode::Space,
+        KeyCode::LShift,
+        KeyCode::Key1,
+    ]
+    .iter()
+    .filter(|_| rng.gen_bool(0.1))
+    .for_each(|key| {
+        let state = if rng.gen_bool(0.5) {
+            ButtonState::Pressed
+        } else {
+            ButtonState::Released
+        };
+        events.send(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(*key),
+            state,
+        });
+    });
+}
+
+// a player bullet dropped right on top of a random enemy, the normal collision does the rest
+fn shoot_an_enemy(app: &mut App, rng: &mut StdRng) {
+    let world = &mut app.world;
+    let targets: Vec<Vec3> = world
+        .query_filtered::<&Transform, With<Enemy>>()
+        .iter(world)
+        .map(|trans| trans.translation)
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+    let position = targets[rng.gen_range(0..targets.len())];
+    world
+        .spawn()
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ))
+        .insert(RigidBody::Sensor)
+        .insert(CollisionShape::Sphere { radius: 4.0 })
+        .insert(collision_layers(Layers::Projectiles))
+        .insert(Collisions::default())
+        .insert(Projectile::new(Weapons::Base))
+        .insert(Bullet {
+            timer: Timer::from_seconds(0.5, false),
+        })
+        .insert(Despawner(Timer::from_seconds(0.5, false)))
+        .insert(RunEntity);
+}
+
+fn hit_the_player(app: &mut App) {
+    let world = &mut app.world;
+    let position = match world.query::<&Player>().iter(world).next() {
+        Some(player) => player.location,
+        None => return,
+    };
+    let layers = world
+        .resource::<CollisionRules>()
+        .layers(Layers::EnemyProjectiles);
+    world
+        .spawn()
+        .insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ))
+        .insert(RigidBody::Sensor)
+        .insert(CollisionShape::Sphere { radius: 4.0 })
+        .insert(layers)
+        .insert(Collisions::default())
+        .insert(EnemyBullet { origin: position })
+        .insert(Despawner(Timer::from_seconds(0.5, false)))
+        .insert(RunEntity);
+}
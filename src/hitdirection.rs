@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use libm::atan2f;
+
+use crate::accessibility::Palette;
+use crate::combat::DamageEvent;
+use crate::depth::RenderLayer;
+use crate::tween::{Ease, Tween, TweenTarget};
+use crate::{GameState, MyCamera, RunEntity};
+
+const ARC_SECONDS: f32 = 0.6;
+// kept this far inside the edge of the view so it's never clipped
+const ARC_INSET: f32 = 8.0;
+
+pub struct HitDirectionPlugin;
+
+impl Plugin for HitDirectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing).with_system(point_at_attackers),
+        );
+    }
+}
+
+// a hit from something the player can't see flashes an arc on the edge of the view facing it
+pub fn point_at_attackers(
+    mut commands: Commands,
+    mut damage: EventReader<DamageEvent>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<MyCamera>>,
+    palette: Res<Palette>,
+) {
+    let (camera, projection) = match cameras.iter().next() {
+        Some(camera) => camera,
+        None => return,
+    };
+    let center = camera.translation.truncate();
+    let half = Vec2::new(projection.right, projection.top) * projection.scale;
+    let mut arc_color = palette.danger;
+    arc_color.set_a(0.9);
+    damage
+        .iter()
+        .filter(|hit| hit.hit_player())
+        .filter_map(|hit| hit.source.map(|source| (hit.position, source)))
+        .for_each(|(victim, source)| {
+            let offset = source.truncate() - center;
+            if offset.x.abs() <= half.x && offset.y.abs() <= half.y {
+                return;
+            }
+            let direction = (source - victim).truncate().normalize_or_zero();
+            if direction == Vec2::ZERO {
+                return;
+            }
+            // walk out from the middle of the view until the direction meets an edge
+            let inner = half - Vec2::splat(ARC_INSET);
+            let reach = (inner.x / direction.x.abs()).min(inner.y / direction.y.abs());
+            let edge = center + direction * reach;
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: arc_color,
+                        custom_size: Some(Vec2::new(3.0, 40.0)),
+                        ..default()
+                    },
+                    transform: Transform {
+                        translation: edge.extend(RenderLayer::Overlay.z() + 2.0),
+                        rotation: Quat::from_rotation_z(atan2f(direction.y, direction.x)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(
+                    Tween::new(ARC_SECONDS, Ease::Linear)
+                        .with(TweenTarget::SpriteAlpha(0.9, 0.0))
+                        .despawn_when_done(),
+                )
+                .insert(Name::new("Hit Direction"))
+                .insert(RunEntity);
+        });
+}
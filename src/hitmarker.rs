@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use libm::atan2f;
+
+use crate::combat::{DamageEvent, DeathEvent};
+use crate::crosshair::AimPoint;
+use crate::depth::RenderLayer;
+use crate::loading::GameAssets;
+use crate::schedule::GameplaySet;
+use crate::settings::Settings;
+use crate::tween::{Ease, Tween, TweenTarget};
+use crate::{GameState, RunEntity};
+
+const MARKER_SECONDS: f32 = 0.15;
+// how far each tick sits from the middle of the crosshair
+const MARKER_OFFSET: f32 = 5.0;
+const KILL_COLOR: Color = Color::rgb(1.0, 0.25, 0.2);
+// kills tick higher so they can be told apart without looking
+const HIT_PITCH: f32 = 1.0;
+const KILL_PITCH: f32 = 1.5;
+
+pub struct HitMarkerPlugin;
+
+impl Plugin for HitMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(mark_hits.after(GameplaySet::CollisionResponse)),
+        );
+    }
+}
+
+// one marker per frame at most, a shotgun blast shouldn't stack a dozen ticks. only the player's
+// own hits count, enemies catching each other in a blast aren't the player's doing
+pub fn mark_hits(
+    mut commands: Commands,
+    mut damage: EventReader<DamageEvent>,
+    mut deaths: EventReader<DeathEvent>,
+    aim: Res<AimPoint>,
+    assets: Res<GameAssets>,
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+) {
+    let hits: Vec<Entity> = damage
+        .iter()
+        .filter(|hit| hit.by_player)
+        .map(|hit| hit.target)
+        .collect();
+    if hits.is_empty() {
+        deaths.clear();
+        return;
+    }
+    let kills = deaths
+        .iter()
+        .filter(|death| death.enemy().is_some() && hits.contains(&death.entity))
+        .count();
+    let (color, pitch) = if kills > 0 {
+        (KILL_COLOR, KILL_PITCH)
+    } else {
+        (Color::WHITE, HIT_PITCH)
+    };
+    audio.play_with_settings(
+        assets.hit_tick.clone(),
+        PlaybackSettings {
+            repeat: false,
+            volume: settings.sfx,
+            speed: pitch,
+        },
+    );
+    // four short diagonal ticks around the crosshair
+    [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)]
+        .iter()
+        .for_each(|(x, y)| {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::new(3.0, 1.0)),
+                        ..default()
+                    },
+                    transform: Transform {
+                        translation: (aim.0 + Vec2::new(*x, *y) * MARKER_OFFSET)
+                            .extend(RenderLayer::Overlay.z() + 1.0),
+                        rotation: Quat::from_rotation_z(atan2f(*y, *x)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(
+                    Tween::new(MARKER_SECONDS, Ease::Linear)
+                        .with(TweenTarget::SpriteAlpha(1.0, 0.0))
+                        .despawn_when_done(),
+                )
+                .insert(Name::new("Hit Marker"))
+                .insert(RunEntity);
+        });
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use libm::atan2f;
+
+use crate::clock::GameClock;
+use crate::crosshair::AimPoint;
+use crate::depth::RenderLayer;
+use crate::schedule::GameplaySet;
+use crate::{Enemy, GameState, HeldItem, Weapon, Weapons};
+
+// radians per second a homing rocket can turn
+const TURN_RATE: f32 = 4.0;
+const BRACKET_SIZE: f32 = 20.0;
+
+// steers the projectile toward the enemy picked when it was fired
+#[derive(Component)]
+pub struct Homing {
+    pub target: Entity,
+}
+
+#[derive(Component)]
+pub struct LockBracket;
+
+pub struct HomingPlugin;
+
+impl Plugin for HomingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(create_lock_bracket).add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(steer_homing.label(GameplaySet::Simulation))
+                .with_system(update_lock_bracket.label(GameplaySet::Presentation)),
+        );
+    }
+}
+
+// the enemy closest to where the player is pointing
+pub fn lock_target(
+    aim: &AimPoint,
+    enemies: &Query<(Entity, &Transform), With<Enemy>>,
+) -> Option<Entity> {
+    enemies
+        .iter()
+        .map(|(entity, trans)| (entity, trans.translation.truncate().distance(aim.0)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+pub fn steer_homing(
+    mut commands: Commands,
+    mut rockets: Query<(Entity, &Homing, &mut Transform, &mut Velocity)>,
+    targets: Query<&GlobalTransform, With<Enemy>>,
+    clock: Res<GameClock>,
+) {
+    rockets
+        .iter_mut()
+        .for_each(|(entity, homing, mut trans, mut velocity)| {
+            // once the target's gone it just flies straight
+            let target = match targets.get(homing.target) {
+                Ok(target) => target.translation().truncate(),
+                Err(_) => {
+                    commands.entity(entity).remove::<Homing>();
+                    return;
+                }
+            };
+            let heading = velocity.linear.truncate();
+            let speed = heading.length();
+            let current = atan2f(heading.y, heading.x);
+            let to_target = target - trans.translation.truncate();
+            let wanted = atan2f(to_target.y, to_target.x);
+            let gap = Vec2::from_angle(current).angle_between(Vec2::from_angle(wanted));
+            let max_turn = TURN_RATE * clock.delta_seconds();
+            let angle = current + gap.clamp(-max_turn, max_turn);
+            velocity.linear = (Vec2::from_angle(angle) * speed).extend(0.0);
+            trans.rotation = Quat::from_rotation_z(angle);
+        });
+}
+
+pub fn create_lock_bracket(mut commands: Commands) {
+    let half = BRACKET_SIZE / 2.0;
+    commands
+        .spawn_bundle(SpatialBundle {
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(LockBracket)
+        .insert(Name::new("Lock Bracket"))
+        .with_children(|parent| {
+            // two strokes per corner
+            [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)]
+                .iter()
+                .for_each(|(x, y)| {
+                    let corner = Vec2::new(x * half, y * half);
+                    [
+                        (Vec2::new(5.0, 1.0), Vec2::new(-x * 2.0, 0.0)),
+                        (Vec2::new(1.0, 5.0), Vec2::new(0.0, -y * 2.0)),
+                    ]
+                    .iter()
+                    .for_each(|(size, offset)| {
+                        parent.spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                color: Color::rgba(1.0, 0.3, 0.2, 0.9),
+                                custom_size: Some(*size),
+                                ..default()
+                            },
+                            transform: Transform::from_translation((corner + *offset).extend(0.0)),
+                            ..default()
+                        });
+                    });
+                });
+        });
+}
+
+// sits over the locked enemy while a rocket is chasing it, or over the one that
+// would be picked while a homing rocket is held
+pub fn update_lock_bracket(
+    aim: Res<AimPoint>,
+    rockets: Query<&Homing>,
+    held_items: Query<&Weapon, With<HeldItem>>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+    mut brackets: Query<(&mut Transform, &mut Visibility), (With<LockBracket>, Without<Enemy>)>,
+) {
+    let holding = held_items
+        .iter()
+        .any(|weapon| matches!(weapon.asset, Weapons::HomingRocket));
+    let target = rockets
+        .iter()
+        .map(|homing| homing.target)
+        .find(|target| enemies.get(*target).is_ok())
+        .or_else(|| {
+            if holding {
+                lock_target(&aim, &enemies)
+            } else {
+                None
+            }
+        })
+        .and_then(|target| enemies.get(target).ok());
+    brackets
+        .iter_mut()
+        .for_each(|(mut trans, mut visibility)| match target {
+            Some((_, target_trans)) => {
+                visibility.is_visible = true;
+                trans.translation = target_trans
+                    .translation
+                    .truncate()
+                    .extend(RenderLayer::Overlay.z());
+            }
+            None => visibility.is_visible = false,
+        });
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::depth::RenderLayer;
+use crate::explosions::ExplosionEvent;
+use crate::modes::GameRng;
+use crate::particles::spawn_burst;
+use crate::schedule::GameplaySet;
+use crate::weapons::weapon_stats;
+use crate::{Despawner, GameState, Layers, RunEntity, Weapons};
+
+const ROCKET_BLAST_RADIUS: f32 = 36.0;
+const DECAL_SECONDS: f32 = 4.0;
+
+// what a player bullet does when it meets the level
+#[derive(Component)]
+pub struct Projectile {
+    weapon: Weapons,
+    // what each hit takes off, straight from the weapon's stats
+    damage: i8,
+    walls_left: u8,
+    // the wall we're currently passing through, so one wall only costs one pierce
+    inside_wall: Option<Entity>,
+}
+
+impl Projectile {
+    pub fn weapon(&self) -> Weapons {
+        self.weapon
+    }
+
+    pub fn damage(&self) -> i8 {
+        self.damage
+    }
+
+    pub fn new(weapon: Weapons) -> Self {
+        Projectile {
+            weapon,
+            damage: weapon_stats(weapon).damage,
+            walls_left: match weapon {
+                Weapons::Sniper => 1,
+                _ => 0,
+            },
+            inside_wall: None,
+        }
+    }
+}
+
+pub struct ImpactPlugin;
+
+impl Plugin for ImpactPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(handle_world_impacts.label(GameplaySet::CollisionResponse)),
+        );
+    }
+}
+
+pub fn handle_world_impacts(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &Transform, &Collisions, &mut Projectile)>,
+    layers: Query<&CollisionLayers>,
+    mut explosions: EventWriter<ExplosionEvent>,
+    mut rng: ResMut<GameRng>,
+) {
+    projectiles
+        .iter_mut()
+        .for_each(|(entity, trans, collisions, mut projectile)| {
+            let wall = collisions.entities().find(|other| {
+                layers
+                    .get(*other)
+                    .map_or(false, |layers| layers.contains_group(Layers::World))
+            });
+            let wall = match wall {
+                Some(wall) => wall,
+                None => {
+                    projectile.inside_wall = None;
+                    return;
+                }
+            };
+            if projectile.inside_wall == Some(wall) {
+                return;
+            }
+            let position = trans.translation;
+            spawn_impact(&mut commands, &mut rng, position);
+            if projectile.walls_left > 0 {
+                projectile.walls_left -= 1;
+                projectile.inside_wall = Some(wall);
+                return;
+            }
+            if matches!(projectile.weapon, Weapons::Rocket | Weapons::HomingRocket) {
+                explosions.send(ExplosionEvent {
+                    position,
+                    radius: ROCKET_BLAST_RADIUS,
+                    hurts_player: false,
+                    weapon: Some(projectile.weapon),
+                });
+            }
+            commands.entity(entity).despawn_recursive();
+        });
+}
+
+// sparks plus a scorch mark that fades out after a few seconds
+fn spawn_impact(commands: &mut Commands, rng: &mut GameRng, position: Vec3) {
+    spawn_burst(commands, rng, position, Color::GRAY, 4, 40.0);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.1, 0.1, 0.1, 0.8),
+                custom_size: Some(Vec2::splat(3.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(RenderLayer::World.at(position.truncate())),
+            ..default()
+        })
+        .insert(Despawner(Timer::from_seconds(DECAL_SECONDS, false)))
+        .insert(Name::new("Impact Decal"))
+        .insert(RunEntity);
+}
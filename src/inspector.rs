@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::plugin::InspectorWindows;
+use bevy_inspector_egui::{InspectorPlugin, WorldInspectorParams, WorldInspectorPlugin};
+
+use crate::GravityData;
+
+// only built with the debug feature, both panels start hidden until F1 is pressed
+pub struct InspectorTogglePlugin;
+
+impl Plugin for InspectorTogglePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WorldInspectorParams {
+            enabled: false,
+            ..default()
+        })
+        .add_plugin(WorldInspectorPlugin::new())
+        .add_plugin(InspectorPlugin::<GravityData>::new())
+        .add_startup_system(hide_gravity_panel)
+        .add_system(toggle_inspector);
+    }
+}
+
+pub fn hide_gravity_panel(mut windows: ResMut<InspectorWindows>) {
+    windows.window_data_mut::<GravityData>().visible = false;
+}
+
+pub fn toggle_inspector(
+    keys: Res<Input<KeyCode>>,
+    mut params: ResMut<WorldInspectorParams>,
+    mut windows: ResMut<InspectorWindows>,
+) {
+    if !keys.just_pressed(KeyCode::F1) {
+        return;
+    }
+    params.enabled = !params.enabled;
+    windows.window_data_mut::<GravityData>().visible = params.enabled;
+}
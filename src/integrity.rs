@@ -0,0 +1,109 @@
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::modes::RunConfig;
+use crate::prestige::ELITE_KILL_MULTIPLIER;
+use crate::replay::Replay;
+use crate::save::RecordedRun;
+use crate::scoring::{SURVIVAL_TICK, WAVE_CLEAR_BONUS};
+use crate::style::max_style_per_kill;
+
+// baked in at build time so the service can tell its own builds apart from hand made requests.
+// anyone with the binary can dig it out, this only keeps casual tampering off the jam board.
+// builds without one send their runs unsigned rather than signing with a key anyone can read
+const SIGNING_KEY: Option<&str> = option_env!("LEADERBOARD_KEY");
+// mirrors the difficulty timer, it starts at one step every 5 seconds and speeds up from 25
+const FIRST_STEP: f32 = 5.0;
+const FASTEST_STEP: f32 = 0.1;
+const SPEEDUP_FROM: i64 = 25;
+// the pickup cooldown caps how often a weapon can be grabbed
+const GRAB_COOLDOWN: f32 = 0.4;
+// a wave is a peak and a rest, the director can't turn them around any quicker
+const MIN_WAVE_SPACING: f32 = 14.0;
+// far more than anyone manages, even clearing a packed screen with a rocket
+const MAX_KILLS_PER_SECOND: f32 = 10.0;
+// a slow frame can come in just after the recording stops
+const REPLAY_SLACK: f32 = 1.0;
+
+// the highest the difficulty can have climbed after this long
+fn max_difficulty(survived: f32) -> i64 {
+    let mut difficulty = 1;
+    let mut step = FIRST_STEP;
+    let mut elapsed = step;
+    while elapsed <= survived {
+        difficulty += 1;
+        if difficulty >= SPEEDUP_FROM {
+            step = (step - 0.1).max(FASTEST_STEP);
+        }
+        elapsed += step;
+    }
+    difficulty
+}
+
+// every kill, gem and survival tick is worth the difficulty at the time, a grab twice that and
+// a wave clear ten times, so a run can't score more than all of them landing at the final
+// difficulty. style bonuses are flat, at most every one of them on every kill. the run's mode
+// decides how fast the difficulty could have climbed, and new game plus multiplies the lot
+pub fn check_run(run: &RecordedRun, replay: &Replay, run_config: &RunConfig) -> Result<(), String> {
+    let score = run.score;
+    let survived = run.survived;
+    if !survived.is_finite() || survived < 0.0 || score < 0 {
+        return Err(String::from("negative or broken run values"));
+    }
+    let kills = run.kills as f32;
+    if kills > survived * MAX_KILLS_PER_SECOND + MAX_KILLS_PER_SECOND {
+        return Err(format!("{} kills in {:.0} seconds", kills, survived));
+    }
+    let difficulty = max_difficulty(survived * run_config.mode.difficulty_ramp());
+    let grabs = (survived / GRAB_COOLDOWN) as i64 + 1;
+    let ticks = (survived / SURVIVAL_TICK) as i64;
+    let waves = (survived / MIN_WAVE_SPACING) as i64 + 1;
+    // each kill drops a gem on top of its own points, and any of them could have been an elite
+    let per_kill = ELITE_KILL_MULTIPLIER + 1;
+    let max_score = ((kills as i64 * per_kill + grabs * 2 + ticks + waves * WAVE_CLEAR_BONUS)
+        * difficulty
+        + kills as i64 * max_style_per_kill())
+        * run_config.score_multiplier();
+    if score > max_score {
+        return Err(format!(
+            "{} points is over the {} possible",
+            score, max_score
+        ));
+    }
+    // bullet time stretches real time past game time, never the other way round
+    let duration = replay.duration();
+    if survived > duration + REPLAY_SLACK {
+        return Err(format!(
+            "{:.0} seconds survived on {:.0} seconds of input",
+            survived, duration
+        ));
+    }
+    if replay
+        .frames
+        .iter()
+        .any(|frame| !frame.looking_at.is_finite() || !frame.aim.is_finite())
+    {
+        return Err(String::from("broken aim in the replay"));
+    }
+    // a fixed seed run has to be the one everyone else played
+    if run_config.fixed_seed && replay.seed != run_config.seed {
+        return Err(String::from("the replay was played on another seed"));
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn replay_hash(replay: &str) -> String {
+    hex(&Sha256::digest(replay.as_bytes()))
+}
+
+// hmac over the fields in a fixed order, the service rebuilds the same line to check it.
+// none when this build has no key, the service treats those runs as unverified
+pub fn sign(fields: &[&str]) -> Option<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SIGNING_KEY?.as_bytes()).ok()?;
+    mac.update(fields.join("|").as_bytes());
+    Some(hex(&mac.finalize().into_bytes()))
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::combat::{DeathEvent, PickupEvent, PickupKind};
+use crate::director::WaveStarted;
+use crate::loading::GameAssets;
+use crate::versus::behavior_name;
+use crate::weapons::weapon_stats;
+use crate::{DifficultyTimer, GameState, ResetGame};
+
+const FEED_LINES: usize = 4;
+const LINE_SECONDS: f32 = 3.0;
+// the last stretch of a line's life is spent fading out
+const FADE_SECONDS: f32 = 0.75;
+
+#[derive(Component)]
+pub struct KillFeed;
+
+#[derive(Component)]
+pub struct FeedLine(Timer);
+
+pub struct KillFeedPlugin;
+
+impl Plugin for KillFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(create_kill_feed)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(post_events)
+                    .with_system(fade_lines),
+            )
+            .add_system(clear_kill_feed);
+    }
+}
+
+pub fn create_kill_feed(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                // newest line sits at the bottom
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(KillFeed)
+        .insert(Name::new("Kill Feed"));
+}
+
+pub fn post_events(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    mut pickups: EventReader<PickupEvent>,
+    mut waves: EventReader<WaveStarted>,
+    feeds: Query<(Entity, Option<&Children>), With<KillFeed>>,
+    assets: Res<GameAssets>,
+    difficulty: Res<DifficultyTimer>,
+) {
+    let mut posted: Vec<String> = Vec::new();
+    waves
+        .iter()
+        .for_each(|WaveStarted(wave)| posted.push(format!("Wave {} incoming", wave)));
+    // kills and guns are worth the same as score_kills and score_pickups hand out
+    deaths.iter().for_each(|death| {
+        if let Some(behavior) = death.enemy() {
+            posted.push(format!(
+                "{} eliminated +{}",
+                behavior_name(behavior),
+                difficulty.difficulty
+            ));
+        }
+    });
+    pickups.iter().for_each(|pickup| match pickup.kind {
+        PickupKind::Weapon(weapon) => posted.push(format!(
+            "{} acquired +{}",
+            weapon_stats(weapon).name,
+            2 * difficulty.difficulty
+        )),
+        PickupKind::Shield => posted.push(String::from("Shield picked up")),
+        PickupKind::ScoreGem(_) => {}
+    });
+    if posted.is_empty() {
+        return;
+    }
+    let (feed, children) = match feeds.iter().next() {
+        Some(feed) => feed,
+        None => return,
+    };
+    // oldest lines make room for new ones
+    let shown = children.map_or(0, |children| children.len());
+    let overflow = (shown + posted.len()).saturating_sub(FEED_LINES);
+    children
+        .into_iter()
+        .flat_map(|children| children.iter())
+        .take(overflow)
+        .for_each(|line| commands.entity(*line).despawn_recursive());
+    let skipped = posted.len().saturating_sub(FEED_LINES);
+    commands.entity(feed).with_children(|parent| {
+        posted.into_iter().skip(skipped).for_each(|text| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        text,
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 18.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(FeedLine(Timer::from_seconds(LINE_SECONDS, false)));
+        });
+    });
+}
+
+pub fn fade_lines(
+    mut commands: Commands,
+    mut lines: Query<(Entity, &mut FeedLine, &mut Text)>,
+    clock: Res<GameClock>,
+) {
+    lines.iter_mut().for_each(|(entity, mut line, mut text)| {
+        line.0.tick(clock.delta());
+        if line.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+        let left = line.0.duration().as_secs_f32() - line.0.elapsed_secs();
+        text.sections[0]
+            .style
+            .color
+            .set_a((left / FADE_SECONDS).min(1.0));
+    });
+}
+
+pub fn clear_kill_feed(
+    mut commands: Commands,
+    lines: Query<Entity, With<FeedLine>>,
+    mut resets: EventReader<ResetGame>,
+) {
+    if resets.iter().count() > 0 {
+        lines.iter().for_each(|line| {
+            commands.entity(line).despawn_recursive();
+        });
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use rand::Rng;
+
+use crate::boss::Boss;
+use crate::clock::GameClock;
+use crate::combat::{return_to_spawn, wound_player, Combatant, DamageEvent, DeathEvent};
+use crate::impacts::Projectile;
+use crate::modes::GameRng;
+use crate::{Enemy, EnemyBullet, GameState, Player, RunEntity};
+
+pub const PLAYER_SPAWN: Vec3 = Vec3::new(0., -92., 1.0);
+// how far past the visible playfield a shot may travel before it's culled
+const PROJECTILE_MARGIN: f32 = 48.0;
+// enemies knocked this far behind the walls are put back through the spawn zone
+const ENEMY_MARGIN: f32 = 24.0;
+const ENEMY_SPAWN_Y: f32 = -92.0;
+const ENEMY_SPAWN_HALF_WIDTH: f32 = 200.0;
+// an enemy that stays this close to one spot for this long is wedged somewhere
+const STUCK_DISTANCE: f32 = 16.0;
+const STUCK_SECONDS: f32 = 15.0;
+
+// anything that ends up past these has fallen out of the arena
+pub struct ArenaBounds {
+    min: Vec2,
+    max: Vec2,
+    // the walled area the camera shows, shots never need to leave it
+    playfield_min: Vec2,
+    playfield_max: Vec2,
+}
+
+impl Default for ArenaBounds {
+    fn default() -> Self {
+        ArenaBounds {
+            min: Vec2::new(-320.0, -180.0),
+            max: Vec2::new(320.0, 400.0),
+            playfield_min: Vec2::new(-256.0, -136.0),
+            playfield_max: Vec2::new(300.0, 160.0),
+        }
+    }
+}
+
+impl ArenaBounds {
+    fn contains(&self, position: Vec3) -> bool {
+        let position = position.truncate();
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+
+    fn near_playfield(&self, position: Vec3, margin: f32) -> bool {
+        let position = position.truncate();
+        position.cmpge(self.playfield_min - margin).all()
+            && position.cmple(self.playfield_max + margin).all()
+    }
+}
+
+// where an enemy was last seen making progress, and how long it's been since
+#[derive(Component)]
+pub struct Watchdog {
+    anchor: Vec2,
+    timer: Timer,
+}
+
+impl Watchdog {
+    fn new(position: Vec3) -> Self {
+        Watchdog {
+            anchor: position.truncate(),
+            timer: Timer::from_seconds(STUCK_SECONDS, false),
+        }
+    }
+}
+
+pub struct KillZonePlugin;
+
+impl Plugin for KillZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ArenaBounds>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(rescue_fallen_player)
+                .with_system(despawn_fallen_entities)
+                .with_system(cull_stray_projectiles)
+                .with_system(watch_new_enemies)
+                .with_system(return_strayed_enemies)
+                .with_system(despawn_stuck_enemies),
+        );
+    }
+}
+
+// falling out costs a heart and puts the player back at spawn
+pub fn rescue_fallen_player(
+    mut commands: Commands,
+    bounds: Res<ArenaBounds>,
+    mut players: Query<(Entity, &mut Player, &mut Transform, &mut Velocity)>,
+    mut damage: EventWriter<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+) {
+    players
+        .iter_mut()
+        .for_each(|(entity, mut player, mut trans, mut velocity)| {
+            if bounds.contains(trans.translation) {
+                return;
+            }
+            damage.send(DamageEvent {
+                target: entity,
+                victim: Combatant::Player,
+                amount: 1,
+                position: trans.translation,
+                absorbed: false,
+                source: None,
+                by_player: false,
+            });
+            let position = trans.translation;
+            if wound_player(&mut commands, &mut deaths, entity, &mut player, position) {
+                return;
+            }
+            return_to_spawn(&mut player, &mut trans, &mut velocity);
+        });
+}
+
+pub fn despawn_fallen_entities(
+    mut commands: Commands,
+    bounds: Res<ArenaBounds>,
+    entities: Query<(Entity, &GlobalTransform), (With<RunEntity>, Without<Player>)>,
+) {
+    entities.iter().for_each(|(entity, trans)| {
+        if !bounds.contains(trans.translation()) {
+            commands.entity(entity).despawn_recursive();
+        }
+    });
+}
+
+// bullets that missed everything would otherwise fly on until their timers ran out
+pub fn cull_stray_projectiles(
+    mut commands: Commands,
+    bounds: Res<ArenaBounds>,
+    projectiles: Query<(Entity, &Transform), Or<(With<Projectile>, With<EnemyBullet>)>>,
+) {
+    projectiles.iter().for_each(|(entity, trans)| {
+        if !bounds.near_playfield(trans.translation, PROJECTILE_MARGIN) {
+            commands.entity(entity).despawn_recursive();
+        }
+    });
+}
+
+pub fn watch_new_enemies(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Transform), (Added<Enemy>, Without<Boss>)>,
+) {
+    enemies.iter().for_each(|(entity, trans)| {
+        commands
+            .entity(entity)
+            .insert(Watchdog::new(trans.translation));
+    });
+}
+
+// slipped behind the walls but still inside the arena, they'd never come back on their own
+pub fn return_strayed_enemies(
+    bounds: Res<ArenaBounds>,
+    mut rng: ResMut<GameRng>,
+    mut enemies: Query<(&mut Transform, &mut Velocity, &mut Watchdog), With<Enemy>>,
+) {
+    enemies
+        .iter_mut()
+        .for_each(|(mut trans, mut velocity, mut watchdog)| {
+            if bounds.near_playfield(trans.translation, ENEMY_MARGIN) {
+                return;
+            }
+            let x = rng.gen_range(-ENEMY_SPAWN_HALF_WIDTH..ENEMY_SPAWN_HALF_WIDTH);
+            trans.translation = Vec3::new(x, ENEMY_SPAWN_Y, trans.translation.z);
+            *velocity = Velocity::default();
+            *watchdog = Watchdog::new(trans.translation);
+        });
+}
+
+// wedged in a wall or standing idle for too long, removed without a kill so nothing is scored
+pub fn despawn_stuck_enemies(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut enemies: Query<(Entity, &Transform, &mut Watchdog), With<Enemy>>,
+) {
+    enemies
+        .iter_mut()
+        .for_each(|(entity, trans, mut watchdog)| {
+            if trans.translation.truncate().distance(watchdog.anchor) > STUCK_DISTANCE {
+                *watchdog = Watchdog::new(trans.translation);
+                return;
+            }
+            watchdog.timer.tick(clock.delta());
+            if watchdog.timer.finished() {
+                commands.entity(entity).despawn_recursive();
+            }
+        });
+}
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::loading::GameAssets;
+use crate::modes::CosmeticRng;
+use crate::particles::spawn_burst;
+use crate::schedule::GameplaySet;
+use crate::settings::Settings;
+use crate::tween::{Ease, Tween, TweenTarget};
+use crate::{Behavior, Enemy, GameState, Layers, Player};
+
+// falling slower than this when touching down doesn't count as a landing
+const LANDING_SPEED: f32 = 40.0;
+const DUST_COLOR: Color = Color::rgb(0.75, 0.7, 0.6);
+
+// remembers last frame's fall so touching down can be spotted
+#[derive(Component, Default)]
+pub struct Lands {
+    last_vertical: f32,
+}
+
+pub struct LandingPlugin;
+
+impl Plugin for LandingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(track_landers)
+                .with_system(land.label(GameplaySet::Presentation)),
+        );
+    }
+}
+
+// the player and jumpers are the only things that fall far enough to be worth it
+pub fn track_landers(
+    mut commands: Commands,
+    players: Query<Entity, Added<Player>>,
+    enemies: Query<(Entity, &Enemy), Added<Enemy>>,
+) {
+    players
+        .iter()
+        .chain(
+            enemies
+                .iter()
+                .filter(|(_, enemy)| enemy.asset == Behavior::Jumper)
+                .map(|(entity, _)| entity),
+        )
+        .for_each(|entity| {
+            commands.entity(entity).insert(Lands::default());
+        });
+}
+
+pub fn land(
+    mut commands: Commands,
+    mut landers: Query<(Entity, &Transform, &Velocity, &Collisions, &mut Lands)>,
+    layers: Query<&CollisionLayers>,
+    mut rng: ResMut<CosmeticRng>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+) {
+    landers
+        .iter_mut()
+        .for_each(|(entity, trans, velocity, collisions, mut lands)| {
+            let falling = lands.last_vertical;
+            lands.last_vertical = velocity.linear.y;
+            if falling > -LANDING_SPEED || velocity.linear.y < 0.0 {
+                return;
+            }
+            let grounded = collisions.entities().any(|other| {
+                layers
+                    .get(other)
+                    .map_or(false, |layers| layers.contains_group(Layers::World))
+            });
+            if !grounded {
+                return;
+            }
+            // harder landings squash further
+            let squash = (-falling / 400.0).clamp(0.15, 0.4);
+            commands
+                .entity(entity)
+                .insert(Tween::new(0.18, Ease::OutCubic).with(TweenTarget::Scale(
+                    Vec3::new(1.0 + squash, 1.0 - squash, 1.0),
+                    Vec3::ONE,
+                )));
+            let feet = trans.translation - Vec3::new(0.0, 6.0, 0.0);
+            spawn_burst(&mut commands, &mut rng, feet, DUST_COLOR, 6, 30.0);
+            audio.play_with_settings(
+                assets.land.clone(),
+                PlaybackSettings {
+                    repeat: false,
+                    volume: settings.sfx * squash * 2.0,
+                    speed: 1.0,
+                },
+            );
+        });
+}
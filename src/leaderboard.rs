@@ -0,0 +1,652 @@
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::achievements::{spawn_toast, Toast};
+use crate::integrity::{check_run, replay_hash, sign};
+use crate::loading::GameAssets;
+use crate::menu::{despawn_screen, spawn_labeled_button, BUTTON_COLOR, HOVERED_COLOR};
+use crate::modes::{GameMode, RunConfig};
+use crate::names::{allowed_char, sanitize_name, validate_name, MAX_NAME_LENGTH};
+use crate::profiles::{ProfileId, Profiles};
+use crate::race::Race;
+use crate::replay::{decode_replay, encode_replay, spawn_ghost, Replay, ReplayRecorder};
+use crate::save::{RecordedRun, SaveData};
+use crate::settings::Settings;
+use crate::stats::RunStats;
+use crate::{GameState, ResetGame, Score};
+
+#[derive(Serialize)]
+struct ScoreSubmission {
+    bucket: String,
+    name: String,
+    score: i64,
+    replay: String,
+    assisted: bool,
+    survived: f32,
+    kills: usize,
+    score_track: Vec<i64>,
+    replay_hash: String,
+    // null from builds made without a signing key
+    signature: Option<String>,
+}
+
+// what the service has on record as this player's best, from any device
+#[derive(Deserialize)]
+pub struct PlayerBest {
+    score: i64,
+}
+
+#[derive(Deserialize)]
+pub struct TopRun {
+    name: String,
+    score: i64,
+    replay: String,
+}
+
+// the leaderboard is optional, without LEADERBOARD_URL nothing leaves the machine
+pub struct Leaderboard {
+    url: Option<String>,
+}
+
+impl Leaderboard {
+    pub fn from_env() -> Self {
+        Leaderboard {
+            url: std::env::var("LEADERBOARD_URL").ok(),
+        }
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+}
+
+// the name last submitted with, or the profile's own name until it has submitted. never the
+// os user, which every profile on the machine shares
+pub fn board_name(settings: &Settings, profiles: &Profiles) -> String {
+    settings
+        .player_name
+        .clone()
+        .unwrap_or_else(|| sanitize_name(profiles.active_name()))
+}
+
+// what's typed into the game over screen, sent once it passes validation
+#[derive(Default)]
+pub struct NameEntry {
+    input: String,
+    error: Option<&'static str>,
+    submitted: bool,
+    // score attack sends the run without waiting for a click, the box is only there if that fails
+    forced: bool,
+}
+
+#[derive(Component)]
+pub struct NameEntryScreen;
+
+#[derive(Component)]
+pub struct NameField;
+
+#[derive(Component)]
+pub struct NameErrorLabel;
+
+#[derive(Component)]
+pub struct SubmitScoreButton;
+
+// the board it was asked for, a restart in another mode or with other modifiers races another one
+pub struct PendingTopRun {
+    bucket: String,
+    receiver: Mutex<Receiver<TopRun>>,
+}
+
+// remembers which board and profile were asked about, either can change before the answer lands
+pub struct PendingPlayerBest {
+    bucket: String,
+    profile: ProfileId,
+    receiver: Mutex<Receiver<PlayerBest>>,
+}
+
+// kept around so every restart can race the same ghost again
+pub struct TopRunGhost {
+    bucket: String,
+    name: String,
+    replay: Replay,
+}
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Leaderboard::from_env())
+            .init_resource::<NameEntry>()
+            .add_startup_system(fetch_top_run)
+            .add_system(fetch_player_best)
+            .add_system(receive_top_run)
+            .add_system(reconcile_player_best)
+            .add_system(respawn_ghost)
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver)
+                    .with_system(bank_best_score)
+                    .with_system(create_name_entry),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(type_name)
+                    .with_system(submit_score.after(type_name))
+                    .with_system(update_name_entry.after(submit_score)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(despawn_screen::<NameEntryScreen>),
+            );
+    }
+}
+
+pub fn fetch_top_run(
+    mut commands: Commands,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+) {
+    start_top_run_fetch(&mut commands, &leaderboard, &run_config);
+}
+
+// asked again whenever a restart lands on another board, a fetch still in flight is replaced
+pub fn start_top_run_fetch(
+    commands: &mut Commands,
+    leaderboard: &Leaderboard,
+    run_config: &RunConfig,
+) {
+    let url = match &leaderboard.url {
+        Some(url) => format!("{}/scores/top", url),
+        None => return,
+    };
+    let bucket = run_config.leaderboard_bucket();
+    let (sender, receiver) = channel();
+    let board = bucket.clone();
+    thread::spawn(move || {
+        let top_run = ureq::get(&url)
+            .query("bucket", &board)
+            .call()
+            .map_err(|err| err.to_string())
+            .and_then(|response| {
+                response
+                    .into_json::<TopRun>()
+                    .map_err(|err| err.to_string())
+            });
+        match top_run {
+            Ok(top_run) => {
+                let _ = sender.send(top_run);
+            }
+            Err(err) => warn!("could not fetch the top run: {}", err),
+        }
+    });
+    commands.insert_resource(PendingTopRun {
+        bucket,
+        receiver: Mutex::new(receiver),
+    });
+}
+
+// asked at startup and again whenever the mode or modifiers move to another board
+pub fn fetch_player_best(
+    mut commands: Commands,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+    settings: Res<Settings>,
+    save: Res<SaveData>,
+    mut asked: Local<Option<String>>,
+) {
+    if !run_config.is_changed() {
+        return;
+    }
+    let bucket = run_config.leaderboard_bucket();
+    if asked.as_ref() == Some(&bucket) {
+        return;
+    }
+    *asked = Some(bucket);
+    start_best_fetch(
+        &mut commands,
+        &leaderboard,
+        &run_config,
+        &settings,
+        save.profile,
+    );
+}
+
+// asked again whenever the profile changes, a fetch still in flight for the last one is replaced
+pub fn start_best_fetch(
+    commands: &mut Commands,
+    leaderboard: &Leaderboard,
+    run_config: &RunConfig,
+    settings: &Settings,
+    profile: ProfileId,
+) {
+    let url = match &leaderboard.url {
+        Some(url) => format!("{}/scores/best", url),
+        None => return,
+    };
+    // a profile that has never submitted has no best on the board, asking under a stand-in
+    // name could come back with someone else's
+    let name = match &settings.player_name {
+        Some(name) => name.clone(),
+        None => return,
+    };
+    let bucket = run_config.leaderboard_bucket();
+    let (sender, receiver) = channel();
+    let board = bucket.clone();
+    thread::spawn(move || {
+        let best = ureq::get(&url)
+            .query("bucket", &board)
+            .query("name", &name)
+            .call()
+            .map_err(|err| err.to_string())
+            .and_then(|response| {
+                response
+                    .into_json::<PlayerBest>()
+                    .map_err(|err| err.to_string())
+            });
+        match best {
+            Ok(best) => {
+                let _ = sender.send(best);
+            }
+            Err(err) => warn!("could not fetch the player's best: {}", err),
+        }
+    });
+    commands.insert_resource(PendingPlayerBest {
+        bucket,
+        profile,
+        receiver: Mutex::new(receiver),
+    });
+}
+
+pub fn recorded_run(score: i64, stats: &RunStats, recorder: &ReplayRecorder) -> RecordedRun {
+    RecordedRun {
+        score,
+        survived: stats.survived,
+        kills: stats.kills.total(),
+        replay: encode_replay(&recorder.replay),
+        score_track: recorder.scores.clone(),
+    }
+}
+
+// each board keeps its own best, a modded or prestige run never stands in for a standard one
+pub fn bank_best_score(
+    score: Res<Score>,
+    run_config: Res<RunConfig>,
+    stats: Res<RunStats>,
+    recorder: Res<ReplayRecorder>,
+    mut save: ResMut<SaveData>,
+) {
+    let bucket = run_config.leaderboard_bucket();
+    if run_config.mode.keeps_scores() && score.score > save.best_score(&bucket) {
+        save.bests
+            .insert(bucket, recorded_run(score.score, &stats, &recorder));
+        save.save();
+    }
+}
+
+// every run sent up goes through here, a fresh one from the game over screen or a local best
+// the service hasn't seen. the service checks all of this again, this just saves sending what
+// it would throw out
+fn signed_submission(
+    name: String,
+    run: &RecordedRun,
+    run_config: &RunConfig,
+) -> Result<ScoreSubmission, String> {
+    let replay = decode_replay(&run.replay)
+        .filter(|replay| !replay.frames.is_empty())
+        .ok_or_else(|| String::from("no replay to send"))?;
+    check_run(run, &replay, run_config)?;
+    let bucket = run_config.leaderboard_bucket();
+    let assisted = run_config.assisted;
+    let hash = replay_hash(&run.replay);
+    let signature = sign(&[
+        &bucket,
+        &name,
+        &run.score.to_string(),
+        &format!("{:.3}", run.survived),
+        &run.kills.to_string(),
+        &hash,
+        &assisted.to_string(),
+    ]);
+    Ok(ScoreSubmission {
+        bucket,
+        name,
+        score: run.score,
+        replay: run.replay.clone(),
+        assisted,
+        survived: run.survived,
+        kills: run.kills,
+        score_track: run.score_track.clone(),
+        replay_hash: hash,
+        signature,
+    })
+}
+
+fn post_submission(url: String, submission: ScoreSubmission) {
+    thread::spawn(move || {
+        if let Err(err) = ureq::post(&url).send_json(&submission) {
+            warn!("could not submit score: {}", err);
+        }
+    });
+}
+
+// the higher of the two bests wins on both sides, a better local run goes up and a better
+// remote one is kept locally with a notice so it doesn't look like the save went missing
+#[allow(clippy::too_many_arguments)]
+pub fn reconcile_player_best(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+    pending: Option<Res<PendingPlayerBest>>,
+    mut save: ResMut<SaveData>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    toasts: Query<(), With<Toast>>,
+) {
+    let pending = match pending {
+        Some(pending) => pending,
+        None => return,
+    };
+    let remote = match pending
+        .receiver
+        .lock()
+        .ok()
+        .and_then(|receiver| receiver.try_recv().ok())
+    {
+        Some(remote) => remote,
+        None => return,
+    };
+    // the resource is left in place, a profile switch this frame may already have put the next
+    // fetch there. this one's sender is gone after its only answer, so it stays quiet. an answer
+    // about whoever played before a switch belongs to nobody now
+    if pending.profile != save.profile {
+        return;
+    }
+    let local = save.bests.get(&pending.bucket).cloned().unwrap_or_default();
+    if remote.score > local.score {
+        save.bests.insert(
+            pending.bucket.clone(),
+            RecordedRun {
+                score: remote.score,
+                ..default()
+            },
+        );
+        save.save();
+        spawn_toast(
+            &mut commands,
+            &assets.font,
+            "Best score synced",
+            &format!("{} points, set on another device", remote.score),
+            toasts.iter().count(),
+        );
+    } else if local.score > remote.score {
+        let url = match &leaderboard.url {
+            Some(url) => format!("{}/scores", url),
+            None => return,
+        };
+        // the run is checked against the mode it was played in, which has to be the one the
+        // board was asked about
+        if run_config.leaderboard_bucket() != pending.bucket {
+            return;
+        }
+        match signed_submission(board_name(&settings, &profiles), &local, &run_config) {
+            Ok(submission) => post_submission(url, submission),
+            Err(reason) => warn!("not uploading the local best: {}", reason),
+        }
+    }
+}
+
+pub fn receive_top_run(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    pending: Option<Res<PendingTopRun>>,
+    run_config: Res<RunConfig>,
+) {
+    let pending = match pending {
+        Some(pending) => pending,
+        None => return,
+    };
+    let top_run = match pending
+        .receiver
+        .lock()
+        .ok()
+        .and_then(|receiver| receiver.try_recv().ok())
+    {
+        Some(top_run) => top_run,
+        None => return,
+    };
+    commands.remove_resource::<PendingTopRun>();
+    match decode_replay(&top_run.replay) {
+        Some(replay) => {
+            info!("racing {}'s ghost ({} points)", top_run.name, top_run.score);
+            // the download can land mid run, the ghost catches up with it on its own
+            spawn_ghost(&mut commands, &assets, &replay, &run_config, &top_run.name);
+            commands.insert_resource(TopRunGhost {
+                bucket: pending.bucket.clone(),
+                name: top_run.name,
+                replay,
+            });
+        }
+        None => warn!("the top run's replay could not be decoded"),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn respawn_ghost(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut resets: EventReader<ResetGame>,
+    ghost: Option<Res<TopRunGhost>>,
+    pending: Option<Res<PendingTopRun>>,
+    race: Res<Race>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+) {
+    if resets.iter().count() == 0 {
+        return;
+    }
+    // the top run fetched so far belongs to the last mode's board, this one gets its own
+    let bucket = run_config.leaderboard_bucket();
+    let stale = ghost
+        .as_ref()
+        .map(|ghost| &ghost.bucket)
+        .or_else(|| pending.as_ref().map(|pending| &pending.bucket))
+        .map_or(false, |fetched| *fetched != bucket);
+    if stale {
+        commands.remove_resource::<TopRunGhost>();
+        start_top_run_fetch(&mut commands, &leaderboard, &run_config);
+    }
+    // a ghost picked off the leaderboard screen stands in for the top run
+    if let Some(target) = &race.target {
+        spawn_ghost(
+            &mut commands,
+            &assets,
+            &target.replay,
+            &run_config,
+            &target.name,
+        );
+    } else if let Some(ghost) = ghost.filter(|_| !stale) {
+        spawn_ghost(
+            &mut commands,
+            &assets,
+            &ghost.replay,
+            &run_config,
+            &ghost.name,
+        );
+    }
+}
+
+// only shown with a leaderboard to send to, seeded with the last name used
+pub fn create_name_entry(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    leaderboard: Res<Leaderboard>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    run_config: Res<RunConfig>,
+    mut entry: ResMut<NameEntry>,
+) {
+    if leaderboard.url.is_none() || !run_config.mode.keeps_scores() {
+        return;
+    }
+    *entry = NameEntry {
+        input: board_name(&settings, &profiles),
+        forced: run_config.mode == GameMode::ScoreAttack,
+        ..default()
+    };
+    let font_handle: Handle<Font> = assets.font.clone();
+    let style = TextStyle {
+        font: font_handle.clone(),
+        font_size: 26.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(20.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(NameEntryScreen)
+        .insert(Name::new("Name Entry"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section("", style.clone()))
+                .insert(NameField);
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.,
+                        color: Color::RED,
+                        ..style
+                    },
+                ))
+                .insert(NameErrorLabel);
+            spawn_labeled_button(
+                parent,
+                &font_handle,
+                "Submit",
+                SubmitScoreButton,
+                Vec2::new(160.0, 40.0),
+            );
+        });
+}
+
+pub fn type_name(
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut entry: ResMut<NameEntry>,
+) {
+    if entry.submitted {
+        characters.clear();
+        return;
+    }
+    characters.iter().for_each(|received| {
+        // anything outside the whitelist never makes it into the box
+        if allowed_char(received.char) && entry.input.chars().count() < MAX_NAME_LENGTH {
+            entry.input.push(received.char);
+            entry.error = None;
+        }
+    });
+    if keys.just_pressed(KeyCode::Back) {
+        entry.input.pop();
+        entry.error = None;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn submit_score(
+    keys: Res<Input<KeyCode>>,
+    mut buttons: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<SubmitScoreButton>),
+    >,
+    mut entry: ResMut<NameEntry>,
+    mut settings: ResMut<Settings>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+    score: Res<Score>,
+    recorder: Res<ReplayRecorder>,
+    stats: Res<RunStats>,
+) {
+    let mut clicked = false;
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, mut color)| match *interaction {
+            Interaction::Clicked => clicked = true,
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+    if !run_config.mode.keeps_scores() {
+        return;
+    }
+    let forced = entry.forced;
+    if entry.submitted || !(clicked || forced || keys.just_pressed(KeyCode::Return)) {
+        return;
+    }
+    // only one automatic try, whatever stops it is left on screen to fix by hand
+    if forced {
+        entry.forced = false;
+    }
+    let url = match &leaderboard.url {
+        Some(url) => format!("{}/scores", url),
+        None => return,
+    };
+    let name = match validate_name(&entry.input) {
+        Ok(name) => name,
+        Err(err) => {
+            entry.error = Some(err.message());
+            return;
+        }
+    };
+    let submission = match signed_submission(
+        name.clone(),
+        &recorded_run(score.score, &stats, &recorder),
+        &run_config,
+    ) {
+        Ok(submission) => submission,
+        Err(reason) => {
+            warn!("not submitting an impossible run: {}", reason);
+            entry.error = Some("This run can't be submitted");
+            return;
+        }
+    };
+    entry.submitted = true;
+    if settings.player_name.as_ref() != Some(&name) {
+        settings.player_name = Some(name);
+    }
+    post_submission(url, submission);
+}
+
+pub fn update_name_entry(
+    entry: Res<NameEntry>,
+    mut fields: Query<&mut Text, With<NameField>>,
+    mut errors: Query<&mut Text, (With<NameErrorLabel>, Without<NameField>)>,
+) {
+    if !entry.is_changed() {
+        return;
+    }
+    fields.iter_mut().for_each(|mut text| {
+        text.sections[0].value = if entry.submitted {
+            format!("Submitted as {}", entry.input.trim())
+        } else {
+            format!("Name: {}_", entry.input)
+        };
+    });
+    errors.iter_mut().for_each(|mut text| {
+        text.sections[0].value = entry.error.unwrap_or("").to_string();
+    });
+}
@@ -0,0 +1,360 @@
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::mesh::MeshVertexBufferLayout;
+use bevy::render::render_resource::{
+    AsBindGroup, BlendComponent, BlendFactor, BlendOperation, BlendState, Extent3d,
+    RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError, TextureDimension,
+    TextureFormat,
+};
+use bevy::sprite::{
+    Material2d, Material2dKey, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle,
+};
+
+use crate::accessibility::Palette;
+use crate::beacon::Beacon;
+use crate::boss::Boss;
+use crate::clock::GameClock;
+use crate::combat::WeaponFiredEvent;
+use crate::depth::RenderLayer;
+use crate::explosions::ExplosionEvent;
+use crate::schedule::GameplaySet;
+use crate::settings::Settings;
+use crate::{GameState, RunEntity, SpawnEnemy};
+
+// width of the generated glow texture, it gets stretched to each light's radius
+const GLOW_SIZE: u32 = 64;
+// the darkness has to cover the view wherever the camera shakes or zooms to
+const DARKNESS_SIZE: f32 = 4096.0;
+// how much of the world shows with no light on it, 1 is fully lit
+const AMBIENT_LEVEL: f32 = 0.9;
+const BOSS_AMBIENT_LEVEL: f32 = 0.55;
+// ambient change per second, so a boss arriving dims the arena instead of cutting to dark
+const AMBIENT_EASE: f32 = 0.5;
+const MUZZLE_COLOR: Color = Color::rgb(1.0, 0.8, 0.4);
+const EXPLOSION_COLOR: Color = Color::rgb(1.0, 0.5, 0.15);
+const BEACON_LIGHT_COLOR: Color = Color::rgb(1.0, 1.0, 0.6);
+
+// how lit the arena is outside of any light
+pub struct Ambient {
+    pub level: f32,
+}
+
+impl Default for Ambient {
+    fn default() -> Self {
+        Ambient {
+            level: AMBIENT_LEVEL,
+        }
+    }
+}
+
+// a soft round falloff, white so every light can tint it, on a quad each light scales to its size
+pub struct Glow {
+    texture: Handle<Image>,
+    quad: Mesh2dHandle,
+}
+
+impl FromWorld for Glow {
+    fn from_world(world: &mut World) -> Self {
+        let half = GLOW_SIZE as f32 / 2.0;
+        let data = (0..GLOW_SIZE * GLOW_SIZE)
+            .flat_map(|index| {
+                let x = (index % GLOW_SIZE) as f32 + 0.5 - half;
+                let y = (index / GLOW_SIZE) as f32 + 0.5 - half;
+                let falloff = (1.0 - Vec2::new(x, y).length() / half).max(0.0);
+                [255, 255, 255, (falloff * falloff * 255.0) as u8]
+            })
+            .collect();
+        let image = Image::new(
+            Extent3d {
+                width: GLOW_SIZE,
+                height: GLOW_SIZE,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let texture = world.resource_mut::<Assets<Image>>().add(image);
+        let quad = Mesh::from(shape::Quad::new(Vec2::ONE));
+        let quad = Mesh2dHandle(world.resource_mut::<Assets<Mesh>>().add(quad));
+        Glow { texture, quad }
+    }
+}
+
+// lights add onto what's under them, so overlapping glows brighten instead of the top one
+// covering the rest
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "5c0e7f1a-93b4-4d2e-8a61-f7d24b9c3e05"]
+pub struct GlowMaterial {
+    // linear rgb, the alpha is the light's current intensity
+    #[uniform(0)]
+    color: Vec4,
+    #[texture(1)]
+    #[sampler(2)]
+    texture: Handle<Image>,
+}
+
+impl Material2d for GlowMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/glow.wgsl".into()
+    }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        _key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let additive = BlendState {
+            color: BlendComponent {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            alpha: BlendComponent::OVER,
+        };
+        if let Some(fragment) = &mut descriptor.fragment {
+            fragment
+                .targets
+                .iter_mut()
+                .flatten()
+                .for_each(|target| target.blend = Some(additive));
+        }
+        Ok(())
+    }
+}
+
+// the dark sheet over the world that the ambient level shows through
+#[derive(Component)]
+pub struct Darkness;
+
+// a glow drawn over the darkness. flashes fade out on their own, lights that follow something
+// last as long as it does
+#[derive(Component)]
+pub struct Light {
+    pub intensity: f32,
+    fade: Option<Timer>,
+    follow: Option<Entity>,
+}
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<GlowMaterial>::default())
+            .init_resource::<Ambient>()
+            .init_resource::<Glow>()
+            .add_startup_system(create_darkness)
+            .add_system(update_ambient)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .label(GameplaySet::Presentation)
+                    .after(GameplaySet::CollisionResponse)
+                    .with_system(flash_lights)
+                    .with_system(light_warnings)
+                    .with_system(update_lights.after(flash_lights).after(light_warnings)),
+            );
+    }
+}
+
+pub fn create_darkness(mut commands: Commands) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.0, 0.0, 0.0, 1.0 - AMBIENT_LEVEL),
+                custom_size: Some(Vec2::splat(DARKNESS_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(RenderLayer::Lighting.at(Vec2::ZERO)),
+            ..default()
+        })
+        .insert(Darkness)
+        .insert(Name::new("Darkness"));
+}
+
+fn spawn_light(
+    commands: &mut Commands,
+    glow: &Glow,
+    materials: &mut Assets<GlowMaterial>,
+    position: Vec2,
+    radius: f32,
+    color: Color,
+    light: Light,
+) {
+    let mut color = Vec4::from(color.as_linear_rgba_f32());
+    color.w = light.intensity;
+    let material = materials.add(GlowMaterial {
+        color,
+        texture: glow.texture.clone(),
+    });
+    commands
+        .spawn_bundle(MaterialMesh2dBundle {
+            mesh: glow.quad.clone(),
+            material,
+            // over the darkness, still under the crosshair and markers
+            transform: Transform {
+                translation: position.extend(RenderLayer::Lighting.z() + 1.0),
+                scale: Vec3::new(radius * 2.0, radius * 2.0, 1.0),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(light)
+        .insert(Name::new("Light"))
+        .insert(RunEntity);
+}
+
+// brief flashes for shots and blasts, as strong as the flash setting allows
+pub fn flash_lights(
+    mut commands: Commands,
+    glow: Res<Glow>,
+    mut materials: ResMut<Assets<GlowMaterial>>,
+    settings: Res<Settings>,
+    mut fired: EventReader<WeaponFiredEvent>,
+    mut explosions: EventReader<ExplosionEvent>,
+) {
+    if settings.flash <= 0.0 {
+        fired.clear();
+        explosions.clear();
+        return;
+    }
+    fired.iter().for_each(|shot| {
+        let light = Light {
+            intensity: 0.5 * settings.flash,
+            fade: Some(Timer::from_seconds(0.08, false)),
+            follow: None,
+        };
+        spawn_light(
+            &mut commands,
+            &glow,
+            &mut materials,
+            shot.position.truncate(),
+            40.0,
+            MUZZLE_COLOR,
+            light,
+        );
+    });
+    explosions.iter().for_each(|explosion| {
+        let light = Light {
+            intensity: 0.7 * settings.flash,
+            fade: Some(Timer::from_seconds(0.3, false)),
+            follow: None,
+        };
+        spawn_light(
+            &mut commands,
+            &glow,
+            &mut materials,
+            explosion.position.truncate(),
+            explosion.radius * 2.0,
+            EXPLOSION_COLOR,
+            light,
+        );
+    });
+}
+
+// enemy warnings glow in the warning color and weapon beacons in their own, for as long as
+// they're up
+pub fn light_warnings(
+    mut commands: Commands,
+    glow: Res<Glow>,
+    mut materials: ResMut<Assets<GlowMaterial>>,
+    palette: Res<Palette>,
+    // both are spawned at the top level, their transforms are already where they'll be drawn
+    warnings: Query<(Entity, &Transform), Added<SpawnEnemy>>,
+    beacons: Query<(Entity, &Transform), Added<Beacon>>,
+) {
+    warnings.iter().for_each(|(warning, trans)| {
+        let light = Light {
+            intensity: 0.35,
+            fade: None,
+            follow: Some(warning),
+        };
+        spawn_light(
+            &mut commands,
+            &glow,
+            &mut materials,
+            trans.translation.truncate(),
+            28.0,
+            palette.danger,
+            light,
+        );
+    });
+    beacons.iter().for_each(|(beacon, trans)| {
+        let light = Light {
+            intensity: 0.3,
+            fade: None,
+            follow: Some(beacon),
+        };
+        spawn_light(
+            &mut commands,
+            &glow,
+            &mut materials,
+            trans.translation.truncate(),
+            20.0,
+            BEACON_LIGHT_COLOR,
+            light,
+        );
+    });
+}
+
+pub fn update_lights(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    sources: Query<&GlobalTransform, Without<Light>>,
+    mut lights: Query<(Entity, &mut Light, &Handle<GlowMaterial>, &mut Transform)>,
+    mut materials: ResMut<Assets<GlowMaterial>>,
+) {
+    lights
+        .iter_mut()
+        .for_each(|(entity, mut light, material, mut trans)| {
+            if let Some(source) = light.follow {
+                match sources.get(source) {
+                    Ok(global) => {
+                        let position = global.translation();
+                        trans.translation.x = position.x;
+                        trans.translation.y = position.y;
+                    }
+                    Err(_) => {
+                        commands.entity(entity).despawn();
+                        return;
+                    }
+                }
+            }
+            let intensity = light.intensity;
+            if let Some(fade) = &mut light.fade {
+                fade.tick(clock.delta());
+                if fade.finished() {
+                    commands.entity(entity).despawn();
+                    return;
+                }
+                if let Some(material) = materials.get_mut(material) {
+                    material.color.w = intensity * (1.0 - fade.percent());
+                }
+            }
+        });
+}
+
+// the arena dims while a boss is in it, or on its way in
+pub fn update_ambient(
+    clock: Res<GameClock>,
+    mut ambient: ResMut<Ambient>,
+    bosses: Query<(), With<Boss>>,
+    mut darkness: Query<&mut Sprite, With<Darkness>>,
+) {
+    let target = if bosses.is_empty() {
+        AMBIENT_LEVEL
+    } else {
+        BOSS_AMBIENT_LEVEL
+    };
+    if ambient.level == target {
+        return;
+    }
+    let step = AMBIENT_EASE * clock.delta_seconds();
+    ambient.level = if ambient.level < target {
+        (ambient.level + step).min(target)
+    } else {
+        (ambient.level - step).max(target)
+    };
+    darkness.iter_mut().for_each(|mut sprite| {
+        sprite.color.set_a(1.0 - ambient.level);
+    });
+}
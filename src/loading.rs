@@ -0,0 +1,393 @@
+use bevy::asset::{HandleId, LoadState};
+use bevy::prelude::*;
+
+use crate::announcer::{line_to_asset, AnnouncerLine, ALL_LINES};
+use crate::characters::{CharacterDef, CHARACTER_FILES};
+use crate::materials::PhysicsMaterials;
+use crate::menu::{despawn_screen, screen_root, spawn_title};
+use crate::sequence::Sequence;
+use crate::spawning::SpawnTable;
+use crate::transition::Transition;
+use crate::{
+    behavior_to_asset, weapon_enum_to_string, Behavior, GameState, Weapons, ALL_BEHAVIORS,
+    ALL_WEAPONS,
+};
+
+const DIGIT_COUNT: usize = 10;
+
+// every handle the game uses, loaded once up front so nothing hitches mid run
+pub struct GameAssets {
+    pub font: Handle<Font>,
+    pub music: Handle<AudioSource>,
+    pub shield_break: Handle<AudioSource>,
+    pub heartbeat: Handle<AudioSource>,
+    pub milestone: Handle<AudioSource>,
+    pub land: Handle<AudioSource>,
+    pub clink: Handle<AudioSource>,
+    pub beep: Handle<AudioSource>,
+    pub enemy_shot: Handle<AudioSource>,
+    pub explosion: Handle<AudioSource>,
+    pub spawn_warning: Handle<AudioSource>,
+    pub hit_tick: Handle<AudioSource>,
+    pub footstep: Handle<AudioSource>,
+    pub dash: Handle<AudioSource>,
+    pub character: Handle<Image>,
+    pub floor: Handle<Image>,
+    pub bullet: Handle<Image>,
+    pub enemy_bullet: Handle<Image>,
+    pub trace: Handle<Image>,
+    pub spawn_enemy: Handle<Image>,
+    pub spawn_weapon: Handle<Image>,
+    pub characters: Vec<Handle<CharacterDef>>,
+    pub boss_intro: Handle<Sequence>,
+    pub game_over: Handle<Sequence>,
+    pub spawn_table: Handle<SpawnTable>,
+    pub physics_materials: Handle<PhysicsMaterials>,
+    weapons: Vec<Handle<Image>>,
+    enemies: Vec<Handle<Image>>,
+    announcer_lines: Vec<Handle<AudioSource>>,
+    digits: Vec<Handle<Image>>,
+}
+
+impl GameAssets {
+    pub fn weapon(&self, weapon: Weapons) -> Handle<Image> {
+        self.weapons[weapon as usize].clone()
+    }
+
+    pub fn announcer_line(&self, line: AnnouncerLine) -> Handle<AudioSource> {
+        self.announcer_lines[line as usize].clone()
+    }
+
+    // the pixel hud's sprite for a single decimal digit
+    pub fn digit(&self, digit: usize) -> Handle<Image> {
+        self.digits[digit % DIGIT_COUNT].clone()
+    }
+
+    pub fn is_digit(&self, handle: &Handle<Image>) -> bool {
+        self.digits.contains(handle)
+    }
+
+    // nothing behind any of the handles, for running the game without a renderer or files
+    pub fn placeholder() -> Self {
+        GameAssets {
+            font: Handle::default(),
+            music: Handle::default(),
+            shield_break: Handle::default(),
+            heartbeat: Handle::default(),
+            milestone: Handle::default(),
+            land: Handle::default(),
+            clink: Handle::default(),
+            beep: Handle::default(),
+            enemy_shot: Handle::default(),
+            explosion: Handle::default(),
+            spawn_warning: Handle::default(),
+            hit_tick: Handle::default(),
+            footstep: Handle::default(),
+            dash: Handle::default(),
+            character: Handle::default(),
+            floor: Handle::default(),
+            bullet: Handle::default(),
+            enemy_bullet: Handle::default(),
+            trace: Handle::default(),
+            spawn_enemy: Handle::default(),
+            spawn_weapon: Handle::default(),
+            characters: Vec::new(),
+            boss_intro: Handle::default(),
+            game_over: Handle::default(),
+            spawn_table: Handle::default(),
+            physics_materials: Handle::default(),
+            weapons: vec![Handle::default(); ALL_WEAPONS.len()],
+            enemies: vec![Handle::default(); ALL_BEHAVIORS.len()],
+            announcer_lines: vec![Handle::default(); ALL_LINES.len()],
+            digits: vec![Handle::default(); DIGIT_COUNT],
+        }
+    }
+
+    fn handle_ids(&self) -> Vec<HandleId> {
+        let mut ids = vec![
+            HandleId::from(&self.font),
+            HandleId::from(&self.music),
+            HandleId::from(&self.shield_break),
+            HandleId::from(&self.heartbeat),
+            HandleId::from(&self.milestone),
+            HandleId::from(&self.land),
+            HandleId::from(&self.clink),
+            HandleId::from(&self.beep),
+            HandleId::from(&self.enemy_shot),
+            HandleId::from(&self.explosion),
+            HandleId::from(&self.spawn_warning),
+            HandleId::from(&self.hit_tick),
+            HandleId::from(&self.footstep),
+            HandleId::from(&self.dash),
+            HandleId::from(&self.character),
+            HandleId::from(&self.floor),
+            HandleId::from(&self.bullet),
+            HandleId::from(&self.enemy_bullet),
+            HandleId::from(&self.trace),
+            HandleId::from(&self.spawn_enemy),
+            HandleId::from(&self.spawn_weapon),
+            HandleId::from(&self.boss_intro),
+            HandleId::from(&self.game_over),
+            HandleId::from(&self.spawn_table),
+            HandleId::from(&self.physics_materials),
+        ];
+        ids.extend(self.characters.iter().map(HandleId::from));
+        ids.extend(self.weapons.iter().map(HandleId::from));
+        ids.extend(self.enemies.iter().map(HandleId::from));
+        ids.extend(self.announcer_lines.iter().map(HandleId::from));
+        ids.extend(self.digits.iter().map(HandleId::from));
+        ids
+    }
+}
+
+// sprite sheets are cut into atlases once here instead of on every spawn
+pub struct AtlasRegistry {
+    pub enemy_bullet: Handle<TextureAtlas>,
+    pub spawn_enemy: Handle<TextureAtlas>,
+    pub spawn_weapon: Handle<TextureAtlas>,
+    enemies: Vec<Handle<TextureAtlas>>,
+    // what each atlas was cut from, so it can be cut again when the sheet changes on disk
+    sheets: Vec<SheetCut>,
+}
+
+struct SheetCut {
+    image: Handle<Image>,
+    atlas: Handle<TextureAtlas>,
+    tile: Vec2,
+}
+
+impl AtlasRegistry {
+    pub fn enemy(&self, behavior: Behavior) -> Handle<TextureAtlas> {
+        self.enemies[behavior as usize].clone()
+    }
+
+    pub fn placeholder() -> Self {
+        AtlasRegistry {
+            enemy_bullet: Handle::default(),
+            spawn_enemy: Handle::default(),
+            spawn_weapon: Handle::default(),
+            enemies: vec![Handle::default(); ALL_BEHAVIORS.len()],
+            sheets: Vec::new(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct LoadingScreen;
+
+#[derive(Component)]
+pub struct LoadingBar;
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PreStartup, load_assets)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Loading).with_system(create_loading_screen),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Loading).with_system(check_loading))
+            .add_system(rebuild_atlases)
+            .add_system_set(
+                SystemSet::on_exit(GameState::Loading).with_system(despawn_screen::<LoadingScreen>),
+            );
+    }
+}
+
+pub fn load_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let assets = GameAssets {
+        font: asset_server.load("fonts/RobotoMono.ttf"),
+        music: asset_server.load("sounds/backtrack.ogg"),
+        shield_break: asset_server.load("sounds/ShieldBreak.ogg"),
+        heartbeat: asset_server.load("sounds/Heartbeat.ogg"),
+        milestone: asset_server.load("sounds/Milestone.ogg"),
+        land: asset_server.load("sounds/Land.ogg"),
+        clink: asset_server.load("sounds/Clink.ogg"),
+        beep: asset_server.load("sounds/Beep.ogg"),
+        enemy_shot: asset_server.load("sounds/EnemyShot.ogg"),
+        explosion: asset_server.load("sounds/Explosion.ogg"),
+        spawn_warning: asset_server.load("sounds/SpawnWarning.ogg"),
+        hit_tick: asset_server.load("sounds/HitTick.ogg"),
+        footstep: asset_server.load("sounds/Footstep.ogg"),
+        dash: asset_server.load("sounds/Dash.ogg"),
+        character: asset_server.load("images/Character.png"),
+        floor: asset_server.load("images/Floor.png"),
+        bullet: asset_server.load("images/Bullet.png"),
+        enemy_bullet: asset_server.load("images/EnemyBullet.png"),
+        trace: asset_server.load("images/Trace.png"),
+        spawn_enemy: asset_server.load("images/SpawnEnemy.png"),
+        spawn_weapon: asset_server.load("images/SpawnWeapon.png"),
+        boss_intro: asset_server.load("sequences/boss_intro.sequence.json"),
+        game_over: asset_server.load("sequences/game_over.sequence.json"),
+        spawn_table: asset_server.load("config/enemies.spawns.json"),
+        physics_materials: asset_server.load("config/physics.materials.json"),
+        characters: CHARACTER_FILES
+            .iter()
+            .map(|file| asset_server.load(*file))
+            .collect(),
+        weapons: ALL_WEAPONS
+            .iter()
+            .map(|weapon| asset_server.load(&weapon_enum_to_string(*weapon)))
+            .collect(),
+        enemies: ALL_BEHAVIORS
+            .iter()
+            .map(|behavior| asset_server.load(&behavior_to_asset(*behavior)))
+            .collect(),
+        announcer_lines: ALL_LINES
+            .iter()
+            .map(|line| asset_server.load(line_to_asset(*line)))
+            .collect(),
+        digits: (0..DIGIT_COUNT)
+            .map(|digit| asset_server.load(&format!("images/digits/{}.png", digit)))
+            .collect(),
+    };
+    let mut sheets = Vec::new();
+    let mut add_atlas = |image: &Handle<Image>, tile: Vec2, columns: usize, rows: usize| {
+        let atlas =
+            texture_atlases.add(TextureAtlas::from_grid(image.clone(), tile, columns, rows));
+        sheets.push(SheetCut {
+            image: image.clone(),
+            atlas: atlas.clone(),
+            tile,
+        });
+        atlas
+    };
+    let mut atlases = AtlasRegistry {
+        enemy_bullet: add_atlas(&assets.enemy_bullet, Vec2::new(3.0, 3.0), 4, 2),
+        spawn_enemy: add_atlas(&assets.spawn_enemy, Vec2::new(5.0, 5.0), 5, 3),
+        spawn_weapon: add_atlas(&assets.spawn_weapon, Vec2::new(4.0, 4.0), 3, 6),
+        enemies: assets
+            .enemies
+            .iter()
+            .map(|enemy| add_atlas(enemy, Vec2::new(15.0, 15.0), 8, 4))
+            .collect(),
+        sheets: Vec::new(),
+    };
+    atlases.sheets = sheets;
+    commands.insert_resource(assets);
+    commands.insert_resource(atlases);
+}
+
+pub fn create_loading_screen(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(screen_root(Color::BLACK))
+        .insert(LoadingScreen)
+        .insert(Name::new("Loading Screen"))
+        .with_children(|parent| {
+            spawn_title(parent, &assets.font, "Loading");
+            parent
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(Color::rgb(0.15, 0.15, 0.15)),
+                    style: Style {
+                        size: Size::new(Val::Px(400.0), Val::Px(24.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn_bundle(NodeBundle {
+                        color: UiColor(Color::WHITE),
+                        style: Style {
+                            size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(LoadingBar);
+                });
+        });
+}
+
+pub fn check_loading(
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    mut bars: Query<&mut Style, With<LoadingBar>>,
+    mut transition: ResMut<Transition>,
+    mut reported: Local<Vec<HandleId>>,
+) {
+    let ids = assets.handle_ids();
+    let mut done = 0;
+    ids.iter()
+        .for_each(|id| match asset_server.get_load_state(*id) {
+            LoadState::Loaded => done += 1,
+            // a missing file shouldn't keep the game on the loading screen forever, but it
+            // should say which one it was
+            LoadState::Failed => {
+                done += 1;
+                if !reported.contains(id) {
+                    reported.push(*id);
+                    match asset_server.get_handle_path(*id) {
+                        Some(path) => error!("failed to load {:?}", path.path()),
+                        None => error!("failed to load an asset with no path"),
+                    }
+                }
+            }
+            _default => {}
+        });
+    let progress = done as f32 / ids.len() as f32;
+    bars.iter_mut().for_each(|mut style| {
+        style.size.width = Val::Percent(progress * 100.0);
+    });
+    if done == ids.len() {
+        transition.fade_to(GameState::Profiles);
+    }
+}
+
+// a sheet saved while the game runs keeps its tile size, but may have gained or lost frames.
+// the grid is cut again from the new image size and running animations are pulled back in range
+pub fn rebuild_atlases(
+    mut events: EventReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+    registry: Option<Res<AtlasRegistry>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut sprites: Query<(&mut TextureAtlasSprite, &Handle<TextureAtlas>)>,
+) {
+    let registry = match registry {
+        Some(registry) => registry,
+        None => return,
+    };
+    events.iter().for_each(|event| {
+        let handle = match event {
+            AssetEvent::Modified { handle } => handle,
+            _default => return,
+        };
+        let image = match images.get(handle) {
+            Some(image) => image,
+            None => return,
+        };
+        let size = image.size();
+        registry
+            .sheets
+            .iter()
+            .filter(|sheet| sheet.image == *handle)
+            .for_each(|sheet| {
+                let columns = ((size.x / sheet.tile.x) as usize).max(1);
+                let rows = ((size.y / sheet.tile.y) as usize).max(1);
+                let frames = columns * rows;
+                // sampler switches modify the image too, those leave the grid as it was
+                let unchanged = texture_atlases
+                    .get(&sheet.atlas)
+                    .map_or(true, |atlas| atlas.textures.len() == frames);
+                if unchanged {
+                    return;
+                }
+                if let Some(atlas) = texture_atlases.get_mut(&sheet.atlas) {
+                    *atlas =
+                        TextureAtlas::from_grid(sheet.image.clone(), sheet.tile, columns, rows);
+                }
+                sprites
+                    .iter_mut()
+                    .filter(|(_, atlas)| **atlas == sheet.atlas)
+                    .for_each(|(mut sprite, _)| {
+                        if sprite.index >= frames {
+                            sprite.index %= frames;
+                        }
+                    });
+                info!("recut {}x{} frames after a sheet changed", columns, rows);
+            });
+    });
+}
@@ -1,29 +1,210 @@
 use std::time::Duration;
 
+use accessibility::{AccessibilityPlugin, Palette};
+use achievements::AchievementPlugin;
+use actions::{Action, Actions};
+use aim::{AimPlugin, GamepadAim};
+use announcer::AnnouncerPlugin;
+use assist::ASSIST_RAMP;
+use beacon::Beacon;
+use bench::{Bench, BenchPlugin};
 use bevy::prelude::{Camera2dBundle, *};
 use bevy::render::camera::{RenderTarget, ScalingMode};
-use bevy_inspector_egui::{Inspectable, InspectorPlugin, WorldInspectorPlugin};
+use bevy::render::texture::ImageSettings;
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::Inspectable;
+use bomber::{BomberFuse, Chase};
+use boss::Boss;
+use bullettime::BulletTime;
+use capture::CapturePlugin;
+use characters::{selected_character, CharacterDef, CharacterPlugin};
+use clock::GameClock;
+use collision::{collision_layers, CollisionRules};
+use combat::{
+    airborne, wound_player, Combatant, DamageEvent, DeathEvent, PickupEvent, PickupKind, Slain,
+    WeaponFiredEvent,
+};
+#[cfg(feature = "debug")]
+use console::ConsolePlugin;
+use crosshair::{AimPoint, CrosshairPlugin};
+use debug::DebugPlugin;
+use depth::RenderLayer;
+use director::Director;
+use display::DisplayPlugin;
+use explosions::ExplosionEvent;
+use fanfare::FanfarePlugin;
+use fonts::{FontPlugin, TextRole};
+use footsteps::FootstepPlugin;
+use gameplay::GameplayPlugin;
+use gibs::GibPlugin;
+use hazards::Hazard;
 use heron::{prelude::*, PhysicsSteps};
+use hitdirection::HitDirectionPlugin;
+use hitmarker::HitMarkerPlugin;
+use homing::{lock_target, Homing};
+use impacts::Projectile;
+#[cfg(feature = "debug")]
+use inspector::InspectorTogglePlugin;
+use killfeed::KillFeedPlugin;
+use landing::LandingPlugin;
+use leaderboard::LeaderboardPlugin;
 use libm::{atan2f, cosf, sinf};
+use lighting::LightingPlugin;
+use loading::{AtlasRegistry, GameAssets, LoadingPlugin};
+use materials::{MaterialClass, PhysicsMaterials};
 use math::round;
+use menu::{despawn_screen, screen_root, spawn_button, spawn_title, MenuButton, MenuPlugin};
+use modes::{GameMode, GameRng, RunConfig};
+use navigation::{NavGraph, JUMP_SPEED};
+use particles::ParticlePlugin;
+use patterns::{fire_pattern, shot_patterns};
+use pause::PausePlugin;
+use perks::Perks;
+use photo::PhotoPlugin;
+use pickups::Magnetic;
+use postprocess::PostProcessPlugin;
+use practice::PracticePlugin;
+use prestige::AffixRegistry;
+use profiles::{ProfilePlugin, Profiles};
+use race::RacePlugin;
 use rand::Rng;
+use rankings::RankingsPlugin;
+use replay::{play_ghosts, record_replay, start_ghosts, ReplayRecorder};
+use save::SaveData;
+use schedule::{FixedStep, GameplaySet};
+use scoring::{ScoreBreakdown, SCORE_SOURCES};
+use serde::Deserialize;
+use settings::{MusicController, Settings, SettingsPlugin};
+use shake::ShakePlugin;
+use shield::{Shield, ShieldBroken};
+use shielded::{blocks, spawn_clink, EnemyShield, Guarding, Turning};
+use shop::{dash_speed, drop_table, run_reward, starting_health, ShopPlugin};
+use smoothing::SmoothingPlugin;
+use spatial::{SpatialAudioPlugin, SpatialSound};
+use spawning::{BehaviorCounts, SpawnMemory, SpawnRules, SpawnTable};
+use summoner::{cast_tween, Casting, Minion};
+use sweep::{FastProjectile, SweptHit, FAST_PROJECTILE_SPEED};
+use tokens::AttackTokens;
+use touch::TouchPlugin;
+use trails::{dash_trail, projectile_trail, TrailPlugin};
+use tutorial::TutorialPlugin;
+use tween::{Ease, Repeat, Tween, TweenPlugin, TweenTarget};
+use versus::VersusPlugin;
+use vignette::VignettePlugin;
+use weapons::{weapon_stats, Magazine, WeaponTooltipPlugin};
+
+mod accessibility;
+mod achievements;
+mod actions;
+mod aim;
+mod announcer;
+mod assist;
+mod beacon;
+mod beam;
+mod bench;
+mod bomber;
+mod boss;
+mod bullettime;
+mod capture;
+mod characters;
+mod clock;
+mod collision;
+mod combat;
+#[cfg(feature = "debug")]
+mod console;
+mod crosshair;
+mod debug;
+mod depth;
+mod director;
+mod display;
+mod explosions;
+mod fanfare;
+mod fonts;
+mod footsteps;
+mod gameplay;
+mod gibs;
+mod grenade;
+mod hazards;
+mod headless;
+mod hitdirection;
+mod hitmarker;
+mod homing;
+mod impacts;
+#[cfg(feature = "debug")]
+mod inspector;
+mod integrity;
+mod killfeed;
+mod killzone;
+mod landing;
+mod leaderboard;
+mod lighting;
+mod loading;
+mod materials;
+mod menu;
+mod modes;
+mod names;
+mod navigation;
+mod particles;
+mod patterns;
+mod pause;
+mod perks;
+mod photo;
+mod pickups;
+mod postprocess;
+mod practice;
+mod prestige;
+mod profiles;
+mod race;
+mod rankings;
+mod replay;
+mod save;
+mod schedule;
+mod scoreattack;
+mod scoring;
+mod sequence;
+mod settings;
+mod shake;
+mod shield;
+mod shielded;
+mod shop;
+mod smoothing;
+mod spatial;
+mod spawning;
+mod stats;
+mod style;
+mod summoner;
+mod sweep;
+mod tokens;
+mod touch;
+mod trails;
+mod transition;
+mod tutorial;
+mod tween;
+mod versus;
+mod vignette;
+mod weapons;
 
-#[derive(Component, Inspectable)]
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 pub struct GravityData {
-    #[inspectable(min = 0.1, max = 1000.0)]
+    #[cfg_attr(feature = "debug", inspectable(min = 0.1, max = 1000.0))]
     phys_time: f32,
-    #[inspectable(min = 1., max = 10000.0)]
+    #[cfg_attr(feature = "debug", inspectable(min = 1., max = 10000.0))]
     phys_step: f32,
-    #[inspectable(min = Vec3::splat(-1000.0), max = Vec3::splat(1000.0))]
+    #[cfg_attr(feature = "debug", inspectable(min = Vec3::splat(-1000.0), max = Vec3::splat(1000.0)))]
     gravity: Vec3,
-    #[inspectable(min = Vec3::splat(-1000.0), max = Vec3::splat(1000.0))]
-    gun_force: Vec3,
-    #[inspectable(min = Vec3::new(0.,-92.0,0.0), max = Vec3::splat(100.0) )]
-    player_pos: Vec3,
-    #[inspectable(min = 1.0, max = 100000.0)]
+    #[cfg_attr(feature = "debug", inspectable(min = 1.0, max = 100000.0))]
     jump_height: f32,
-    #[inspectable(min = 0.1, max = 2.0)]
+    #[cfg_attr(feature = "debug", inspectable(min = 0.1, max = 2.0))]
     weapon_time: f32,
+    // walking top speed in units per second, before the character's multiplier
+    #[cfg_attr(feature = "debug", inspectable(min = 1.0, max = 500.0))]
+    run_speed: f32,
+    #[cfg_attr(feature = "debug", inspectable(min = 1.0, max = 5000.0))]
+    run_accel: f32,
+    // how fast enemies get around, as a share of their normal speed
+    #[cfg_attr(feature = "debug", inspectable(min = 0.1, max = 5.0))]
+    enemy_speed: f32,
 }
 
 impl Default for GravityData {
@@ -32,15 +213,76 @@ impl Default for GravityData {
             phys_time: 1.,
             phys_step: 30.,
             gravity: Vec3::new(0., -70.1, 0.),
-            gun_force: Vec3::new(0.0, 0.0, 0.0),
-            player_pos: Vec3::new(0., -92.0, 0.0),
             jump_height: 100.0,
             weapon_time: 1.0,
+            run_speed: 60.0,
+            run_accel: 600.0,
+            enemy_speed: 1.0,
         }
     }
 }
 
-#[derive(PartialEq, Eq)]
+// the tuning values are the source of truth, physics and the player are kept in sync with them
+pub fn apply_gravity_data(
+    data: Res<GravityData>,
+    mut gravity: ResMut<Gravity>,
+    mut steps: ResMut<PhysicsSteps>,
+    mut weapon_spawns: ResMut<WeaponSpawns>,
+    mut players: Query<(&mut Player, &Perks)>,
+) {
+    if data.is_changed() {
+        *gravity = Gravity::from(data.gravity);
+        *steps = PhysicsSteps::from_steps_per_seconds(data.phys_step);
+    }
+    // these get rebuilt on every reset, so check them each frame rather than on change
+    let reload_scale = players
+        .iter()
+        .next()
+        .map_or(1.0, |(_, perks)| perks.reload_scale());
+    let weapon_time = Duration::from_secs_f32(data.weapon_time * reload_scale);
+    if weapon_spawns.timer.duration() != weapon_time {
+        weapon_spawns.timer.set_duration(weapon_time);
+    }
+    players.iter_mut().for_each(|(mut player, _)| {
+        let jump_height = data.jump_height * player.jump_scale;
+        if player.jump_height != jump_height {
+            player.jump_height = jump_height;
+        }
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    Loading,
+    MainMenu,
+    // who's playing, straight after loading and again from the main menu
+    Profiles,
+    Playing,
+    Paused,
+    Settings,
+    // window and scaling options, opened from settings
+    Display,
+    // palettes, warning colors and outlines, opened from settings
+    Accessibility,
+    // physics rate and smoothing, opened from settings
+    Advanced,
+    Achievements,
+    // top 100 for the current mode, opened from the main menu
+    Leaderboard,
+    Shop,
+    PerkChoice,
+    // scripted camera moves with the run frozen underneath
+    Cutscene,
+    // free camera over a frozen run, for taking pictures
+    PhotoMode,
+    // god mode, infinite ammo and the weapon picker, practice runs only
+    PracticeMenu,
+    GameOver,
+}
+
+pub const PLAYER_MAX_HEALTH: i8 = 3;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub enum Directions {
     Left,
     Right,
@@ -49,18 +291,21 @@ pub enum Directions {
 pub struct DashTimer {
     timer: Timer,
     direction: Directions,
+    // no new dash until this runs out
+    cooldown: Timer,
 }
 
 impl Default for DashTimer {
     fn default() -> Self {
         DashTimer {
-            timer: Timer::from_seconds(0.5, false),
+            timer: Timer::from_seconds(0.0001, false),
             direction: Directions::Left,
+            cooldown: Timer::from_seconds(0.0001, false),
         }
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Deserialize)]
 pub enum Weapons {
     Base,
     Rocket,
@@ -68,8 +313,29 @@ pub enum Weapons {
     Shotgun,
     Rock,
     Airplane,
+    // dropped by burst shooters, never in the regular drop table
+    EnemyGun,
+    HomingRocket,
+    Laser,
+    Grenade,
+    // dropped by plain shooters, never in the regular drop table
+    Smg,
 }
 
+pub const ALL_WEAPONS: [Weapons; 11] = [
+    Weapons::Base,
+    Weapons::Rocket,
+    Weapons::Sniper,
+    Weapons::Shotgun,
+    Weapons::Rock,
+    Weapons::Airplane,
+    Weapons::EnemyGun,
+    Weapons::HomingRocket,
+    Weapons::Laser,
+    Weapons::Grenade,
+    Weapons::Smg,
+];
+
 #[derive(Component, Clone, Copy)]
 pub struct Weapon {
     asset: Weapons,
@@ -90,81 +356,314 @@ pub struct DifficultyTimer {
     timer: Timer,
 }
 
+impl Default for DifficultyTimer {
+    fn default() -> Self {
+        DifficultyTimer {
+            difficulty: 1,
+            timer: Timer::from_seconds(5.0, true),
+        }
+    }
+}
+
 pub struct EnemyTimer {
     timer: Timer,
 }
 
-/* pub struct ConfigSettings {
-    sfx: f32,
-    music: f32,
+impl Default for EnemyTimer {
+    fn default() -> Self {
+        EnemyTimer {
+            timer: Timer::from_seconds(2.0, true),
+        }
+    }
+}
+
+// sent to throw away the current run and start a fresh one
+pub struct ResetGame;
 
-} */
+// everything spawned during a run, so a reset knows what to clean up
+#[derive(Component)]
+pub struct RunEntity;
 
 fn main() {
-    App::new()
+    if std::env::args().any(|arg| arg == "--headless-check") {
+        if let Err(err) = headless::run_checks() {
+            eprintln!("headless check failed: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let run_config = RunConfig::from_args();
+    let versus = run_config.mode == GameMode::Versus;
+    let bench = Bench::from_args();
+    let profiles = Profiles::load();
+    let profile = profiles.active();
+    let mut app = App::new();
+    // debug builds pick up saved sprites and config files without a restart
+    #[cfg(feature = "debug")]
+    app.insert_resource(bevy::asset::AssetServerSettings {
+        watch_for_changes: true,
+        ..default()
+    });
+    app.insert_resource(run_config.rng())
+        .insert_resource(run_config)
+        .insert_resource(profiles)
+        // the art is all small pixel sprites, smoothing them only smears them
+        .insert_resource(ImageSettings::default_nearest())
         .add_plugins(DefaultPlugins)
-        .add_plugin(PhysicsPlugin::default())
-        .insert_resource(DifficultyTimer {
-            difficulty: 1,
-            timer: Timer::from_seconds(5.0, true),
-        })
-        .insert_resource(EnemyTimer {
-            timer: Timer::from_seconds(2.0, true),
-        })
-        .insert_resource(Gravity::from(Vec3::new(0.0, -70.1, 0.0)))
-        .insert_resource(PhysicsTime::new(1.))
-        .insert_resource(PhysicsSteps::from_steps_per_seconds(30.))
-        .insert_resource(WeaponSpawns {
-            timer: Timer::from_seconds(1.0, true),
-        })
-        .insert_resource(Score::default())
-        .add_system(fire_weapon)
-        //.add_plugin(WorldInspectorPlugin::new())
-        .insert_resource(DashTimer {
-            timer: Timer::from_seconds(0.0001, false),
-            direction: Directions::Left,
-        })
+        .add_plugin(GameplayPlugin)
+        .add_plugin(TouchPlugin)
+        .add_plugin(AimPlugin)
+        .add_plugin(CrosshairPlugin)
+        .add_plugin(TweenPlugin)
+        .add_plugin(FanfarePlugin)
+        .add_plugin(LandingPlugin)
+        .add_plugin(SpatialAudioPlugin)
+        .add_plugin(AnnouncerPlugin)
+        .add_plugin(HitMarkerPlugin)
+        .add_plugin(FootstepPlugin)
+        .add_plugin(KillFeedPlugin)
+        .add_plugin(HitDirectionPlugin)
+        .add_plugin(CapturePlugin)
+        .add_plugin(PhotoPlugin)
+        .add_plugin(DisplayPlugin)
+        .add_plugin(FontPlugin)
+        .add_plugin(AccessibilityPlugin)
+        .add_plugin(ShakePlugin)
+        .add_plugin(SmoothingPlugin)
+        .add_plugin(CharacterPlugin)
+        .add_plugin(PracticePlugin)
+        .add_plugin(ProfilePlugin)
+        .add_plugin(LoadingPlugin)
+        .add_plugin(LeaderboardPlugin)
+        .add_plugin(RankingsPlugin)
+        .add_plugin(RacePlugin)
+        .add_plugin(MenuPlugin)
+        .add_plugin(PausePlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(DebugPlugin)
+        .add_plugin(ParticlePlugin)
+        .add_plugin(GibPlugin)
+        .add_plugin(TrailPlugin)
+        .add_plugin(LightingPlugin)
+        .add_plugin(PostProcessPlugin)
+        .add_plugin(TutorialPlugin)
+        .add_plugin(VignettePlugin)
+        .add_plugin(WeaponTooltipPlugin)
+        .add_plugin(AchievementPlugin)
+        .add_plugin(ShopPlugin)
+        .add_state(GameState::Loading)
+        .insert_resource(SaveData::load(profile))
+        .init_resource::<ReplayRecorder>()
         .add_startup_system(create_character)
         .add_startup_system(create_scoreboard)
         .add_startup_system(setup_camera)
-        .add_system(tick_timers)
-        .add_system(move_player)
-        .add_startup_system(create_borders)
-        .add_system(grab_weapon)
-        .add_system(point_held_item)
-        .add_system(update_score)
-        .add_system(spin_spinners)
-        .add_system(move_enemies)
-        .add_system(spawn_warned)
-        .add_system(animate_sprites)
-        .add_system(spawn_warned_enemy)
-        .add_system(handle_slides)
-        .add_system(handle_jumpers)
-        .add_system(handle_bullet_collision)
-        .add_system(handle_despawner)
-        .add_system(handle_shooter)
-        .add_startup_system(play_music)
-        .run();
-}
-
-pub fn handle_bullet_collision(mut commands: Commands, bullets: Query<&Collisions, With<Bullet>>) {
-    bullets.iter().for_each(|collision| {
-        collision.entities().for_each(|entity| {
-            commands.entity(entity).log_components();
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .label(GameplaySet::Simulation)
+                .after(GameplaySet::Input)
+                .with_system(record_replay),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .label(GameplaySet::Presentation)
+                .after(GameplaySet::CollisionResponse)
+                .with_system(point_held_item)
+                .with_system(start_ghosts.before(play_ghosts))
+                .with_system(play_ghosts),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(show_game_over))
+        .add_system_set(
+            SystemSet::on_exit(GameState::GameOver).with_system(despawn_screen::<GameOverScreen>),
+        )
+        .add_system(update_score.after(GameplaySet::CollisionResponse))
+        .add_system(animate_sprites.label(GameplaySet::Presentation))
+        .add_startup_system(play_music);
+    if versus {
+        app.add_plugin(VersusPlugin);
+    }
+    if let Some(bench) = bench {
+        app.insert_resource(bench).add_plugin(BenchPlugin);
+    }
+    // cheats stay out of release builds, where runs are scored, submitted and raced
+    #[cfg(feature = "debug")]
+    app.add_plugin(InspectorTogglePlugin)
+        .add_plugin(ConsolePlugin);
+    app.run();
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_bullet_collision(
+    mut commands: Commands,
+    bullets: Query<
+        (
+            Entity,
+            &Collisions,
+            &Transform,
+            Option<&Velocity>,
+            Option<&Projectile>,
+        ),
+        (With<Bullet>, Without<ThrownWeapon>),
+    >,
+    enemies: Query<(&Transform, &Enemy, Option<&Velocity>), Without<Boss>>,
+    guards: Query<&Enemy, With<Guarding>>,
+    shields: Query<&Parent, With<EnemyShield>>,
+    mut damage: EventWriter<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut slain: ResMut<Slain>,
+    mut swept_hits: EventReader<SweptHit>,
+    mut rng: ResMut<GameRng>,
+    mut affixes: ResMut<AffixRegistry>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+) {
+    let swept: Vec<&SweptHit> = swept_hits.iter().collect();
+    bullets
+        .iter()
+        .for_each(|(bullet, collision, bullet_trans, velocity, projectile)| {
+            // what the bullet is touching now plus whatever it flew through since last frame
+            let mut touched: Vec<Entity> = collision
+                .entities()
+                .chain(
+                    swept
+                        .iter()
+                        .filter(|hit| hit.bullet == bullet)
+                        .map(|hit| hit.target),
+                )
+                .collect();
+            // an enemy can be both touched and swept through, it still only takes the one hit
+            touched.sort_unstable();
+            touched.dedup();
+            // touching a raised shield from the front, or the body behind it, stops the bullet
+            let heading = velocity.map_or(Vec3::ZERO, |velocity| velocity.linear);
+            let blocked = touched.iter().any(|&entity| {
+                let owner = shields.get(entity).map_or(entity, |parent| parent.get());
+                guards
+                    .get(owner)
+                    .map_or(false, |enemy| blocks(enemy, heading))
+            });
+            if blocked {
+                spawn_clink(
+                    &mut commands,
+                    &mut rng,
+                    bullet_trans.translation,
+                    &audio,
+                    &assets,
+                    &settings,
+                );
+                commands.entity(bullet).despawn_recursive();
+                return;
+            }
+            touched.iter().for_each(|&entity| {
+                if let Ok((trans, enemy, enemy_velocity)) = enemies.get(entity) {
+                    let victim = Combatant::Enemy(enemy.asset);
+                    let amount = projectile.map_or(1, Projectile::damage);
+                    // armor takes the bullet, the enemy carries on
+                    if affixes.absorb(entity) {
+                        damage.send(DamageEvent {
+                            target: entity,
+                            victim,
+                            amount,
+                            position: trans.translation,
+                            absorbed: true,
+                            source: Some(bullet_trans.translation),
+                            by_player: projectile.is_some(),
+                        });
+                        commands.entity(bullet).despawn_recursive();
+                        return;
+                    }
+                    damage.send(DamageEvent {
+                        target: entity,
+                        victim,
+                        amount,
+                        position: trans.translation,
+                        absorbed: false,
+                        source: Some(bullet_trans.translation),
+                        by_player: projectile.is_some(),
+                    });
+                    if !slain.claim(entity) {
+                        return;
+                    }
+                    deaths.send(DeathEvent {
+                        entity,
+                        victim,
+                        position: trans.translation,
+                        weapon: projectile.map(Projectile::weapon),
+                        airborne: airborne(enemy_velocity),
+                        thrown: false,
+                    });
+                    commands.entity(entity).despawn_recursive();
+                }
+            });
+        });
+}
+
+// a thrown gun hits as hard as it shot, then it's just debris bouncing off the level
+pub fn handle_thrown_weapon_collision(
+    mut commands: Commands,
+    thrown: Query<(Entity, &Collisions, &Transform, &ThrownWeapon)>,
+    enemies: Query<(&Transform, &Enemy, Option<&Velocity>), Without<Boss>>,
+    mut damage: EventWriter<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut slain: ResMut<Slain>,
+    mut affixes: ResMut<AffixRegistry>,
+) {
+    thrown
+        .iter()
+        .for_each(|(gun, collisions, gun_trans, thrown)| {
+            let hit = collisions
+                .entities()
+                .find_map(|other| enemies.get(other).ok().map(|found| (other, found)));
+            let (entity, (trans, enemy, enemy_velocity)) = match hit {
+                Some(hit) => hit,
+                None => return,
+            };
+            commands
+                .entity(gun)
+                .remove::<ThrownWeapon>()
+                .insert(collision_layers(Layers::Debris));
+            let victim = Combatant::Enemy(enemy.asset);
+            let absorbed = affixes.absorb(entity);
+            damage.send(DamageEvent {
+                target: entity,
+                victim,
+                amount: weapon_stats(thrown.weapon).damage,
+                position: trans.translation,
+                absorbed,
+                source: Some(gun_trans.translation),
+                by_player: true,
+            });
+            if absorbed || !slain.claim(entity) {
+                return;
+            }
+            deaths.send(DeathEvent {
+                entity,
+                victim,
+                position: trans.translation,
+                weapon: Some(thrown.weapon),
+                airborne: airborne(enemy_velocity),
+                thrown: true,
+            });
             commands.entity(entity).despawn_recursive();
         });
-    });
 }
 
-pub fn play_music(audio: Res<Audio>, asset_server: Res<AssetServer>) {
-    audio.play_with_settings(
-        asset_server.load("sounds/backtrack.ogg"),
+pub fn play_music(
+    mut commands: Commands,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    settings: Res<Settings>,
+) {
+    let sink = audio.play_with_settings(
+        assets.music.clone(),
         PlaybackSettings {
             repeat: true,
-            volume: 0.1,
+            volume: settings.music,
             speed: 1.0,
         },
     );
+    commands.insert_resource(MusicController(audio_sinks.get_handle(sink)));
 }
 
 pub fn weapon_enum_to_string(weapon: Weapons) -> String {
@@ -176,6 +675,11 @@ pub fn weapon_enum_to_string(weapon: Weapons) -> String {
         Weapons::Airplane => output += "images/Airplane.png",
         Weapons::Shotgun => output += "images/Shotgun.png",
         Weapons::Rocket => output += "images/Rocket.png",
+        Weapons::EnemyGun => output += "images/EnemyGun.png",
+        Weapons::HomingRocket => output += "images/HomingRocket.png",
+        Weapons::Laser => output += "images/Laser.png",
+        Weapons::Grenade => output += "images/Grenade.png",
+        Weapons::Smg => output += "images/Smg.png",
         //_defualt => output += "",
     }
     output
@@ -206,14 +710,29 @@ pub fn handle_difficulty(
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
 pub enum Behavior {
     Walker,
     Jumper,
     Shooter,
     BurstShooter,
+    Charger,
+    Summoner,
+    Shielded,
+    Bomber,
 }
 
+pub const ALL_BEHAVIORS: [Behavior; 8] = [
+    Behavior::Walker,
+    Behavior::Jumper,
+    Behavior::Shooter,
+    Behavior::BurstShooter,
+    Behavior::Charger,
+    Behavior::Summoner,
+    Behavior::Shielded,
+    Behavior::Bomber,
+];
+
 #[derive(Component, Deref, DerefMut)]
 pub struct AnimationTimer(Timer);
 
@@ -238,6 +757,10 @@ pub fn behavior_to_asset(behav: Behavior) -> String {
         Behavior::Jumper => String::from("images/Jumper.png"),
         Behavior::Shooter => String::from("images/Shooter.png"),
         Behavior::BurstShooter => String::from("images/BurstShooter.png"),
+        Behavior::Charger => String::from("images/Charger.png"),
+        Behavior::Summoner => String::from("images/Summoner.png"),
+        Behavior::Shielded => String::from("images/Shielded.png"),
+        Behavior::Bomber => String::from("images/Bomber.png"),
     }
 }
 
@@ -265,27 +788,39 @@ pub struct Slide {
 }
 
 #[derive(Component)]
-pub struct BurstShot {
+pub struct Shooter {
     timer: Timer,
     audio: Sounds,
 }
 
+#[derive(PartialEq, Eq)]
+enum ChargePhase {
+    // stands still and flashes with a line out toward the player
+    Windup,
+    Rush,
+    // dazed after slamming into a wall
+    Stunned,
+}
+
 #[derive(Component)]
-pub struct Shooter {
+pub struct Charge {
+    phase: ChargePhase,
     timer: Timer,
-    audio: Sounds,
+    direction: f32,
 }
 
+const CHARGE_SPEED: f32 = 220.0;
+
 #[derive(Component)]
 pub struct Despawner(Timer);
 
 pub fn handle_despawner(
     mut commands: Commands,
     mut despawners: Query<(&mut Despawner, Entity), With<Despawner>>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
 ) {
     despawners.iter_mut().for_each(|(mut despawn, entity)| {
-        despawn.0.tick(time.delta());
+        despawn.0.tick(clock.delta());
         if despawn.0.finished() {
             commands.entity(entity).despawn_recursive();
         }
@@ -294,42 +829,47 @@ pub fn handle_despawner(
 
 pub fn handle_shooter(
     mut commands: Commands,
-    mut shooters: Query<(&Transform, &mut Shooter, Entity), With<Shooter>>,
+    mut shooters: Query<(&Transform, &mut Shooter, Entity, &Enemy), With<Shooter>>,
     player: Query<&Player>,
-    time: Res<Time>,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    step: Res<FixedStep>,
+    assets: Res<GameAssets>,
+    atlases: Res<AtlasRegistry>,
+    rules: Res<CollisionRules>,
+    mut rng: ResMut<GameRng>,
+    mut tokens: ResMut<AttackTokens>,
+    mut sounds: EventWriter<SpatialSound>,
 ) {
     player.iter().for_each(|player| {
         shooters
             .iter_mut()
-            .for_each(|(trans, mut shooter, entity)| {
-                shooter.timer.tick(time.delta());
+            .for_each(|(trans, mut shooter, entity, enemy)| {
+                // holds its fire until it's this shooter's turn
+                if !tokens.request(entity) {
+                    return;
+                }
+                shooter.timer.tick(step.delta());
                 let dx = trans.translation.x - player.location.x;
                 let dy = trans.translation.y - player.location.y;
                 let angle = atan2f(dy, dx);
                 if shooter.timer.finished() {
-                    let bullet_handle: Handle<Image> = asset_server.load("images/EnemyBullet.png");
-                    let texture_atlas =
-                        TextureAtlas::from_grid(bullet_handle, Vec2::new(3.0, 3.0), 4, 2);
-                    let sprite = texture_atlases.add(texture_atlas);
-                    commands
-                        .spawn_bundle(SpriteSheetBundle {
-                            transform: Transform::from_translation(trans.translation),
-                            texture_atlas: sprite,
-                            ..default()
-                        })
-                        .insert(AnimationTimer(Timer::from_seconds(0.2, true)))
-                        .insert(Collisions::default())
-                        .insert(RigidBody::Sensor)
-                        .insert(CollisionShape::Sphere { radius: 1.5 })
-                        .insert(Velocity {
-                            linear: Vec3::new(-sinf(angle) * 50.0, cosf(angle) * 50.0, 0.0),
-                            ..default()
-                        });
+                    let patterns = shot_patterns(enemy.asset);
+                    let pattern = patterns[rng.gen_range(0..patterns.len())];
+                    fire_pattern(
+                        &mut commands,
+                        &atlases,
+                        &rules,
+                        trans.translation,
+                        player.location,
+                        pattern,
+                    );
+                    sounds.send(SpatialSound {
+                        sound: assets.enemy_shot.clone(),
+                        position: trans.translation,
+                    });
+                    tokens.release(entity);
                     commands.entity(entity).remove::<Shooter>();
                 }
-                let trace_handle: Handle<Image> = asset_server.load("images/Trace.png");
+                let trace_handle: Handle<Image> = assets.trace.clone();
                 let sprite_size = Vec2::new(500., 1.0);
                 commands
                     .spawn_bundle(SpriteBundle {
@@ -339,13 +879,15 @@ pub fn handle_shooter(
                             ..default()
                         },
                         transform: Transform {
-                            translation: trans.translation,
+                            translation: RenderLayer::Projectiles.at(trans.translation.truncate()),
                             rotation: Quat::from_rotation_z(angle),
                             ..default()
                         },
                         ..default()
                     })
-                    .insert(Despawner(Timer::from_seconds(0.05, false)));
+                    // one trace per tick, gone by the next
+                    .insert(Despawner(Timer::new(step.delta(), false)))
+                    .insert(RunEntity);
             });
     });
 }
@@ -353,83 +895,225 @@ pub fn handle_shooter(
 pub fn handle_slides(
     mut commands: Commands,
     mut sliders: Query<(&mut Transform, &mut Slide, Entity, &Enemy), With<Slide>>,
-    time: Res<Time>,
+    step: Res<FixedStep>,
+    gravity: Res<GravityData>,
 ) {
     sliders
         .iter_mut()
         .for_each(|(mut trans, mut slide, entity, enemy)| {
-            slide.timer.tick(time.delta());
+            slide.timer.tick(step.delta());
             if slide.timer.finished() {
                 trans.scale.x = 1.0;
-                commands.entity(entity).remove::<Slide>();
+                commands.entity(entity).remove::<Slide>().remove::<Tween>();
                 return;
             }
-            trans.translation.x += 20.0 * time.delta_seconds() * enemy.direction;
-            trans.scale.x += 0.5 * time.delta_seconds();
+            trans.translation.x +=
+                20.0 * gravity.enemy_speed * step.delta_seconds() * enemy.direction;
         });
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn handle_chargers(
+    mut commands: Commands,
+    mut chargers: Query<(
+        Entity,
+        &Transform,
+        &mut Charge,
+        &mut Velocity,
+        &mut Enemy,
+        &mut TextureAtlasSprite,
+    )>,
+    players: Query<&Player>,
+    nav: Res<NavGraph>,
+    step: Res<FixedStep>,
+    assets: Res<GameAssets>,
+    palette: Res<Palette>,
+    gravity: Res<GravityData>,
+) {
+    let player = match players.iter().next() {
+        Some(player) => player.location,
+        None => return,
+    };
+    let charge_speed = CHARGE_SPEED * gravity.enemy_speed;
+    chargers.iter_mut().for_each(
+        |(entity, trans, mut charge, mut velocity, mut enemy, mut sprite)| {
+            charge.timer.tick(step.delta());
+            match charge.phase {
+                ChargePhase::Windup => {
+                    // picks a side and commits to it, the player gets the whole windup to dodge
+                    // toward the way up when the player is on a platform out of reach
+                    charge.direction = nav.step_toward(trans.translation, player).direction;
+                    let flash = (charge.timer.elapsed_secs() * 10.0) as u32 % 2 == 0;
+                    sprite.color = if flash { palette.danger } else { Color::WHITE };
+                    commands
+                        .spawn_bundle(SpriteBundle {
+                            texture: assets.trace.clone(),
+                            sprite: Sprite {
+                                custom_size: Some(Vec2::new(500.0, 1.0)),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(
+                                RenderLayer::Projectiles.at(trans.translation.truncate()
+                                    + Vec2::new(250.0 * charge.direction, 0.0)),
+                            ),
+                            ..default()
+                        })
+                        .insert(Despawner(Timer::new(step.delta(), false)))
+                        .insert(RunEntity);
+                    if charge.timer.finished() {
+                        sprite.color = Color::WHITE;
+                        enemy.direction = charge.direction;
+                        charge.phase = ChargePhase::Rush;
+                        charge.timer = Timer::from_seconds(1.5, false);
+                    }
+                }
+                ChargePhase::Rush => {
+                    // anything that soaked up most of last tick's speed was a wall
+                    let blocked = charge.timer.elapsed() > step.delta()
+                        && velocity.linear.x.abs() < charge_speed * 0.5;
+                    if blocked {
+                        velocity.linear = Vec3::new(-charge.direction * 80.0, 60.0, 0.0);
+                        sprite.color = palette.stunned;
+                        charge.phase = ChargePhase::Stunned;
+                        charge.timer = Timer::from_seconds(1.0, false);
+                    } else if charge.timer.finished() {
+                        commands.entity(entity).remove::<Charge>();
+                    } else {
+                        velocity.linear.x = charge_speed * charge.direction;
+                    }
+                }
+                ChargePhase::Stunned => {
+                    if charge.timer.finished() {
+                        sprite.color = Color::WHITE;
+                        enemy.direction = -charge.direction;
+                        commands.entity(entity).remove::<Charge>();
+                    }
+                }
+            }
+        },
+    );
+}
+
 pub fn handle_jumpers(
     mut commands: Commands,
     mut jumpers: Query<(&mut Transform, &mut Jump, &mut Velocity, Entity, &mut Enemy), With<Jump>>,
-    time: Res<Time>,
+    step: Res<FixedStep>,
+    mut rng: ResMut<GameRng>,
+    gravity: Res<GravityData>,
 ) {
     jumpers
         .iter_mut()
         .for_each(|(mut trans, mut jump, mut vel, entity, mut enemy)| {
-            jump.timer.tick(time.delta());
+            jump.timer.tick(step.delta());
             if jump.timer.finished() {
                 trans.scale.y = 1.0;
                 enemy.delay_move.reset();
-                let mut rand = rand::thread_rng();
-                let x_vel = rand.gen_range(20.0..100.0) as f32;
-                let y_vel = rand.gen_range(200.0..500.0) as f32;
-                let direction = round::floor(rand.gen_range(-1.0..1.0), -1) as f32;
+                let x_vel = rng.gen_range(20.0..100.0) as f32;
+                let y_vel = rng.gen_range(200.0..500.0) as f32;
+                let direction = round::floor(rng.gen_range(-1.0..1.0), -1) as f32;
                 vel.linear.y = y_vel;
-                vel.linear.x = x_vel * direction;
-                commands.entity(entity).remove::<Jump>();
+                vel.linear.x = x_vel * gravity.enemy_speed * direction;
+                commands.entity(entity).remove::<Jump>().remove::<Tween>();
                 return;
             }
-            trans.scale.y -= 0.3 * time.delta_seconds();
         });
 }
 
 pub fn move_enemies(
     mut commands: Commands,
     mut enemies: Query<
-        (&mut Enemy, Entity),
+        (&mut Enemy, Entity, &Transform, &mut Velocity),
         (
             With<Enemy>,
             Without<Jump>,
             Without<Slide>,
             Without<Shooter>,
-            Without<BurstShot>,
+            Without<Charge>,
+            Without<Casting>,
+            Without<Turning>,
+            Without<Chase>,
+            Without<BomberFuse>,
         ),
     >,
-    time: Res<Time>,
+    players: Query<&Player>,
+    nav: Res<NavGraph>,
+    step: Res<FixedStep>,
 ) {
-    enemies.iter_mut().for_each(|(mut enemy, entity)| {
-        enemy.delay_move.tick(time.delta());
-        if enemy.delay_move.finished() {
-            match enemy.asset {
-                Behavior::Walker => commands.entity(entity).insert(Slide {
-                    timer: Timer::from_seconds(0.5, false),
-                }),
-                Behavior::BurstShooter => commands.entity(entity).insert(BurstShot {
-                    timer: Timer::from_seconds(1.4, false),
-                    audio: Sounds::EnemyShot,
-                }),
-                Behavior::Jumper => commands.entity(entity).insert(Jump {
-                    timer: Timer::from_seconds(2.5, false),
-                    audio: Sounds::EnemyJump,
-                }),
-                Behavior::Shooter => commands.entity(entity).insert(Shooter {
-                    timer: Timer::from_seconds(1.0, false),
-                    audio: Sounds::EnemyShot,
-                }),
-            };
-        }
-    });
+    let player = players.iter().next().map(|player| player.location);
+    enemies
+        .iter_mut()
+        .for_each(|(mut enemy, entity, trans, mut velocity)| {
+            enemy.delay_move.tick(step.delta());
+            if enemy.delay_move.finished() {
+                match enemy.asset {
+                    // heads for the player, hopping up to whatever platform they're on
+                    Behavior::Walker => {
+                        if let Some(player) = player {
+                            let next = nav.step_toward(trans.translation, player);
+                            enemy.direction = next.direction;
+                            if next.jump {
+                                velocity.linear.y = JUMP_SPEED;
+                            }
+                        }
+                        commands
+                            .entity(entity)
+                            .insert(Slide {
+                                timer: Timer::from_seconds(0.5, false),
+                            })
+                            // one stretch and back over the slide instead of snapping at the end
+                            .insert(
+                                Tween::new(0.25, Ease::InOut)
+                                    .with(TweenTarget::ScaleX(1.0, 1.15))
+                                    .repeat(Repeat::PingPong),
+                            )
+                    }
+                    Behavior::BurstShooter => commands.entity(entity).insert(Shooter {
+                        timer: Timer::from_seconds(1.4, false),
+                        audio: Sounds::EnemyShot,
+                    }),
+                    Behavior::Jumper => commands
+                        .entity(entity)
+                        .insert(Jump {
+                            timer: Timer::from_seconds(2.5, false),
+                            audio: Sounds::EnemyJump,
+                        })
+                        .insert(
+                            Tween::new(2.5, Ease::Linear)
+                                .with(TweenTarget::Scale(Vec3::ONE, Vec3::new(1.0, 0.25, 1.0))),
+                        ),
+                    Behavior::Shooter => commands.entity(entity).insert(Shooter {
+                        timer: Timer::from_seconds(1.0, false),
+                        audio: Sounds::EnemyShot,
+                    }),
+                    Behavior::Charger => commands.entity(entity).insert(Charge {
+                        phase: ChargePhase::Windup,
+                        timer: Timer::from_seconds(0.8, false),
+                        direction: enemy.direction,
+                    }),
+                    Behavior::Summoner => commands
+                        .entity(entity)
+                        .insert(Casting::default())
+                        .insert(cast_tween()),
+                    // turns to face the player when they get behind it, otherwise walks on
+                    Behavior::Shielded => {
+                        let behind = player.map_or(false, |player| {
+                            (player.x - trans.translation.x) * enemy.direction < 0.0
+                        });
+                        if behind {
+                            commands
+                                .entity(entity)
+                                .remove::<Guarding>()
+                                .insert(Turning::default())
+                        } else {
+                            commands.entity(entity).insert(Slide {
+                                timer: Timer::from_seconds(0.5, false),
+                            })
+                        }
+                    }
+                    Behavior::Bomber => commands.entity(entity).insert(Chase::default()),
+                };
+            }
+        });
 }
 
 /* pub fn spawn_enemy(commands: &mut Commands, asset_server: AssetServer, behavior: Behavior) {
@@ -465,7 +1149,7 @@ pub fn move_enemies(
 }
  */
 pub fn animate_sprites(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     texture_atlases: Res<Assets<TextureAtlas>>,
     mut sprites: Query<(
         &mut AnimationTimer,
@@ -474,7 +1158,7 @@ pub fn animate_sprites(
     )>,
 ) {
     for (mut timer, mut sprite, texture_atlas_handle) in &mut sprites {
-        timer.tick(time.delta());
+        timer.tick(clock.delta());
         if timer.just_finished() {
             let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
             sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
@@ -484,137 +1168,172 @@ pub fn animate_sprites(
 
 pub fn spawn_warned(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    materials: Res<PhysicsMaterials>,
     mut waiting_weapons: Query<(&mut SpawnWeapon, Entity), With<SpawnWeapon>>,
-    time: Res<Time>,
+    mut beacons: Query<&mut Beacon>,
+    clock: Res<GameClock>,
 ) {
     waiting_weapons.iter_mut().for_each(|(mut weapon, entity)| {
-        weapon.timer.tick(time.delta());
+        weapon.timer.tick(clock.delta());
         if weapon.timer.finished() {
             commands.entity(entity).despawn_recursive();
-            let weapon_string = weapon_enum_to_string(weapon.asset);
-            let weapon_sprite: Handle<Image> = asset_server.load(&weapon_string);
-            let weapon_size = Vec2::new(14., 4.);
-            commands
+            let pickup = spawn_weapon_pickup(
+                &mut commands,
+                &assets,
+                &materials,
+                weapon.position,
+                weapon.asset,
+            );
+            // the beacon keeps shining until the weapon itself is picked up
+            beacons
+                .iter_mut()
+                .filter(|beacon| beacon.source == entity)
+                .for_each(|mut beacon| beacon.source = pickup);
+        }
+    });
+}
+
+// a falling weapon with a grab sensor around it, returns the sensor
+pub fn spawn_weapon_pickup(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    materials: &PhysicsMaterials,
+    position: Vec3,
+    weapon: Weapons,
+) -> Entity {
+    let weapon_sprite = assets.weapon(weapon);
+    let weapon_size = Vec2::new(14., 4.);
+    let mut pickup = None;
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform::from_translation(RenderLayer::Pickups.at(position.truncate())),
+            sprite: Sprite {
+                color: Color::Rgba {
+                    red: 0.0,
+                    green: 0.0,
+                    blue: 0.0,
+                    alpha: 0.0,
+                },
+                custom_size: Some(weapon_size),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            let child = parent
                 .spawn_bundle(SpriteBundle {
-                    transform: Transform::from_translation(weapon.position),
+                    texture: weapon_sprite,
                     sprite: Sprite {
-                        color: Color::Rgba {
-                            red: 0.0,
-                            green: 0.0,
-                            blue: 0.0,
-                            alpha: 0.0,
-                        },
                         custom_size: Some(weapon_size),
                         ..default()
                     },
                     ..default()
                 })
-                .with_children(|parent| {
-                    parent
-                        .spawn_bundle(SpriteBundle {
-                            texture: weapon_sprite,
-                            sprite: Sprite {
-                                custom_size: Some(weapon_size),
-                                ..default()
-                            },
-                            ..default()
-                        })
-                        .insert(RigidBody::Sensor)
-                        .insert(CollisionShape::Sphere { radius: 15.0 })
-                        .insert(
-                            CollisionLayers::none()
-                                .with_group(Layers::Weapons)
-                                .with_mask(Layers::Player),
-                        )
-                        .insert(Weapon {
-                            asset: Weapons::Base,
-                        })
-                        .insert(Collisions::default());
-                })
-                .insert(RigidBody::Dynamic)
-                .insert(CollisionShape::Cuboid {
-                    half_extends: weapon_size.extend(0.) / 2.0,
-                    border_radius: None,
-                })
-                .insert(CollisionLayers::none().with_group(Layers::Weapons))
-                .insert(Bullet {
-                    timer: Timer::from_seconds(5.0, false),
-                })
-                .insert(Name::new("Weapon"));
-        }
-    });
+                .insert(RigidBody::Sensor)
+                .insert(CollisionShape::Sphere { radius: 15.0 })
+                .insert(collision_layers(Layers::Weapons))
+                .insert(Weapon { asset: weapon })
+                .insert(Collisions::default())
+                .id();
+            pickup = Some(child);
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(CollisionShape::Cuboid {
+            half_extends: weapon_size.extend(0.) / 2.0,
+            border_radius: None,
+        })
+        .insert(materials.weapon(weapon, false))
+        .insert(collision_layers(Layers::Weapons))
+        .insert(Velocity::default())
+        .insert(Magnetic::default())
+        .insert(Bullet {
+            timer: Timer::from_seconds(5.0, false),
+        })
+        .insert(Name::new("Weapon"))
+        .insert(RunEntity);
+    pickup.unwrap()
 }
 
 pub fn spawn_warned_enemy(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    mut waiting_weapons: Query<(&mut SpawnEnemy, Entity), With<SpawnEnemy>>,
-    time: Res<Time>,
+    atlases: Res<AtlasRegistry>,
+    mut waiting_weapons: Query<
+        (&mut SpawnEnemy, Entity, Option<&Minion>, Option<&Boss>),
+        With<SpawnEnemy>,
+    >,
+    clock: Res<GameClock>,
+    rules: Res<CollisionRules>,
+    materials: Res<PhysicsMaterials>,
 ) {
     // poor naming here, thanks copy paste :)
-    waiting_weapons.iter_mut().for_each(|(mut weapon, entity)| {
-        weapon.timer.tick(time.delta());
-        if weapon.timer.finished() {
-            commands.entity(entity).despawn_recursive();
-            let weapon_string = behavior_to_asset(weapon.asset.clone());
-            let weapon_sprite: Handle<Image> = asset_server.load(&weapon_string);
-            let texture_atlas = TextureAtlas::from_grid(weapon_sprite, Vec2::new(15., 15.), 8, 4);
-            let sprite = texture_atlases.add(texture_atlas);
-            commands
-                .spawn_bundle(SpriteSheetBundle {
-                    transform: Transform::from_translation(weapon.location),
-                    texture_atlas: sprite.clone(),
-                    ..default()
-                })
-                .insert(CollisionShape::Cuboid {
-                    border_radius: None,
-                    half_extends: Vec3::new(7.5, 7.5, 0.0),
-                })
-                .insert(RigidBody::Dynamic)
-                .insert(
-                    CollisionLayers::none()
-                        .with_group(Layers::Enemies)
-                        .with_mask(Layers::World)
-                        .with_mask(Layers::Player)
-                        .with_mask(Layers::Projectiles),
-                )
-                .insert(AnimationTimer(Timer::from_seconds(0.055, true)))
-                .insert(Collisions::default())
-                .insert(Enemy {
-                    asset: weapon.asset,
-                    health: 1,
-                    direction: 1.0,
-                    delay_move: Timer::from_seconds(
-                        match weapon.asset {
-                            Behavior::Jumper => 2.0,
-                            _default => 1.0,
-                        },
-                        true,
-                    ),
-                })
-                .insert(Velocity::default())
-                .insert(Name::new("Enemy"));
-        }
-    });
+    waiting_weapons
+        .iter_mut()
+        .for_each(|(mut weapon, entity, minion, boss)| {
+            weapon.timer.tick(clock.delta());
+            if weapon.timer.finished() {
+                commands.entity(entity).despawn_recursive();
+                let enemy = commands
+                    .spawn_bundle(SpriteSheetBundle {
+                        transform: Transform::from_translation(
+                            RenderLayer::Enemies.at(weapon.location.truncate()),
+                        ),
+                        texture_atlas: atlases.enemy(weapon.asset),
+                        ..default()
+                    })
+                    .insert(CollisionShape::Cuboid {
+                        border_radius: None,
+                        half_extends: Vec3::new(7.5, 7.5, 0.0),
+                    })
+                    .insert(RigidBody::Dynamic)
+                    .insert(materials.get(MaterialClass::Enemy))
+                    .insert(RotationConstraints::lock())
+                    .insert(rules.layers(Layers::Enemies))
+                    .insert(AnimationTimer(Timer::from_seconds(0.055, true)))
+                    .insert(Collisions::default())
+                    .insert(Enemy {
+                        asset: weapon.asset,
+                        health: 1,
+                        direction: 1.0,
+                        delay_move: Timer::from_seconds(
+                            match weapon.asset {
+                                Behavior::Jumper => 2.0,
+                                Behavior::Charger => 1.5,
+                                Behavior::Summoner => 3.0,
+                                Behavior::Bomber => 0.2,
+                                _default => 1.0,
+                            },
+                            true,
+                        ),
+                    })
+                    .insert(Velocity::default())
+                    .insert(Name::new("Enemy"))
+                    .insert(RunEntity)
+                    .id();
+                // summoned enemies stay tied to whoever called them in
+                if let Some(minion) = minion {
+                    commands.entity(enemy).insert(Minion {
+                        summoner: minion.summoner,
+                    });
+                }
+                if boss.is_some() {
+                    commands.entity(enemy).insert(Boss);
+                }
+            }
+        });
 }
 
 pub fn spawn_enemy_warning(
     commands: &mut Commands,
-    texture: Handle<Image>,
-    texture_atlases: &mut ResMut<Assets<TextureAtlas>>,
+    atlases: &AtlasRegistry,
     behavior: Behavior,
-) {
-    let mut random = rand::thread_rng();
-    let random_x = random.gen_range(-200.0..200.0) as f32;
-    let texture_atlas = TextureAtlas::from_grid(texture, Vec2::new(5.0, 5.0), 5, 3);
-    let sprite = texture_atlases.add(texture_atlas);
+    x: f32,
+) -> Entity {
     commands
         .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: sprite,
+            texture_atlas: atlases.spawn_enemy.clone(),
             transform: Transform {
-                translation: Vec3::new(random_x, -92.0, 0.0),
+                translation: RenderLayer::World.at(Vec2::new(x, -92.0)),
                 scale: Vec3::splat(2.0),
                 ..default()
             },
@@ -624,85 +1343,311 @@ pub fn spawn_enemy_warning(
         .insert(SpawnEnemy {
             timer: Timer::from_seconds(1.0, false),
             asset: behavior,
-            location: Vec3::new(random_x, -92.0, 0.0),
+            location: Vec3::new(x, -92.0, 0.0),
         })
-        .insert(Name::new("Weapon Warning"));
+        .insert(
+            Tween::new(0.3, Ease::InOut)
+                .with(TweenTarget::Scale(Vec3::splat(2.0), Vec3::splat(2.4)))
+                .repeat(Repeat::PingPong),
+        )
+        .insert(Name::new("Weapon Warning"))
+        .insert(RunEntity)
+        .id()
+}
+
+pub fn spawn_enemies(
+    mut commands: Commands,
+    atlases: Res<AtlasRegistry>,
+    clock: Res<GameClock>,
+    enemy_timer: ResMut<EnemyTimer>,
+    mut difficulty: ResMut<DifficultyTimer>,
+    mut director: ResMut<Director>,
+    enemies: Query<(&Enemy, &Transform)>,
+    warnings: Query<&SpawnEnemy>,
+    players: Query<&Player>,
+    rules: Res<SpawnRules>,
+    table: Res<SpawnTable>,
+    mut memory: ResMut<SpawnMemory>,
+    mut rng: ResMut<GameRng>,
+    run_config: Res<RunConfig>,
+    bosses: Query<(), With<Boss>>,
+) {
+    // assisted runs climb slower, score attack a lot faster
+    let assist = if run_config.assisted {
+        ASSIST_RAMP
+    } else {
+        1.0
+    };
+    let ramp = assist * run_config.mode.difficulty_ramp();
+    difficulty.timer.tick(clock.delta().mul_f32(ramp));
+    // the enemy timer only sets the base interval now, the director decides when waves go out
+    let wave_due = director.spawn_ready(clock.delta(), enemy_timer.timer.duration());
+    // in versus mode the second player places every enemy by hand, and a boss fight is one on one
+    if wave_due && run_config.mode != GameMode::Versus && bosses.is_empty() {
+        let mut counts = BehaviorCounts::new(&enemies, &warnings);
+        if counts.total() < rules.soft_cap {
+            let multiplier = if run_config.modifiers.double_enemies {
+                2
+            } else {
+                1
+            };
+            let pressure = rules.pressure(players.iter().next(), &enemies);
+            let wanted = (difficulty.difficulty.min(rules.max_per_tick) * multiplier) as f32;
+            // ease off while the player is hurt or swarmed, but always send something
+            let spawn_count = ((wanted * (1.0 - pressure * rules.ease)).round() as usize)
+                .max(1)
+                .min(rules.soft_cap - counts.total());
+            for _i in 0..spawn_count {
+                // behaviors at their limit drop out of the roll
+                let spawned_type = match table.pick(
+                    difficulty.difficulty,
+                    director.phase(),
+                    &memory,
+                    |behavior| counts.get(behavior) < rules.behavior_limit(behavior),
+                    &mut rng,
+                ) {
+                    Some(spawned_type) => spawned_type,
+                    None => break,
+                };
+                counts.add(spawned_type);
+                table.remember(&mut memory, spawned_type);
+                let random_x = rng.gen_range(-200.0..200.0) as f32;
+                spawn_enemy_warning(&mut commands, &atlases, spawned_type, random_x);
+            }
+        }
+    }
+    if difficulty.timer.finished() {
+        handle_difficulty(enemy_timer, difficulty);
+    }
+}
+
+pub fn tick_timers(
+    mut commands: Commands,
+    atlases: Res<AtlasRegistry>,
+    mut weapon_time: ResMut<WeaponSpawns>,
+    mut dash_time: ResMut<DashTimer>,
+    mut any_dashing: Query<(Entity, &mut Dashing), With<Dashing>>,
+    mut any_bullets: Query<(Entity, &mut Bullet)>,
+    clock: Res<GameClock>,
+    mut rng: ResMut<GameRng>,
+    run_config: Res<RunConfig>,
+    save: Res<SaveData>,
+) {
+    any_dashing.iter_mut().for_each(|(dasher, mut dashing)| {
+        dashing.timer.tick(clock.unscaled_delta());
+        if dashing.timer.finished() {
+            commands.entity(dasher).remove::<Dashing>();
+        }
+    });
+    any_bullets.iter_mut().for_each(|(entity, mut bullet)| {
+        bullet.timer.tick(clock.delta());
+        if bullet.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    });
+    dash_time.timer.tick(clock.unscaled_delta());
+    dash_time.cooldown.tick(clock.unscaled_delta());
+    weapon_time
+        .timer
+        .tick(clock.delta().mul_f32(run_config.mode.weapon_drop_rate()));
+    if weapon_time.timer.finished() {
+        let weapon = if run_config.modifiers.shotgun_only {
+            Weapons::Shotgun
+        } else {
+            let table = drop_table(&save);
+            table[rng.gen_range(0..table.len())]
+        };
+        warn_weapon_spawn(commands, &atlases, weapon, &mut rng);
+    }
+}
+
+#[derive(Component)]
+pub struct EnemyBullet {
+    // where it was fired from, so a hit can point back at the shooter
+    origin: Vec3,
+}
+
+#[derive(Component)]
+pub struct Invulnerable(Timer);
+
+// debug console and practice cheat, the player ignores every hit while this is on
+#[derive(Component)]
+pub struct GodMode;
+
+pub fn hurt_player(
+    mut commands: Commands,
+    mut players: Query<
+        (Entity, &mut Player, &Collisions, Option<&mut Shield>),
+        (Without<Invulnerable>, Without<GodMode>),
+    >,
+    enemies: Query<&Transform, With<Enemy>>,
+    enemy_bullets: Query<&EnemyBullet>,
+    hazards: Query<&Transform, With<Hazard>>,
+    mut explosions: EventReader<ExplosionEvent>,
+    mut shield_broken: EventWriter<ShieldBroken>,
+    mut damage: EventWriter<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+) {
+    let blasts: Vec<&ExplosionEvent> = explosions
+        .iter()
+        .filter(|explosion| explosion.hurts_player)
+        .collect();
+    players
+        .iter_mut()
+        .for_each(|(entity, mut player, collisions, shield)| {
+            let mut source = blasts
+                .iter()
+                .find(|blast| blast.reaches(player.location))
+                .map(|blast| blast.position);
+            collisions.entities().for_each(|other| {
+                if let Ok(bullet) = enemy_bullets.get(other) {
+                    commands.entity(other).despawn_recursive();
+                    source = source.or(Some(bullet.origin));
+                } else if let Ok(trans) = enemies.get(other).or_else(|_| hazards.get(other)) {
+                    source = source.or(Some(trans.translation));
+                }
+            });
+            if source.is_none() {
+                return;
+            }
+            let absorbed = shield.map_or(false, |mut shield| {
+                let absorbed = shield.absorb();
+                if absorbed && shield.is_broken() {
+                    shield_broken.send(ShieldBroken(player.location));
+                }
+                absorbed
+            });
+            damage.send(DamageEvent {
+                target: entity,
+                victim: Combatant::Player,
+                amount: 1,
+                position: player.location,
+                absorbed,
+                source,
+                by_player: false,
+            });
+            if absorbed {
+                commands
+                    .entity(entity)
+                    .insert(Invulnerable(Timer::from_seconds(1.0, false)));
+                return;
+            }
+            let position = player.location;
+            wound_player(&mut commands, &mut deaths, entity, &mut player, position);
+        });
+}
+
+pub fn handle_invulnerable(
+    mut commands: Commands,
+    mut invulnerable: Query<(Entity, &mut Invulnerable, &mut Sprite)>,
+    clock: Res<GameClock>,
+) {
+    invulnerable
+        .iter_mut()
+        .for_each(|(entity, mut invulnerable, mut sprite)| {
+            invulnerable.0.tick(clock.delta());
+            if invulnerable.0.finished() {
+                sprite.color.set_a(1.0);
+                commands.entity(entity).remove::<Invulnerable>();
+                return;
+            }
+            // blink so it's obvious the hit registered
+            let blink = (invulnerable.0.elapsed_secs() * 10.0) as i32 % 2 == 0;
+            sprite.color.set_a(if blink { 0.3 } else { 1.0 });
+        });
+}
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+pub fn show_game_over(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    score: Res<Score>,
+    breakdown: Res<ScoreBreakdown>,
+) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.6)))
+        .insert(GameOverScreen)
+        .insert(Name::new("Game Over"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Game Over");
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    format!("Score: {}", score.score),
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 40.,
+                        color: Color::WHITE,
+                    },
+                ),
+                ..default()
+            });
+            let lines: Vec<String> = SCORE_SOURCES
+                .iter()
+                .map(|source| format!("{}: {}", source.name(), breakdown.points(*source)))
+                .collect();
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    lines.join("\n"),
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 20.,
+                        color: Color::rgba(1.0, 1.0, 1.0, 0.7),
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                ..default()
+            });
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    format!("+{} coins", run_reward(&score)),
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 30.,
+                        color: Color::GOLD,
+                    },
+                ),
+                ..default()
+            });
+            spawn_button(parent, &font_handle, "Retry", MenuButton::Restart);
+            spawn_button(parent, &font_handle, "Quit to Menu", MenuButton::QuitToMenu);
+        });
 }
 
-pub fn tick_timers(
+pub fn reset_world(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    mut weapon_time: ResMut<WeaponSpawns>,
-    mut dash_time: ResMut<DashTimer>,
-    mut any_dashing: Query<(Entity, &mut Dashing), With<Dashing>>,
-    mut any_bullets: Query<(Entity, &mut Bullet)>,
-    time: Res<Time>,
-    mut any_spinners: Query<&mut Spinning>,
-    mut enemy_timer: ResMut<EnemyTimer>,
-    mut difficulty: ResMut<DifficultyTimer>,
-    enemies: Query<&Enemy>,
+    mut resets: EventReader<ResetGame>,
+    run_entities: Query<Entity, With<RunEntity>>,
+    assets: Res<GameAssets>,
+    mut run_config: ResMut<RunConfig>,
+    save: Res<SaveData>,
+    settings: Res<Settings>,
+    characters: Res<Assets<CharacterDef>>,
+    materials: Res<PhysicsMaterials>,
 ) {
-    difficulty.timer.tick(time.delta());
-    enemy_timer.timer.tick(time.delta());
-    if enemy_timer.timer.finished() {
-        if enemies.iter().len() <= 100 as usize {
-            for _i in 0..if difficulty.difficulty < 8 {
-                difficulty.difficulty
-            } else {
-                6
-            } {
-                let sheet: Handle<Image> = asset_server.load("images/SpawnEnemy.png");
-                let mut rand = rand::thread_rng();
-                let decider = rand.gen_range(0..difficulty.difficulty);
-                let spawned_type = match decider % 12 {
-                    0 => Behavior::Walker,
-                    1 => Behavior::Jumper,
-                    2 => Behavior::Shooter,
-                    3 => Behavior::BurstShooter,
-                    4 => Behavior::Jumper,
-                    5 => Behavior::Jumper,
-                    6 => Behavior::Shooter,
-                    7 => Behavior::Jumper,
-                    8 => Behavior::BurstShooter,
-                    9 => Behavior::Walker,
-                    10 => Behavior::Shooter,
-                    11 => Behavior::Shooter,
-                    _def => Behavior::Jumper,
-                };
-                spawn_enemy_warning(&mut commands, sheet, &mut texture_atlases, spawned_type);
-                //spawn_enemy(&mut commands, asset_server.clone(), Behavior::Walker);
-            }
-        }
-    }
-    if difficulty.timer.finished() {
-        handle_difficulty(enemy_timer, difficulty);
+    // a retry click and a state change can both ask for a reset, only do it once
+    if resets.iter().count() == 0 {
+        return;
     }
-    any_spinners.iter_mut().for_each(|mut spinner| {
-        spinner.timer.tick(time.delta());
+    run_entities.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
     });
-    any_dashing.iter_mut().for_each(|(dasher, mut dashing)| {
-        dashing.timer.tick(time.delta());
-        if dashing.timer.finished() {
-            commands.entity(dasher).remove::<Dashing>();
-        }
-    });
-    any_bullets.iter_mut().for_each(|(entity, mut bullet)| {
-        bullet.timer.tick(time.delta());
-        if bullet.timer.finished() {
-            commands.entity(entity).despawn_recursive();
-        }
-    });
-    dash_time.timer.tick(time.delta());
-    weapon_time.timer.tick(time.delta());
-    if weapon_time.timer.finished() {
-        let warn_sprite: Handle<Image> = asset_server.load("images/SpawnWeapon.png");
-        warn_weapon_spawn(commands, warn_sprite, texture_atlases, Weapons::Base);
-    }
+    run_config.reroll();
+    commands.insert_resource(run_config.rng());
+    commands.insert_resource(Score::default());
+    commands.insert_resource(DifficultyTimer::default());
+    commands.insert_resource(EnemyTimer::default());
+    commands.insert_resource(WeaponSpawns::default());
+    commands.insert_resource(DashTimer::default());
+    commands.insert_resource(ReplayRecorder::new(run_config.seed, &save, &settings));
+    commands.insert_resource(SpawnMemory::default());
+    commands.insert_resource(GameClock::default());
+    let character = selected_character(&settings, &assets, &characters);
+    spawn_player(&mut commands, &assets, &materials, &save, &character);
 }
 
-pub fn hurt_player() {}
-
 #[derive(Component)]
 pub struct SpawnWeapon {
     timer: Timer,
@@ -712,19 +1657,16 @@ pub struct SpawnWeapon {
 
 pub fn warn_weapon_spawn(
     mut commands: Commands,
-    image: Handle<Image>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    atlases: &AtlasRegistry,
     weapon: Weapons,
+    rng: &mut GameRng,
 ) {
-    let mut random = rand::thread_rng();
-    let random_x = random.gen_range(-200.0..200.0) as f32;
-    let texture_atlas = TextureAtlas::from_grid(image, Vec2::new(4.0, 4.0), 3, 6);
-    let sprite = texture_atlases.add(texture_atlas);
+    let random_x = rng.gen_range(-200.0..200.0) as f32;
     commands
         .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: sprite,
+            texture_atlas: atlases.spawn_weapon.clone(),
             transform: Transform {
-                translation: Vec3::new(random_x, 120.0, 0.0),
+                translation: RenderLayer::World.at(Vec2::new(random_x, 120.0)),
                 scale: Vec3::splat(2.0),
                 ..default()
             },
@@ -736,75 +1678,168 @@ pub fn warn_weapon_spawn(
             asset: weapon,
             position: Vec3::new(random_x, 120.0, 0.0),
         })
-        .insert(Name::new("Weapon Warning"));
+        .insert(
+            Tween::new(0.3, Ease::InOut)
+                .with(TweenTarget::Scale(Vec3::splat(2.0), Vec3::splat(2.4)))
+                .repeat(Repeat::PingPong),
+        )
+        .insert(Name::new("Weapon Warning"))
+        .insert(RunEntity);
 }
 
+// a short window after grabbing where the player can't swap again by accident
+#[derive(Component)]
+pub struct PickupCooldown(Timer);
+
 pub fn grab_weapon(
     mut commands: Commands,
-    weapons: Query<(Entity, &Collisions, &Weapon), With<Weapon>>,
+    pickups: Query<(&Parent, &Collisions, &Weapon, &GlobalTransform)>,
+    mut players: Query<(Entity, Option<&mut PickupCooldown>), With<Player>>,
+    held_items: Query<(), With<HeldItem>>,
+    mut picked_up: EventWriter<PickupEvent>,
+    actions: Res<Actions>,
+    clock: Res<GameClock>,
+) {
+    let (player, cooldown) = match players.iter_mut().next() {
+        Some(player) => player,
+        None => return,
+    };
+    if let Some(mut cooldown) = cooldown {
+        cooldown.0.tick(clock.unscaled_delta());
+        if !cooldown.0.finished() {
+            return;
+        }
+        commands.entity(player).remove::<PickupCooldown>();
+    }
+    // an empty hand grabs on touch, swapping out a held weapon needs the grab button
+    if !held_items.is_empty() && !actions.just_pressed(Action::Grab) {
+        return;
+    }
+    // only the first weapon touched this frame counts
+    let touched = pickups
+        .iter()
+        .find(|(_, collisions, _, _)| collisions.entities().any(|other| other == player));
+    if let Some((parent, _, weapon, trans)) = touched {
+        // the sensor is a child of the falling body, take the whole drop away
+        commands.entity(parent.get()).despawn_recursive();
+        commands
+            .entity(player)
+            .insert(PickupCooldown(Timer::from_seconds(0.4, false)));
+        picked_up.send(PickupEvent {
+            kind: PickupKind::Weapon(weapon.asset),
+            position: trans.translation(),
+        });
+    }
+}
+
+pub fn equip_picked_up_weapon(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    materials: Res<PhysicsMaterials>,
+    mut picked_up: EventReader<PickupEvent>,
     player_query: Query<&Player>,
-    asset_server: Res<AssetServer>,
     query_held_item: Query<(Entity, &Weapon), With<HeldItem>>,
-    mut score: ResMut<Score>,
-    difficulty: Res<DifficultyTimer>,
 ) {
-    let player_check = player_query.iter().next();
-    match player_check {
-        Some(player) => {
-            weapons.iter().for_each(|(entity, collisions, &weapon)| {
-                collisions.entities().for_each(|_collision| {
-                    score.score += 2 * difficulty.difficulty;
-                    query_held_item.iter().for_each(|(held_item, &weapon)| {
-                        let asset_str = weapon_enum_to_string(weapon.asset);
-                        let thrown_sprite: Handle<Image> = asset_server.load(&asset_str);
-                        commands.entity(held_item).despawn_recursive();
-                        commands
-                            .spawn_bundle(SpriteBundle {
-                                transform: Transform::from_translation(player.location),
-                                texture: thrown_sprite,
-                                ..default()
-                            })
-                            .insert(RigidBody::Dynamic)
-                            .insert(CollisionShape::Cuboid {
-                                half_extends: Vec3::new(4.0, 4.0, 0.0),
-                                border_radius: None,
-                            })
-                            .insert(Bullet {
-                                timer: Timer::from_seconds(4.0, false),
-                            })
-                            .insert(Name::new("Spent Weapon"))
-                            .insert(
-                                CollisionLayers::none()
-                                    .with_group(Layers::Projectiles)
-                                    .with_mask(Layers::World),
-                            );
-                    });
-                    let cloned = weapon.clone();
-                    let string_handle = weapon_enum_to_string(cloned.asset);
-                    let texture: Handle<Image> = asset_server.load(&string_handle);
-                    commands.entity(entity).despawn_recursive();
-                    commands
-                        .spawn_bundle(SpriteBundle {
-                            sprite: Sprite {
-                                flip_x: true,
-                                ..default()
-                            },
-                            texture,
-                            transform: Transform::from_translation(player.location),
-                            ..default()
-                        })
-                        .insert(HeldItem)
-                        .insert(Weapon {
-                            asset: cloned.asset,
-                        })
-                        .insert(Name::new("Held Item"));
-                })
-            });
+    picked_up.iter().for_each(|pickup| {
+        let weapon = match pickup.kind {
+            PickupKind::Weapon(weapon) => weapon,
+            _default => return,
+        };
+        if let Some(player) = player_query.iter().next() {
+            equip_weapon(
+                &mut commands,
+                &assets,
+                &materials,
+                player,
+                &query_held_item,
+                weapon,
+            );
+            spawn_pickup_pop(&mut commands, &assets, weapon, pickup.position);
         }
-        None => {
-            return;
-        } // do nothing
-    }
+    });
+}
+
+// the grabbed weapon swells and fades where it was caught
+pub fn spawn_pickup_pop(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    weapon: Weapons,
+    position: Vec3,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: assets.weapon(weapon),
+            transform: Transform::from_translation(RenderLayer::Particles.at(position.truncate())),
+            ..default()
+        })
+        .insert(
+            Tween::new(0.2, Ease::Linear)
+                .with(TweenTarget::Scale(Vec3::ONE, Vec3::splat(2.0)))
+                .with(TweenTarget::SpriteAlpha(1.0, 0.0))
+                .despawn_when_done(),
+        )
+        .insert(RunEntity);
+}
+
+// throws away whatever the player is holding and puts the new weapon in their hands
+pub fn equip_weapon(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    materials: &PhysicsMaterials,
+    player: &Player,
+    query_held_item: &Query<(Entity, &Weapon), With<HeldItem>>,
+    weapon: Weapons,
+) {
+    query_held_item.iter().for_each(|(held_item, &held)| {
+        let thrown_sprite = assets.weapon(held.asset);
+        commands.entity(held_item).despawn_recursive();
+        commands
+            .spawn_bundle(SpriteBundle {
+                transform: Transform::from_translation(
+                    RenderLayer::Projectiles.at(player.location.truncate()),
+                ),
+                texture: thrown_sprite,
+                ..default()
+            })
+            .insert(RigidBody::Dynamic)
+            .insert(CollisionShape::Cuboid {
+                half_extends: Vec3::new(4.0, 4.0, 0.0),
+                border_radius: None,
+            })
+            .insert(materials.weapon(held.asset, true))
+            .insert(Bullet {
+                timer: Timer::from_seconds(4.0, false),
+            })
+            .insert(Name::new("Spent Weapon"))
+            .insert(RunEntity)
+            .insert(ThrownWeapon { weapon: held.asset })
+            .insert(Collisions::default())
+            .insert(collision_layers(Layers::Thrown));
+    });
+    spawn_held_item(commands, assets, player.location, weapon);
+}
+
+pub fn spawn_held_item(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    location: Vec3,
+    weapon: Weapons,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                flip_x: true,
+                ..default()
+            },
+            texture: assets.weapon(weapon),
+            transform: Transform::from_translation(location.truncate().extend(held_item_z())),
+            ..default()
+        })
+        .insert(HeldItem)
+        .insert(Weapon { asset: weapon })
+        .insert(Magazine::new(weapon))
+        .insert(Name::new("Held Item"))
+        .insert(RunEntity);
 }
 
 #[derive(Component)]
@@ -812,84 +1847,74 @@ pub struct Bullet {
     timer: Timer,
 }
 
+// a thrown gun tumbling away
 #[derive(Component)]
-pub struct Spinning {
-    last_angle: f32,
-    timer: Timer,
+pub struct Spinning;
+
+// a thrown gun that hasn't hit anyone yet, taken off after its one hit
+#[derive(Component)]
+pub struct ThrownWeapon {
+    weapon: Weapons,
 }
 
-pub fn spin_spinners(mut spinners: Query<(&mut Transform, &mut Spinning), With<Spinning>>) {
-    spinners.iter_mut().for_each(|(mut trans, mut spin)| {
-        if spin.timer.finished() {
-            return;
-        }
-        spin.last_angle += 0.1;
-        trans.rotation = Quat::from_rotation_z(spin.last_angle);
-    });
+// whether the fire trigger was just pressed and whether it's held
+pub fn trigger_input(actions: &Actions) -> (bool, bool) {
+    (
+        actions.just_pressed(Action::Fire),
+        actions.pressed(Action::Fire),
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn fire_weapon(
     mut commands: Commands,
-    query_held_item: Query<(&mut Transform, Entity, &Weapon), With<HeldItem>>,
-    mut player_query: Query<(&mut Player, &mut Velocity), With<Player>>,
-    asset_server: Res<AssetServer>,
-    buttons: Res<Input<MouseButton>>,
+    mut query_held_item: Query<(&Transform, Entity, &Weapon, &mut Magazine), With<HeldItem>>,
+    mut player_query: Query<(&mut Player, &mut Velocity, &Perks), With<Player>>,
+    assets: Res<GameAssets>,
+    materials: Res<PhysicsMaterials>,
+    actions: Res<Actions>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<GameClock>,
+    aim: Res<AimPoint>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+    mut fired: EventWriter<WeaponFiredEvent>,
+    bullet_time: Res<BulletTime>,
 ) {
     let player_check = player_query.iter_mut().next();
+    let (trigger, trigger_held) = trigger_input(&actions);
     match player_check {
-        Some((player, mut player_vel)) => {
-            if buttons.just_pressed(MouseButton::Left) {
-                query_held_item
-                    .iter()
-                    .for_each(|(held_trans, held_item, weapon)| {
-                        let mut rand = rand::thread_rng();
-                        let random_x = rand.gen_range(30.0..100.0) as f32;
-                        let random_y = rand.gen_range(30.0..100.0) as f32;
-                        commands.entity(held_item).despawn_recursive();
-                        let spent_weapon = weapon_enum_to_string(weapon.asset);
-                        let spent_asset: Handle<Image> = asset_server.load(&spent_weapon);
-                        let looking_at = player.looking_at;
-
-                        commands
-                            .spawn_bundle(SpriteBundle {
-                                texture: spent_asset,
-                                transform: Transform::from_translation(held_trans.translation),
-                                ..default()
-                            })
-                            .insert(RigidBody::Dynamic)
-                            .insert(CollisionShape::Cuboid {
-                                half_extends: Vec3::new(4.0, 4.0, 0.0),
-                                border_radius: None,
-                            })
-                            .insert(Spinning {
-                                last_angle: 0.,
-                                timer: Timer::from_seconds(1.0, false),
-                            })
-                            .insert(
-                                CollisionLayers::none()
-                                    .with_group(Layers::Projectiles)
-                                    .with_mask(Layers::World),
-                            )
-                            .insert(Bullet {
-                                timer: Timer::from_seconds(4.0, false),
-                            })
-                            .insert(Name::new("Spent spinning gun"))
-                            .insert(Velocity {
-                                linear: Vec3::new(
-                                    -cosf(looking_at) * random_x,
-                                    -sinf(looking_at) * random_y,
-                                    0.0,
-                                ),
-                                ..default()
-                            });
-                        let bullet: Handle<Image> = asset_server.load("images/Bullet.png");
-                        let bullet_speed = 500.0;
-                        commands
+        Some((player, mut player_vel, perks)) => {
+            query_held_item
+                .iter_mut()
+                .for_each(|(held_trans, held_item, weapon, mut magazine)| {
+                    // the trigger keeps real time through bullet time, like the rest of the player
+                    if !magazine.pull(trigger, trigger_held, clock.unscaled_delta()) {
+                        return;
+                    }
+                    let looking_at = player.looking_at;
+                    fired.send(WeaponFiredEvent {
+                        weapon: weapon.asset,
+                        position: held_trans.translation,
+                        angle: looking_at,
+                    });
+                    let bullet_speed = 500.0;
+                    let lock = match weapon.asset {
+                        Weapons::HomingRocket => lock_target(&aim, &enemies),
+                        _ => None,
+                    };
+                    // the weapon's pellets and any extra from perks fan out evenly around the aim
+                    let stats = weapon_stats(weapon.asset);
+                    let pellets = u32::from(stats.pellets) + perks.extra_pellets;
+                    (0..pellets).for_each(|pellet| {
+                        let spread = (pellet as f32 - (pellets - 1) as f32 / 2.0) * 0.1;
+                        let angle = looking_at + spread;
+                        let bullet = commands
                             .spawn_bundle(SpriteBundle {
-                                texture: bullet,
+                                texture: assets.bullet.clone(),
                                 transform: Transform {
-                                    translation: held_trans.translation,
-                                    rotation: Quat::from_rotation_z(looking_at),
+                                    translation: RenderLayer::Projectiles
+                                        .at(held_trans.translation.truncate()),
+                                    rotation: Quat::from_rotation_z(angle),
                                     ..default()
                                 },
                                 ..default()
@@ -898,16 +1923,13 @@ pub fn fire_weapon(
                                 half_extends: Vec3::new(2.0, 2.0, 1.0),
                                 border_radius: None,
                             })
-                            .insert(RigidBody::Dynamic)
-                            .insert(
-                                CollisionLayers::none()
-                                    .with_group(Layers::Projectiles)
-                                    .with_mask(Layers::Enemies),
-                            )
+                            .insert(RigidBody::Sensor)
+                            .insert(collision_layers(Layers::Projectiles))
+                            .insert(Projectile::new(weapon.asset))
                             .insert(Velocity {
                                 linear: Vec3::new(
-                                    cosf(looking_at) * bullet_speed,
-                                    sinf(looking_at) * bullet_speed,
+                                    cosf(angle) * bullet_speed,
+                                    sinf(angle) * bullet_speed,
                                     0.0,
                                 ),
                                 ..default()
@@ -916,34 +1938,127 @@ pub fn fire_weapon(
                                 timer: Timer::from_seconds(5.0, false),
                             })
                             .insert(Collisions::default())
-                            .insert(Name::new("bullet"));
-                        player_vel.linear =
-                            Vec3::new(-cosf(looking_at) * 100.0, -sinf(looking_at) * 100.0, 0.);
+                            .insert(Name::new("bullet"))
+                            .insert(RunEntity)
+                            .id();
+                        if let Some(target) = lock {
+                            commands.entity(bullet).insert(Homing { target });
+                        }
+                        if bullet_speed >= FAST_PROJECTILE_SPEED {
+                            commands
+                                .entity(bullet)
+                                .insert(FastProjectile::new(held_trans.translation));
+                        }
+                        if let Some(trail) = projectile_trail(weapon.asset) {
+                            commands.entity(bullet).insert(trail);
+                        }
                     });
-            }
+                    player_vel.linear =
+                        Vec3::new(-cosf(looking_at) * 100.0, -sinf(looking_at) * 100.0, 0.)
+                            / bullet_time.player_scale();
+                    if magazine.is_empty() {
+                        commands.entity(held_item).despawn_recursive();
+                        throw_spent_weapon(
+                            &mut commands,
+                            &assets,
+                            &materials,
+                            &mut rng,
+                            held_trans,
+                            weapon,
+                            looking_at,
+                        );
+                    }
+                });
         }
         None => {} // do nothing
     }
 }
 
-#[derive(PhysicsLayer)]
-enum Layers {
+// an empty gun gets tossed back over the shoulder
+fn throw_spent_weapon(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    materials: &PhysicsMaterials,
+    rng: &mut GameRng,
+    held_trans: &Transform,
+    weapon: &Weapon,
+    looking_at: f32,
+) {
+    let random_x = rng.gen_range(30.0..100.0) as f32;
+    let random_y = rng.gen_range(30.0..100.0) as f32;
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: assets.weapon(weapon.asset),
+            transform: Transform::from_translation(
+                RenderLayer::Projectiles.at(held_trans.translation.truncate()),
+            ),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(CollisionShape::Cuboid {
+            half_extends: Vec3::new(4.0, 4.0, 0.0),
+            border_radius: None,
+        })
+        .insert(materials.weapon(weapon.asset, true))
+        .insert(Spinning)
+        .insert(Tween::new(1.0, Ease::OutCubic).with(TweenTarget::Rotation(0.0, 6.0)))
+        .insert(ThrownWeapon {
+            weapon: weapon.asset,
+        })
+        .insert(Collisions::default())
+        .insert(collision_layers(Layers::Thrown))
+        .insert(Bullet {
+            timer: Timer::from_seconds(4.0, false),
+        })
+        .insert(Name::new("Spent spinning gun"))
+        .insert(RunEntity)
+        .insert(Velocity {
+            linear: Vec3::new(
+                -cosf(looking_at) * random_x,
+                -sinf(looking_at) * random_y,
+                0.0,
+            ),
+            ..default()
+        });
+}
+
+// which layers touch is decided in collision.rs, not at the spawn sites
+#[derive(PhysicsLayer, Copy, Clone, PartialEq, Eq)]
+pub enum Layers {
     World,
     Player,
     Enemies,
     Weapons,
     Projectiles,
+    // gibs and grenades, they only bounce off the level
+    Debris,
+    EnemyProjectiles,
+    Hazard,
+    // spent guns on their way out of the hand, they knock down the first enemy they meet
+    Thrown,
 }
 
 pub struct WeaponSpawns {
     timer: Timer,
 }
 
+impl Default for WeaponSpawns {
+    fn default() -> Self {
+        WeaponSpawns {
+            timer: Timer::from_seconds(1.0, true),
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Player {
+    health: i8,
     jump_height: f32,
     location: Vec3,
     looking_at: f32,
+    move_speed: f32,
+    jump_scale: f32,
+    dash_cooldown: f32,
 }
 
 #[derive(Component)]
@@ -966,8 +2081,13 @@ pub fn setup_camera(mut commands: Commands) {
 #[derive(Component)]
 pub struct HeldItem;
 
-pub fn create_borders(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let floor_sprite: Handle<Image> = asset_server.load("images/Floor.png");
+// on the player's layer, just in front of them
+fn held_item_z() -> f32 {
+    RenderLayer::Player.z() + 1.0
+}
+
+pub fn create_borders(mut commands: Commands, assets: Res<GameAssets>) {
+    let floor_sprite: Handle<Image> = assets.floor.clone();
     let floor_size = Vec2::new(28.0, 28.0);
     commands
         .spawn_bundle(SpriteBundle {
@@ -980,6 +2100,7 @@ pub fn create_borders(mut commands: Commands, asset_server: Res<AssetServer>) {
                 },
                 ..default()
             },
+            transform: Transform::from_translation(RenderLayer::Background.at(Vec2::ZERO)),
             ..default()
         })
         .with_children(|parent| {
@@ -1005,13 +2126,7 @@ pub fn create_borders(mut commands: Commands, asset_server: Res<AssetServer>) {
                         half_extends: floor_size.extend(0.0) / 2.,
                         border_radius: None,
                     })
-                    .insert(
-                        CollisionLayers::none()
-                            .with_group(Layers::World)
-                            .with_mask(Layers::Player)
-                            .with_mask(Layers::Projectiles)
-                            .with_mask(Layers::Enemies),
-                    )
+                    .insert(collision_layers(Layers::World))
                     .insert(Name::new(block_name));
             }
         });
@@ -1051,13 +2166,7 @@ pub fn create_borders(mut commands: Commands, asset_server: Res<AssetServer>) {
                         half_extends: floor_size.extend(0.0) / 2.,
                         border_radius: None,
                     })
-                    .insert(
-                        CollisionLayers::none()
-                            .with_group(Layers::World)
-                            .with_mask(Layers::Player)
-                            .with_mask(Layers::Projectiles)
-                            .with_mask(Layers::Enemies),
-                    )
+                    .insert(collision_layers(Layers::World))
                     .insert(Name::new(block_name));
             }
         });
@@ -1097,13 +2206,7 @@ pub fn create_borders(mut commands: Commands, asset_server: Res<AssetServer>) {
                         half_extends: floor_size.extend(0.0) / 2.,
                         border_radius: None,
                     })
-                    .insert(
-                        CollisionLayers::none()
-                            .with_group(Layers::World)
-                            .with_mask(Layers::Player)
-                            .with_mask(Layers::Projectiles)
-                            .with_mask(Layers::Enemies),
-                    )
+                    .insert(collision_layers(Layers::World))
                     .insert(Name::new(block_name));
             }
         });
@@ -1143,13 +2246,7 @@ pub fn create_borders(mut commands: Commands, asset_server: Res<AssetServer>) {
                         half_extends: floor_size.extend(0.0) / 2.,
                         border_radius: None,
                     })
-                    .insert(
-                        CollisionLayers::none()
-                            .with_group(Layers::World)
-                            .with_mask(Layers::Player)
-                            .with_mask(Layers::Projectiles)
-                            .with_mask(Layers::Enemies),
-                    )
+                    .insert(collision_layers(Layers::World))
                     .insert(Name::new(block_name));
             }
         });
@@ -1170,8 +2267,8 @@ impl Default for Dashing {
     }
 }
 
-pub fn create_scoreboard(mut commads: Commands, asset_server: Res<AssetServer>) {
-    let font_handle: Handle<Font> = asset_server.load("fonts/RobotoMono.ttf");
+pub fn create_scoreboard(mut commads: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
     commads
         .spawn_bundle(NodeBundle {
             color: UiColor(Color::Rgba {
@@ -1220,20 +2317,22 @@ pub fn create_scoreboard(mut commads: Commands, asset_server: Res<AssetServer>)
                 .insert(ScoreParent)
                 .insert(Name::new("Score Block"))
                 .with_children(|score_parent| {
-                    score_parent.spawn_bundle(TextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: String::from("Score: "),
-                                style: TextStyle {
-                                    font: font_handle.clone(),
-                                    font_size: 40.,
-                                    ..default()
-                                },
-                            }],
+                    score_parent
+                        .spawn_bundle(TextBundle {
+                            text: Text {
+                                sections: vec![TextSection {
+                                    value: String::from("Score: "),
+                                    style: TextStyle {
+                                        font: font_handle.clone(),
+                                        font_size: 40.,
+                                        ..default()
+                                    },
+                                }],
+                                ..default()
+                            },
                             ..default()
-                        },
-                        ..default()
-                    });
+                        })
+                        .insert(TextRole::Hud);
                     score_parent
                         .spawn_bundle(TextBundle {
                             text: Text {
@@ -1249,57 +2348,54 @@ pub fn create_scoreboard(mut commads: Commands, asset_server: Res<AssetServer>)
                             },
                             ..default()
                         })
-                        .insert(ScoreLabel);
+                        .insert(ScoreLabel)
+                        .insert(TextRole::Hud)
+                        .insert(
+                            Tween::new(0.25, Ease::OutCubic)
+                                .with(TweenTarget::Scale(Vec3::splat(1.4), Vec3::ONE))
+                                .with(TweenTarget::TextColor(Color::GOLD, Color::WHITE))
+                                .real_time()
+                                .idle(),
+                        );
                 });
         });
 }
 
-pub fn update_score(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    score: Res<Score>,
-    existing_score: Query<Entity, With<ScoreLabel>>,
-    score_parent: Query<Entity, With<ScoreParent>>,
-) {
+pub fn update_score(score: Res<Score>, mut labels: Query<&mut Text, With<ScoreLabel>>) {
     if !score.is_changed() {
         return;
     }
-    let font_handle: Handle<Font> = asset_server.load("fonts/RobotoMono.ttf");
-
-    existing_score.iter().for_each(|score| {
-        commands.entity(score).despawn_recursive();
-    });
-    score_parent.iter().for_each(|parent| {
-        commands.entity(parent).add_children(|builder| {
-            let mut new_score = String::new();
-            new_score += &score.score.to_string();
-            builder
-                .spawn_bundle(TextBundle {
-                    text: Text {
-                        sections: vec![TextSection {
-                            value: new_score,
-                            style: TextStyle {
-                                font: font_handle.clone(),
-                                font_size: 40.,
-                                ..default()
-                            },
-                        }],
-                        ..default()
-                    },
-                    ..default()
-                })
-                .insert(ScoreLabel);
-        });
+    labels.iter_mut().for_each(|mut text| {
+        text.sections[0].value = score.score.to_string();
     });
 }
+
 #[derive(Component)]
 pub struct ScoreLabel;
 
 #[derive(Component)]
 pub struct ScoreParent;
 
-pub fn create_character(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let character_sprite: Handle<Image> = asset_server.load("images/Character.png");
+pub fn create_character(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    save: Res<SaveData>,
+    settings: Res<Settings>,
+    characters: Res<Assets<CharacterDef>>,
+    materials: Res<PhysicsMaterials>,
+) {
+    let character = selected_character(&settings, &assets, &characters);
+    spawn_player(&mut commands, &assets, &materials, &save, &character);
+}
+
+pub fn spawn_player(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    materials: &PhysicsMaterials,
+    save: &SaveData,
+    character: &CharacterDef,
+) {
+    let character_sprite: Handle<Image> = character.texture.clone();
     let sprite_size = Vec2::new(12.0, 28.0);
     commands
         .spawn_bundle(SpriteBundle {
@@ -1308,7 +2404,7 @@ pub fn create_character(mut commands: Commands, asset_server: Res<AssetServer>)
                 ..Default::default()
             },
             texture: character_sprite.clone(),
-            transform: Transform::from_translation(Vec3::new(0., -92., 1.0)),
+            transform: Transform::from_translation(RenderLayer::Player.at(Vec2::new(0., -92.))),
             ..default()
         })
         .insert(RigidBody::Dynamic)
@@ -1316,81 +2412,120 @@ pub fn create_character(mut commands: Commands, asset_server: Res<AssetServer>)
             half_extends: sprite_size.extend(0.0) / 2.0,
             border_radius: None,
         })
+        .insert(materials.get(MaterialClass::Player))
+        // keeps the player upright when a corner clips them instead of snapping back every frame
+        .insert(RotationConstraints::lock())
         .insert(Player {
+            health: starting_health(save),
             jump_height: 100.0,
             location: Vec3::new(0., 92., 1.),
             looking_at: 0.0,
+            move_speed: character.move_speed,
+            jump_scale: character.jump_scale,
+            dash_cooldown: character.dash_cooldown,
         })
         .insert(Velocity { ..default() })
-        .insert(
-            CollisionLayers::none()
-                .with_group(Layers::Player)
-                .with_mask(Layers::World)
-                .with_mask(Layers::Weapons)
-                .with_mask(Layers::Enemies),
-        )
+        .insert(collision_layers(Layers::Player))
+        .insert(dash_trail())
         .insert(Name::new("Player"))
+        .insert(RunEntity)
+        .insert(Perks::default())
         .insert(Collisions::default());
+    if let Some(weapon) = character.starting_weapon {
+        spawn_held_item(commands, assets, Vec3::new(0., -92., 1.0), weapon);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn move_player(
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
-    mut player_query: Query<(&mut Transform, &mut Velocity, &mut Player, Entity), Without<Dashing>>,
+    actions: Res<Actions>,
+    mut player_query: Query<(&Transform, &mut Velocity, &mut Player, Entity), Without<Dashing>>,
     mut dashers: Query<(&mut Transform, &mut Player, &Dashing, &mut Velocity), With<Dashing>>,
     mut dash_time: ResMut<DashTimer>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
+    save: Res<SaveData>,
+    gravity: Res<GravityData>,
+    run_config: Res<RunConfig>,
+    bullet_time: Res<BulletTime>,
 ) {
+    let speed = dash_speed(&save);
+    let can_dash = !run_config.modifiers.no_dash;
     let player_check = player_query.iter_mut().next();
     match player_check {
-        Some((mut trans, mut velocity, mut player, entity)) => {
-            trans.rotation = Quat::from_rotation_z(0.0);
-            if keys.just_pressed(KeyCode::D) {
-                if dash_time.timer.finished() || dash_time.direction != Directions::Right {
+        Some((trans, mut velocity, mut player, entity)) => {
+            if actions.just_pressed(Action::MoveRight) {
+                if dash_time.timer.finished()
+                    || dash_time.direction != Directions::Right
+                    || !dash_time.cooldown.finished()
+                    || !can_dash
+                {
                     dash_time.timer = Timer::from_seconds(0.2, false);
                     dash_time.direction = Directions::Right;
-                    if velocity.linear.x >= -50. {
-                        trans.translation.x += 1.0;
-                        velocity.linear.x = 0.;
-                    }
                 } else {
                     //trans.translation.x += 20.0;
                     dash_time.timer.set_elapsed(Duration::from_secs(50));
+                    dash_time.cooldown = Timer::from_seconds(player.dash_cooldown, false);
                     commands.entity(entity).insert(Dashing {
                         direction: Directions::Right,
                         ..default()
                     });
                 }
-            } else if keys.pressed(KeyCode::D) {
-                if velocity.linear.x >= -50. {
-                    trans.translation.x += 1.0;
-                    velocity.linear.x = 0.;
-                }
             }
-            if keys.just_pressed(KeyCode::A) {
-                if dash_time.timer.finished() || dash_time.direction != Directions::Left {
+            if actions.just_pressed(Action::MoveLeft) {
+                if dash_time.timer.finished()
+                    || dash_time.direction != Directions::Left
+                    || !dash_time.cooldown.finished()
+                    || !can_dash
+                {
                     dash_time.timer = Timer::from_seconds(0.2, false);
                     dash_time.direction = Directions::Left;
-                    if velocity.linear.x <= 50. {
-                        trans.translation.x += -1.0;
-                        velocity.linear.x = 0.;
-                    }
                 } else {
                     //trans.translation.x += -20.0;
                     dash_time.timer.set_elapsed(Duration::from_secs(50));
+                    dash_time.cooldown = Timer::from_seconds(player.dash_cooldown, false);
                     commands.entity(entity).insert(Dashing::default());
                 }
-            } else if keys.pressed(KeyCode::A) {
-                if velocity.linear.x <= 50. {
-                    trans.translation.x += -1.0;
-                    velocity.linear.x = 0.;
-                }
             }
-            if keys.just_pressed(KeyCode::Space) {
+            // the dash button goes the way the player is pushing, or last went
+            if actions.just_pressed(Action::Dash) && dash_time.cooldown.finished() && can_dash {
+                let direction = if actions.pressed(Action::MoveLeft) {
+                    Directions::Left
+                } else if actions.pressed(Action::MoveRight) {
+                    Directions::Right
+                } else {
+                    dash_time.direction
+                };
+                dash_time.cooldown = Timer::from_seconds(player.dash_cooldown, false);
+                commands.entity(entity).insert(Dashing {
+                    direction,
+                    ..default()
+                });
+            }
+            let input = match (
+                actions.pressed(Action::MoveLeft),
+                actions.pressed(Action::MoveRight),
+            ) {
+                (true, false) => -1.0,
+                (false, true) => 1.0,
+                _ => 0.0,
+            };
+            // bullet time doesn't slow the player down, so this works in real time and speeds
+            let slow = bullet_time.player_scale();
+            let mut real = Velocity::from_linear(velocity.linear * slow);
+            walk(
+                &mut real,
+                input,
+                gravity.run_speed * player.move_speed,
+                gravity.run_accel,
+                clock.unscaled_delta().as_secs_f32(),
+            );
+            if actions.just_pressed(Action::Jump) {
                 if trans.translation.y <= -85. {
-                    velocity.linear.y = player.jump_height;
+                    real.linear.y = player.jump_height;
                 }
             }
+            velocity.linear = real.linear / slow;
             player.location = trans.translation;
         }
         _default => {} // do nothing
@@ -1401,54 +2536,96 @@ pub fn move_player(
             vel.linear = Vec3::splat(0.);
             match dashing.direction {
                 Directions::Left => {
-                    dasher_trans.translation.x -= 250.0 * time.delta_seconds();
+                    dasher_trans.translation.x -= speed * clock.unscaled_delta().as_secs_f32();
                 }
                 Directions::Right => {
-                    dasher_trans.translation.x += 250.0 * time.delta_seconds();
+                    dasher_trans.translation.x += speed * clock.unscaled_delta().as_secs_f32();
                 }
             }
             dasher.location = dasher_trans.translation;
         });
 }
 
+// eases toward the run speed, anything faster like recoil is left for physics to bleed off
+fn walk(velocity: &mut Velocity, input: f32, max_speed: f32, accel: f32, delta: f32) {
+    let current = velocity.linear.x;
+    if input == 0.0 && current.abs() > max_speed {
+        return;
+    }
+    // pushing against a strong knockback doesn't steer, same as before
+    if current * input < -50.0 || current * input > max_speed {
+        return;
+    }
+    let target = input * max_speed;
+    let step = accel * delta;
+    velocity.linear.x = if (target - current).abs() <= step {
+        target
+    } else {
+        current + step * (target - current).signum()
+    };
+}
+
+pub fn cursor_to_world(
+    wnds: &Windows,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let wnd = if let RenderTarget::Window(id) = camera.target {
+        wnds.get(id).unwrap()
+    } else {
+        wnds.get_primary().unwrap()
+    };
+    let screen_pos = wnd.cursor_position()?;
+    // get the size of the window
+    let window_size = Vec2::new(wnd.width() as f32, wnd.height() as f32);
+
+    // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
+    let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
+
+    // matrix for undoing the projection and camera transform
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+
+    // use it to convert ndc to world-space coordinates
+    let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
+
+    // reduce it to a 2D value
+    Some(world_pos.truncate())
+}
+
+pub fn aim_held_item(held_item: &mut Transform, player: &Player, angle: f32, distance: f32) {
+    let distance_from_center = if distance > 5.0 { 5.0 } else { distance };
+    held_item.translation = Vec3::new(
+        player.location.x + cosf(angle) * distance_from_center,
+        player.location.y + sinf(angle) * distance_from_center,
+        held_item_z(),
+    );
+    held_item.rotation = Quat::from_rotation_z(angle);
+}
+
 fn point_held_item(
     wnds: Res<Windows>,
     q_camera: Query<(&Camera, &GlobalTransform), With<MyCamera>>,
     mut players: Query<&mut Player>,
     mut held_items: Query<&mut Transform, With<HeldItem>>,
+    run_config: Res<RunConfig>,
+    gamepad_aim: Res<GamepadAim>,
+    mut aim_point: ResMut<AimPoint>,
 ) {
+    if run_config.mode == GameMode::Versus || gamepad_aim.active {
+        return;
+    }
     // get the camera info and transform
     // assuming there is exactly one main camera entity, so query::single() is OK
     let (camera, camera_transform) = q_camera.single();
 
-    let wnd = if let RenderTarget::Window(id) = camera.target {
-        wnds.get(id).unwrap()
-    } else {
-        wnds.get_primary().unwrap()
-    };
-
-    if let Some(screen_pos) = wnd.cursor_position() {
+    if let Some(world_pos) = cursor_to_world(&wnds, camera, camera_transform) {
+        aim_point.0 = world_pos;
         let player_maybe = players.iter_mut().next();
         let held_item_maybe = held_items.iter_mut().next();
         match player_maybe {
             Some(mut player) => {
                 match held_item_maybe {
-                    // get the size of the window
                     Some(mut held_item) => {
-                        let window_size = Vec2::new(wnd.width() as f32, wnd.height() as f32);
-
-                        // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
-                        let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
-
-                        // matrix for undoing the projection and camera transform
-                        let ndc_to_world = camera_transform.compute_matrix()
-                            * camera.projection_matrix().inverse();
-
-                        // use it to convert ndc to world-space coordinates
-                        let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
-
-                        // reduce it to a 2D value
-                        let world_pos: Vec2 = world_pos.truncate();
                         let player_to_cursor = Vec2::new(
                             world_pos.x - player.location.x,
                             world_pos.y - player.location.y,
@@ -1457,18 +2634,7 @@ fn point_held_item(
                         player.looking_at = angle;
                         let player_cursor_distance =
                             (player_to_cursor.x.powi(2) + player_to_cursor.y.powi(2)).sqrt();
-                        let distance_from_center = if player_cursor_distance > 5.0 {
-                            5.0
-                        } else {
-                            player_cursor_distance
-                        };
-                        let item_pos = Vec3::new(
-                            player.location.x + cosf(angle) * distance_from_center,
-                            player.location.y + sinf(angle) * distance_from_center,
-                            10.0,
-                        );
-                        held_item.translation = item_pos;
-                        held_item.rotation = Quat::from_rotation_z(angle);
+                        aim_held_item(&mut held_item, &player, angle, player_cursor_distance);
                     }
                     _default => {
                         // none found
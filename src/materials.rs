@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use heron::PhysicMaterial;
+use serde::Deserialize;
+
+use crate::loading::GameAssets;
+use crate::Weapons;
+
+// the shipped presets, used until the asset loads or if it fails to
+const BUILT_IN_MATERIALS: &str = include_str!("../assets/config/physics.materials.json");
+
+// every kind of dynamic body that gets its own feel
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaterialClass {
+    Player,
+    Enemy,
+    // a fresh weapon falling in to be picked up
+    Weapon,
+    // an emptied gun tossed away
+    SpentWeapon,
+    Rock,
+    // gems and shields
+    Pickup,
+    Grenade,
+    // the bits enemies leave behind
+    Gib,
+}
+
+// missing fields fall back to heron's own defaults
+#[derive(Copy, Clone, Deserialize)]
+pub struct MaterialPreset {
+    #[serde(default = "default_friction")]
+    friction: f32,
+    #[serde(default)]
+    restitution: f32,
+    #[serde(default = "default_density")]
+    density: f32,
+}
+
+fn default_friction() -> f32 {
+    PhysicMaterial::default().friction
+}
+
+fn default_density() -> f32 {
+    PhysicMaterial::default().density
+}
+
+// friction and bounce per class, loaded from assets/config
+#[derive(Clone, Deserialize, TypeUuid)]
+#[uuid = "8d41f6b2-97c3-4e5a-b0d8-2a6e13c7f954"]
+pub struct PhysicsMaterials(HashMap<MaterialClass, MaterialPreset>);
+
+impl Default for PhysicsMaterials {
+    fn default() -> Self {
+        serde_json::from_str(BUILT_IN_MATERIALS).unwrap_or_else(|err| {
+            warn!("the built in physics materials are broken: {}", err);
+            PhysicsMaterials(HashMap::new())
+        })
+    }
+}
+
+impl PhysicsMaterials {
+    pub fn get(&self, class: MaterialClass) -> PhysicMaterial {
+        match self.0.get(&class) {
+            Some(preset) => PhysicMaterial {
+                friction: preset.friction,
+                restitution: preset.restitution,
+                density: preset.density,
+            },
+            None => PhysicMaterial::default(),
+        }
+    }
+
+    // rocks bounce whether they're dropped or thrown away, everything else depends on use
+    pub fn weapon(&self, weapon: Weapons, spent: bool) -> PhysicMaterial {
+        match (weapon, spent) {
+            (Weapons::Rock, _) => self.get(MaterialClass::Rock),
+            (_, true) => self.get(MaterialClass::SpentWeapon),
+            (_, false) => self.get(MaterialClass::Weapon),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PhysicsMaterialsLoader;
+
+impl AssetLoader for PhysicsMaterialsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let materials: PhysicsMaterials = serde_json::from_slice(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(materials));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["materials.json"]
+    }
+}
+
+pub struct MaterialsPlugin;
+
+impl Plugin for MaterialsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PhysicsMaterials>()
+            .init_asset_loader::<PhysicsMaterialsLoader>()
+            .init_resource::<PhysicsMaterials>()
+            .add_system(apply_physics_materials);
+    }
+}
+
+// bodies pick their material up when they spawn, so a changed file only affects new ones
+pub fn apply_physics_materials(
+    mut events: EventReader<AssetEvent<PhysicsMaterials>>,
+    loaded: Res<Assets<PhysicsMaterials>>,
+    assets: Option<Res<GameAssets>>,
+    mut materials: ResMut<PhysicsMaterials>,
+) {
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
+    events.iter().for_each(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle }
+            if *handle == assets.physics_materials =>
+        {
+            if let Some(presets) = loaded.get(handle) {
+                *materials = presets.clone();
+            }
+        }
+        _default => {}
+    });
+}
@@ -0,0 +1,489 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::accessibility::{cycle, PALETTES, WARNING_COLORS};
+use crate::characters::{spawn_character_select, CHARACTER_FILES};
+use crate::display::{MAX_UI_SCALE, MIN_UI_SCALE, WINDOW_SIZES};
+use crate::fonts::TextRole;
+use crate::loading::GameAssets;
+use crate::modes::{spawn_mode_select, spawn_modifier_select, RunConfig, MENU_MODES};
+use crate::prestige::spawn_new_game_plus_toggle;
+use crate::profiles::Profiles;
+use crate::save::SaveData;
+use crate::settings::Settings;
+use crate::smoothing::PHYSICS_RATES;
+use crate::transition::Transition;
+use crate::{GameState, ResetGame};
+
+pub const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+pub const HOVERED_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+#[derive(Component, Copy, Clone, PartialEq, Eq)]
+pub enum MenuButton {
+    Play,
+    Resume,
+    Restart,
+    Settings,
+    Achievements,
+    Leaderboard,
+    Shop,
+    QuitToMenu,
+    Quit,
+    Back,
+    Profiles,
+    MusicDown,
+    MusicUp,
+    SfxDown,
+    SfxUp,
+    AimAssistDown,
+    AimAssistUp,
+    ShowCursor,
+    HideCursor,
+    AnnouncerOff,
+    AnnouncerOn,
+    Display,
+    WindowSmaller,
+    WindowLarger,
+    PixelPerfectOff,
+    PixelPerfectOn,
+    CrispPixelsOff,
+    CrispPixelsOn,
+    PixelHudOff,
+    PixelHudOn,
+    LetterboxOff,
+    LetterboxOn,
+    FullscreenOff,
+    FullscreenOn,
+    FocusPauseOff,
+    FocusPauseOn,
+    ScoreSourcesOff,
+    ScoreSourcesOn,
+    Accessibility,
+    PreviousPalette,
+    NextPalette,
+    PreviousWarningColor,
+    NextWarningColor,
+    OutlinesOff,
+    OutlinesOn,
+    ShakeDown,
+    ShakeUp,
+    FlashDown,
+    FlashUp,
+    UiScaleDown,
+    UiScaleUp,
+    AssistOff,
+    AssistOn,
+    Advanced,
+    PhysicsRateDown,
+    PhysicsRateUp,
+    SmoothingOff,
+    SmoothingOn,
+    PreviousCharacter,
+    NextCharacter,
+    PreviousMode,
+    NextMode,
+}
+
+#[derive(Component)]
+pub struct MainMenuScreen;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(create_main_menu))
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu)
+                    .with_system(despawn_screen::<MainMenuScreen>),
+            )
+            .add_system(handle_menu_buttons);
+    }
+}
+
+pub fn despawn_screen<T: Component>(mut commands: Commands, screens: Query<Entity, With<T>>) {
+    screens.iter().for_each(|screen| {
+        commands.entity(screen).despawn_recursive();
+    });
+}
+
+// full screen column that later screens drop their title and buttons into
+pub fn screen_root(color: Color) -> NodeBundle {
+    NodeBundle {
+        color: UiColor(color),
+        style: Style {
+            position_type: PositionType::Absolute,
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    }
+}
+
+pub fn spawn_title(parent: &mut ChildBuilder, font: &Handle<Font>, title: &str) {
+    parent
+        .spawn_bundle(TextBundle {
+            text: Text::from_section(
+                title,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 60.,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(TextRole::Title);
+}
+
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: MenuButton,
+) {
+    spawn_labeled_button(parent, font, label, button, Vec2::new(260.0, 50.0));
+}
+
+// square buttons for +/- style controls
+pub fn spawn_small_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: MenuButton,
+) {
+    spawn_labeled_button(parent, font, label, button, Vec2::new(50.0, 50.0));
+}
+
+// any component works as the button's identity, screens with their own clicks handle them
+pub fn spawn_labeled_button<T: Component>(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: T,
+    size: Vec2,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            color: UiColor(BUTTON_COLOR),
+            style: Style {
+                size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+                margin: UiRect::all(Val::Px(6.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(button)
+        .with_children(|button| {
+            button.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.,
+                        color: Color::WHITE,
+                    },
+                ),
+                ..default()
+            });
+        });
+}
+
+pub fn create_main_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    save: Res<SaveData>,
+    run_config: Res<RunConfig>,
+    profiles: Res<Profiles>,
+) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.8)))
+        .insert(MainMenuScreen)
+        .insert(Name::new("Main Menu"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Score Space");
+            spawn_character_select(parent, &font_handle);
+            spawn_mode_select(parent, &font_handle);
+            spawn_modifier_select(parent, &font_handle);
+            spawn_new_game_plus_toggle(parent, &font_handle, &save, &run_config);
+            spawn_button(parent, &font_handle, "Play", MenuButton::Play);
+            spawn_button(parent, &font_handle, "Settings", MenuButton::Settings);
+            spawn_button(parent, &font_handle, "Unlocks", MenuButton::Shop);
+            spawn_button(parent, &font_handle, "Leaderboard", MenuButton::Leaderboard);
+            spawn_button(
+                parent,
+                &font_handle,
+                "Achievements",
+                MenuButton::Achievements,
+            );
+            spawn_button(
+                parent,
+                &font_handle,
+                &format!("Profile: {}", profiles.active_name()),
+                MenuButton::Profiles,
+            );
+            spawn_button(parent, &font_handle, "Quit", MenuButton::Quit);
+        });
+}
+
+pub fn handle_menu_buttons(
+    mut buttons: Query<(&Interaction, &MenuButton, &mut UiColor), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+    mut settings: ResMut<Settings>,
+    mut exit: EventWriter<AppExit>,
+    mut resets: EventWriter<ResetGame>,
+    mut transition: ResMut<Transition>,
+    mut run_config: ResMut<RunConfig>,
+) {
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, button, mut color)| match *interaction {
+            Interaction::Clicked => {
+                // transitions can fail if one is already queued this frame, that's fine
+                let _ = match button {
+                    // respawn so the run starts with the character picked on the menu
+                    MenuButton::Play => {
+                        resets.send(ResetGame);
+                        transition.fade_to(GameState::Playing);
+                        Ok(())
+                    }
+                    MenuButton::Resume | MenuButton::Back => state.pop(),
+                    MenuButton::Restart => {
+                        resets.send(ResetGame);
+                        transition.fade_to(GameState::Playing);
+                        Ok(())
+                    }
+                    MenuButton::Settings => state.push(GameState::Settings),
+                    MenuButton::Display => state.push(GameState::Display),
+                    MenuButton::Accessibility => state.push(GameState::Accessibility),
+                    MenuButton::Advanced => state.push(GameState::Advanced),
+                    MenuButton::Achievements => state.push(GameState::Achievements),
+                    MenuButton::Leaderboard => state.push(GameState::Leaderboard),
+                    MenuButton::Profiles => {
+                        transition.fade_to(GameState::Profiles);
+                        Ok(())
+                    }
+                    MenuButton::Shop => state.push(GameState::Shop),
+                    MenuButton::QuitToMenu => {
+                        resets.send(ResetGame);
+                        transition.fade_to(GameState::MainMenu);
+                        Ok(())
+                    }
+                    MenuButton::Quit => {
+                        exit.send(AppExit);
+                        Ok(())
+                    }
+                    MenuButton::MusicDown => {
+                        settings.music = (settings.music - 0.05).max(0.0);
+                        Ok(())
+                    }
+                    MenuButton::MusicUp => {
+                        settings.music = (settings.music + 0.05).min(1.0);
+                        Ok(())
+                    }
+                    MenuButton::SfxDown => {
+                        settings.sfx = (settings.sfx - 0.05).max(0.0);
+                        Ok(())
+                    }
+                    MenuButton::SfxUp => {
+                        settings.sfx = (settings.sfx + 0.05).min(1.0);
+                        Ok(())
+                    }
+                    MenuButton::AimAssistDown => {
+                        settings.aim_assist = (settings.aim_assist - 0.1).max(0.0);
+                        Ok(())
+                    }
+                    MenuButton::AimAssistUp => {
+                        settings.aim_assist = (settings.aim_assist + 0.1).min(1.0);
+                        Ok(())
+                    }
+                    MenuButton::ShowCursor => {
+                        settings.hide_cursor = false;
+                        Ok(())
+                    }
+                    MenuButton::HideCursor => {
+                        settings.hide_cursor = true;
+                        Ok(())
+                    }
+                    MenuButton::AnnouncerOff => {
+                        settings.announcer = false;
+                        Ok(())
+                    }
+                    MenuButton::AnnouncerOn => {
+                        settings.announcer = true;
+                        Ok(())
+                    }
+                    MenuButton::WindowSmaller => {
+                        settings.window_size = settings.window_size.saturating_sub(1);
+                        Ok(())
+                    }
+                    MenuButton::WindowLarger => {
+                        settings.window_size =
+                            (settings.window_size + 1).min(WINDOW_SIZES.len() - 1);
+                        Ok(())
+                    }
+                    MenuButton::PixelPerfectOff => {
+                        settings.pixel_perfect = false;
+                        Ok(())
+                    }
+                    MenuButton::PixelPerfectOn => {
+                        settings.pixel_perfect = true;
+                        Ok(())
+                    }
+                    MenuButton::CrispPixelsOff => {
+                        settings.crisp_pixels = false;
+                        Ok(())
+                    }
+                    MenuButton::CrispPixelsOn => {
+                        settings.crisp_pixels = true;
+                        Ok(())
+                    }
+                    MenuButton::PixelHudOff => {
+                        settings.pixel_hud = false;
+                        Ok(())
+                    }
+                    MenuButton::PixelHudOn => {
+                        settings.pixel_hud = true;
+                        Ok(())
+                    }
+                    MenuButton::LetterboxOff => {
+                        settings.letterbox = false;
+                        Ok(())
+                    }
+                    MenuButton::LetterboxOn => {
+                        settings.letterbox = true;
+                        Ok(())
+                    }
+                    MenuButton::FullscreenOff => {
+                        settings.fullscreen = false;
+                        Ok(())
+                    }
+                    MenuButton::FullscreenOn => {
+                        settings.fullscreen = true;
+                        Ok(())
+                    }
+                    MenuButton::FocusPauseOff => {
+                        settings.pause_on_focus_loss = false;
+                        Ok(())
+                    }
+                    MenuButton::FocusPauseOn => {
+                        settings.pause_on_focus_loss = true;
+                        Ok(())
+                    }
+                    MenuButton::ScoreSourcesOff => {
+                        settings.score_sources = false;
+                        Ok(())
+                    }
+                    MenuButton::ScoreSourcesOn => {
+                        settings.score_sources = true;
+                        Ok(())
+                    }
+                    MenuButton::PreviousPalette => {
+                        settings.palette = cycle(&PALETTES, settings.palette, -1);
+                        Ok(())
+                    }
+                    MenuButton::NextPalette => {
+                        settings.palette = cycle(&PALETTES, settings.palette, 1);
+                        Ok(())
+                    }
+                    MenuButton::PreviousWarningColor => {
+                        settings.warning_color = cycle(&WARNING_COLORS, settings.warning_color, -1);
+                        Ok(())
+                    }
+                    MenuButton::NextWarningColor => {
+                        settings.warning_color = cycle(&WARNING_COLORS, settings.warning_color, 1);
+                        Ok(())
+                    }
+                    MenuButton::OutlinesOff => {
+                        settings.outlines = false;
+                        Ok(())
+                    }
+                    MenuButton::OutlinesOn => {
+                        settings.outlines = true;
+                        Ok(())
+                    }
+                    MenuButton::ShakeDown => {
+                        settings.shake = (settings.shake - 0.25).max(0.0);
+                        Ok(())
+                    }
+                    MenuButton::ShakeUp => {
+                        settings.shake = (settings.shake + 0.25).min(1.0);
+                        Ok(())
+                    }
+                    MenuButton::FlashDown => {
+                        settings.flash = (settings.flash - 0.25).max(0.0);
+                        Ok(())
+                    }
+                    MenuButton::FlashUp => {
+                        settings.flash = (settings.flash + 0.25).min(1.0);
+                        Ok(())
+                    }
+                    MenuButton::UiScaleDown => {
+                        settings.ui_scale = (settings.ui_scale - 0.25).max(MIN_UI_SCALE);
+                        Ok(())
+                    }
+                    MenuButton::UiScaleUp => {
+                        settings.ui_scale = (settings.ui_scale + 0.25).min(MAX_UI_SCALE);
+                        Ok(())
+                    }
+                    MenuButton::AssistOff => {
+                        settings.assist = false;
+                        Ok(())
+                    }
+                    MenuButton::AssistOn => {
+                        settings.assist = true;
+                        Ok(())
+                    }
+                    MenuButton::PhysicsRateDown => {
+                        settings.physics_rate = settings.physics_rate.saturating_sub(1);
+                        Ok(())
+                    }
+                    MenuButton::PhysicsRateUp => {
+                        settings.physics_rate =
+                            (settings.physics_rate + 1).min(PHYSICS_RATES.len() - 1);
+                        Ok(())
+                    }
+                    MenuButton::SmoothingOff => {
+                        settings.smoothing = false;
+                        Ok(())
+                    }
+                    MenuButton::SmoothingOn => {
+                        settings.smoothing = true;
+                        Ok(())
+                    }
+                    MenuButton::PreviousCharacter => {
+                        settings.character = (settings.character + CHARACTER_FILES.len() - 1)
+                            % CHARACTER_FILES.len();
+                        Ok(())
+                    }
+                    MenuButton::NextCharacter => {
+                        settings.character = (settings.character + 1) % CHARACTER_FILES.len();
+                        Ok(())
+                    }
+                    // a run launched as daily or versus stays that way
+                    MenuButton::PreviousMode | MenuButton::NextMode => {
+                        if MENU_MODES.contains(&run_config.mode) {
+                            let step = if *button == MenuButton::NextMode {
+                                1
+                            } else {
+                                -1
+                            };
+                            run_config.mode = cycle(&MENU_MODES, run_config.mode, step);
+                        }
+                        Ok(())
+                    }
+                };
+            }
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+}
@@ -0,0 +1,482 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::fonts::TextRole;
+use crate::menu::{
+    spawn_labeled_button, spawn_small_button, MenuButton, BUTTON_COLOR, HOVERED_COLOR,
+};
+use crate::{GameState, GravityData, ResetGame};
+
+// mixed into the day number so the daily seed isn't just "days since 1970"
+const DAILY_SALT: u64 = 0x5C0E_5FAC_E202_2000;
+const SCORE_ATTACK_SECONDS: f32 = 180.0;
+const FAST_ENEMY_SPEED: f32 = 2.0;
+const NEW_GAME_PLUS_SCORE_MULTIPLIER: i64 = 2;
+// modifier buttons that are switched on
+const ACTIVE_COLOR: Color = Color::rgb(0.2, 0.45, 0.25);
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum GameMode {
+    Standard,
+    Daily,
+    Versus,
+    // three minutes to score as much as possible, with everything turned up
+    ScoreAttack,
+    // a sandbox for learning the enemies, with cheats on a quick menu and nothing kept
+    Practice,
+}
+
+// what the main menu switches between, daily and versus are picked at launch
+pub const MENU_MODES: [GameMode; 3] = [
+    GameMode::Standard,
+    GameMode::ScoreAttack,
+    GameMode::Practice,
+];
+
+impl GameMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Standard => "Standard",
+            GameMode::Daily => "Daily",
+            GameMode::Versus => "Versus",
+            GameMode::ScoreAttack => "Score Attack",
+            GameMode::Practice => "Practice",
+        }
+    }
+
+    // practice runs never touch the save, the shop or the leaderboard
+    pub fn keeps_scores(self) -> bool {
+        self != GameMode::Practice
+    }
+
+    // the run ends when this runs out, however the player is doing
+    pub fn time_limit(self) -> Option<f32> {
+        match self {
+            GameMode::ScoreAttack => Some(SCORE_ATTACK_SECONDS),
+            _default => None,
+        }
+    }
+
+    // how much faster than normal the difficulty climbs
+    pub fn difficulty_ramp(self) -> f32 {
+        match self {
+            GameMode::ScoreAttack => 3.0,
+            _default => 1.0,
+        }
+    }
+
+    // how much more often than normal weapons drop
+    pub fn weapon_drop_rate(self) -> f32 {
+        match self {
+            GameMode::ScoreAttack => 2.0,
+            _default => 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ModeLabel;
+
+// the challenge modifiers that can be picked on the main menu, daily runs roll their own
+#[derive(Component, Copy, Clone, PartialEq, Eq)]
+pub enum Modifier {
+    ShotgunOnly,
+    NoDash,
+    FastEnemies,
+    LowGravity,
+}
+
+const MENU_MODIFIERS: [Modifier; 4] = [
+    Modifier::ShotgunOnly,
+    Modifier::NoDash,
+    Modifier::FastEnemies,
+    Modifier::LowGravity,
+];
+
+impl Modifier {
+    fn name(self) -> &'static str {
+        match self {
+            Modifier::ShotgunOnly => "Shotgun Only",
+            Modifier::NoDash => "No Dash",
+            Modifier::FastEnemies => "Fast Enemies",
+            Modifier::LowGravity => "Half Gravity",
+        }
+    }
+}
+
+pub struct ModePlugin;
+
+impl Plugin for ModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_run_modifiers).add_system_set(
+            SystemSet::on_update(GameState::MainMenu)
+                .with_system(update_mode_label)
+                .with_system(toggle_modifiers)
+                .with_system(color_modifier_buttons.after(toggle_modifiers)),
+        );
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub double_enemies: bool,
+    pub low_gravity: bool,
+    pub shotgun_only: bool,
+    pub no_dash: bool,
+    // enemies move at twice the speed
+    pub fast_enemies: bool,
+}
+
+impl Modifiers {
+    pub fn get(&self, modifier: Modifier) -> bool {
+        match modifier {
+            Modifier::ShotgunOnly => self.shotgun_only,
+            Modifier::NoDash => self.no_dash,
+            Modifier::FastEnemies => self.fast_enemies,
+            Modifier::LowGravity => self.low_gravity,
+        }
+    }
+
+    fn toggle(&mut self, modifier: Modifier) {
+        let flag = match modifier {
+            Modifier::ShotgunOnly => &mut self.shotgun_only,
+            Modifier::NoDash => &mut self.no_dash,
+            Modifier::FastEnemies => &mut self.fast_enemies,
+            Modifier::LowGravity => &mut self.low_gravity,
+        };
+        *flag = !*flag;
+    }
+
+    // one word per modifier that's on, in a fixed order so each combination has one board
+    pub fn tag(&self) -> String {
+        [
+            (self.shotgun_only, "shotgun"),
+            (self.no_dash, "nodash"),
+            (self.fast_enemies, "fast"),
+            (self.low_gravity, "lowgrav"),
+            (self.double_enemies, "double"),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, word)| *word)
+        .collect::<Vec<&str>>()
+        .join("-")
+    }
+}
+
+pub struct RunConfig {
+    pub mode: GameMode,
+    pub seed: u64,
+    // daily runs and entered seeds replay the same seed on every restart
+    pub fixed_seed: bool,
+    pub day: u64,
+    pub modifiers: Modifiers,
+    // affixed enemies, more elites and more points, picked on the menu once it's unlocked
+    pub new_game_plus: bool,
+    // locked in from the assist setting as each run starts, see assist.rs
+    pub assisted: bool,
+}
+
+#[derive(Deref, DerefMut)]
+pub struct GameRng(pub StdRng);
+
+// for effects only the live game draws, like gibs and footsteps. raced ghosts don't have them,
+// so they mustn't take draws from the run's seeded rng or the ghost falls out of step
+#[derive(Deref, DerefMut)]
+pub struct CosmeticRng(pub StdRng);
+
+impl Default for CosmeticRng {
+    fn default() -> Self {
+        CosmeticRng(StdRng::from_entropy())
+    }
+}
+
+impl RunConfig {
+    // --daily picks today's seed and modifiers, --versus hands enemy spawns to a second
+    // player, --seed <n> replays a specific run
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let day = current_day();
+        let entered_seed = args
+            .iter()
+            .position(|arg| arg == "--seed")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|seed| seed.parse::<u64>().ok());
+        if args.iter().any(|arg| arg == "--daily") {
+            if entered_seed.is_some() {
+                // everyone plays the same daily run, so the seed entry is locked
+                warn!("--seed is ignored in daily mode");
+            }
+            return RunConfig::daily(day);
+        }
+        RunConfig {
+            mode: if args.iter().any(|arg| arg == "--versus") {
+                GameMode::Versus
+            } else {
+                GameMode::Standard
+            },
+            seed: entered_seed.unwrap_or_else(|| rand::thread_rng().gen()),
+            fixed_seed: entered_seed.is_some(),
+            day,
+            modifiers: Modifiers::default(),
+            new_game_plus: false,
+            assisted: false,
+        }
+    }
+
+    pub fn daily(day: u64) -> Self {
+        let seed = splitmix(day ^ DAILY_SALT);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut modifiers = Modifiers {
+            double_enemies: rng.gen_bool(0.5),
+            low_gravity: rng.gen_bool(0.5),
+            shotgun_only: rng.gen_bool(0.5),
+            ..default()
+        };
+        if !(modifiers.double_enemies || modifiers.low_gravity || modifiers.shotgun_only) {
+            match rng.gen_range(0..3) {
+                0 => modifiers.double_enemies = true,
+                1 => modifiers.low_gravity = true,
+                _default => modifiers.shotgun_only = true,
+            }
+        }
+        RunConfig {
+            mode: GameMode::Daily,
+            seed,
+            fixed_seed: true,
+            day,
+            modifiers,
+            new_game_plus: false,
+            assisted: false,
+        }
+    }
+
+    pub fn leaderboard_bucket(&self) -> String {
+        let bucket = match self.mode {
+            GameMode::Standard => String::from("standard"),
+            GameMode::Versus => String::from("versus"),
+            GameMode::ScoreAttack => String::from("score-attack"),
+            GameMode::Practice => String::from("practice"),
+            GameMode::Daily => {
+                let (year, month, day) = civil_from_days(self.day as i64);
+                format!("daily-{:04}-{:02}-{:02}", year, month, day)
+            }
+        };
+        // every modifier combination gets a board of its own, the daily's are the same for
+        // everyone that day so its date already covers them
+        let tag = self.modifiers.tag();
+        let bucket = if self.mode == GameMode::Daily || tag.is_empty() {
+            bucket
+        } else {
+            format!("{}-{}", bucket, tag)
+        };
+        let bucket = if self.new_game_plus {
+            format!("{}-ngplus", bucket)
+        } else {
+            bucket
+        };
+        // assisted runs get a board of their own
+        if self.assisted {
+            format!("{}-assist", bucket)
+        } else {
+            bucket
+        }
+    }
+
+    // every point gained is multiplied by this
+    pub fn score_multiplier(&self) -> i64 {
+        if self.new_game_plus {
+            NEW_GAME_PLUS_SCORE_MULTIPLIER
+        } else {
+            1
+        }
+    }
+
+    pub fn reroll(&mut self) {
+        if !self.fixed_seed {
+            self.seed = rand::thread_rng().gen();
+        }
+    }
+
+    pub fn rng(&self) -> GameRng {
+        GameRng(StdRng::seed_from_u64(self.seed))
+    }
+
+    // the same mode and modifiers held on a recorded run's seed, to play it back
+    pub fn replaying(&self, seed: u64) -> RunConfig {
+        RunConfig {
+            mode: self.mode,
+            seed,
+            fixed_seed: true,
+            day: self.day,
+            modifiers: self.modifiers,
+            new_game_plus: self.new_game_plus,
+            assisted: self.assisted,
+        }
+    }
+}
+
+// modifiers can change on the menu between runs, so each run takes back what the last one
+// applied before putting on its own
+pub fn apply_run_modifiers(
+    mut resets: EventReader<ResetGame>,
+    mut gravity: ResMut<GravityData>,
+    run_config: Res<RunConfig>,
+    mut applied: Local<Modifiers>,
+) {
+    if resets.iter().count() == 0 && !run_config.is_added() {
+        return;
+    }
+    let modifiers = run_config.modifiers;
+    if applied.low_gravity != modifiers.low_gravity {
+        gravity.gravity *= if modifiers.low_gravity { 0.5 } else { 2.0 };
+    }
+    gravity.enemy_speed = if modifiers.fast_enemies {
+        FAST_ENEMY_SPEED
+    } else {
+        1.0
+    };
+    *applied = modifiers;
+    info!(
+        "starting {} run with seed {}",
+        run_config.leaderboard_bucket(),
+        run_config.seed
+    );
+}
+
+pub fn spawn_mode_select(parent: &mut ChildBuilder, font: &Handle<Font>) {
+    parent
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_small_button(row, font, "<", MenuButton::PreviousMode);
+            row.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.,
+                        color: Color::WHITE,
+                    },
+                ),
+                style: Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(ModeLabel)
+            .insert(TextRole::Label);
+            spawn_small_button(row, font, ">", MenuButton::NextMode);
+        });
+}
+
+pub fn spawn_modifier_select(parent: &mut ChildBuilder, font: &Handle<Font>) {
+    parent
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            MENU_MODIFIERS.iter().for_each(|modifier| {
+                spawn_labeled_button(
+                    row,
+                    font,
+                    modifier.name(),
+                    *modifier,
+                    Vec2::new(200.0, 50.0),
+                );
+            });
+        });
+}
+
+// the daily's modifiers are the same for everyone, so they can't be switched
+pub fn toggle_modifiers(
+    buttons: Query<(&Interaction, &Modifier), Changed<Interaction>>,
+    mut run_config: ResMut<RunConfig>,
+) {
+    if run_config.mode == GameMode::Daily {
+        return;
+    }
+    buttons.iter().for_each(|(interaction, modifier)| {
+        if *interaction == Interaction::Clicked {
+            run_config.modifiers.toggle(*modifier);
+        }
+    });
+}
+
+// modifiers that are on stay lit while the mouse is elsewhere
+pub fn color_modifier_buttons(
+    run_config: Res<RunConfig>,
+    mut buttons: Query<(&Interaction, &Modifier, &mut UiColor)>,
+) {
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, modifier, mut color)| {
+            let wanted = match *interaction {
+                Interaction::None if run_config.modifiers.get(*modifier) => ACTIVE_COLOR,
+                Interaction::None => BUTTON_COLOR,
+                _default => HOVERED_COLOR,
+            };
+            if color.0 != wanted {
+                *color = UiColor(wanted);
+            }
+        });
+}
+
+pub fn update_mode_label(
+    run_config: Res<RunConfig>,
+    mut labels: Query<&mut Text, With<ModeLabel>>,
+    new_labels: Query<(), Added<ModeLabel>>,
+) {
+    if !run_config.is_changed() && new_labels.is_empty() {
+        return;
+    }
+    labels.iter_mut().for_each(|mut text| {
+        text.sections[0].value = format!("Mode: {}", run_config.mode.name());
+    });
+}
+
+// days since the unix epoch, in UTC so every player rolls over at the same moment
+fn current_day() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() / 86400)
+        .unwrap_or(0)
+}
+
+fn splitmix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+// Howard Hinnant's days -> (year, month, day) conversion
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = (if z >= 0 { z } else { z - 146096 }) / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + (if month <= 2 { 1 } else { 0 });
+    (year, month, day)
+}
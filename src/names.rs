@@ -0,0 +1,108 @@
+pub const MIN_NAME_LENGTH: usize = 3;
+pub const MAX_NAME_LENGTH: usize = 12;
+
+// a short client side list to catch the obvious ones. each is matched against whole words of
+// the name, so scunthorpe or dickens get through
+const BLOCKED_WORDS: [&str; 22] = [
+    "fuck", "fucker", "fucking", "shit", "cunt", "bitch", "nigger", "nigga", "fag", "faggot",
+    "whore", "slut", "rape", "rapist", "nazi", "cock", "dick", "pussy", "twat", "wank", "wanker",
+    "wanking",
+];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum NameError {
+    TooShort,
+    TooLong,
+    BadCharacter,
+    Blocked,
+}
+
+impl NameError {
+    pub fn message(self) -> &'static str {
+        match self {
+            NameError::TooShort => "Names need at least 3 characters",
+            NameError::TooLong => "Names can be at most 12 characters",
+            NameError::BadCharacter => "Letters, numbers, spaces, - and _ only",
+            NameError::Blocked => "Please pick another name",
+        }
+    }
+}
+
+pub fn allowed_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_'
+}
+
+// lowercase letters only with the usual number swaps undone, so sh1t still matches
+fn squash(word: &str) -> String {
+    word.chars()
+        .filter_map(|c| match c.to_ascii_lowercase() {
+            '0' => Some('o'),
+            '1' => Some('i'),
+            '3' => Some('e'),
+            '4' => Some('a'),
+            '5' => Some('s'),
+            '7' => Some('t'),
+            c if c.is_ascii_lowercase() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+// the name split on spaces, - and _ and squashed. letters spelled out one at a time are put
+// back together, so f_u_c_k reads as one word
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut spelled = String::new();
+    name.split(&[' ', '-', '_'][..])
+        .map(squash)
+        .filter(|word| !word.is_empty())
+        .for_each(|word| {
+            if word.len() == 1 {
+                spelled.push_str(&word);
+                return;
+            }
+            if !spelled.is_empty() {
+                words.push(std::mem::take(&mut spelled));
+            }
+            words.push(word);
+        });
+    if !spelled.is_empty() {
+        words.push(spelled);
+    }
+    words
+}
+
+// a plural still counts as the word
+fn blocked(word: &str) -> bool {
+    let singular = word.strip_suffix('s').unwrap_or(word);
+    BLOCKED_WORDS
+        .iter()
+        .any(|blocked| word == *blocked || singular == *blocked)
+}
+
+// the trimmed name when it's fit for the leaderboard
+pub fn validate_name(name: &str) -> Result<String, NameError> {
+    let name = name.trim();
+    let length = name.chars().count();
+    if length < MIN_NAME_LENGTH {
+        return Err(NameError::TooShort);
+    }
+    if length > MAX_NAME_LENGTH {
+        return Err(NameError::TooLong);
+    }
+    if !name.chars().all(allowed_char) {
+        return Err(NameError::BadCharacter);
+    }
+    if words(name).iter().any(|word| blocked(word)) {
+        return Err(NameError::Blocked);
+    }
+    Ok(name.to_string())
+}
+
+// whatever of a name can be kept, used to seed the entry box from the profile name
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| allowed_char(*c))
+        .take(MAX_NAME_LENGTH)
+        .collect()
+}
@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::Layers;
+
+// how high and far a ground enemy can get in one hop
+const JUMP_RISE: f32 = 60.0;
+const JUMP_REACH: f32 = 80.0;
+// enough launch speed to clear a full rise under the default gravity
+pub const JUMP_SPEED: f32 = 110.0;
+// how close to a takeoff point counts as being on it
+const TAKEOFF_SLACK: f32 = 4.0;
+// a body standing on a span is at most this far above its top
+const STANDING_HEIGHT: f32 = 24.0;
+
+// a stretch of level top that can be walked end to end
+#[derive(Clone, Copy)]
+struct Span {
+    left: f32,
+    right: f32,
+    top: f32,
+}
+
+impl Span {
+    fn holds(&self, position: Vec2) -> bool {
+        position.x >= self.left
+            && position.x <= self.right
+            && position.y >= self.top
+            && position.y - self.top <= STANDING_HEIGHT
+    }
+}
+
+// a hop or drop from the edge of one span onto another
+struct JumpLink {
+    from: usize,
+    to: usize,
+    takeoff: f32,
+    rises: bool,
+}
+
+// where a ground enemy should head next to reach its target
+pub struct NavStep {
+    pub direction: f32,
+    pub jump: bool,
+}
+
+// walkable spans and the jumps between them, rebuilt from the world colliders
+#[derive(Default)]
+pub struct NavGraph {
+    spans: Vec<Span>,
+    links: Vec<JumpLink>,
+}
+
+impl NavGraph {
+    fn span_at(&self, position: Vec2) -> Option<usize> {
+        self.spans
+            .iter()
+            .enumerate()
+            .filter(|(_, span)| span.holds(position))
+            .max_by(|a, b| a.1.top.total_cmp(&b.1.top))
+            .map(|(index, _)| index)
+    }
+
+    // breadth first over the links, returns the first one to take
+    fn first_link(&self, start: usize, goal: usize) -> Option<&JumpLink> {
+        let mut came_by: Vec<Option<usize>> = vec![None; self.spans.len()];
+        let mut seen = vec![false; self.spans.len()];
+        let mut queue = VecDeque::from([start]);
+        seen[start] = true;
+        while let Some(span) = queue.pop_front() {
+            if span == goal {
+                break;
+            }
+            self.links
+                .iter()
+                .enumerate()
+                .filter(|(_, link)| link.from == span && !seen[link.to])
+                .for_each(|(index, link)| {
+                    seen[link.to] = true;
+                    came_by[link.to] = Some(index);
+                    queue.push_back(link.to);
+                });
+        }
+        // walk back from the goal to the link that leaves the start
+        let mut link = came_by[goal]?;
+        while self.links[link].from != start {
+            link = came_by[self.links[link].from]?;
+        }
+        Some(&self.links[link])
+    }
+
+    // straight at the target when it's on the same span or unreachable, otherwise toward the
+    // next jump on the way there
+    pub fn step_toward(&self, from: Vec3, to: Vec3) -> NavStep {
+        let from = from.truncate();
+        let to = to.truncate();
+        let straight = NavStep {
+            direction: if to.x < from.x { -1.0 } else { 1.0 },
+            jump: false,
+        };
+        let (start, goal) = match (self.span_at(from), self.span_at(to)) {
+            (Some(start), Some(goal)) if start != goal => (start, goal),
+            _default => return straight,
+        };
+        match self.first_link(start, goal) {
+            Some(link) if (link.takeoff - from.x).abs() <= TAKEOFF_SLACK => NavStep {
+                direction: if self.spans[link.to].left > from.x {
+                    1.0
+                } else {
+                    -1.0
+                },
+                jump: link.rises,
+            },
+            Some(link) => NavStep {
+                direction: if link.takeoff < from.x { -1.0 } else { 1.0 },
+                jump: false,
+            },
+            None => straight,
+        }
+    }
+}
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGraph>()
+            .add_system_to_stage(CoreStage::PostUpdate, build_nav_graph);
+    }
+}
+
+struct Block {
+    left: f32,
+    right: f32,
+    top: f32,
+    bottom: f32,
+}
+
+fn block_bounds(trans: &GlobalTransform, shape: &CollisionShape) -> Option<Block> {
+    match shape {
+        CollisionShape::Cuboid { half_extends, .. } => {
+            let center = trans.translation().truncate();
+            Some(Block {
+                left: center.x - half_extends.x,
+                right: center.x + half_extends.x,
+                top: center.y + half_extends.y,
+                bottom: center.y - half_extends.y,
+            })
+        }
+        _default => None,
+    }
+}
+
+// runs whenever a piece of the level moves or appears, which is once at startup for now
+pub fn build_nav_graph(
+    mut graph: ResMut<NavGraph>,
+    changed: Query<&CollisionLayers, (Changed<GlobalTransform>, With<RigidBody>)>,
+    level: Query<(
+        &GlobalTransform,
+        &CollisionShape,
+        &CollisionLayers,
+        &RigidBody,
+    )>,
+) {
+    if !changed
+        .iter()
+        .any(|layers| layers.contains_group(Layers::World))
+    {
+        return;
+    }
+    let blocks: Vec<Block> = level
+        .iter()
+        .filter(|(_, _, layers, body)| {
+            layers.contains_group(Layers::World) && matches!(body, RigidBody::Static)
+        })
+        .filter_map(|(trans, shape, _, _)| block_bounds(trans, shape))
+        .collect();
+    // a top with another block resting on it isn't walkable
+    let mut tops: Vec<Span> = blocks
+        .iter()
+        .filter(|block| {
+            !blocks.iter().any(|other| {
+                (other.bottom - block.top).abs() < 1.0
+                    && other.left <= block.left + 1.0
+                    && other.right >= block.right - 1.0
+            })
+        })
+        .map(|block| Span {
+            left: block.left,
+            right: block.right,
+            top: block.top,
+        })
+        .collect();
+    tops.sort_by(|a, b| a.top.total_cmp(&b.top).then(a.left.total_cmp(&b.left)));
+    // neighbouring tops at the same height join into one span
+    let spans = tops.into_iter().fold(Vec::<Span>::new(), |mut spans, top| {
+        match spans.last_mut() {
+            Some(last) if (last.top - top.top).abs() < 1.0 && top.left - last.right < 1.0 => {
+                last.right = last.right.max(top.right);
+            }
+            _default => spans.push(top),
+        }
+        spans
+    });
+    let mut links = Vec::new();
+    spans.iter().enumerate().for_each(|(from, a)| {
+        spans.iter().enumerate().for_each(|(to, b)| {
+            if from == to || b.top - a.top > JUMP_RISE {
+                return;
+            }
+            // leave from the edge of a closest to b
+            let takeoff = if b.right < a.left {
+                a.left
+            } else if b.left > a.right {
+                a.right
+            } else {
+                b.left.max(a.left).min(a.right)
+            };
+            let landing = takeoff.clamp(b.left, b.right);
+            if (landing - takeoff).abs() <= JUMP_REACH {
+                links.push(JumpLink {
+                    from,
+                    to,
+                    takeoff,
+                    rises: b.top > a.top,
+                });
+            }
+        });
+    });
+    *graph = NavGraph { spans, links };
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use libm::{cosf, sinf};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::clock::GameClock;
+use crate::depth::RenderLayer;
+use crate::modes::CosmeticRng;
+use crate::{GameState, RunEntity};
+
+// cheap sprite particles, they don't touch physics so a burst never costs a collision check
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    timer: Timer,
+}
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CosmeticRng>()
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(move_particles));
+    }
+}
+
+// takes the run's rng from gameplay and the cosmetic one from everything else
+pub fn spawn_burst(
+    commands: &mut Commands,
+    rng: &mut StdRng,
+    position: Vec3,
+    color: Color,
+    count: usize,
+    speed: f32,
+) {
+    for _i in 0..count {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = speed * rng.gen_range(0.5..1.0);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(2.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    RenderLayer::Particles.at(position.truncate()),
+                ),
+                ..default()
+            })
+            .insert(Particle {
+                velocity: Vec2::new(cosf(angle), sinf(angle)) * speed,
+                timer: Timer::from_seconds(rng.gen_range(0.3..0.6), false),
+            })
+            .insert(RunEntity);
+    }
+}
+
+pub fn move_particles(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    clock: Res<GameClock>,
+) {
+    particles
+        .iter_mut()
+        .for_each(|(entity, mut particle, mut transform, mut sprite)| {
+            particle.timer.tick(clock.delta());
+            if particle.timer.finished() {
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+            transform.translation += (particle.velocity * clock.delta_seconds()).extend(0.0);
+            sprite.color.set_a(1.0 - particle.timer.percent());
+        });
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::clock::GameClock;
+use crate::collision::CollisionRules;
+use crate::depth::RenderLayer;
+use crate::loading::AtlasRegistry;
+use crate::tween::{Ease, Repeat, Tween, TweenTarget};
+use crate::{AnimationTimer, Behavior, Despawner, EnemyBullet, GameState, Layers, RunEntity};
+
+// pull on lobbed shots, stronger than the world's so mortars come down quickly
+const MORTAR_GRAVITY: f32 = 200.0;
+const MORTAR_FLIGHT_SECONDS: f32 = 1.2;
+const MARKER_COLOR: Color = Color::rgba(1.0, 0.2, 0.1, 0.7);
+
+#[derive(Copy, Clone)]
+pub enum ProjectileKind {
+    // flies straight at the player
+    Bullet,
+    // lobbed to land where the player stood, with a marker on the spot
+    Mortar,
+    // big and slow, easy to read but hard to slip past
+    Orb,
+}
+
+#[derive(Copy, Clone)]
+pub struct ShotPattern {
+    pub kind: ProjectileKind,
+    pub count: u8,
+    // radians between neighbouring shots in a volley
+    pub spread: f32,
+    pub speed: f32,
+}
+
+const SINGLE_SHOT: ShotPattern = ShotPattern {
+    kind: ProjectileKind::Bullet,
+    count: 1,
+    spread: 0.0,
+    speed: 50.0,
+};
+
+const MORTAR: ShotPattern = ShotPattern {
+    kind: ProjectileKind::Mortar,
+    count: 1,
+    spread: 0.0,
+    speed: 0.0,
+};
+
+const THREE_WAY: ShotPattern = ShotPattern {
+    kind: ProjectileKind::Bullet,
+    count: 3,
+    spread: 0.3,
+    speed: 60.0,
+};
+
+const ORB: ShotPattern = ShotPattern {
+    kind: ProjectileKind::Orb,
+    count: 1,
+    spread: 0.0,
+    speed: 25.0,
+};
+
+// what each shooting behavior picks from for a volley
+pub fn shot_patterns(behavior: Behavior) -> &'static [ShotPattern] {
+    match behavior {
+        Behavior::Shooter => &[SINGLE_SHOT, SINGLE_SHOT, MORTAR],
+        Behavior::BurstShooter => &[THREE_WAY, THREE_WAY, ORB],
+        _default => &[SINGLE_SHOT],
+    }
+}
+
+// falls under its own gravity, enemy bullets are sensors so the world's doesn't reach them
+#[derive(Component)]
+pub struct Lobbed;
+
+pub struct PatternPlugin;
+
+impl Plugin for PatternPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(pull_lobbed));
+    }
+}
+
+pub fn fire_pattern(
+    commands: &mut Commands,
+    atlases: &AtlasRegistry,
+    rules: &CollisionRules,
+    origin: Vec3,
+    target: Vec3,
+    pattern: ShotPattern,
+) {
+    let aim = (target - origin).truncate().normalize_or_zero();
+    (0..pattern.count).for_each(|shot| {
+        let offset = (shot as f32 - (pattern.count - 1) as f32 / 2.0) * pattern.spread;
+        let velocity = match pattern.kind {
+            ProjectileKind::Mortar => {
+                // solves for an arc that lands on the target after a fixed flight time
+                let span = (target - origin).truncate();
+                let time = MORTAR_FLIGHT_SECONDS;
+                spawn_landing_marker(commands, target, time);
+                Vec2::new(span.x / time, span.y / time + 0.5 * MORTAR_GRAVITY * time)
+            }
+            _default => Vec2::from_angle(offset).rotate(aim) * pattern.speed,
+        };
+        let (scale, radius) = match pattern.kind {
+            ProjectileKind::Orb => (3.0, 4.5),
+            _default => (1.0, 1.5),
+        };
+        let projectile = commands
+            .spawn_bundle(SpriteSheetBundle {
+                transform: Transform {
+                    translation: RenderLayer::Projectiles.at(origin.truncate()),
+                    scale: Vec3::splat(scale),
+                    ..default()
+                },
+                texture_atlas: atlases.enemy_bullet.clone(),
+                ..default()
+            })
+            .insert(AnimationTimer(Timer::from_seconds(0.2, true)))
+            .insert(Collisions::default())
+            .insert(EnemyBullet { origin })
+            .insert(RunEntity)
+            .insert(RigidBody::Sensor)
+            .insert(rules.layers(Layers::EnemyProjectiles))
+            .insert(CollisionShape::Sphere { radius })
+            .insert(Velocity::from_linear(velocity.extend(0.0)))
+            .id();
+        if let ProjectileKind::Mortar = pattern.kind {
+            commands.entity(projectile).insert(Lobbed);
+        }
+    });
+}
+
+// shows where a mortar is coming down, gone when it lands
+fn spawn_landing_marker(commands: &mut Commands, position: Vec3, seconds: f32) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: MARKER_COLOR,
+                custom_size: Some(Vec2::new(12.0, 2.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(RenderLayer::World.at(position.truncate())),
+            ..default()
+        })
+        .insert(
+            Tween::new(0.2, Ease::InOut)
+                .with(TweenTarget::ScaleX(1.0, 1.6))
+                .repeat(Repeat::PingPong),
+        )
+        .insert(Despawner(Timer::from_seconds(seconds, false)))
+        .insert(Name::new("Landing Marker"))
+        .insert(RunEntity);
+}
+
+pub fn pull_lobbed(mut lobbed: Query<&mut Velocity, With<Lobbed>>, clock: Res<GameClock>) {
+    lobbed.iter_mut().for_each(|mut velocity| {
+        velocity.linear.y -= MORTAR_GRAVITY * clock.delta_seconds();
+    });
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+
+use crate::actions::{Action, ActionPrompt, Actions};
+use crate::clock::GameClock;
+use crate::loading::GameAssets;
+use crate::menu::{despawn_screen, screen_root, spawn_button, spawn_title, MenuButton};
+use crate::settings::{MusicDuck, Settings};
+use crate::GameState;
+
+// share of the music volume left playing while the window is in the background
+const UNFOCUSED_MUSIC: f32 = 0.2;
+
+#[derive(Component)]
+pub struct PauseScreen;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_pause)
+            .add_system(pause_on_focus_loss)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Paused)
+                    .with_system(create_pause_menu)
+                    .with_system(freeze_game),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused)
+                    .with_system(despawn_screen::<PauseScreen>)
+                    .with_system(unfreeze_game),
+            );
+    }
+}
+
+pub fn toggle_pause(actions: Res<Actions>, mut state: ResMut<State<GameState>>) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+    let _ = match state.current() {
+        GameState::Playing => state.push(GameState::Paused),
+        GameState::Paused
+        | GameState::Settings
+        | GameState::Display
+        | GameState::Accessibility
+        | GameState::Advanced
+        | GameState::Achievements
+        | GameState::Leaderboard
+        | GameState::Shop
+        | GameState::PhotoMode
+        | GameState::PracticeMenu => state.pop(),
+        _default => Ok(()),
+    };
+}
+
+// alt-tabbing out mid run used to leave the player to die unseen
+pub fn pause_on_focus_loss(
+    mut focus: EventReader<WindowFocused>,
+    mut state: ResMut<State<GameState>>,
+    settings: Res<Settings>,
+    mut duck: ResMut<MusicDuck>,
+) {
+    let focused = match focus.iter().last() {
+        Some(event) => event.focused,
+        None => return,
+    };
+    duck.0 = if focused { 1.0 } else { UNFOCUSED_MUSIC };
+    if !focused && settings.pause_on_focus_loss && *state.current() == GameState::Playing {
+        let _ = state.push(GameState::Paused);
+    }
+}
+
+pub fn freeze_game(mut clock: ResMut<GameClock>) {
+    clock.pause();
+}
+
+pub fn unfreeze_game(mut clock: ResMut<GameClock>) {
+    clock.resume();
+}
+
+pub fn create_pause_menu(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.6)))
+        .insert(PauseScreen)
+        .insert(Name::new("Pause Menu"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Paused");
+            spawn_button(parent, &font_handle, "Resume", MenuButton::Resume);
+            spawn_button(parent, &font_handle, "Restart Run", MenuButton::Restart);
+            spawn_button(parent, &font_handle, "Settings", MenuButton::Settings);
+            spawn_button(parent, &font_handle, "Quit to Menu", MenuButton::QuitToMenu);
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 20.,
+                        color: Color::GRAY,
+                    },
+                ))
+                .insert(ActionPrompt {
+                    action: Action::Pause,
+                    label: "to resume",
+                });
+        });
+}
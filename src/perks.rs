@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::combat::DeathEvent;
+use crate::loading::GameAssets;
+use crate::menu::{
+    despawn_screen, screen_root, spawn_labeled_button, spawn_title, BUTTON_COLOR, HOVERED_COLOR,
+};
+use crate::modes::GameRng;
+use crate::pause::{freeze_game, unfreeze_game};
+use crate::{
+    Dashing, DifficultyTimer, GameState, Invulnerable, Player, ResetGame, PLAYER_MAX_HEALTH,
+};
+
+// a perk card comes up every this many difficulty levels
+const LEVELS_PER_PERK: i64 = 5;
+const KILLS_PER_HEAL: u32 = 15;
+
+#[derive(Component, Copy, Clone, PartialEq, Eq)]
+pub enum Perk {
+    FasterReload,
+    ExtraPellet,
+    DashFrames,
+    Vampirism,
+}
+
+const ALL_PERKS: [Perk; 4] = [
+    Perk::FasterReload,
+    Perk::ExtraPellet,
+    Perk::DashFrames,
+    Perk::Vampirism,
+];
+
+fn perk_text(perk: Perk) -> &'static str {
+    match perk {
+        Perk::FasterReload => "Faster reload: weapons drop 15% sooner",
+        Perk::ExtraPellet => "+1 pellet on every shot",
+        Perk::DashFrames => "Dashing makes you briefly invulnerable",
+        Perk::Vampirism => "Heal a heart every 15 kills",
+    }
+}
+
+// everything picked so far this run, perks stack by count
+#[derive(Component, Default)]
+pub struct Perks {
+    reload: u32,
+    pub extra_pellets: u32,
+    dash_frames: u32,
+    vampirism: u32,
+    kills_since_heal: u32,
+}
+
+impl Perks {
+    fn add(&mut self, perk: Perk) {
+        match perk {
+            Perk::FasterReload => self.reload += 1,
+            Perk::ExtraPellet => self.extra_pellets += 1,
+            Perk::DashFrames => self.dash_frames += 1,
+            Perk::Vampirism => self.vampirism += 1,
+        }
+    }
+
+    // multiplier on the weapon drop interval
+    pub fn reload_scale(&self) -> f32 {
+        0.85_f32.powi(self.reload as i32)
+    }
+}
+
+pub struct PerkSchedule {
+    next_level: i64,
+}
+
+// the card picked, counting from 0 in the order they're shown, so a replay can pick it again
+pub struct PerkChosen(pub u8);
+
+impl Default for PerkSchedule {
+    fn default() -> Self {
+        PerkSchedule {
+            next_level: 1 + LEVELS_PER_PERK,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PerkScreen;
+
+pub struct PerkPlugin;
+
+impl Plugin for PerkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerkSchedule>()
+            .add_event::<PerkChosen>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(offer_perks)
+                    .with_system(dash_invulnerability)
+                    .with_system(vampirism),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::PerkChoice)
+                    .with_system(create_perk_cards)
+                    .with_system(freeze_game),
+            )
+            .add_system_set(SystemSet::on_update(GameState::PerkChoice).with_system(choose_perk))
+            .add_system_set(
+                SystemSet::on_exit(GameState::PerkChoice)
+                    .with_system(despawn_screen::<PerkScreen>)
+                    .with_system(unfreeze_game),
+            )
+            .add_system(reset_perk_schedule);
+    }
+}
+
+pub fn offer_perks(
+    mut schedule: ResMut<PerkSchedule>,
+    difficulty: Res<DifficultyTimer>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if difficulty.difficulty < schedule.next_level {
+        return;
+    }
+    if state.push(GameState::PerkChoice).is_ok() {
+        schedule.next_level += LEVELS_PER_PERK;
+    }
+}
+
+pub fn create_perk_cards(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut rng: ResMut<GameRng>,
+) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    let cards: Vec<Perk> = ALL_PERKS.choose_multiple(&mut rng.0, 3).copied().collect();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.7)))
+        .insert(PerkScreen)
+        .insert(Name::new("Perk Choice"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Pick a perk");
+            cards.iter().enumerate().for_each(|(index, perk)| {
+                spawn_labeled_button(
+                    parent,
+                    &font_handle,
+                    &format!("{}. {}", index + 1, perk_text(*perk)),
+                    *perk,
+                    Vec2::new(700.0, 50.0),
+                );
+            });
+        });
+}
+
+pub fn choose_perk(
+    mut cards: Query<(&Interaction, &Perk, &mut UiColor)>,
+    mut players: Query<&mut Perks, With<Player>>,
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    mut picks: EventWriter<PerkChosen>,
+) {
+    let number_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+    let mut chosen = None;
+    cards
+        .iter_mut()
+        .enumerate()
+        .for_each(|(index, (interaction, perk, mut color))| {
+            let pressed = number_keys
+                .get(index)
+                .map_or(false, |key| keys.just_pressed(*key));
+            match *interaction {
+                Interaction::Clicked => chosen = Some((index, *perk)),
+                Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+                Interaction::None => *color = UiColor(BUTTON_COLOR),
+            }
+            if pressed {
+                chosen = Some((index, *perk));
+            }
+        });
+    if let Some((index, perk)) = chosen {
+        picks.send(PerkChosen(index as u8));
+        players.iter_mut().for_each(|mut perks| perks.add(perk));
+        let _ = state.pop();
+    }
+}
+
+pub fn dash_invulnerability(
+    mut commands: Commands,
+    dashers: Query<(Entity, &Perks), Added<Dashing>>,
+) {
+    dashers.iter().for_each(|(entity, perks)| {
+        if perks.dash_frames > 0 {
+            let seconds = 0.2 + 0.1 * perks.dash_frames as f32;
+            commands
+                .entity(entity)
+                .insert(Invulnerable(Timer::from_seconds(seconds, false)));
+        }
+    });
+}
+
+pub fn vampirism(
+    mut deaths: EventReader<DeathEvent>,
+    mut players: Query<(&mut Player, &mut Perks)>,
+) {
+    let count = deaths.iter().filter_map(DeathEvent::enemy).count() as u32;
+    players.iter_mut().for_each(|(mut player, mut perks)| {
+        if perks.vampirism == 0 {
+            return;
+        }
+        perks.kills_since_heal += count * perks.vampirism;
+        while perks.kills_since_heal >= KILLS_PER_HEAL {
+            perks.kills_since_heal -= KILLS_PER_HEAL;
+            if player.health < PLAYER_MAX_HEALTH {
+                player.health += 1;
+            }
+        }
+    });
+}
+
+pub fn reset_perk_schedule(mut schedule: ResMut<PerkSchedule>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *schedule = PerkSchedule::default();
+    }
+}
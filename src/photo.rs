@@ -0,0 +1,120 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::ui::UiCameraConfig;
+
+use crate::capture::{CaptureKind, CaptureRequests};
+use crate::clock::RealClock;
+use crate::pause::{freeze_game, unfreeze_game};
+use crate::postprocess::PostCamera;
+use crate::{GameState, MyCamera};
+
+// world units a second at the default zoom, the camera covers ground faster zoomed out
+const PAN_SPEED: f32 = 200.0;
+const ZOOM_STEP: f32 = 0.1;
+// as a share of the regular zoom
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 2.0;
+
+// where the camera was when photo mode opened, put back on the way out
+#[derive(Default)]
+pub struct PhotoCamera {
+    translation: Vec3,
+    scale: f32,
+}
+
+pub struct PhotoPlugin;
+
+impl Plugin for PhotoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhotoCamera>()
+            .add_system(toggle_photo_mode)
+            .add_system_set(
+                SystemSet::on_enter(GameState::PhotoMode)
+                    .with_system(enter_photo_mode)
+                    .with_system(freeze_game),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::PhotoMode)
+                    .with_system(fly_camera)
+                    .with_system(take_photo),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::PhotoMode)
+                    .with_system(leave_photo_mode)
+                    .with_system(unfreeze_game),
+            );
+    }
+}
+
+pub fn toggle_photo_mode(keys: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if !keys.just_pressed(KeyCode::F2) {
+        return;
+    }
+    let _ = match state.current() {
+        GameState::Playing => state.push(GameState::PhotoMode),
+        GameState::PhotoMode => state.pop(),
+        _default => Ok(()),
+    };
+}
+
+pub fn enter_photo_mode(
+    mut photo: ResMut<PhotoCamera>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<MyCamera>>,
+    mut ui_cameras: Query<&mut UiCameraConfig, With<PostCamera>>,
+) {
+    cameras.iter().for_each(|(trans, projection)| {
+        photo.translation = trans.translation;
+        photo.scale = projection.scale;
+    });
+    // the ui is drawn by the post-process camera, not the one being flown around
+    ui_cameras.iter_mut().for_each(|mut ui| ui.show_ui = false);
+}
+
+pub fn fly_camera(
+    keys: Res<Input<KeyCode>>,
+    mut wheel: EventReader<MouseWheel>,
+    photo: Res<PhotoCamera>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<MyCamera>>,
+    clock: Res<RealClock>,
+) {
+    let scroll: f32 = wheel.iter().map(|event| event.y).sum();
+    let held = |codes: [KeyCode; 2]| codes.iter().any(|code| keys.pressed(*code));
+    let mut direction = Vec2::ZERO;
+    if held([KeyCode::A, KeyCode::Left]) {
+        direction.x -= 1.0;
+    }
+    if held([KeyCode::D, KeyCode::Right]) {
+        direction.x += 1.0;
+    }
+    if held([KeyCode::W, KeyCode::Up]) {
+        direction.y += 1.0;
+    }
+    if held([KeyCode::S, KeyCode::Down]) {
+        direction.y -= 1.0;
+    }
+    cameras.iter_mut().for_each(|(mut trans, mut projection)| {
+        // scrolling up pulls in
+        let zoom = (projection.scale / photo.scale - scroll * ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM);
+        projection.scale = photo.scale * zoom;
+        let step = direction.normalize_or_zero() * PAN_SPEED * zoom * clock.delta_seconds();
+        trans.translation += step.extend(0.0);
+    });
+}
+
+pub fn take_photo(keys: Res<Input<KeyCode>>, mut captures: ResMut<CaptureRequests>) {
+    if keys.just_pressed(KeyCode::Return) {
+        captures.request(CaptureKind::Photo);
+    }
+}
+
+pub fn leave_photo_mode(
+    photo: Res<PhotoCamera>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<MyCamera>>,
+    mut ui_cameras: Query<&mut UiCameraConfig, With<PostCamera>>,
+) {
+    cameras.iter_mut().for_each(|(mut trans, mut projection)| {
+        trans.translation = photo.translation;
+        projection.scale = photo.scale;
+    });
+    ui_cameras.iter_mut().for_each(|mut ui| ui.show_ui = true);
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::accessibility::Palette;
+use crate::clock::GameClock;
+use crate::collision::collision_layers;
+use crate::combat::{DeathEvent, PickupEvent, PickupKind};
+use crate::depth::RenderLayer;
+use crate::materials::{MaterialClass, PhysicsMaterials};
+use crate::{DifficultyTimer, GameState, Layers, Player, RunEntity};
+
+const MAX_PULL_SPEED: f32 = 300.0;
+
+// pulls the entity toward the player once they're within `radius`, harder the closer it gets
+#[derive(Component)]
+pub struct Magnetic {
+    pub radius: f32,
+    pub strength: f32,
+}
+
+impl Default for Magnetic {
+    fn default() -> Self {
+        Magnetic {
+            radius: 60.0,
+            strength: 900.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ScoreGem {
+    value: i64,
+}
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(attract_magnetic)
+                .with_system(drop_score_gems)
+                .with_system(collect_score_gems),
+        );
+    }
+}
+
+pub fn attract_magnetic(
+    players: Query<&Player>,
+    mut magnets: Query<(&Transform, &mut Velocity, &Magnetic)>,
+    clock: Res<GameClock>,
+) {
+    let player = match players.iter().next() {
+        Some(player) => player,
+        None => return,
+    };
+    magnets
+        .iter_mut()
+        .for_each(|(trans, mut velocity, magnet)| {
+            let offset = (player.location - trans.translation).truncate();
+            let distance = offset.length();
+            if distance > magnet.radius || distance < f32::EPSILON {
+                return;
+            }
+            let pull = magnet.strength * (1.0 - distance / magnet.radius);
+            let linear =
+                velocity.linear.truncate() + offset / distance * pull * clock.delta_seconds();
+            velocity.linear = linear.clamp_length_max(MAX_PULL_SPEED).extend(0.0);
+        });
+}
+
+pub fn drop_score_gems(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    difficulty: Res<DifficultyTimer>,
+    palette: Res<Palette>,
+    materials: Res<PhysicsMaterials>,
+) {
+    deaths.iter().for_each(|death| {
+        if death.enemy().is_none() {
+            return;
+        }
+        let size = Vec2::new(4.0, 4.0);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: palette.gem,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    RenderLayer::Pickups.at(death.position.truncate()),
+                ),
+                ..default()
+            })
+            .insert(RigidBody::Dynamic)
+            .insert(CollisionShape::Cuboid {
+                half_extends: size.extend(0.0) / 2.0,
+                border_radius: None,
+            })
+            .insert(materials.get(MaterialClass::Pickup))
+            .insert(collision_layers(Layers::Weapons))
+            .insert(Velocity::from_linear(Vec3::new(0.0, 60.0, 0.0)))
+            .insert(Collisions::default())
+            .insert(Magnetic::default())
+            .insert(ScoreGem {
+                value: difficulty.difficulty,
+            })
+            .insert(Name::new("Score Gem"))
+            .insert(RunEntity);
+    });
+}
+
+pub fn collect_score_gems(
+    mut commands: Commands,
+    gems: Query<(Entity, &Collisions, &ScoreGem, &Transform)>,
+    players: Query<(), With<Player>>,
+    mut picked_up: EventWriter<PickupEvent>,
+) {
+    gems.iter().for_each(|(entity, collisions, gem, trans)| {
+        if collisions
+            .entities()
+            .any(|other| players.get(other).is_ok())
+        {
+            picked_up.send(PickupEvent {
+                kind: PickupKind::ScoreGem(gem.value),
+                position: trans.translation,
+            });
+            commands.entity(entity).despawn_recursive();
+        }
+    });
+}
@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    AsBindGroup, Extent3d, ShaderRef, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages,
+};
+use bevy::render::texture::BevyDefault;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::ui::UiCameraConfig;
+
+use crate::clock::RealClock;
+use crate::combat::PlayerDefeated;
+use crate::explosions::ExplosionEvent;
+use crate::settings::Settings;
+use crate::MyCamera;
+
+// the post-process quad and its camera live here, away from everything the game camera draws
+const POST_LAYER: u8 = 1;
+// red and blue split this far apart at the screen edges, in uv, at full strength
+const EXPLOSION_ABERRATION: f32 = 0.015;
+const EXPLOSION_FLASH: f32 = 0.35;
+const DEATH_ABERRATION: f32 = 0.04;
+const DEATH_FLASH: f32 = 0.6;
+
+// what the game camera's frame is drawn through on its way to the window
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "af4dcc70-1d21-47d5-87de-1e0248946088"]
+pub struct PostMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    frame: Handle<Image>,
+    // x is the channel split, y the white flash, see assets/shaders/post.wgsl
+    #[uniform(2)]
+    effect: Vec4,
+}
+
+impl Material2d for PostMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/post.wgsl".into()
+    }
+}
+
+// the image the game camera draws into, kept the size of the window
+pub struct PostTarget {
+    image: Handle<Image>,
+    material: Handle<PostMaterial>,
+    // logical window size the quad was last built for
+    size: Vec2,
+}
+
+// the last big hit, fading out over its timer
+pub struct PostEffects {
+    aberration: f32,
+    flash: f32,
+    fade: Timer,
+}
+
+impl Default for PostEffects {
+    fn default() -> Self {
+        let mut fade = Timer::from_seconds(0.2, false);
+        // start finished so nothing shows on the first frame
+        fade.tick(fade.duration());
+        PostEffects {
+            aberration: 0.0,
+            flash: 0.0,
+            fade,
+        }
+    }
+}
+
+impl PostEffects {
+    // a new hit only takes over if it's at least as strong as what's still showing
+    fn kick(&mut self, aberration: f32, flash: f32, seconds: f32) {
+        let remaining = 1.0 - self.fade.percent();
+        if aberration < self.aberration * remaining && flash < self.flash * remaining {
+            return;
+        }
+        self.aberration = aberration;
+        self.flash = flash;
+        self.fade = Timer::from_seconds(seconds, false);
+    }
+}
+
+// draws the post-processed frame and the ui to the window
+#[derive(Component)]
+pub struct PostCamera;
+
+#[derive(Component)]
+pub struct PostQuad;
+
+pub struct PostProcessPlugin;
+
+impl Plugin for PostProcessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<PostMaterial>::default())
+            .init_resource::<PostEffects>()
+            .add_startup_system(create_post_process)
+            .add_system(route_game_camera)
+            .add_system(fit_post_target)
+            .add_system(kick_post_effects)
+            .add_system(update_post_material.after(kick_post_effects));
+    }
+}
+
+fn frame_image(width: u32, height: u32) -> Image {
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("post process frame"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::bevy_default(),
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+pub fn create_post_process(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PostMaterial>>,
+    windows: Res<Windows>,
+) {
+    let (physical, logical) = windows
+        .get_primary()
+        .map_or((UVec2::ONE, Vec2::ONE), |window| {
+            (
+                UVec2::new(window.physical_width(), window.physical_height()).max(UVec2::ONE),
+                Vec2::new(window.width(), window.height()),
+            )
+        });
+    let image = images.add(frame_image(physical.x, physical.y));
+    let material = materials.add(PostMaterial {
+        frame: image.clone(),
+        effect: Vec4::ZERO,
+    });
+    commands
+        .spawn_bundle(MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(Mesh::from(shape::Quad::new(logical)))),
+            material: material.clone(),
+            ..default()
+        })
+        .insert(RenderLayers::layer(POST_LAYER))
+        .insert(PostQuad)
+        .insert(Name::new("Post Process Quad"));
+    commands
+        .spawn_bundle(Camera2dBundle {
+            camera: Camera {
+                // after the game camera has filled the image
+                priority: 1,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(RenderLayers::layer(POST_LAYER))
+        // the ui is drawn here, on top of the effects and at the window's own scale
+        .insert(UiCameraConfig { show_ui: true })
+        .insert(PostCamera)
+        .insert(Name::new("Post Process Camera"));
+    commands.insert_resource(PostTarget {
+        image,
+        material,
+        size: logical,
+    });
+}
+
+// the game camera draws into the frame image instead of the window, and leaves the ui to the
+// post camera
+pub fn route_game_camera(
+    mut commands: Commands,
+    target: Option<Res<PostTarget>>,
+    mut cameras: Query<(Entity, &mut Camera), Added<MyCamera>>,
+) {
+    let target = match target {
+        Some(target) => target,
+        None => return,
+    };
+    cameras.iter_mut().for_each(|(entity, mut camera)| {
+        camera.target = RenderTarget::Image(target.image.clone());
+        commands
+            .entity(entity)
+            .insert(UiCameraConfig { show_ui: false });
+    });
+}
+
+// follows the window, the image at its physical size and the quad at its logical one
+pub fn fit_post_target(
+    windows: Res<Windows>,
+    target: Option<ResMut<PostTarget>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut quads: Query<&mut Mesh2dHandle, With<PostQuad>>,
+) {
+    let (mut target, window) = match (target, windows.get_primary()) {
+        (Some(target), Some(window)) => (target, window),
+        _default => return,
+    };
+    let physical = Extent3d {
+        width: window.physical_width().max(1),
+        height: window.physical_height().max(1),
+        depth_or_array_layers: 1,
+    };
+    // checked first, every get_mut counts as a change and rebuilds the texture
+    let resized = images
+        .get(&target.image)
+        .map_or(false, |image| image.texture_descriptor.size != physical);
+    if resized {
+        if let Some(image) = images.get_mut(&target.image) {
+            image.resize(physical);
+        }
+    }
+    let logical = Vec2::new(window.width(), window.height());
+    if target.size != logical {
+        target.size = logical;
+        quads.iter_mut().for_each(|mut mesh| {
+            *mesh = Mesh2dHandle(meshes.add(Mesh::from(shape::Quad::new(logical))));
+        });
+    }
+}
+
+// explosions and the player going down for good both kick the screen, as hard as the flash slider says
+pub fn kick_post_effects(
+    mut effects: ResMut<PostEffects>,
+    mut explosions: EventReader<ExplosionEvent>,
+    mut defeated: EventReader<PlayerDefeated>,
+    settings: Res<Settings>,
+    clock: Res<RealClock>,
+) {
+    let strength = settings.flash;
+    if explosions.iter().count() > 0 {
+        effects.kick(
+            EXPLOSION_ABERRATION * strength,
+            EXPLOSION_FLASH * strength,
+            0.15,
+        );
+    }
+    if defeated.iter().count() > 0 {
+        effects.kick(DEATH_ABERRATION * strength, DEATH_FLASH * strength, 0.5);
+    }
+    effects.fade.tick(clock.delta());
+}
+
+pub fn update_post_material(
+    effects: Res<PostEffects>,
+    target: Option<Res<PostTarget>>,
+    mut materials: ResMut<Assets<PostMaterial>>,
+) {
+    let target = match target {
+        Some(target) => target,
+        None => return,
+    };
+    let remaining = 1.0 - effects.fade.percent();
+    let effect = Vec4::new(
+        effects.aberration * remaining,
+        effects.flash * remaining,
+        0.0,
+        0.0,
+    );
+    // only written when it moves, every write sends the material to the gpu again
+    let unchanged = materials
+        .get(&target.material)
+        .map_or(true, |material| material.effect == effect);
+    if unchanged {
+        return;
+    }
+    if let Some(material) = materials.get_mut(&target.material) {
+        material.effect = effect;
+    }
+}
@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+
+use crate::fonts::TextRole;
+use crate::loading::GameAssets;
+use crate::materials::PhysicsMaterials;
+use crate::menu::{
+    despawn_screen, screen_root, spawn_button, spawn_labeled_button, spawn_title, MenuButton,
+    BUTTON_COLOR, HOVERED_COLOR,
+};
+use crate::modes::{GameMode, RunConfig};
+use crate::pause::{freeze_game, unfreeze_game};
+use crate::weapons::{weapon_stats, Magazine};
+use crate::{
+    equip_weapon, GameState, GodMode, HeldItem, Player, ResetGame, Weapon, Weapons, ALL_WEAPONS,
+};
+
+// the cheats picked on the practice menu, they last until the next run starts
+#[derive(Default)]
+pub struct PracticeToggles {
+    god: bool,
+    infinite_ammo: bool,
+    // handed to the player once the menu closes and the run is back on
+    pending_weapon: Option<Weapons>,
+}
+
+#[derive(Component)]
+pub struct PracticeScreen;
+
+#[derive(Component, Copy, Clone)]
+pub enum PracticeButton {
+    God,
+    InfiniteAmmo,
+    Weapon(Weapons),
+}
+
+#[derive(Component)]
+pub struct PracticeHint;
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PracticeToggles>()
+            .add_system(toggle_practice_menu)
+            .add_system(reset_practice_toggles)
+            .add_system(create_practice_hint)
+            .add_system(update_practice_hint)
+            .add_system_set(
+                SystemSet::on_enter(GameState::PracticeMenu)
+                    .with_system(create_practice_menu)
+                    .with_system(freeze_game),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::PracticeMenu)
+                    .with_system(handle_practice_buttons)
+                    .with_system(update_practice_labels.after(handle_practice_buttons)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::PracticeMenu)
+                    .with_system(despawn_screen::<PracticeScreen>)
+                    .with_system(unfreeze_game),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(apply_practice_toggles.after(reset_practice_toggles)),
+            );
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn practice_label(button: PracticeButton, toggles: &PracticeToggles) -> String {
+    match button {
+        PracticeButton::God => format!("God Mode: {}", on_off(toggles.god)),
+        PracticeButton::InfiniteAmmo => {
+            format!("Infinite Ammo: {}", on_off(toggles.infinite_ammo))
+        }
+        PracticeButton::Weapon(weapon) => String::from(weapon_stats(weapon).name),
+    }
+}
+
+pub fn toggle_practice_menu(
+    keys: Res<Input<KeyCode>>,
+    run_config: Res<RunConfig>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !keys.just_pressed(KeyCode::P) || run_config.mode != GameMode::Practice {
+        return;
+    }
+    let _ = match state.current() {
+        GameState::Playing => state.push(GameState::PracticeMenu),
+        GameState::PracticeMenu => state.pop(),
+        _default => Ok(()),
+    };
+}
+
+pub fn reset_practice_toggles(
+    mut resets: EventReader<ResetGame>,
+    mut toggles: ResMut<PracticeToggles>,
+) {
+    if resets.iter().count() > 0 {
+        *toggles = PracticeToggles::default();
+    }
+}
+
+pub fn create_practice_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    toggles: Res<PracticeToggles>,
+) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.6)))
+        .insert(PracticeScreen)
+        .insert(Name::new("Practice Menu"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Practice");
+            [PracticeButton::God, PracticeButton::InfiniteAmmo]
+                .iter()
+                .for_each(|button| {
+                    spawn_labeled_button(
+                        parent,
+                        &font_handle,
+                        &practice_label(*button, &toggles),
+                        *button,
+                        Vec2::new(360.0, 50.0),
+                    );
+                });
+            // every weapon, in rows that wrap to the window
+            parent
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(Color::NONE),
+                    style: Style {
+                        max_size: Size::new(Val::Px(900.0), Val::Undefined),
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    ALL_WEAPONS.iter().for_each(|weapon| {
+                        let button = PracticeButton::Weapon(*weapon);
+                        spawn_labeled_button(
+                            row,
+                            &font_handle,
+                            &practice_label(button, &toggles),
+                            button,
+                            Vec2::new(200.0, 50.0),
+                        );
+                    });
+                });
+            spawn_button(parent, &font_handle, "Resume", MenuButton::Resume);
+        });
+}
+
+pub fn handle_practice_buttons(
+    mut buttons: Query<(&Interaction, &PracticeButton, &mut UiColor), Changed<Interaction>>,
+    mut toggles: ResMut<PracticeToggles>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let mut picked = false;
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, button, mut color)| match *interaction {
+            Interaction::Clicked => match button {
+                PracticeButton::God => toggles.god = !toggles.god,
+                PracticeButton::InfiniteAmmo => toggles.infinite_ammo = !toggles.infinite_ammo,
+                PracticeButton::Weapon(weapon) => {
+                    toggles.pending_weapon = Some(*weapon);
+                    picked = true;
+                }
+            },
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+    // picking a weapon goes straight back to the run with it in hand
+    if picked {
+        let _ = state.pop();
+    }
+}
+
+pub fn update_practice_labels(
+    toggles: Res<PracticeToggles>,
+    buttons: Query<(&PracticeButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !toggles.is_changed() {
+        return;
+    }
+    buttons.iter().for_each(|(button, children)| {
+        let label = practice_label(*button, &toggles);
+        children.iter().for_each(|child| {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.clone();
+                }
+            }
+        });
+    });
+}
+
+// keeps the player and their gun in line with the toggles, the weapon is equipped the same way
+// a pickup is
+#[allow(clippy::too_many_arguments)]
+pub fn apply_practice_toggles(
+    mut commands: Commands,
+    run_config: Res<RunConfig>,
+    mut toggles: ResMut<PracticeToggles>,
+    players: Query<(Entity, &Player, Option<&GodMode>)>,
+    mut magazines: Query<&mut Magazine, With<HeldItem>>,
+    held: Query<(Entity, &Weapon), With<HeldItem>>,
+    assets: Res<GameAssets>,
+    materials: Res<PhysicsMaterials>,
+) {
+    if run_config.mode != GameMode::Practice {
+        return;
+    }
+    players.iter().for_each(|(player, _, god)| {
+        if toggles.god && god.is_none() {
+            commands.entity(player).insert(GodMode);
+        } else if !toggles.god && god.is_some() {
+            commands.entity(player).remove::<GodMode>();
+        }
+    });
+    magazines.iter_mut().for_each(|mut magazine| {
+        if magazine.infinite() != toggles.infinite_ammo {
+            magazine.set_infinite(toggles.infinite_ammo);
+        }
+    });
+    // read before taking, taking marks the toggles changed every frame
+    if let Some(weapon) = toggles.pending_weapon {
+        toggles.pending_weapon = None;
+        if let Some((_, player, _)) = players.iter().next() {
+            equip_weapon(&mut commands, &assets, &materials, player, &held, weapon);
+        }
+    }
+}
+
+pub fn create_practice_hint(
+    mut commands: Commands,
+    assets: Option<Res<GameAssets>>,
+    hints: Query<(), With<PracticeHint>>,
+) {
+    if !hints.is_empty() {
+        return;
+    }
+    let font_handle = match assets {
+        Some(assets) => assets.font.clone(),
+        None => return,
+    };
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "P: practice options",
+                TextStyle {
+                    font: font_handle,
+                    font_size: 20.,
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    // above the bullet time meter
+                    bottom: Val::Px(30.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(PracticeHint)
+        .insert(TextRole::Label)
+        .insert(Name::new("Practice Hint"));
+}
+
+pub fn update_practice_hint(
+    run_config: Res<RunConfig>,
+    state: Res<State<GameState>>,
+    mut hints: Query<&mut Visibility, With<PracticeHint>>,
+) {
+    let visible = run_config.mode == GameMode::Practice && *state.current() == GameState::Playing;
+    hints.iter_mut().for_each(|mut visibility| {
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+    });
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::achievements::spawn_toast;
+use crate::boss::Boss;
+use crate::combat::DeathEvent;
+use crate::explosions::ExplosionEvent;
+use crate::loading::GameAssets;
+use crate::menu::{spawn_labeled_button, BUTTON_COLOR, HOVERED_COLOR};
+use crate::modes::{GameMode, GameRng, RunConfig};
+use crate::save::SaveData;
+use crate::schedule::GameplaySet;
+use crate::scoring::{ScoreEvent, ScoreSource};
+use crate::{DifficultyTimer, Enemy, GameState, ResetGame};
+
+// surviving until the difficulty gets this high unlocks new game plus, so does felling a boss
+const UNLOCK_DIFFICULTY: i64 = 60;
+const ELITE_CHANCE: f64 = 0.03;
+const NG_PLUS_ELITE_CHANCE: f64 = 0.15;
+// an elite pays out this many kills
+pub const ELITE_KILL_MULTIPLIER: i64 = 3;
+const VOLATILE_RADIUS: f32 = 30.0;
+const ARMOR_PIP_COLOR: Color = Color::rgb(0.6, 0.65, 0.75);
+const VOLATILE_PIP_COLOR: Color = Color::rgb(1.0, 0.35, 0.1);
+const ELITE_PIP_COLOR: Color = Color::GOLD;
+
+// what an enemy was spawned with on top of its behavior. kept by entity rather than as a
+// component, an enemy is already despawned by the time most systems read its death
+#[derive(Copy, Clone, Default)]
+pub struct Affixes {
+    // bullets soaked up before one gets through
+    armor: u8,
+    // goes off like a grenade when it dies, player included
+    volatile: bool,
+    // carries every affix and is worth more
+    elite: bool,
+}
+
+#[derive(Default)]
+pub struct AffixRegistry {
+    enemies: HashMap<Entity, Affixes>,
+}
+
+impl AffixRegistry {
+    // true when the enemy's armor took the hit instead of it
+    pub fn absorb(&mut self, enemy: Entity) -> bool {
+        match self.enemies.get_mut(&enemy) {
+            Some(affixes) if affixes.armor > 0 => {
+                affixes.armor -= 1;
+                true
+            }
+            _default => false,
+        }
+    }
+
+    fn armored(&self, enemy: Entity) -> bool {
+        self.enemies
+            .get(&enemy)
+            .map_or(false, |affixes| affixes.armor > 0)
+    }
+}
+
+// the marker over an armored enemy, the other affixes' markers stay put until it dies
+#[derive(Component)]
+pub struct ArmorPip;
+
+#[derive(Component)]
+pub struct NewGamePlusButton;
+
+pub struct BossDefeated;
+
+pub struct PrestigePlugin;
+
+impl Plugin for PrestigePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AffixRegistry>()
+            .add_event::<BossDefeated>()
+            .add_system(reset_affixes)
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu)
+                    .with_system(toggle_new_game_plus)
+                    .with_system(update_new_game_plus_button.after(toggle_new_game_plus)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(apply_affixes)
+                    .with_system(unlock_new_game_plus),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .label(GameplaySet::Presentation)
+                    .after(GameplaySet::CollisionResponse)
+                    .with_system(resolve_affixed_deaths)
+                    .with_system(break_armor_pips),
+            );
+    }
+}
+
+pub fn reset_affixes(mut resets: EventReader<ResetGame>, mut registry: ResMut<AffixRegistry>) {
+    if resets.iter().count() > 0 {
+        registry.enemies.clear();
+    }
+}
+
+// the little markers floating over an affixed enemy, so the player can tell what's coming
+fn pip(color: Color, x: f32, size: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::splat(size)),
+            ..default()
+        },
+        transform: Transform::from_xyz(x, 12.0, 0.1),
+        ..default()
+    }
+}
+
+// new game plus gives every enemy an affix, elites turn up in any run but far more often there
+pub fn apply_affixes(
+    mut commands: Commands,
+    mut registry: ResMut<AffixRegistry>,
+    mut rng: ResMut<GameRng>,
+    run_config: Res<RunConfig>,
+    enemies: Query<Entity, (Added<Enemy>, Without<Boss>)>,
+) {
+    enemies.iter().for_each(|enemy| {
+        let elite_chance = if run_config.new_game_plus {
+            NG_PLUS_ELITE_CHANCE
+        } else {
+            ELITE_CHANCE
+        };
+        let affixes = if rng.gen_bool(elite_chance) {
+            Affixes {
+                armor: 1,
+                volatile: true,
+                elite: true,
+            }
+        } else if run_config.new_game_plus {
+            let volatile = rng.gen_bool(0.5);
+            Affixes {
+                armor: if volatile { 0 } else { 1 },
+                volatile,
+                elite: false,
+            }
+        } else {
+            return;
+        };
+        registry.enemies.insert(enemy, affixes);
+        commands.entity(enemy).with_children(|parent| {
+            let mut x = if affixes.elite { -4.0 } else { 0.0 };
+            if affixes.elite {
+                parent.spawn_bundle(pip(ELITE_PIP_COLOR, x, 4.0));
+                x += 4.0;
+            }
+            if affixes.armor > 0 {
+                parent
+                    .spawn_bundle(pip(ARMOR_PIP_COLOR, x, 3.0))
+                    .insert(ArmorPip);
+                x += 4.0;
+            }
+            if affixes.volatile {
+                parent.spawn_bundle(pip(VOLATILE_PIP_COLOR, x, 3.0));
+            }
+        });
+    });
+}
+
+// the armor marker drops off once the armor has taken its hit
+pub fn break_armor_pips(
+    mut commands: Commands,
+    registry: Res<AffixRegistry>,
+    pips: Query<(Entity, &Parent), With<ArmorPip>>,
+) {
+    pips.iter().for_each(|(pip, parent)| {
+        if !registry.armored(parent.get()) {
+            commands.entity(pip).despawn();
+        }
+    });
+}
+
+pub fn resolve_affixed_deaths(
+    mut registry: ResMut<AffixRegistry>,
+    mut deaths: EventReader<DeathEvent>,
+    mut explosions: EventWriter<ExplosionEvent>,
+    mut scores: EventWriter<ScoreEvent>,
+    difficulty: Res<DifficultyTimer>,
+) {
+    deaths.iter().for_each(|death| {
+        let affixes = match registry.enemies.remove(&death.entity) {
+            Some(affixes) => affixes,
+            None => return,
+        };
+        if affixes.volatile {
+            explosions.send(ExplosionEvent {
+                position: death.position,
+                radius: VOLATILE_RADIUS,
+                hurts_player: true,
+                weapon: None,
+            });
+        }
+        // the regular kill is already counted, this is the rest of the bounty
+        if affixes.elite {
+            scores.send(ScoreEvent {
+                source: ScoreSource::Kill,
+                points: (ELITE_KILL_MULTIPLIER - 1) * difficulty.difficulty,
+            });
+        }
+    });
+}
+
+// unlocked for good the first time a scored run gets far enough, a toast says where to find it
+pub fn unlock_new_game_plus(
+    mut commands: Commands,
+    mut save: ResMut<SaveData>,
+    mut bosses: EventReader<BossDefeated>,
+    difficulty: Res<DifficultyTimer>,
+    run_config: Res<RunConfig>,
+    assets: Res<GameAssets>,
+) {
+    let boss_down = bosses.iter().count() > 0;
+    if save.new_game_plus || !run_config.mode.keeps_scores() {
+        return;
+    }
+    if !boss_down && difficulty.difficulty < UNLOCK_DIFFICULTY {
+        return;
+    }
+    save.new_game_plus = true;
+    save.save();
+    spawn_toast(
+        &mut commands,
+        &assets.font,
+        "New Game+ unlocked",
+        "Turn it on from the main menu",
+        0,
+    );
+}
+
+fn new_game_plus_label(on: bool) -> String {
+    format!("New Game+: {}", if on { "On" } else { "Off" })
+}
+
+// only shows once it's been earned
+pub fn spawn_new_game_plus_toggle(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    save: &SaveData,
+    run_config: &RunConfig,
+) {
+    if !save.new_game_plus {
+        return;
+    }
+    spawn_labeled_button(
+        parent,
+        font,
+        &new_game_plus_label(run_config.new_game_plus),
+        NewGamePlusButton,
+        Vec2::new(260.0, 50.0),
+    );
+}
+
+// daily runs are the same for everyone, so they stay as they are
+pub fn toggle_new_game_plus(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<NewGamePlusButton>)>,
+    mut run_config: ResMut<RunConfig>,
+) {
+    if run_config.mode == GameMode::Daily {
+        return;
+    }
+    if buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        run_config.new_game_plus = !run_config.new_game_plus;
+    }
+}
+
+pub fn update_new_game_plus_button(
+    run_config: Res<RunConfig>,
+    mut buttons: Query<(&Interaction, &Children, &mut UiColor), With<NewGamePlusButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, children, mut color)| {
+            let wanted = match *interaction {
+                Interaction::None => BUTTON_COLOR,
+                _default => HOVERED_COLOR,
+            };
+            if color.0 != wanted {
+                *color = UiColor(wanted);
+            }
+            let label = new_game_plus_label(run_config.new_game_plus);
+            children.iter().for_each(|child| {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    if text.sections[0].value != label {
+                        text.sections[0].value = label.clone();
+                    }
+                }
+            });
+        });
+}
@@ -0,0 +1,363 @@
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::fonts::TextRole;
+use crate::leaderboard::{start_best_fetch, Leaderboard};
+use crate::loading::GameAssets;
+use crate::menu::{
+    despawn_screen, screen_root, spawn_labeled_button, spawn_title, BUTTON_COLOR, HOVERED_COLOR,
+};
+use crate::modes::RunConfig;
+use crate::names::{allowed_char, validate_name, NameError, MAX_NAME_LENGTH};
+use crate::save::{
+    backup_path, load_file, save_file, wrap_unversioned, Migration, SaveData, SAVE_FILE,
+};
+use crate::settings::{Settings, SETTINGS_FILE};
+use crate::transition::Transition;
+use crate::GameState;
+
+const PROFILES_PATH: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+const PROFILE_MIGRATIONS: &[Migration] = &[wrap_unversioned];
+// as many as fit on the screen under the title
+const MAX_PROFILES: usize = 6;
+// who the files from before profiles existed are handed to
+const FIRST_PROFILE_NAME: &str = "Player";
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileId(pub u32);
+
+impl ProfileId {
+    // each profile keeps its files in a folder of its own
+    pub fn path(self, file: &str) -> String {
+        format!("{}/{}/{}", PROFILES_DIR, self.0, file)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: ProfileId,
+    pub name: String,
+}
+
+// everyone who plays on this machine, the files themselves live under profiles/<id>/
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+    // picked last time, the game starts up with their settings
+    last: Option<ProfileId>,
+    // ids are never handed out twice, even if a folder is deleted by hand
+    next_id: u32,
+}
+
+impl Profiles {
+    pub fn load() -> Self {
+        let mut profiles: Profiles = load_file(PROFILES_PATH, PROFILE_MIGRATIONS);
+        if profiles.profiles.is_empty() {
+            let first = profiles.add(FIRST_PROFILE_NAME);
+            adopt_unprofiled_files(first);
+            profiles.last = Some(first);
+            profiles.save();
+        }
+        profiles
+    }
+
+    fn save(&self) {
+        save_file(PROFILES_PATH, PROFILE_MIGRATIONS, self);
+    }
+
+    fn add(&mut self, name: &str) -> ProfileId {
+        let id = ProfileId(self.next_id);
+        self.next_id += 1;
+        self.profiles.push(Profile {
+            id,
+            name: name.to_string(),
+        });
+        id
+    }
+
+    pub fn active(&self) -> ProfileId {
+        self.last
+            .or_else(|| self.profiles.first().map(|profile| profile.id))
+            .unwrap_or_default()
+    }
+
+    pub fn active_name(&self) -> &str {
+        let active = self.active();
+        self.profiles
+            .iter()
+            .find(|profile| profile.id == active)
+            .map_or(FIRST_PROFILE_NAME, |profile| profile.name.as_str())
+    }
+}
+
+// saves and settings from before profiles sat next to the game, they become the first
+// profile's rather than being left behind. their backups come along so a broken file can
+// still fall back on one
+fn adopt_unprofiled_files(profile: ProfileId) {
+    [SAVE_FILE, SETTINGS_FILE]
+        .iter()
+        .flat_map(|file| [file.to_string(), backup_path(file)])
+        .for_each(|file| {
+            let target = profile.path(&file);
+            if !Path::new(&file).exists() || Path::new(&target).exists() {
+                return;
+            }
+            let result = Path::new(&target)
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::rename(&file, &target));
+            if let Err(err) = result {
+                warn!("could not move {} into {}: {}", file, target, err);
+            }
+        });
+}
+
+// the name being typed for a new profile
+#[derive(Default)]
+pub struct ProfileEntry {
+    input: String,
+    error: Option<NameError>,
+}
+
+#[derive(Component)]
+pub struct ProfileScreen;
+
+#[derive(Component)]
+pub struct ProfileButton(ProfileId);
+
+#[derive(Component)]
+pub struct NewProfileButton;
+
+#[derive(Component)]
+pub struct ProfileNameLabel;
+
+#[derive(Component)]
+pub struct ProfileErrorLabel;
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProfileEntry>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Profiles).with_system(create_profile_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Profiles)
+                    .with_system(type_profile_name)
+                    .with_system(pick_profile)
+                    .with_system(create_profile.after(type_profile_name))
+                    .with_system(update_profile_entry.after(create_profile)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Profiles)
+                    .with_system(despawn_screen::<ProfileScreen>),
+            );
+    }
+}
+
+fn entry_text(font: &Handle<Font>, size: f32, color: Color) -> TextBundle {
+    TextBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font: font.clone(),
+                font_size: size,
+                color,
+            },
+        ),
+        style: Style {
+            margin: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+pub fn create_profile_screen(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    profiles: Res<Profiles>,
+    mut entry: ResMut<ProfileEntry>,
+) {
+    *entry = ProfileEntry::default();
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.8)))
+        .insert(ProfileScreen)
+        .insert(Name::new("Profile Select"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Who's playing?");
+            profiles.profiles.iter().for_each(|profile| {
+                spawn_labeled_button(
+                    parent,
+                    &font_handle,
+                    &profile.name,
+                    ProfileButton(profile.id),
+                    Vec2::new(260.0, 50.0),
+                );
+            });
+            if profiles.profiles.len() >= MAX_PROFILES {
+                return;
+            }
+            parent
+                .spawn_bundle(entry_text(&font_handle, 30., Color::WHITE))
+                .insert(ProfileNameLabel)
+                .insert(TextRole::Label);
+            parent
+                .spawn_bundle(entry_text(&font_handle, 20., Color::rgb(0.95, 0.35, 0.3)))
+                .insert(ProfileErrorLabel)
+                .insert(TextRole::Label);
+            spawn_labeled_button(
+                parent,
+                &font_handle,
+                "New Profile",
+                NewProfileButton,
+                Vec2::new(260.0, 50.0),
+            );
+        });
+}
+
+pub fn type_profile_name(
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut entry: ResMut<ProfileEntry>,
+) {
+    characters.iter().for_each(|received| {
+        if allowed_char(received.char) && entry.input.chars().count() < MAX_NAME_LENGTH {
+            entry.input.push(received.char);
+            entry.error = None;
+        }
+    });
+    if keys.just_pressed(KeyCode::Back) {
+        entry.input.pop();
+        entry.error = None;
+    }
+}
+
+// everything kept per player is read again, settings included, before the menu comes up.
+// their best is checked against the leaderboard under their own name
+#[allow(clippy::too_many_arguments)]
+fn switch_profile(
+    id: ProfileId,
+    commands: &mut Commands,
+    profiles: &mut Profiles,
+    save: &mut SaveData,
+    settings: &mut Settings,
+    transition: &mut Transition,
+    leaderboard: &Leaderboard,
+    run_config: &RunConfig,
+) {
+    profiles.last = Some(id);
+    profiles.save();
+    *save = SaveData::load(id);
+    *settings = Settings::load(id);
+    start_best_fetch(commands, leaderboard, run_config, settings, id);
+    transition.fade_to(GameState::MainMenu);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn pick_profile(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &ProfileButton, &mut UiColor), Changed<Interaction>>,
+    mut profiles: ResMut<Profiles>,
+    mut save: ResMut<SaveData>,
+    mut settings: ResMut<Settings>,
+    mut transition: ResMut<Transition>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+) {
+    let mut picked = None;
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, button, mut color)| match *interaction {
+            Interaction::Clicked => picked = Some(button.0),
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+    if let Some(id) = picked {
+        switch_profile(
+            id,
+            &mut commands,
+            &mut profiles,
+            &mut save,
+            &mut settings,
+            &mut transition,
+            &leaderboard,
+            &run_config,
+        );
+    }
+}
+
+// the typed name goes through the same checks as leaderboard names
+#[allow(clippy::too_many_arguments)]
+pub fn create_profile(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut buttons: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<NewProfileButton>),
+    >,
+    mut entry: ResMut<ProfileEntry>,
+    mut profiles: ResMut<Profiles>,
+    mut save: ResMut<SaveData>,
+    mut settings: ResMut<Settings>,
+    mut transition: ResMut<Transition>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+) {
+    let mut clicked = false;
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, mut color)| match *interaction {
+            Interaction::Clicked => clicked = true,
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+    if !clicked && !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+    if profiles.profiles.len() >= MAX_PROFILES {
+        return;
+    }
+    let name = match validate_name(&entry.input) {
+        Ok(name) => name,
+        Err(err) => {
+            entry.error = Some(err);
+            return;
+        }
+    };
+    let id = profiles.add(&name);
+    switch_profile(
+        id,
+        &mut commands,
+        &mut profiles,
+        &mut save,
+        &mut settings,
+        &mut transition,
+        &leaderboard,
+        &run_config,
+    );
+}
+
+pub fn update_profile_entry(
+    entry: Res<ProfileEntry>,
+    mut names: Query<&mut Text, (With<ProfileNameLabel>, Without<ProfileErrorLabel>)>,
+    mut errors: Query<&mut Text, With<ProfileErrorLabel>>,
+    new_labels: Query<(), Added<ProfileNameLabel>>,
+) {
+    if !entry.is_changed() && new_labels.is_empty() {
+        return;
+    }
+    names.iter_mut().for_each(|mut text| {
+        text.sections[0].value = format!("Name: {}_", entry.input);
+    });
+    errors.iter_mut().for_each(|mut text| {
+        text.sections[0].value = entry.error.map_or("", NameError::message).to_string();
+    });
+}
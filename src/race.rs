@@ -0,0 +1,247 @@
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::achievements::{spawn_toast, Toast};
+use crate::leaderboard::Leaderboard;
+use crate::loading::GameAssets;
+use crate::modes::RunConfig;
+use crate::replay::{decode_replay, Replay, ReplayRecorder};
+use crate::transition::Transition;
+use crate::{GameState, ResetGame, Score};
+
+const AHEAD_COLOR: Color = Color::rgb(0.4, 0.9, 0.4);
+const BEHIND_COLOR: Color = Color::rgb(0.95, 0.35, 0.3);
+
+#[derive(Deserialize)]
+struct RaceEntry {
+    name: String,
+    score: i64,
+    replay: String,
+    // runs sent before scores were tracked per second don't have one
+    #[serde(default)]
+    score_track: Vec<i64>,
+}
+
+pub struct RaceTarget {
+    pub name: String,
+    pub replay: Replay,
+    final_score: i64,
+    score_track: Vec<i64>,
+}
+
+impl RaceTarget {
+    // what the ghost had at this point of its run
+    fn score_at(&self, elapsed: f32) -> i64 {
+        if let Some(last) = self.score_track.last() {
+            return *self.score_track.get(elapsed as usize).unwrap_or(last);
+        }
+        // without a track the best guess is a steady climb over the length of the replay
+        let duration = self.replay.duration();
+        if duration <= 0.0 {
+            return self.final_score;
+        }
+        (self.final_score as f32 * (elapsed / duration).min(1.0)) as i64
+    }
+}
+
+// the run picked to race off the leaderboard screen, kept across restarts until the main menu
+#[derive(Default)]
+pub struct Race {
+    pub target: Option<RaceTarget>,
+}
+
+pub struct PendingRace(Mutex<Receiver<Result<RaceEntry, String>>>);
+
+#[derive(Component)]
+pub struct RaceLabel;
+
+pub struct RacePlugin;
+
+impl Plugin for RacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Race>()
+            .add_system(receive_race)
+            .add_system(create_race_label)
+            .add_system(update_race_label)
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(end_race));
+    }
+}
+
+// downloads the run at this rank, the race starts once it lands
+pub fn fetch_race(
+    commands: &mut Commands,
+    leaderboard: &Leaderboard,
+    run_config: &RunConfig,
+    rank: u32,
+) {
+    let url = match leaderboard.url() {
+        Some(url) => format!("{}/scores/entry", url),
+        None => return,
+    };
+    let bucket = run_config.leaderboard_bucket();
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let entry = ureq::get(&url)
+            .query("bucket", &bucket)
+            .query("rank", &rank.to_string())
+            .call()
+            .map_err(|err| err.to_string())
+            .and_then(|response| {
+                response
+                    .into_json::<RaceEntry>()
+                    .map_err(|err| err.to_string())
+            });
+        let _ = sender.send(entry);
+    });
+    commands.insert_resource(PendingRace(Mutex::new(receiver)));
+}
+
+pub fn receive_race(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    pending: Option<Res<PendingRace>>,
+    mut race: ResMut<Race>,
+    mut resets: EventWriter<ResetGame>,
+    mut transition: ResMut<Transition>,
+    toasts: Query<(), With<Toast>>,
+) {
+    let pending = match pending {
+        Some(pending) => pending,
+        None => return,
+    };
+    let result = match pending
+        .0
+        .lock()
+        .ok()
+        .and_then(|receiver| receiver.try_recv().ok())
+    {
+        Some(result) => result,
+        None => return,
+    };
+    commands.remove_resource::<PendingRace>();
+    let entry = match result {
+        Ok(entry) => entry,
+        Err(err) => {
+            warn!("could not fetch the run to race: {}", err);
+            spawn_toast(
+                &mut commands,
+                &assets.font,
+                "Race unavailable",
+                "Could not download that run",
+                toasts.iter().count(),
+            );
+            return;
+        }
+    };
+    let replay = match decode_replay(&entry.replay) {
+        Some(replay) => replay,
+        None => {
+            warn!("{}'s replay could not be decoded", entry.name);
+            spawn_toast(
+                &mut commands,
+                &assets.font,
+                "Race unavailable",
+                "That run has no usable replay",
+                toasts.iter().count(),
+            );
+            return;
+        }
+    };
+    info!("racing {}'s ghost ({} points)", entry.name, entry.score);
+    // set straight away rather than through commands, the reset below spawns the ghost from it
+    race.target = Some(RaceTarget {
+        name: entry.name,
+        replay,
+        final_score: entry.score,
+        score_track: entry.score_track,
+    });
+    resets.send(ResetGame);
+    transition.fade_to(GameState::Playing);
+}
+
+pub fn end_race(mut race: ResMut<Race>) {
+    race.target = None;
+}
+
+pub fn create_race_label(
+    mut commands: Commands,
+    race: Res<Race>,
+    assets: Option<Res<GameAssets>>,
+    labels: Query<(), With<RaceLabel>>,
+) {
+    if race.target.is_none() || !labels.is_empty() {
+        return;
+    }
+    let font_handle = match assets {
+        Some(assets) => assets.font.clone(),
+        None => return,
+    };
+    commands
+        .spawn_bundle(
+            TextBundle::from_sections([
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        font: font_handle.clone(),
+                        font_size: 20.,
+                        color: Color::rgba(1.0, 1.0, 1.0, 0.7),
+                    },
+                ),
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        font: font_handle,
+                        font_size: 20.,
+                        color: AHEAD_COLOR,
+                    },
+                ),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(RaceLabel)
+        .insert(Name::new("Race Label"));
+}
+
+// the ghost's score ticks along next to the player's, with how far ahead or behind they are
+pub fn update_race_label(
+    race: Res<Race>,
+    score: Res<Score>,
+    recorder: Res<ReplayRecorder>,
+    state: Res<State<GameState>>,
+    mut labels: Query<(&mut Text, &mut Visibility), With<RaceLabel>>,
+) {
+    let target = match (&race.target, state.current()) {
+        (Some(target), GameState::Playing) => Some(target),
+        _ => None,
+    };
+    labels.iter_mut().for_each(|(mut text, mut visibility)| {
+        if visibility.is_visible != target.is_some() {
+            visibility.is_visible = target.is_some();
+        }
+        let target = match target {
+            Some(target) => target,
+            None => return,
+        };
+        let ghost_score = target.score_at(recorder.elapsed);
+        let lead = score.score - ghost_score;
+        text.sections[0].value = format!("{}: {}  ", target.name, ghost_score);
+        text.sections[1].value = if lead >= 0 {
+            format!("Ahead +{}", lead)
+        } else {
+            format!("Behind {}", lead)
+        };
+        text.sections[1].style.color = if lead >= 0 { AHEAD_COLOR } else { BEHIND_COLOR };
+    });
+}
@@ -0,0 +1,519 @@
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::leaderboard::{board_name, Leaderboard};
+use crate::loading::GameAssets;
+use crate::menu::{
+    despawn_screen, screen_root, spawn_button, spawn_labeled_button, spawn_title, MenuButton,
+    BUTTON_COLOR, HOVERED_COLOR,
+};
+use crate::modes::RunConfig;
+use crate::profiles::Profiles;
+use crate::race::fetch_race;
+use crate::settings::Settings;
+use crate::touch::touch_position;
+use crate::GameState;
+
+const PAGE_SIZE: usize = 10;
+const TOP_COUNT: usize = 100;
+// wheel lines or touch pixels past the top of the first page before it counts as a pull
+const PULL_LINES: f32 = 3.0;
+const PULL_PIXELS: f32 = 80.0;
+const FRIEND_COLOR: Color = Color::rgb(0.4, 0.9, 0.4);
+const OWN_COLOR: Color = Color::GOLD;
+
+#[derive(Deserialize, Clone)]
+pub struct RankedEntry {
+    rank: u32,
+    name: String,
+    score: i64,
+}
+
+#[derive(Deserialize)]
+struct TopList {
+    entries: Vec<RankedEntry>,
+}
+
+type RankingsResult = Result<(Vec<RankedEntry>, Option<RankedEntry>), String>;
+
+// the top 100 and the player's own spot, fetched together each time the screen opens
+#[derive(Default)]
+pub struct Rankings {
+    entries: Vec<RankedEntry>,
+    own: Option<RankedEntry>,
+    page: usize,
+    loading: bool,
+    error: Option<String>,
+    // how far the list has been pulled down past the first entry
+    pull: f32,
+}
+
+impl Rankings {
+    fn pages(&self) -> usize {
+        ((self.entries.len() + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+    }
+}
+
+pub struct PendingRankings(Mutex<Receiver<RankingsResult>>);
+
+#[derive(Component)]
+pub struct RankingsScreen;
+
+#[derive(Component)]
+pub struct RankingsStatus;
+
+#[derive(Component)]
+pub struct OwnRankLabel;
+
+// a row on the current page, clicking it adds or removes that name from the friends list
+#[derive(Component)]
+pub struct RankRow(usize);
+
+// next to each row, downloads that run and starts a race against its ghost
+#[derive(Component)]
+pub struct RaceButton(usize);
+
+#[derive(Component, Copy, Clone)]
+pub enum RankingsButton {
+    PreviousPage,
+    NextPage,
+    Refresh,
+}
+
+pub struct RankingsPlugin;
+
+impl Plugin for RankingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rankings>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Leaderboard)
+                    .with_system(create_rankings_screen)
+                    .with_system(fetch_rankings),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Leaderboard)
+                    .with_system(receive_rankings)
+                    .with_system(handle_rankings_buttons)
+                    .with_system(toggle_friends)
+                    .with_system(start_race)
+                    .with_system(pull_to_refresh)
+                    .with_system(update_rankings_view),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Leaderboard)
+                    .with_system(despawn_screen::<RankingsScreen>),
+            );
+    }
+}
+
+pub fn create_rankings_screen(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    let style = TextStyle {
+        font: font_handle.clone(),
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.9)))
+        .insert(RankingsScreen)
+        .insert(Name::new("Leaderboard"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Leaderboard");
+            parent
+                .spawn_bundle(TextBundle::from_section("", style.clone()))
+                .insert(RankingsStatus);
+            (0..PAGE_SIZE).for_each(|index| {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        color: UiColor(Color::NONE),
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn_bundle(row_button(340.0))
+                            .insert(RankRow(index))
+                            .with_children(|button| {
+                                button.spawn_bundle(TextBundle::from_section("", style.clone()));
+                            });
+                        row.spawn_bundle(row_button(76.0))
+                            .insert(RaceButton(index))
+                            .with_children(|button| {
+                                button.spawn_bundle(TextBundle::from_section(
+                                    "Race",
+                                    TextStyle {
+                                        font_size: 16.,
+                                        ..style.clone()
+                                    },
+                                ));
+                            });
+                    });
+            });
+            // pinned under the list so the player's own spot shows from any page
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            color: OWN_COLOR,
+                            ..style.clone()
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(OwnRankLabel);
+            parent
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(Color::NONE),
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    let size = Vec2::new(130.0, 40.0);
+                    spawn_labeled_button(
+                        row,
+                        &font_handle,
+                        "<",
+                        RankingsButton::PreviousPage,
+                        size,
+                    );
+                    spawn_labeled_button(
+                        row,
+                        &font_handle,
+                        "Refresh",
+                        RankingsButton::Refresh,
+                        size,
+                    );
+                    spawn_labeled_button(row, &font_handle, ">", RankingsButton::NextPage, size);
+                });
+            spawn_button(parent, &font_handle, "Back", MenuButton::Back);
+        });
+}
+
+fn row_button(width: f32) -> ButtonBundle {
+    ButtonBundle {
+        color: UiColor(BUTTON_COLOR),
+        style: Style {
+            size: Size::new(Val::Px(width), Val::Px(26.0)),
+            margin: UiRect::all(Val::Px(2.0)),
+            padding: UiRect::new(Val::Px(8.0), Val::Px(8.0), Val::Px(0.0), Val::Px(0.0)),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    }
+}
+
+pub fn fetch_rankings(
+    mut commands: Commands,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut rankings: ResMut<Rankings>,
+) {
+    start_fetch(
+        &mut commands,
+        &leaderboard,
+        &run_config,
+        &settings,
+        &profiles,
+        &mut rankings,
+    );
+}
+
+fn start_fetch(
+    commands: &mut Commands,
+    leaderboard: &Leaderboard,
+    run_config: &RunConfig,
+    settings: &Settings,
+    profiles: &Profiles,
+    rankings: &mut Rankings,
+) {
+    let url = match leaderboard.url() {
+        Some(url) => url.to_string(),
+        None => {
+            rankings.error = Some(String::from("No leaderboard configured"));
+            return;
+        }
+    };
+    rankings.loading = true;
+    rankings.error = None;
+    rankings.pull = 0.0;
+    let bucket = run_config.leaderboard_bucket();
+    let name = board_name(settings, profiles);
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let top = ureq::get(&format!("{}/scores/list", url))
+            .query("bucket", &bucket)
+            .query("limit", &TOP_COUNT.to_string())
+            .call()
+            .map_err(|err| err.to_string())
+            .and_then(|response| {
+                response
+                    .into_json::<TopList>()
+                    .map_err(|err| err.to_string())
+            });
+        // not having a rank yet isn't an error, the list still shows
+        let own = ureq::get(&format!("{}/scores/rank", url))
+            .query("bucket", &bucket)
+            .query("name", &name)
+            .call()
+            .ok()
+            .and_then(|response| response.into_json::<RankedEntry>().ok());
+        let _ = sender.send(top.map(|top| (top.entries, own)));
+    });
+    commands.insert_resource(PendingRankings(Mutex::new(receiver)));
+}
+
+pub fn receive_rankings(
+    mut commands: Commands,
+    pending: Option<Res<PendingRankings>>,
+    mut rankings: ResMut<Rankings>,
+) {
+    let pending = match pending {
+        Some(pending) => pending,
+        None => return,
+    };
+    let result = match pending
+        .0
+        .lock()
+        .ok()
+        .and_then(|receiver| receiver.try_recv().ok())
+    {
+        Some(result) => result,
+        None => return,
+    };
+    commands.remove_resource::<PendingRankings>();
+    rankings.loading = false;
+    match result {
+        Ok((entries, own)) => {
+            rankings.entries = entries;
+            rankings.own = own;
+            rankings.page = rankings.page.min(rankings.pages() - 1);
+        }
+        Err(err) => {
+            warn!("could not fetch the leaderboard: {}", err);
+            rankings.error = Some(String::from("Could not reach the leaderboard"));
+        }
+    }
+}
+
+pub fn handle_rankings_buttons(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &RankingsButton, &mut UiColor), Changed<Interaction>>,
+    mut rankings: ResMut<Rankings>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+) {
+    let mut refresh = false;
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, button, mut color)| match *interaction {
+            Interaction::Clicked => match button {
+                RankingsButton::PreviousPage => rankings.page = rankings.page.saturating_sub(1),
+                RankingsButton::NextPage => {
+                    rankings.page = (rankings.page + 1).min(rankings.pages() - 1)
+                }
+                RankingsButton::Refresh => refresh = true,
+            },
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+    if refresh && !rankings.loading {
+        start_fetch(
+            &mut commands,
+            &leaderboard,
+            &run_config,
+            &settings,
+            &profiles,
+            &mut rankings,
+        );
+    }
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+pub fn toggle_friends(
+    mut rows: Query<(&Interaction, &RankRow, &mut UiColor), Changed<Interaction>>,
+    rankings: Res<Rankings>,
+    mut settings: ResMut<Settings>,
+) {
+    rows.iter_mut()
+        .for_each(|(interaction, row, mut color)| match *interaction {
+            Interaction::Clicked => {
+                let entry = match rankings.entries.get(rankings.page * PAGE_SIZE + row.0) {
+                    Some(entry) => entry,
+                    None => return,
+                };
+                let known = settings
+                    .friends
+                    .iter()
+                    .position(|friend| same_name(friend, &entry.name));
+                match known {
+                    Some(index) => {
+                        settings.friends.remove(index);
+                    }
+                    None => settings.friends.push(entry.name.clone()),
+                }
+            }
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+}
+
+pub fn start_race(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &RaceButton, &mut UiColor), Changed<Interaction>>,
+    rankings: Res<Rankings>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+) {
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, button, mut color)| match *interaction {
+            Interaction::Clicked => {
+                if let Some(entry) = rankings.entries.get(rankings.page * PAGE_SIZE + button.0) {
+                    fetch_race(&mut commands, &leaderboard, &run_config, entry.rank);
+                }
+            }
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+}
+
+// scrolling or dragging down past the top of the first page refetches, like a phone list.
+// anywhere else the same gestures turn pages
+#[allow(clippy::too_many_arguments)]
+pub fn pull_to_refresh(
+    mut commands: Commands,
+    mut wheel: EventReader<MouseWheel>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    mut rankings: ResMut<Rankings>,
+    leaderboard: Res<Leaderboard>,
+    run_config: Res<RunConfig>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+) {
+    let scrolled: f32 = wheel.iter().map(|event| event.y).sum();
+    // positive once a finger has been dragged down the screen
+    let dragged = windows.get_primary().and_then(|window| {
+        touches
+            .iter_just_released()
+            .map(|touch| {
+                touch_position(touch.start_position(), window).y
+                    - touch_position(touch.position(), window).y
+            })
+            .find(|drag| drag.abs() > PULL_PIXELS)
+    });
+    let pulled = match dragged {
+        Some(drag) if drag > 0.0 && rankings.page == 0 => true,
+        Some(drag) if drag > 0.0 => {
+            rankings.page -= 1;
+            false
+        }
+        Some(_) => {
+            rankings.page = (rankings.page + 1).min(rankings.pages() - 1);
+            false
+        }
+        None if scrolled > 0.0 && rankings.page == 0 => {
+            rankings.pull += scrolled;
+            rankings.pull >= PULL_LINES
+        }
+        None if scrolled > 0.0 => {
+            rankings.page -= 1;
+            false
+        }
+        None if scrolled < 0.0 => {
+            rankings.page = (rankings.page + 1).min(rankings.pages() - 1);
+            false
+        }
+        None => false,
+    };
+    if pulled && !rankings.loading {
+        start_fetch(
+            &mut commands,
+            &leaderboard,
+            &run_config,
+            &settings,
+            &profiles,
+            &mut rankings,
+        );
+    }
+}
+
+pub fn update_rankings_view(
+    rankings: Res<Rankings>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut statuses: Query<&mut Text, With<RankingsStatus>>,
+    mut own_labels: Query<&mut Text, (With<OwnRankLabel>, Without<RankingsStatus>)>,
+    rows: Query<(&RankRow, &Children)>,
+    mut row_texts: Query<&mut Text, (Without<OwnRankLabel>, Without<RankingsStatus>)>,
+) {
+    if !rankings.is_changed() && !settings.is_changed() && !profiles.is_changed() {
+        return;
+    }
+    let own_name = board_name(&settings, &profiles);
+    statuses.iter_mut().for_each(|mut text| {
+        text.sections[0].value = match (&rankings.error, rankings.loading) {
+            (Some(error), _) => error.clone(),
+            (None, true) => String::from("Loading..."),
+            (None, false) => format!(
+                "Page {} of {}, click a name to mark a friend or Race to take on their ghost",
+                rankings.page + 1,
+                rankings.pages()
+            ),
+        };
+    });
+    own_labels.iter_mut().for_each(|mut text| {
+        text.sections[0].value = match &rankings.own {
+            Some(own) => format!("You: #{} {} - {}", own.rank, own.name, own.score),
+            None => String::from("You: not ranked yet"),
+        };
+    });
+    rows.iter().for_each(|(row, children)| {
+        let entry = rankings.entries.get(rankings.page * PAGE_SIZE + row.0);
+        children.iter().for_each(|child| {
+            if let Ok(mut text) = row_texts.get_mut(*child) {
+                let section = &mut text.sections[0];
+                section.value = entry.map_or(String::new(), |entry| {
+                    format!("#{:<4} {:<14} {}", entry.rank, entry.name, entry.score)
+                });
+                let friend = entry.map_or(false, |entry| {
+                    settings
+                        .friends
+                        .iter()
+                        .any(|friend| same_name(friend, &entry.name))
+                });
+                let own = entry.map_or(false, |entry| same_name(&own_name, &entry.name));
+                section.style.color = if own {
+                    OWN_COLOR
+                } else if friend {
+                    FRIEND_COLOR
+                } else {
+                    Color::WHITE
+                };
+            }
+        });
+    });
+}
@@ -0,0 +1,396 @@
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::actions::{read_actions, Actions};
+use crate::characters::CharacterDef;
+use crate::clock::GameClock;
+use crate::crosshair::AimPoint;
+use crate::depth::RenderLayer;
+use crate::headless::simulation_app;
+use crate::loading::GameAssets;
+use crate::materials::PhysicsMaterials;
+use crate::modes::RunConfig;
+use crate::perks::PerkChosen;
+use crate::save::SaveData;
+use crate::settings::Settings;
+use crate::shop::{Unlock, ALL_UNLOCKS};
+use crate::spawning::SpawnTable;
+use crate::{aim_held_item, HeldItem, Player, ResetGame, RunEntity, Score};
+
+// bumped whenever a frame's layout changes, older replays are turned away rather than misread
+const REPLAY_FORMAT: u8 = 3;
+const HEADER_BYTES: usize = 11;
+const FRAME_BYTES: usize = 21;
+// a ghost that fell behind runs this many of its frames per real one until it's caught up
+const MAX_CATCH_UP_FRAMES: usize = 8;
+const PERK_KEYS: [KeyCode; 3] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+
+// everything the player put in on one frame, and how long the frame was
+#[derive(Copy, Clone, Default)]
+pub struct InputFrame {
+    // real time, bullet time slows the world down from it the same way it did live
+    pub nanos: u32,
+    pub held: u16,
+    pub just_pressed: u16,
+    pub looking_at: f32,
+    pub aim: Vec2,
+    // the perk card picked on this frame counting from 1, 0 when there wasn't one
+    pub perk: u8,
+}
+
+// a run's seed, loadout and inputs, running them through the game again plays the whole run
+// out again
+#[derive(Clone, Default)]
+pub struct Replay {
+    pub seed: u64,
+    pub unlocks: Vec<Unlock>,
+    pub character: usize,
+    pub frames: Vec<InputFrame>,
+}
+
+impl Replay {
+    // the real time the frames add up to, never less than the game time the run lasted
+    pub fn duration(&self) -> f32 {
+        self.frames
+            .iter()
+            .map(|frame| Duration::from_nanos(frame.nanos.into()).as_secs_f32())
+            .sum()
+    }
+}
+
+#[derive(Default)]
+pub struct ReplayRecorder {
+    pub replay: Replay,
+    // the score at every whole second, so a ghost's score can tick along with it
+    pub scores: Vec<i64>,
+    pub elapsed: f32,
+}
+
+impl ReplayRecorder {
+    pub fn new(seed: u64, save: &SaveData, settings: &Settings) -> Self {
+        ReplayRecorder {
+            replay: Replay {
+                seed,
+                unlocks: save.unlocks.clone(),
+                character: settings.character,
+                frames: Vec::new(),
+            },
+            ..default()
+        }
+    }
+}
+
+pub fn record_replay(
+    mut recorder: ResMut<ReplayRecorder>,
+    players: Query<&Player>,
+    actions: Res<Actions>,
+    aim: Res<AimPoint>,
+    mut perks: EventReader<PerkChosen>,
+    score: Res<Score>,
+    clock: Res<GameClock>,
+) {
+    while recorder.scores.len() as f32 <= recorder.elapsed {
+        recorder.scores.push(score.score);
+    }
+    let perk = perks.iter().last().map_or(0, |chosen| chosen.0 + 1);
+    let nanos = clock.unscaled_delta().as_nanos().min(u32::MAX.into()) as u32;
+    // nothing moves on a paused frame, so there's nothing to play back. the frame a perk is
+    // picked on is still paused, but the ghost has to pick it too
+    if nanos == 0 && perk == 0 {
+        return;
+    }
+    recorder.elapsed += clock.delta_seconds();
+    let (held, just_pressed) = actions.bits();
+    let looking_at = players
+        .iter()
+        .next()
+        .map_or(0.0, |player| player.looking_at);
+    recorder.replay.frames.push(InputFrame {
+        nanos,
+        held,
+        just_pressed,
+        looking_at,
+        aim: aim.0,
+        perk,
+    });
+}
+
+// input barely changes from one frame to the next, so deflate squeezes it well before base64
+// makes it fit in a json body
+pub fn encode_replay(replay: &Replay) -> String {
+    let mut raw = Vec::with_capacity(HEADER_BYTES + replay.frames.len() * FRAME_BYTES);
+    raw.push(REPLAY_FORMAT);
+    raw.extend_from_slice(&replay.seed.to_le_bytes());
+    raw.push(
+        replay
+            .unlocks
+            .iter()
+            .fold(0, |bits, unlock| bits | (1 << *unlock as u8)),
+    );
+    raw.push(replay.character.min(u8::MAX.into()) as u8);
+    replay.frames.iter().for_each(|frame| {
+        raw.extend_from_slice(&frame.nanos.to_le_bytes());
+        raw.extend_from_slice(&frame.held.to_le_bytes());
+        raw.extend_from_slice(&frame.just_pressed.to_le_bytes());
+        raw.extend_from_slice(&frame.looking_at.to_le_bytes());
+        raw.extend_from_slice(&frame.aim.x.to_le_bytes());
+        raw.extend_from_slice(&frame.aim.y.to_le_bytes());
+        raw.push(frame.perk);
+    });
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    if encoder.write_all(&raw).is_err() {
+        return String::new();
+    }
+    encoder.finish().map(base64::encode).unwrap_or_default()
+}
+
+// replays from an older format can't be played back, those come back as none
+pub fn decode_replay(encoded: &str) -> Option<Replay> {
+    let compressed = base64::decode(encoded).ok()?;
+    let mut raw = Vec::new();
+    DeflateDecoder::new(&compressed[..])
+        .read_to_end(&mut raw)
+        .ok()?;
+    if raw.len() < HEADER_BYTES || raw[0] != REPLAY_FORMAT {
+        return None;
+    }
+    let (header, body) = raw.split_at(HEADER_BYTES);
+    if body.len() % FRAME_BYTES != 0 {
+        return None;
+    }
+    let seed = u64::from_le_bytes(header[1..9].try_into().ok()?);
+    let unlocks = ALL_UNLOCKS
+        .iter()
+        .filter(|unlock| header[9] & (1 << **unlock as u8) != 0)
+        .copied()
+        .collect();
+    let f32_at = |chunk: &[u8], at: usize| {
+        f32::from_le_bytes([chunk[at], chunk[at + 1], chunk[at + 2], chunk[at + 3]])
+    };
+    let frames = body
+        .chunks(FRAME_BYTES)
+        .map(|chunk| InputFrame {
+            nanos: u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+            held: u16::from_le_bytes([chunk[4], chunk[5]]),
+            just_pressed: u16::from_le_bytes([chunk[6], chunk[7]]),
+            looking_at: f32_at(chunk, 8),
+            aim: Vec2::new(f32_at(chunk, 12), f32_at(chunk, 16)),
+            perk: chunk[20],
+        })
+        .collect();
+    Some(Replay {
+        seed,
+        unlocks,
+        character: header[10].into(),
+        frames,
+    })
+}
+
+// the raced run played again from its seed and inputs in a world of its own, so its enemies,
+// bullets and death happen the way they did the first time. the overlay only shows where that
+// world's player is
+#[derive(Component)]
+pub struct Ghost {
+    world: World,
+    schedule: Schedule,
+    frames: Vec<InputFrame>,
+    next: usize,
+    start: Instant,
+    played: Duration,
+}
+
+// a ghost waiting on start_ghosts, which has the live config its world is copied from
+#[derive(Component)]
+pub struct PendingGhost {
+    replay: Replay,
+    run_config: RunConfig,
+}
+
+// the recorded frame the ghost's world is running
+#[derive(Default)]
+pub struct GhostInput(InputFrame);
+
+// what a ghost's world can't load for itself, copied from the game it's raced in
+struct GhostConfig<'a> {
+    spawn_table: &'a SpawnTable,
+    materials: &'a PhysicsMaterials,
+    character: Option<&'a CharacterDef>,
+}
+
+impl Ghost {
+    fn new(replay: &Replay, run_config: &RunConfig, config: GhostConfig) -> Self {
+        let mut app = simulation_app(run_config.replaying(replay.seed));
+        app.insert_resource(config.spawn_table.clone())
+            .insert_resource(config.materials.clone())
+            .insert_resource(SaveData {
+                unlocks: replay.unlocks.clone(),
+                ..default()
+            })
+            .init_resource::<GhostInput>()
+            .add_system_to_stage(CoreStage::PreUpdate, feed_ghost_input.after(read_actions));
+        // the only character the ghost's world knows is the one the run was played with
+        if let Some(character) = config.character {
+            let handle = app
+                .world
+                .resource_mut::<Assets<CharacterDef>>()
+                .add(character.clone());
+            app.world.resource_mut::<GameAssets>().characters = vec![handle];
+        }
+        app.world.send_event(ResetGame);
+        let start = Instant::now();
+        // the reset frame, recording starts on the frame after it
+        app.world.resource_mut::<Time>().update_with_instant(start);
+        app.update();
+        Ghost {
+            world: std::mem::take(&mut app.world),
+            schedule: std::mem::take(&mut app.schedule),
+            frames: replay.frames.clone(),
+            next: 0,
+            start,
+            played: Duration::ZERO,
+        }
+    }
+
+    // runs the next recorded frame, false once there are none left
+    fn step(&mut self) -> bool {
+        let frame = match self.frames.get(self.next) {
+            Some(frame) => *frame,
+            None => return false,
+        };
+        self.next += 1;
+        self.played += Duration::from_nanos(frame.nanos.into());
+        self.world.resource_mut::<GhostInput>().0 = frame;
+        let now = self.start + self.played;
+        self.world.resource_mut::<Time>().update_with_instant(now);
+        self.schedule.run(&mut self.world);
+        true
+    }
+
+    // none once the ghost's run has ended in a death
+    fn location(&mut self) -> Option<Vec3> {
+        self.world
+            .query::<&Player>()
+            .iter(&self.world)
+            .next()
+            .map(|player| player.location)
+    }
+}
+
+// only added to a ghost's world, where nobody is at the keys. the aim and held item are put
+// where the live game's presentation systems left them for the frame
+pub fn feed_ghost_input(
+    input: Res<GhostInput>,
+    mut actions: ResMut<Actions>,
+    mut aim: ResMut<AimPoint>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut players: Query<&mut Player>,
+    mut held_items: Query<&mut Transform, With<HeldItem>>,
+) {
+    let frame = input.0;
+    actions.set_bits(frame.held, frame.just_pressed);
+    aim.0 = frame.aim;
+    if let Some(key) = PERK_KEYS.get(usize::from(frame.perk).wrapping_sub(1)) {
+        keys.reset(*key);
+        keys.press(*key);
+    }
+    players.iter_mut().for_each(|mut player| {
+        player.looking_at = frame.looking_at;
+        let distance = (frame.aim - player.location.truncate()).length();
+        held_items.iter_mut().for_each(|mut held_item| {
+            aim_held_item(&mut held_item, &player, frame.looking_at, distance);
+        });
+    });
+}
+
+pub fn spawn_ghost(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    replay: &Replay,
+    run_config: &RunConfig,
+    name: &str,
+) {
+    let character_sprite: Handle<Image> = assets.character.clone();
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+                custom_size: Some(Vec2::new(12.0, 28.0)),
+                ..default()
+            },
+            texture: character_sprite,
+            transform: Transform::from_translation(
+                // just behind the player it's racing
+                RenderLayer::Player.at(Vec2::new(0., -92.)) - Vec3::Z,
+            ),
+            ..default()
+        })
+        .insert(PendingGhost {
+            replay: replay.clone(),
+            run_config: run_config.replaying(replay.seed),
+        })
+        .insert(Name::new(format!("Ghost of {}", name)))
+        .insert(RunEntity);
+}
+
+pub fn start_ghosts(
+    mut commands: Commands,
+    pending: Query<(Entity, &PendingGhost)>,
+    spawn_table: Res<SpawnTable>,
+    materials: Res<PhysicsMaterials>,
+    assets: Res<GameAssets>,
+    characters: Res<Assets<CharacterDef>>,
+) {
+    pending.iter().for_each(|(entity, pending)| {
+        let character = assets
+            .characters
+            .get(pending.replay.character)
+            .and_then(|handle| characters.get(handle));
+        let ghost = Ghost::new(
+            &pending.replay,
+            &pending.run_config,
+            GhostConfig {
+                spawn_table: &spawn_table,
+                materials: &materials,
+                character,
+            },
+        );
+        commands
+            .entity(entity)
+            .remove::<PendingGhost>()
+            .insert(ghost);
+    });
+}
+
+// keeps each ghost level with the player's run, frame for frame. one that lands mid run starts
+// from the top and runs quicker until it's caught up
+pub fn play_ghosts(
+    mut commands: Commands,
+    mut ghosts: Query<(Entity, &mut Ghost, &mut Transform)>,
+    recorder: Res<ReplayRecorder>,
+) {
+    let recorded = recorder.replay.frames.len();
+    ghosts
+        .iter_mut()
+        .for_each(|(entity, mut ghost, mut trans)| {
+            let mut steps = 0;
+            while ghost.next < recorded && steps < MAX_CATCH_UP_FRAMES {
+                if !ghost.step() {
+                    commands.entity(entity).despawn_recursive();
+                    return;
+                }
+                steps += 1;
+            }
+            match ghost.location() {
+                Some(position) => {
+                    trans.translation.x = position.x;
+                    trans.translation.y = position.y;
+                }
+                None => commands.entity(entity).despawn_recursive(),
+            }
+        });
+}
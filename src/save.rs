@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::achievements::Achievement;
+use crate::profiles::ProfileId;
+use crate::shop::Unlock;
+
+pub const SAVE_FILE: &str = "save.json";
+// everything written before files carried a version, the data sat at the top level
+const VERSION_KEY: &str = "version";
+const DATA_KEY: &str = "data";
+
+// rewrites a file's data from one version to the next, entry n in a list takes version n to n + 1
+pub type Migration = fn(Value) -> Value;
+
+// the first versioned files hold exactly what the bare ones did
+pub fn wrap_unversioned(data: Value) -> Value {
+    data
+}
+
+// one best used to cover every board, there's no telling which one it was set on so it's kept
+// as the standard board's. without a replay it stays local and is never uploaded
+fn split_best_score(mut data: Value) -> Value {
+    if let Value::Object(map) = &mut data {
+        if let Some(score) = map.remove("best_score") {
+            map.insert(
+                String::from("bests"),
+                json!({ "standard": { "score": score } }),
+            );
+        }
+    }
+    data
+}
+
+// add a step here whenever a field is renamed or changes meaning, never edit an old one
+const SAVE_MIGRATIONS: &[Migration] = &[wrap_unversioned, split_best_score];
+
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    version: usize,
+    data: &'a T,
+}
+
+pub fn backup_path(path: &str) -> String {
+    format!("{}.bak", path)
+}
+
+fn read_file<T: DeserializeOwned>(path: &str, migrations: &[Migration]) -> Result<T, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let value: Value = serde_json::from_str(&contents).map_err(|err| err.to_string())?;
+    let (version, data) = match value {
+        Value::Object(mut map) if map.contains_key(DATA_KEY) => {
+            let version = map.get(VERSION_KEY).and_then(Value::as_u64).unwrap_or(0) as usize;
+            (version, map.remove(DATA_KEY).unwrap_or_default())
+        }
+        bare => (0, bare),
+    };
+    if version > migrations.len() {
+        // an older build reading a newer file keeps whatever fields it still knows
+        bevy::log::warn!("{} was written by a newer version ({})", path, version);
+    }
+    let data = migrations
+        .iter()
+        .skip(version)
+        .fold(data, |data, migrate| migrate(data));
+    serde_json::from_value(data).map_err(|err| err.to_string())
+}
+
+// falls back to the backup and then to defaults, a broken file is moved aside rather than
+// overwritten so it can still be recovered by hand
+pub fn load_file<T: DeserializeOwned + Default>(path: &str, migrations: &[Migration]) -> T {
+    let err = match read_file(path, migrations) {
+        Ok(data) => return data,
+        Err(err) => err,
+    };
+    if Path::new(path).exists() {
+        bevy::log::warn!("could not read {}, trying the backup: {}", path, err);
+        if let Err(err) = fs::rename(path, format!("{}.corrupt", path)) {
+            bevy::log::warn!("could not move {} aside: {}", path, err);
+        }
+    }
+    // also covers a crash between the two renames in save_file
+    read_file(&backup_path(path), migrations).unwrap_or_default()
+}
+
+// the new file is written next to the old one and swapped in, the old one becomes the backup
+pub fn save_file<T: Serialize>(path: &str, migrations: &[Migration], data: &T) {
+    let versioned = Versioned {
+        version: migrations.len(),
+        data,
+    };
+    let temp = format!("{}.tmp", path);
+    // a new profile's folder doesn't exist until its first save
+    let folder = Path::new(path)
+        .parent()
+        .filter(|folder| !folder.as_os_str().is_empty());
+    let result = folder
+        .map_or(Ok(()), fs::create_dir_all)
+        .map_err(|err| err.to_string())
+        .and_then(|_| serde_json::to_string_pretty(&versioned).map_err(|err| err.to_string()))
+        .and_then(|json| fs::write(&temp, json).map_err(|err| err.to_string()))
+        .and_then(|_| {
+            if Path::new(path).exists() {
+                fs::rename(path, backup_path(path)).map_err(|err| err.to_string())?;
+            }
+            fs::rename(&temp, path).map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        bevy::log::warn!("could not write {}: {}", path, err);
+    }
+}
+
+// a finished run, kept whole so it goes through the same checks as a fresh submission
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordedRun {
+    pub score: i64,
+    pub survived: f32,
+    pub kills: usize,
+    // empty for a best synced down from the service, there's nothing to send back
+    pub replay: String,
+    pub score_track: Vec<i64>,
+}
+
+// progress that outlives a run, settings live in their own file
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveData {
+    pub achievements: Vec<Achievement>,
+    pub jumpers_killed: u32,
+    pub coins: u32,
+    pub unlocks: Vec<Unlock>,
+    // the best run on each leaderboard board, the announcer calls it out when it's beaten
+    pub bests: BTreeMap<String, RecordedRun>,
+    // new game plus has been earned and shows on the main menu
+    pub new_game_plus: bool,
+    // whose progress this is, it decides where the file is written
+    #[serde(skip)]
+    pub profile: ProfileId,
+}
+
+impl SaveData {
+    pub fn load(profile: ProfileId) -> Self {
+        SaveData {
+            profile,
+            ..load_file(&profile.path(SAVE_FILE), SAVE_MIGRATIONS)
+        }
+    }
+
+    pub fn save(&self) {
+        save_file(&self.profile.path(SAVE_FILE), SAVE_MIGRATIONS, self);
+    }
+
+    pub fn best_score(&self, bucket: &str) -> i64 {
+        self.bests.get(bucket).map_or(0, |best| best.score)
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::GravityData;
+
+// a slow frame runs at most this many catch-up steps instead of spiralling
+const MAX_STEPS_PER_FRAME: u32 = 5;
+
+// the order gameplay runs in every frame, so a hit is always resolved before the score and
+// sprites look at it
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameplaySet {
+    Input,
+    Simulation,
+    CollisionResponse,
+    Presentation,
+}
+
+// runs after update at the physics rate, for anything that moves or spawns per tick
+#[derive(StageLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FixedGameplayStage;
+
+// game time banked for the fixed stage, follows the clock so it pauses and slows with it
+pub struct FixedStep {
+    step: Duration,
+    accumulator: Duration,
+    steps_this_frame: u32,
+    looping: bool,
+}
+
+impl Default for FixedStep {
+    fn default() -> Self {
+        FixedStep {
+            step: Duration::from_secs_f32(1.0 / 30.0),
+            accumulator: Duration::ZERO,
+            steps_this_frame: 0,
+            looping: false,
+        }
+    }
+}
+
+impl FixedStep {
+    pub fn delta(&self) -> Duration {
+        self.step
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+}
+
+pub struct SchedulePlugin;
+
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FixedStep>()
+            .add_stage_after(
+                CoreStage::Update,
+                FixedGameplayStage,
+                SystemStage::parallel().with_run_criteria(run_fixed_step),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, sync_fixed_step);
+    }
+}
+
+// keeps the step in line with the physics rate
+pub fn sync_fixed_step(gravity: Res<GravityData>, mut fixed: ResMut<FixedStep>) {
+    let step = Duration::from_secs_f32(1.0 / gravity.phys_step);
+    if fixed.step != step {
+        fixed.step = step;
+    }
+}
+
+pub fn run_fixed_step(mut fixed: ResMut<FixedStep>, clock: Res<GameClock>) -> ShouldRun {
+    // the first check each frame banks the frame's time, the rest just drain it
+    if !fixed.looping {
+        fixed.accumulator += clock.delta();
+        fixed.steps_this_frame = 0;
+    }
+    if fixed.accumulator >= fixed.step && fixed.steps_this_frame < MAX_STEPS_PER_FRAME {
+        let step = fixed.step;
+        fixed.accumulator -= step;
+        fixed.steps_this_frame += 1;
+        fixed.looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        if fixed.steps_this_frame == MAX_STEPS_PER_FRAME {
+            fixed.accumulator = Duration::ZERO;
+        }
+        fixed.looping = false;
+        ShouldRun::No
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::fonts::TextRole;
+use crate::loading::GameAssets;
+use crate::modes::RunConfig;
+use crate::transition::Transition;
+use crate::{GameState, ResetGame};
+
+// the clock on a timed run, none in modes that go on until the player dies
+#[derive(Default)]
+pub struct TimeLimit {
+    timer: Option<Timer>,
+}
+
+#[derive(Component)]
+pub struct TimeLimitLabel;
+
+pub struct ScoreAttackPlugin;
+
+impl Plugin for ScoreAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeLimit>()
+            .add_system(reset_time_limit)
+            .add_system(create_time_limit_label)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(tick_time_limit.after(reset_time_limit)),
+            )
+            .add_system(update_time_limit_label.after(tick_time_limit));
+    }
+}
+
+pub fn reset_time_limit(
+    mut resets: EventReader<ResetGame>,
+    run_config: Res<RunConfig>,
+    mut limit: ResMut<TimeLimit>,
+) {
+    if resets.iter().count() == 0 {
+        return;
+    }
+    limit.timer = run_config
+        .mode
+        .time_limit()
+        .map(|seconds| Timer::from_seconds(seconds, false));
+}
+
+// when the clock runs out the run is over, same as dying, and the score goes up on its own
+pub fn tick_time_limit(
+    clock: Res<GameClock>,
+    mut limit: ResMut<TimeLimit>,
+    mut transition: ResMut<Transition>,
+) {
+    if let Some(timer) = &mut limit.timer {
+        timer.tick(clock.delta());
+        if timer.just_finished() {
+            transition.fade_to(GameState::GameOver);
+        }
+    }
+}
+
+pub fn create_time_limit_label(
+    mut commands: Commands,
+    assets: Option<Res<GameAssets>>,
+    labels: Query<(), With<TimeLimitLabel>>,
+) {
+    if !labels.is_empty() {
+        return;
+    }
+    let font_handle = match assets {
+        Some(assets) => assets.font.clone(),
+        None => return,
+    };
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: font_handle,
+                    font_size: 30.,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(TimeLimitLabel)
+        .insert(TextRole::Label)
+        .insert(Name::new("Time Limit Label"));
+}
+
+pub fn update_time_limit_label(
+    limit: Res<TimeLimit>,
+    state: Res<State<GameState>>,
+    mut labels: Query<(&mut Text, &mut Visibility), With<TimeLimitLabel>>,
+) {
+    let timer = match (&limit.timer, state.current()) {
+        (Some(timer), GameState::Playing) => Some(timer),
+        _ => None,
+    };
+    labels.iter_mut().for_each(|(mut text, mut visibility)| {
+        if visibility.is_visible != timer.is_some() {
+            visibility.is_visible = timer.is_some();
+        }
+        let timer = match timer {
+            Some(timer) => timer,
+            None => return,
+        };
+        let remaining = (timer.duration() - timer.elapsed()).as_secs_f32().ceil() as u32;
+        let value = format!("{}:{:02}", remaining / 60, remaining % 60);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+        // the last ten seconds go red
+        let color = if remaining <= 10 {
+            Color::rgb(0.95, 0.35, 0.3)
+        } else {
+            Color::WHITE
+        };
+        if text.sections[0].style.color != color {
+            text.sections[0].style.color = color;
+        }
+    });
+}
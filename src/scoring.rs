@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::combat::{DeathEvent, PickupEvent, PickupKind};
+use crate::director::WaveCleared;
+use crate::loading::GameAssets;
+use crate::modes::RunConfig;
+use crate::settings::Settings;
+use crate::{DifficultyTimer, GameState, ResetGame, Score};
+
+// every this many seconds alive pays out the current difficulty
+pub const SURVIVAL_TICK: f32 = 10.0;
+// outlasting a wave's peak is worth this many times the difficulty
+pub const WAVE_CLEAR_BONUS: i64 = 10;
+// how long the hud keeps showing where the last points came from
+const LAST_GAIN_SECONDS: f32 = 1.5;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ScoreSource {
+    Kill,
+    Pickup,
+    WaveClear,
+    Survival,
+    Style,
+}
+
+pub const SCORE_SOURCES: [ScoreSource; 5] = [
+    ScoreSource::Kill,
+    ScoreSource::Pickup,
+    ScoreSource::WaveClear,
+    ScoreSource::Survival,
+    ScoreSource::Style,
+];
+
+impl ScoreSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            ScoreSource::Kill => "Kills",
+            ScoreSource::Pickup => "Pickups",
+            ScoreSource::WaveClear => "Wave clears",
+            ScoreSource::Survival => "Survival",
+            ScoreSource::Style => "Style",
+        }
+    }
+}
+
+// the only way points reach the score, so the breakdown always adds up to the total
+pub struct ScoreEvent {
+    pub source: ScoreSource,
+    pub points: i64,
+}
+
+// this run's points split by where they came from
+#[derive(Default)]
+pub struct ScoreBreakdown {
+    points: [i64; SCORE_SOURCES.len()],
+    last: Option<(ScoreSource, i64)>,
+}
+
+impl ScoreBreakdown {
+    pub fn points(&self, source: ScoreSource) -> i64 {
+        self.points[source as usize]
+    }
+}
+
+pub struct SurvivalTicks(Timer);
+
+impl Default for SurvivalTicks {
+    fn default() -> Self {
+        SurvivalTicks(Timer::from_seconds(SURVIVAL_TICK, true))
+    }
+}
+
+#[derive(Component)]
+pub struct LastGainLabel;
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ScoreEvent>()
+            .init_resource::<ScoreBreakdown>()
+            .init_resource::<SurvivalTicks>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(score_kills)
+                    .with_system(score_pickups)
+                    .with_system(score_wave_clears)
+                    .with_system(score_survival)
+                    .with_system(
+                        apply_score_events
+                            .after(score_kills)
+                            .after(score_pickups)
+                            .after(score_wave_clears)
+                            .after(score_survival),
+                    ),
+            )
+            .add_system(create_last_gain_label)
+            .add_system(update_last_gain_label)
+            .add_system(reset_scoring);
+    }
+}
+
+pub fn score_kills(
+    mut deaths: EventReader<DeathEvent>,
+    difficulty: Res<DifficultyTimer>,
+    mut scores: EventWriter<ScoreEvent>,
+) {
+    deaths.iter().filter_map(DeathEvent::enemy).for_each(|_| {
+        scores.send(ScoreEvent {
+            source: ScoreSource::Kill,
+            points: difficulty.difficulty,
+        });
+    });
+}
+
+pub fn score_pickups(
+    mut pickups: EventReader<PickupEvent>,
+    difficulty: Res<DifficultyTimer>,
+    mut scores: EventWriter<ScoreEvent>,
+) {
+    pickups.iter().for_each(|pickup| {
+        let points = match pickup.kind {
+            PickupKind::Weapon(_) => 2 * difficulty.difficulty,
+            PickupKind::ScoreGem(value) => value,
+            PickupKind::Shield => return,
+        };
+        scores.send(ScoreEvent {
+            source: ScoreSource::Pickup,
+            points,
+        });
+    });
+}
+
+pub fn score_wave_clears(
+    mut cleared: EventReader<WaveCleared>,
+    difficulty: Res<DifficultyTimer>,
+    mut scores: EventWriter<ScoreEvent>,
+) {
+    cleared.iter().for_each(|_| {
+        scores.send(ScoreEvent {
+            source: ScoreSource::WaveClear,
+            points: WAVE_CLEAR_BONUS * difficulty.difficulty,
+        });
+    });
+}
+
+pub fn score_survival(
+    mut ticks: ResMut<SurvivalTicks>,
+    clock: Res<GameClock>,
+    difficulty: Res<DifficultyTimer>,
+    mut scores: EventWriter<ScoreEvent>,
+) {
+    ticks.0.tick(clock.delta());
+    (0..ticks.0.times_finished()).for_each(|_| {
+        scores.send(ScoreEvent {
+            source: ScoreSource::Survival,
+            points: difficulty.difficulty,
+        });
+    });
+}
+
+pub fn apply_score_events(
+    mut events: EventReader<ScoreEvent>,
+    mut score: ResMut<Score>,
+    mut breakdown: ResMut<ScoreBreakdown>,
+    run_config: Res<RunConfig>,
+) {
+    let multiplier = run_config.score_multiplier();
+    events.iter().for_each(|event| {
+        let points = event.points * multiplier;
+        score.score += points;
+        breakdown.points[event.source as usize] += points;
+        breakdown.last = Some((event.source, points));
+    });
+}
+
+pub fn reset_scoring(
+    mut breakdown: ResMut<ScoreBreakdown>,
+    mut ticks: ResMut<SurvivalTicks>,
+    mut resets: EventReader<ResetGame>,
+) {
+    if resets.iter().count() > 0 {
+        *breakdown = ScoreBreakdown::default();
+        *ticks = SurvivalTicks::default();
+    }
+}
+
+// a line under the score naming the last gain, only built when the setting asks for it
+pub fn create_last_gain_label(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    assets: Option<Res<GameAssets>>,
+    labels: Query<(), With<LastGainLabel>>,
+) {
+    if !settings.score_sources || !labels.is_empty() {
+        return;
+    }
+    let font_handle = match assets {
+        Some(assets) => assets.font.clone(),
+        None => return,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Percent(10.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Name::new("Last Gain"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font_handle,
+                        font_size: 20.,
+                        color: Color::GOLD,
+                    },
+                ))
+                .insert(LastGainLabel);
+        });
+}
+
+pub fn update_last_gain_label(
+    breakdown: Res<ScoreBreakdown>,
+    settings: Res<Settings>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+    mut age: Local<f32>,
+    mut labels: Query<&mut Text, With<LastGainLabel>>,
+) {
+    *age = if breakdown.is_changed() {
+        0.0
+    } else {
+        *age + time.delta_seconds()
+    };
+    let shown = match breakdown.last {
+        Some((source, points))
+            if settings.score_sources
+                && *state.current() == GameState::Playing
+                && *age < LAST_GAIN_SECONDS =>
+        {
+            format!("+{} {}", points, source.name())
+        }
+        _ => String::new(),
+    };
+    labels.iter_mut().for_each(|mut text| {
+        if text.sections[0].value != shown {
+            text.sections[0].value = shown.clone();
+        }
+    });
+}
@@ -0,0 +1,290 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use bevy::asset::{AssetLoader, AssetPath, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use serde::Deserialize;
+
+use crate::clock::RealClock;
+use crate::loading::{AtlasRegistry, GameAssets};
+use crate::menu::despawn_screen;
+use crate::pause::{freeze_game, unfreeze_game};
+use crate::settings::Settings;
+use crate::transition::Transition;
+use crate::tween::ease_in_out;
+use crate::{spawn_enemy_warning, Behavior, GameState, MyCamera};
+
+// where a camera move ends up
+#[derive(Clone, Deserialize)]
+pub enum CameraTarget {
+    // wherever the camera sat when the sequence started
+    Home,
+    // the point the sequence was started with, like a boss that just landed
+    Focus,
+    Point { x: f32, y: f32 },
+}
+
+// one beat of a sequence, each runs for its own length before the next one starts
+#[derive(Clone, Deserialize)]
+pub enum Step {
+    // eases the camera over for the length of the step
+    MoveCamera(CameraTarget),
+    // puts a warning down, the enemy lands once play resumes
+    SpawnEnemy { behavior: Behavior, x: f32 },
+    // a title across the middle of the screen
+    ShowText(String),
+    // counts the step's length down in whole seconds
+    Countdown,
+    PlaySound(String),
+    Wait,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct TimedStep {
+    step: Step,
+    #[serde(default)]
+    seconds: f32,
+}
+
+// a timeline of steps, loaded from assets/sequences
+#[derive(Clone, Deserialize, TypeUuid)]
+#[uuid = "9b1e4c7a-6f2d-4a83-b5e0-3c8d1f7a2e64"]
+pub struct Sequence {
+    steps: Vec<TimedStep>,
+}
+
+#[derive(Default)]
+pub struct SequenceLoader;
+
+impl AssetLoader for SequenceLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let sequence: Sequence = serde_json::from_slice(bytes)?;
+            // sounds come in with the sequence so nothing hitches when it plays
+            let sounds: Vec<_> = sequence
+                .steps
+                .iter()
+                .filter_map(|timed| match &timed.step {
+                    Step::PlaySound(path) => Some(AssetPath::new(PathBuf::from(path), None)),
+                    _default => None,
+                })
+                .collect();
+            load_context.set_default_asset(LoadedAsset::new(sequence).with_dependencies(sounds));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sequence.json"]
+    }
+}
+
+// plays one sequence at a time with the run frozen underneath, whatever state it was started from
+#[derive(Default)]
+pub struct Sequencer {
+    steps: VecDeque<TimedStep>,
+    current: Option<(Step, Timer)>,
+    // where the camera was when the current step started
+    camera_from: Vec2,
+    // where the camera sits outside of a sequence, put back when it ends
+    home: Vec3,
+    focus: Vec2,
+    // faded to once the sequence ends, otherwise play picks up where it was
+    then: Option<GameState>,
+}
+
+impl Sequencer {
+    // false when the sequence hasn't loaded, the caller carries on without it
+    pub fn play(
+        &mut self,
+        sequences: &Assets<Sequence>,
+        sequence: &Handle<Sequence>,
+        focus: Vec2,
+        state: &mut State<GameState>,
+    ) -> bool {
+        let sequence = match sequences.get(sequence) {
+            Some(sequence) => sequence,
+            None => return false,
+        };
+        if state.push(GameState::Cutscene).is_err() {
+            return false;
+        }
+        self.steps = sequence.steps.iter().cloned().collect();
+        self.current = None;
+        self.focus = focus;
+        self.then = None;
+        true
+    }
+
+    pub fn then(&mut self, state: GameState) {
+        self.then = Some(state);
+    }
+}
+
+#[derive(Component)]
+pub struct SequenceScreen;
+
+#[derive(Component)]
+pub struct SequenceCaption;
+
+pub struct SequencePlugin;
+
+impl Plugin for SequencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Sequence>()
+            .init_asset_loader::<SequenceLoader>()
+            .init_resource::<Sequencer>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Cutscene)
+                    .with_system(start_sequence)
+                    .with_system(freeze_game),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Cutscene).with_system(run_sequence))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Cutscene)
+                    .with_system(despawn_screen::<SequenceScreen>)
+                    .with_system(return_camera)
+                    .with_system(unfreeze_game),
+            );
+    }
+}
+
+pub fn start_sequence(
+    mut commands: Commands,
+    mut sequencer: ResMut<Sequencer>,
+    cameras: Query<&Transform, With<MyCamera>>,
+    assets: Res<GameAssets>,
+) {
+    sequencer.home = cameras
+        .iter()
+        .next()
+        .map_or(Vec3::ZERO, |camera| camera.translation);
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(SequenceScreen)
+        .insert(Name::new("Sequence"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 72.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(SequenceCaption);
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_sequence(
+    mut commands: Commands,
+    mut sequencer: ResMut<Sequencer>,
+    mut state: ResMut<State<GameState>>,
+    mut transition: ResMut<Transition>,
+    mut cameras: Query<&mut Transform, With<MyCamera>>,
+    mut captions: Query<&mut Text, With<SequenceCaption>>,
+    atlases: Res<AtlasRegistry>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    clock: Res<RealClock>,
+) {
+    let mut camera = match cameras.iter_mut().next() {
+        Some(camera) => camera,
+        None => return,
+    };
+    if sequencer.current.is_none() {
+        let next = match sequencer.steps.pop_front() {
+            Some(next) => next,
+            None => {
+                let _ = state.pop();
+                if let Some(next) = sequencer.then.take() {
+                    transition.fade_to(next);
+                }
+                return;
+            }
+        };
+        // one off steps fire once as they start
+        match &next.step {
+            Step::SpawnEnemy { behavior, x } => {
+                spawn_enemy_warning(&mut commands, &atlases, *behavior, *x);
+            }
+            Step::PlaySound(path) => {
+                audio.play_with_settings(
+                    asset_server.load(path.as_str()),
+                    PlaybackSettings {
+                        repeat: false,
+                        volume: settings.sfx,
+                        speed: 1.0,
+                    },
+                );
+            }
+            _default => {}
+        }
+        sequencer.camera_from = camera.translation.truncate();
+        sequencer.current = Some((next.step, Timer::from_seconds(next.seconds, false)));
+    }
+    let (from, home, focus) = (
+        sequencer.camera_from,
+        sequencer.home.truncate(),
+        sequencer.focus,
+    );
+    let (step, timer) = match sequencer.current.as_mut() {
+        Some(current) => current,
+        None => return,
+    };
+    timer.tick(clock.delta());
+    let caption = match step {
+        Step::MoveCamera(target) => {
+            let to = match target {
+                CameraTarget::Home => home,
+                CameraTarget::Focus => focus,
+                CameraTarget::Point { x, y } => Vec2::new(*x, *y),
+            };
+            let moved = from.lerp(to, ease_in_out(timer.percent()));
+            camera.translation = moved.extend(camera.translation.z);
+            String::new()
+        }
+        Step::ShowText(text) => text.clone(),
+        Step::Countdown => {
+            let left = timer.duration().as_secs_f32() - timer.elapsed_secs();
+            format!("{}", left.ceil().max(1.0) as u32)
+        }
+        _default => String::new(),
+    };
+    captions.iter_mut().for_each(|mut text| {
+        text.sections[0].value = caption.clone();
+    });
+    if timer.finished() {
+        sequencer.current = None;
+    }
+}
+
+pub fn return_camera(
+    sequencer: Res<Sequencer>,
+    mut cameras: Query<&mut Transform, With<MyCamera>>,
+) {
+    cameras.iter_mut().for_each(|mut camera| {
+        camera.translation = sequencer.home;
+    });
+}
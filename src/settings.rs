@@ -0,0 +1,377 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::{PaletteChoice, WarningColor};
+use crate::display::WINDOW_SIZES;
+use crate::fonts::TextRole;
+use crate::loading::GameAssets;
+use crate::menu::{
+    despawn_screen, screen_root, spawn_button, spawn_small_button, spawn_title, MenuButton,
+};
+use crate::profiles::{ProfileId, Profiles};
+use crate::save::{load_file, save_file, wrap_unversioned, Migration};
+use crate::smoothing::physics_rate;
+use crate::GameState;
+
+pub const SETTINGS_FILE: &str = "settings.json";
+const SETTINGS_MIGRATIONS: &[Migration] = &[wrap_unversioned];
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub sfx: f32,
+    pub music: f32,
+    pub tutorial_seen: bool,
+    // index into the character list
+    pub character: usize,
+    // how hard gamepad aim is pulled toward enemies, 0 turns it off
+    pub aim_assist: f32,
+    // only draw the crosshair during a run, not the os cursor
+    pub hide_cursor: bool,
+    // voice lines for waves, multi kills and new high scores
+    pub announcer: bool,
+    // index into display::WINDOW_SIZES
+    pub window_size: usize,
+    // whole screen pixels per world unit, the view grows instead of stretching
+    pub pixel_perfect: bool,
+    // nearest neighbour sampling, with sprites and the camera held to whole screen pixels
+    pub crisp_pixels: bool,
+    // the score drawn from blocky digit sprites instead of the font
+    pub pixel_hud: bool,
+    // keeps the view at 16:9 with bars around it
+    pub letterbox: bool,
+    // borderless, covering the monitor the window is on
+    pub fullscreen: bool,
+    // where the window sat before going fullscreen
+    pub window_position: Option<(i32, i32)>,
+    // pushes the pause menu when the window loses focus during a run
+    pub pause_on_focus_loss: bool,
+    // names where the last points came from under the score
+    pub score_sources: bool,
+    pub palette: PaletteChoice,
+    pub warning_color: WarningColor,
+    // solid outlines behind enemies and pickups
+    pub outlines: bool,
+    // how far the camera kicks on hits and blasts, 0 turns it off
+    pub shake: f32,
+    // strength of the damage and explosion flashes, 0 turns them off
+    pub flash: f32,
+    // multiplies the size of all text and hud, for small laptops and big tvs alike
+    pub ui_scale: f32,
+    // fewer enemies, a slower climb and spare lives, scored on a separate board
+    pub assist: bool,
+    // the name last sent to the leaderboard, offered again on the next game over
+    pub player_name: Option<String>,
+    // names picked out on the leaderboard screen, highlighted wherever they rank
+    pub friends: Vec<String>,
+    // index into smoothing::PHYSICS_RATES
+    pub physics_rate: usize,
+    // draws physics bodies between steps instead of where the last step left them
+    pub smoothing: bool,
+    // whose settings these are, it decides where the file is written
+    #[serde(skip)]
+    pub profile: ProfileId,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            sfx: 0.5,
+            music: 0.1,
+            tutorial_seen: false,
+            character: 0,
+            aim_assist: 0.5,
+            hide_cursor: true,
+            announcer: true,
+            window_size: 1,
+            pixel_perfect: false,
+            crisp_pixels: true,
+            pixel_hud: false,
+            letterbox: false,
+            fullscreen: false,
+            window_position: None,
+            pause_on_focus_loss: true,
+            score_sources: false,
+            palette: PaletteChoice::Standard,
+            warning_color: WarningColor::Red,
+            outlines: false,
+            shake: 1.0,
+            flash: 1.0,
+            ui_scale: 1.0,
+            assist: false,
+            player_name: None,
+            friends: Vec::new(),
+            physics_rate: 1,
+            smoothing: true,
+            profile: ProfileId::default(),
+        }
+    }
+}
+
+impl Settings {
+    // a missing file just means first launch, or a profile that's never changed anything
+    pub fn load(profile: ProfileId) -> Self {
+        Settings {
+            profile,
+            ..load_file(&profile.path(SETTINGS_FILE), SETTINGS_MIGRATIONS)
+        }
+    }
+
+    fn save(&self) {
+        save_file(&self.profile.path(SETTINGS_FILE), SETTINGS_MIGRATIONS, self);
+    }
+}
+
+pub struct MusicController(pub Handle<AudioSink>);
+
+// share of the music setting that's let through, turned down while the window is in the
+// background. kept apart from the setting so ducking never ends up saved as the volume
+pub struct MusicDuck(pub f32);
+
+impl Default for MusicDuck {
+    fn default() -> Self {
+        MusicDuck(1.0)
+    }
+}
+
+#[derive(Component)]
+pub struct SettingsScreen;
+
+#[derive(Component)]
+pub enum SliderLabel {
+    Music,
+    Sfx,
+    AimAssist,
+    Cursor,
+    Announcer,
+    WindowSize,
+    PixelPerfect,
+    CrispPixels,
+    PixelHud,
+    Letterbox,
+    Fullscreen,
+    FocusPause,
+    ScoreSources,
+    Palette,
+    WarningColor,
+    Outlines,
+    Shake,
+    Flash,
+    UiScale,
+    Assist,
+    PhysicsRate,
+    Smoothing,
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // main puts the profile list in before any plugin, settings start as the last player's
+        let profile = app
+            .world
+            .get_resource::<Profiles>()
+            .map_or(ProfileId::default(), Profiles::active);
+        app.insert_resource(Settings::load(profile))
+            .init_resource::<MusicDuck>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Settings).with_system(create_settings_menu),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Settings)
+                    .with_system(despawn_screen::<SettingsScreen>),
+            )
+            .add_system(update_slider_labels)
+            .add_system(apply_music_volume)
+            .add_system(save_settings);
+    }
+}
+
+pub fn create_settings_menu(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.9)))
+        .insert(SettingsScreen)
+        .insert(Name::new("Settings Menu"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Settings");
+            spawn_slider_row(parent, &font_handle, SliderLabel::Music);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Sfx);
+            spawn_slider_row(parent, &font_handle, SliderLabel::AimAssist);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Cursor);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Announcer);
+            spawn_slider_row(parent, &font_handle, SliderLabel::FocusPause);
+            spawn_slider_row(parent, &font_handle, SliderLabel::ScoreSources);
+            spawn_button(parent, &font_handle, "Display", MenuButton::Display);
+            spawn_button(
+                parent,
+                &font_handle,
+                "Accessibility",
+                MenuButton::Accessibility,
+            );
+            spawn_button(parent, &font_handle, "Advanced", MenuButton::Advanced);
+            spawn_button(parent, &font_handle, "Back", MenuButton::Back);
+        });
+}
+
+pub fn spawn_slider_row(parent: &mut ChildBuilder, font: &Handle<Font>, label: SliderLabel) {
+    let (down, up) = match label {
+        SliderLabel::Music => (MenuButton::MusicDown, MenuButton::MusicUp),
+        SliderLabel::Sfx => (MenuButton::SfxDown, MenuButton::SfxUp),
+        SliderLabel::AimAssist => (MenuButton::AimAssistDown, MenuButton::AimAssistUp),
+        SliderLabel::Cursor => (MenuButton::ShowCursor, MenuButton::HideCursor),
+        SliderLabel::Announcer => (MenuButton::AnnouncerOff, MenuButton::AnnouncerOn),
+        SliderLabel::WindowSize => (MenuButton::WindowSmaller, MenuButton::WindowLarger),
+        SliderLabel::PixelPerfect => (MenuButton::PixelPerfectOff, MenuButton::PixelPerfectOn),
+        SliderLabel::CrispPixels => (MenuButton::CrispPixelsOff, MenuButton::CrispPixelsOn),
+        SliderLabel::PixelHud => (MenuButton::PixelHudOff, MenuButton::PixelHudOn),
+        SliderLabel::Letterbox => (MenuButton::LetterboxOff, MenuButton::LetterboxOn),
+        SliderLabel::Fullscreen => (MenuButton::FullscreenOff, MenuButton::FullscreenOn),
+        SliderLabel::FocusPause => (MenuButton::FocusPauseOff, MenuButton::FocusPauseOn),
+        SliderLabel::ScoreSources => (MenuButton::ScoreSourcesOff, MenuButton::ScoreSourcesOn),
+        SliderLabel::Palette => (MenuButton::PreviousPalette, MenuButton::NextPalette),
+        SliderLabel::WarningColor => (
+            MenuButton::PreviousWarningColor,
+            MenuButton::NextWarningColor,
+        ),
+        SliderLabel::Outlines => (MenuButton::OutlinesOff, MenuButton::OutlinesOn),
+        SliderLabel::Shake => (MenuButton::ShakeDown, MenuButton::ShakeUp),
+        SliderLabel::Flash => (MenuButton::FlashDown, MenuButton::FlashUp),
+        SliderLabel::UiScale => (MenuButton::UiScaleDown, MenuButton::UiScaleUp),
+        SliderLabel::Assist => (MenuButton::AssistOff, MenuButton::AssistOn),
+        SliderLabel::PhysicsRate => (MenuButton::PhysicsRateDown, MenuButton::PhysicsRateUp),
+        SliderLabel::Smoothing => (MenuButton::SmoothingOff, MenuButton::SmoothingOn),
+    };
+    parent
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_small_button(row, font, "-", down);
+            row.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.,
+                        color: Color::WHITE,
+                    },
+                ),
+                style: Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(label)
+            .insert(TextRole::Label);
+            spawn_small_button(row, font, "+", up);
+        });
+}
+
+pub fn update_slider_labels(
+    settings: Res<Settings>,
+    mut labels: Query<(&mut Text, &SliderLabel)>,
+    new_labels: Query<(), Added<SliderLabel>>,
+) {
+    if !settings.is_changed() && new_labels.is_empty() {
+        return;
+    }
+    labels.iter_mut().for_each(|(mut text, label)| {
+        text.sections[0].value = match label {
+            SliderLabel::Music => format!("Music {:>3.0}%", settings.music * 100.0),
+            SliderLabel::Sfx => format!("SFX {:>3.0}%", settings.sfx * 100.0),
+            SliderLabel::AimAssist => format!("Aim assist {:>3.0}%", settings.aim_assist * 100.0),
+            SliderLabel::Cursor => format!(
+                "Crosshair only {}",
+                if settings.hide_cursor { "on" } else { "off" }
+            ),
+            SliderLabel::Announcer => format!(
+                "Announcer {}",
+                if settings.announcer { "on" } else { "off" }
+            ),
+            SliderLabel::WindowSize => {
+                let (width, height) =
+                    WINDOW_SIZES[settings.window_size.min(WINDOW_SIZES.len() - 1)];
+                format!("Window {}x{}", width, height)
+            }
+            SliderLabel::PixelPerfect => format!(
+                "Pixel perfect {}",
+                if settings.pixel_perfect { "on" } else { "off" }
+            ),
+            SliderLabel::CrispPixels => format!(
+                "Crisp pixels {}",
+                if settings.crisp_pixels { "on" } else { "off" }
+            ),
+            SliderLabel::PixelHud => format!(
+                "Pixel score {}",
+                if settings.pixel_hud { "on" } else { "off" }
+            ),
+            SliderLabel::Letterbox => format!(
+                "Letterbox {}",
+                if settings.letterbox { "on" } else { "off" }
+            ),
+            SliderLabel::Fullscreen => format!(
+                "Fullscreen {}",
+                if settings.fullscreen { "on" } else { "off" }
+            ),
+            SliderLabel::FocusPause => format!(
+                "Pause when unfocused {}",
+                if settings.pause_on_focus_loss {
+                    "on"
+                } else {
+                    "off"
+                }
+            ),
+            SliderLabel::ScoreSources => format!(
+                "Score sources {}",
+                if settings.score_sources { "on" } else { "off" }
+            ),
+            SliderLabel::Palette => format!("Colors {}", settings.palette.name()),
+            SliderLabel::WarningColor => format!("Warnings {}", settings.warning_color.name()),
+            SliderLabel::Outlines => {
+                format!("Outlines {}", if settings.outlines { "on" } else { "off" })
+            }
+            SliderLabel::Shake => format!("Screen shake {:>3.0}%", settings.shake * 100.0),
+            SliderLabel::Flash => format!("Flashes {:>3.0}%", settings.flash * 100.0),
+            SliderLabel::UiScale => format!("UI scale {:>3.0}%", settings.ui_scale * 100.0),
+            SliderLabel::Assist => {
+                format!("Assist mode {}", if settings.assist { "on" } else { "off" })
+            }
+            SliderLabel::PhysicsRate => format!("Physics {:.0} steps/s", physics_rate(&settings)),
+            SliderLabel::Smoothing => {
+                format!(
+                    "Smoothing {}",
+                    if settings.smoothing { "on" } else { "off" }
+                )
+            }
+        };
+    });
+}
+
+pub fn apply_music_volume(
+    settings: Res<Settings>,
+    duck: Res<MusicDuck>,
+    music: Option<Res<MusicController>>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if !settings.is_changed() && !duck.is_changed() {
+        return;
+    }
+    if let Some(sink) = music.and_then(|music| audio_sinks.get(&music.0)) {
+        sink.set_volume(settings.music * duck.0);
+    }
+}
+
+pub fn save_settings(settings: Res<Settings>) {
+    // skip the first frame, that change is just the file we loaded
+    if settings.is_changed() && !settings.is_added() {
+        settings.save();
+    }
+}
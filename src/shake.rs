@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use libm::sinf;
+
+use crate::clock::RealClock;
+use crate::combat::DamageEvent;
+use crate::explosions::ExplosionEvent;
+use crate::schedule::GameplaySet;
+use crate::settings::Settings;
+use crate::{GameState, MyCamera, ResetGame};
+
+// world units at full trauma, before the settings slider scales it down
+const MAX_OFFSET: f32 = 6.0;
+// trauma lost per second
+const DECAY: f32 = 1.5;
+const HIT_TRAUMA: f32 = 0.5;
+const BLAST_TRAUMA: f32 = 0.4;
+
+// trauma stacks from hits and blasts and wears off, the offset grows with its square so small
+// knocks barely register
+#[derive(Default)]
+pub struct ScreenShake {
+    trauma: f32,
+    time: f32,
+    // what was added to the camera last frame, taken back off before the next offset goes on
+    applied: Vec2,
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
+pub struct ShakePlugin;
+
+impl Plugin for ShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(shake_on_impacts.after(GameplaySet::CollisionResponse))
+                    .with_system(shake_camera.label(GameplaySet::Presentation)),
+            )
+            .add_system(reset_shake);
+    }
+}
+
+pub fn shake_on_impacts(
+    mut shake: ResMut<ScreenShake>,
+    mut damage: EventReader<DamageEvent>,
+    mut explosions: EventReader<ExplosionEvent>,
+) {
+    if damage.iter().any(|hit| hit.hit_player()) {
+        shake.add_trauma(HIT_TRAUMA);
+    }
+    explosions
+        .iter()
+        .for_each(|_| shake.add_trauma(BLAST_TRAUMA));
+}
+
+pub fn shake_camera(
+    mut shake: ResMut<ScreenShake>,
+    mut cameras: Query<&mut Transform, With<MyCamera>>,
+    settings: Res<Settings>,
+    clock: Res<RealClock>,
+) {
+    shake.trauma = (shake.trauma - DECAY * clock.delta_seconds()).max(0.0);
+    shake.time += clock.delta_seconds();
+    // a couple of out of step waves per axis read as noise without needing a noise function
+    let t = shake.time;
+    let wobble = Vec2::new(
+        sinf(t * 47.0) + sinf(t * 29.0 + 1.3),
+        sinf(t * 53.0 + 0.7) + sinf(t * 31.0),
+    ) * 0.5;
+    let offset = wobble * MAX_OFFSET * shake.trauma * shake.trauma * settings.shake;
+    let applied = shake.applied;
+    cameras.iter_mut().for_each(|mut camera| {
+        camera.translation += (offset - applied).extend(0.0);
+    });
+    shake.applied = offset;
+}
+
+pub fn reset_shake(mut shake: ResMut<ScreenShake>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        shake.trauma = 0.0;
+    }
+}
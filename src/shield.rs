@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use rand::Rng;
+
+use crate::accessibility::Palette;
+use crate::clock::GameClock;
+use crate::collision::collision_layers;
+use crate::combat::{PickupEvent, PickupKind};
+use crate::depth::RenderLayer;
+use crate::loading::GameAssets;
+use crate::materials::{MaterialClass, PhysicsMaterials};
+use crate::modes::GameRng;
+use crate::particles::spawn_burst;
+use crate::pickups::Magnetic;
+use crate::settings::Settings;
+use crate::{GameState, Layers, Player, ResetGame, RunEntity};
+
+const SHIELD_COLOR: Color = Color::rgba(0.3, 0.7, 1.0, 0.35);
+const SHARD_COLOR: Color = Color::rgb(0.3, 0.7, 1.0);
+
+#[derive(Component)]
+pub struct Shield {
+    points: u8,
+    max: u8,
+    since_hit: Timer,
+    regen: Timer,
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Shield {
+            points: 2,
+            max: 2,
+            since_hit: Timer::from_seconds(4.0, false),
+            regen: Timer::from_seconds(1.5, true),
+        }
+    }
+}
+
+impl Shield {
+    // takes a hit if there's anything left, returns false when health should take it instead
+    pub fn absorb(&mut self) -> bool {
+        if self.points == 0 {
+            return false;
+        }
+        self.points -= 1;
+        self.since_hit.reset();
+        true
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.points == 0
+    }
+}
+
+#[derive(Component)]
+pub struct ShieldBubble;
+
+#[derive(Component)]
+pub struct ShieldPickup;
+
+pub struct ShieldSpawns {
+    timer: Timer,
+}
+
+impl Default for ShieldSpawns {
+    fn default() -> Self {
+        ShieldSpawns {
+            timer: Timer::from_seconds(20.0, true),
+        }
+    }
+}
+
+// sent with the player's position when the last shield point is knocked off
+pub struct ShieldBroken(pub Vec3);
+
+pub struct ShieldPlugin;
+
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShieldSpawns>()
+            .add_event::<ShieldBroken>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(spawn_shield_pickups)
+                    .with_system(collect_shield_pickups)
+                    .with_system(regen_shields)
+                    .with_system(update_shield_bubbles)
+                    .with_system(shatter_shields),
+            )
+            .add_system(reset_shield_spawns);
+    }
+}
+
+pub fn spawn_shield_pickups(
+    mut commands: Commands,
+    mut spawns: ResMut<ShieldSpawns>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<GameClock>,
+    palette: Res<Palette>,
+    materials: Res<PhysicsMaterials>,
+) {
+    spawns.timer.tick(clock.delta());
+    if !spawns.timer.finished() {
+        return;
+    }
+    let random_x = rng.gen_range(-200.0..200.0) as f32;
+    let size = Vec2::new(8.0, 8.0);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: palette.shield,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(
+                RenderLayer::Pickups.at(Vec2::new(random_x, 120.0)),
+            ),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(CollisionShape::Cuboid {
+            half_extends: size.extend(0.0) / 2.0,
+            border_radius: None,
+        })
+        .insert(materials.get(MaterialClass::Pickup))
+        .insert(collision_layers(Layers::Weapons))
+        .insert(Velocity::default())
+        .insert(Magnetic::default())
+        .insert(Collisions::default())
+        .insert(ShieldPickup)
+        .insert(Name::new("Shield Pickup"))
+        .insert(RunEntity);
+}
+
+pub fn collect_shield_pickups(
+    mut commands: Commands,
+    pickups: Query<(Entity, &Collisions, &Transform), With<ShieldPickup>>,
+    mut players: Query<(Entity, Option<&mut Shield>), With<Player>>,
+    mut picked_up: EventWriter<PickupEvent>,
+) {
+    pickups.iter().for_each(|(pickup, collisions, trans)| {
+        collisions.entities().for_each(|other| {
+            if let Ok((player, shield)) = players.get_mut(other) {
+                commands.entity(pickup).despawn_recursive();
+                picked_up.send(PickupEvent {
+                    kind: PickupKind::Shield,
+                    position: trans.translation,
+                });
+                match shield {
+                    Some(mut shield) => shield.points = shield.max,
+                    None => {
+                        commands
+                            .entity(player)
+                            .insert(Shield::default())
+                            .with_children(|parent| {
+                                parent
+                                    .spawn_bundle(SpriteBundle {
+                                        sprite: Sprite {
+                                            color: SHIELD_COLOR,
+                                            custom_size: Some(Vec2::new(22.0, 36.0)),
+                                            ..default()
+                                        },
+                                        transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                                        ..default()
+                                    })
+                                    .insert(ShieldBubble)
+                                    .insert(Name::new("Shield Bubble"));
+                            });
+                    }
+                }
+            }
+        });
+    });
+}
+
+pub fn regen_shields(mut shields: Query<&mut Shield>, clock: Res<GameClock>) {
+    shields.iter_mut().for_each(|mut shield| {
+        shield.since_hit.tick(clock.delta());
+        if !shield.since_hit.finished() || shield.points >= shield.max {
+            return;
+        }
+        shield.regen.tick(clock.delta());
+        if shield.regen.just_finished() {
+            shield.points += 1;
+        }
+    });
+}
+
+pub fn update_shield_bubbles(
+    shields: Query<&Shield>,
+    mut bubbles: Query<(&Parent, &mut Sprite), With<ShieldBubble>>,
+) {
+    bubbles.iter_mut().for_each(|(parent, mut sprite)| {
+        if let Ok(shield) = shields.get(parent.get()) {
+            let strength = shield.points as f32 / shield.max as f32;
+            sprite.color.set_a(SHIELD_COLOR.a() * strength);
+        }
+    });
+}
+
+pub fn shatter_shields(
+    mut commands: Commands,
+    mut broken: EventReader<ShieldBroken>,
+    mut rng: ResMut<GameRng>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+) {
+    broken.iter().for_each(|ShieldBroken(position)| {
+        spawn_burst(&mut commands, &mut rng, *position, SHARD_COLOR, 16, 60.0);
+        audio.play_with_settings(
+            assets.shield_break.clone(),
+            PlaybackSettings {
+                repeat: false,
+                volume: settings.sfx,
+                speed: 1.0,
+            },
+        );
+    });
+}
+
+pub fn reset_shield_spawns(mut spawns: ResMut<ShieldSpawns>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *spawns = ShieldSpawns::default();
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::collision::CollisionRules;
+use crate::loading::GameAssets;
+use crate::modes::GameRng;
+use crate::particles::spawn_burst;
+use crate::schedule::{FixedGameplayStage, FixedStep};
+use crate::settings::Settings;
+use crate::{Behavior, Enemy, GameState, Layers};
+
+// how far in front of the body the shield sits
+const SHIELD_OFFSET: f32 = 9.0;
+const TURN_SECONDS: f32 = 0.6;
+const SHIELD_COLOR: Color = Color::rgb(0.6, 0.65, 0.75);
+const CLINK_COLOR: Color = Color::rgb(1.0, 0.9, 0.5);
+
+// the shield is up and facing the enemy's direction
+#[derive(Component)]
+pub struct Guarding;
+
+// swinging around to face the player, the shield is down until it's done
+#[derive(Component)]
+pub struct Turning {
+    timer: Timer,
+}
+
+impl Default for Turning {
+    fn default() -> Self {
+        Turning {
+            timer: Timer::from_seconds(TURN_SECONDS, false),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct EnemyShield;
+
+pub struct ShieldedPlugin;
+
+impl Plugin for ShieldedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(equip_shields))
+            .add_system_set_to_stage(
+                FixedGameplayStage,
+                SystemSet::on_update(GameState::Playing).with_system(handle_turns),
+            );
+    }
+}
+
+// true when a bullet moving this way would hit the front of a guarding enemy
+pub fn blocks(enemy: &Enemy, heading: Vec3) -> bool {
+    heading.x * enemy.direction < 0.0
+}
+
+pub fn spawn_clink(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    position: Vec3,
+    audio: &Audio,
+    assets: &GameAssets,
+    settings: &Settings,
+) {
+    spawn_burst(commands, rng, position, CLINK_COLOR, 5, 50.0);
+    audio.play_with_settings(
+        assets.clink.clone(),
+        PlaybackSettings {
+            repeat: false,
+            volume: settings.sfx,
+            speed: 1.0,
+        },
+    );
+}
+
+pub fn equip_shields(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Enemy), Added<Enemy>>,
+    rules: Res<CollisionRules>,
+) {
+    enemies
+        .iter()
+        .filter(|(_, enemy)| enemy.asset == Behavior::Shielded)
+        .for_each(|(entity, enemy)| {
+            commands
+                .entity(entity)
+                .insert(Guarding)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                color: SHIELD_COLOR,
+                                custom_size: Some(Vec2::new(3.0, 16.0)),
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(
+                                SHIELD_OFFSET * enemy.direction,
+                                0.0,
+                                0.1,
+                            ),
+                            ..default()
+                        })
+                        .insert(CollisionShape::Cuboid {
+                            half_extends: Vec3::new(1.5, 8.0, 0.0),
+                            border_radius: None,
+                        })
+                        .insert(rules.layers(Layers::Enemies))
+                        .insert(EnemyShield)
+                        .insert(Name::new("Enemy Shield"));
+                });
+        });
+}
+
+pub fn handle_turns(
+    mut commands: Commands,
+    mut turners: Query<(Entity, &mut Turning, &mut Enemy, &Children)>,
+    mut shields: Query<(&mut Transform, &mut Sprite), With<EnemyShield>>,
+    step: Res<FixedStep>,
+) {
+    turners
+        .iter_mut()
+        .for_each(|(entity, mut turning, mut enemy, children)| {
+            turning.timer.tick(step.delta());
+            let done = turning.timer.finished();
+            if done {
+                enemy.direction = -enemy.direction;
+                commands.entity(entity).remove::<Turning>().insert(Guarding);
+            }
+            children.iter().for_each(|child| {
+                if let Ok((mut trans, mut sprite)) = shields.get_mut(*child) {
+                    // the shield swings across the body as it turns
+                    let swing = if done {
+                        1.0
+                    } else {
+                        1.0 - 2.0 * turning.timer.percent()
+                    };
+                    trans.translation.x = SHIELD_OFFSET * enemy.direction * swing;
+                    sprite.color.set_a(if done { 1.0 } else { 0.4 });
+                }
+            });
+        });
+}
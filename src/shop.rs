@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::loading::GameAssets;
+use crate::menu::{
+    despawn_screen, screen_root, spawn_button, spawn_labeled_button, spawn_title, MenuButton,
+    BUTTON_COLOR, HOVERED_COLOR,
+};
+use crate::modes::RunConfig;
+use crate::save::SaveData;
+use crate::{Behavior, GameState, Score, Weapons, PLAYER_MAX_HEALTH};
+
+const SCORE_PER_COIN: i64 = 100;
+const DASH_SPEED: f32 = 250.0;
+const LOCKED_COLOR: Color = Color::rgb(0.4, 0.4, 0.4);
+
+#[derive(Component, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unlock {
+    Shotgun,
+    Sniper,
+    Rocket,
+    ExtraLife,
+    LongDash,
+    HomingRockets,
+    Laser,
+    Grenade,
+}
+
+pub const ALL_UNLOCKS: [Unlock; 8] = [
+    Unlock::Shotgun,
+    Unlock::Sniper,
+    Unlock::Rocket,
+    Unlock::Laser,
+    Unlock::Grenade,
+    Unlock::HomingRockets,
+    Unlock::ExtraLife,
+    Unlock::LongDash,
+];
+
+fn unlock_name(unlock: Unlock) -> &'static str {
+    match unlock {
+        Unlock::Shotgun => "Shotgun drops",
+        Unlock::Sniper => "Sniper drops",
+        Unlock::Rocket => "Rocket drops",
+        Unlock::ExtraLife => "Extra life",
+        Unlock::LongDash => "Long dash",
+        Unlock::HomingRockets => "Homing rockets",
+        Unlock::Laser => "Laser drops",
+        Unlock::Grenade => "Grenade drops",
+    }
+}
+
+fn unlock_cost(unlock: Unlock) -> u32 {
+    match unlock {
+        Unlock::Shotgun => 20,
+        Unlock::Sniper => 40,
+        Unlock::Rocket => 80,
+        Unlock::ExtraLife => 60,
+        Unlock::LongDash => 30,
+        Unlock::HomingRockets => 120,
+        Unlock::Laser => 100,
+        Unlock::Grenade => 50,
+    }
+}
+
+// upgrades that do nothing until something else is owned
+fn unlock_requires(unlock: Unlock) -> Option<Unlock> {
+    match unlock {
+        Unlock::HomingRockets => Some(Unlock::Rocket),
+        _default => None,
+    }
+}
+
+fn can_buy(unlock: Unlock, save: &SaveData) -> bool {
+    unlock_requires(unlock).map_or(true, |needed| save.unlocks.contains(&needed))
+}
+
+// coins a finished run is worth
+pub fn run_reward(score: &Score) -> u32 {
+    (score.score / SCORE_PER_COIN).max(0) as u32
+}
+
+// every weapon that can fall from the sky, the pistol is always in there
+pub fn drop_table(save: &SaveData) -> Vec<Weapons> {
+    let mut table = vec![Weapons::Base];
+    save.unlocks.iter().for_each(|unlock| match unlock {
+        Unlock::Shotgun => table.push(Weapons::Shotgun),
+        Unlock::Sniper => table.push(Weapons::Sniper),
+        Unlock::Rocket => table.push(Weapons::Rocket),
+        Unlock::Laser => table.push(Weapons::Laser),
+        Unlock::Grenade => table.push(Weapons::Grenade),
+        _default => {}
+    });
+    // the upgrade swaps every rocket drop for the homing one
+    if save.unlocks.contains(&Unlock::HomingRockets) {
+        table.iter_mut().for_each(|weapon| {
+            if matches!(weapon, Weapons::Rocket) {
+                *weapon = Weapons::HomingRocket;
+            }
+        });
+    }
+    table
+}
+
+// what an enemy might leave behind and how likely it is
+pub fn enemy_drop(behavior: Behavior) -> Option<(Weapons, f64)> {
+    match behavior {
+        Behavior::Shooter => Some((Weapons::Smg, 0.25)),
+        Behavior::BurstShooter => Some((Weapons::EnemyGun, 0.4)),
+        _default => None,
+    }
+}
+
+pub fn starting_health(save: &SaveData) -> i8 {
+    if save.unlocks.contains(&Unlock::ExtraLife) {
+        PLAYER_MAX_HEALTH + 1
+    } else {
+        PLAYER_MAX_HEALTH
+    }
+}
+
+pub fn dash_speed(save: &SaveData) -> f32 {
+    if save.unlocks.contains(&Unlock::LongDash) {
+        DASH_SPEED * 1.4
+    } else {
+        DASH_SPEED
+    }
+}
+
+#[derive(Component)]
+pub struct ShopScreen;
+
+#[derive(Component)]
+pub struct CoinLabel;
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(award_coins))
+            .add_system_set(SystemSet::on_enter(GameState::Shop).with_system(create_shop))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Shop).with_system(despawn_screen::<ShopScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Shop)
+                    .with_system(buy_unlocks)
+                    .with_system(update_shop_labels),
+            );
+    }
+}
+
+pub fn award_coins(score: Res<Score>, run_config: Res<RunConfig>, mut save: ResMut<SaveData>) {
+    if !run_config.mode.keeps_scores() {
+        return;
+    }
+    save.coins += run_reward(&score);
+    save.save();
+}
+
+pub fn create_shop(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.9)))
+        .insert(ShopScreen)
+        .insert(Name::new("Shop"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Unlocks");
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_handle.clone(),
+                            font_size: 30.,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(CoinLabel);
+            ALL_UNLOCKS.iter().for_each(|unlock| {
+                spawn_labeled_button(parent, &font_handle, "", *unlock, Vec2::new(400.0, 50.0));
+            });
+            spawn_button(parent, &font_handle, "Back", MenuButton::Back);
+        });
+}
+
+pub fn buy_unlocks(
+    mut buttons: Query<(&Interaction, &Unlock, &mut UiColor), Changed<Interaction>>,
+    mut save: ResMut<SaveData>,
+) {
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, unlock, mut color)| match *interaction {
+            Interaction::Clicked => {
+                let cost = unlock_cost(*unlock);
+                if save.unlocks.contains(unlock) || save.coins < cost || !can_buy(*unlock, &save) {
+                    return;
+                }
+                save.coins -= cost;
+                save.unlocks.push(*unlock);
+                save.save();
+            }
+            Interaction::Hovered => *color = UiColor(HOVERED_COLOR),
+            Interaction::None => *color = UiColor(BUTTON_COLOR),
+        });
+}
+
+pub fn update_shop_labels(
+    save: Res<SaveData>,
+    buttons: Query<(&Unlock, &Children)>,
+    mut coin_labels: Query<&mut Text, With<CoinLabel>>,
+    mut texts: Query<&mut Text, Without<CoinLabel>>,
+    new_buttons: Query<(), Added<Unlock>>,
+) {
+    if !save.is_changed() && new_buttons.is_empty() {
+        return;
+    }
+    coin_labels.iter_mut().for_each(|mut label| {
+        label.sections[0].value = format!("Coins: {}", save.coins);
+    });
+    buttons.iter().for_each(|(unlock, children)| {
+        let label = if save.unlocks.contains(unlock) {
+            format!("{} - owned", unlock_name(*unlock))
+        } else if !can_buy(*unlock, &save) {
+            let needed = unlock_requires(*unlock).map_or("", unlock_name);
+            format!("{} - needs {}", unlock_name(*unlock), needed)
+        } else {
+            format!("{} - {}", unlock_name(*unlock), unlock_cost(*unlock))
+        };
+        let color = if can_buy(*unlock, &save) {
+            Color::WHITE
+        } else {
+            LOCKED_COLOR
+        };
+        children.iter().for_each(|child| {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = label.clone();
+                text.sections[0].style.color = color;
+            }
+        });
+    });
+}
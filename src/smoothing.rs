@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::clock::GameClock;
+use crate::loading::GameAssets;
+use crate::menu::{despawn_screen, screen_root, spawn_button, spawn_title, MenuButton};
+use crate::settings::{spawn_slider_row, Settings, SliderLabel};
+use crate::{GameState, GravityData};
+
+// physics steps per second offered in the advanced menu, lower is easier on old machines
+pub const PHYSICS_RATES: [f32; 4] = [20.0, 30.0, 45.0, 60.0];
+// a body that moved further than this in one step was placed there, so it snaps instead of gliding
+const SNAP_DISTANCE: f32 = 48.0;
+
+// draws a physics body part way between its last two steps, so a 30 step body doesn't judder
+// on a 144hz screen. only the drawn position moves, transforms stay where physics put them
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+    since_step: f32,
+    // what was added to the drawn position this frame, taken back off before the next one
+    offset: Vec3,
+}
+
+impl Interpolated {
+    fn new(position: Vec3) -> Self {
+        Interpolated {
+            previous: position,
+            current: position,
+            since_step: 0.0,
+            offset: Vec3::ZERO,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct AdvancedScreen;
+
+pub struct SmoothingPlugin;
+
+impl Plugin for SmoothingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Advanced).with_system(create_advanced_menu),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Advanced).with_system(despawn_screen::<AdvancedScreen>),
+        )
+        .add_system(apply_physics_rate)
+        .add_system(interpolate_new_bodies)
+        // after physics and transforms are done for the frame, and undone before anything reads
+        // them again
+        .add_system_to_stage(CoreStage::Last, smooth_bodies)
+        .add_system_to_stage(CoreStage::First, unsmooth_bodies);
+    }
+}
+
+pub fn create_advanced_menu(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    commands
+        .spawn_bundle(screen_root(Color::rgba(0.0, 0.0, 0.0, 0.9)))
+        .insert(AdvancedScreen)
+        .insert(Name::new("Advanced Menu"))
+        .with_children(|parent| {
+            spawn_title(parent, &font_handle, "Advanced");
+            spawn_slider_row(parent, &font_handle, SliderLabel::PhysicsRate);
+            spawn_slider_row(parent, &font_handle, SliderLabel::Smoothing);
+            spawn_button(parent, &font_handle, "Back", MenuButton::Back);
+        });
+}
+
+pub fn physics_rate(settings: &Settings) -> f32 {
+    PHYSICS_RATES[settings.physics_rate.min(PHYSICS_RATES.len() - 1)]
+}
+
+// the tuning data stays the one place the step rate lives, the setting just writes into it
+pub fn apply_physics_rate(settings: Res<Settings>, mut gravity: ResMut<GravityData>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let rate = physics_rate(&settings);
+    if gravity.phys_step != rate {
+        gravity.phys_step = rate;
+    }
+}
+
+// only bodies at the top of the tree, children are carried along with their parent
+pub fn interpolate_new_bodies(
+    mut commands: Commands,
+    bodies: Query<(Entity, &RigidBody, &Transform), (Added<RigidBody>, Without<Parent>)>,
+) {
+    bodies
+        .iter()
+        .filter(|(_, body, _)| matches!(body, RigidBody::Dynamic | RigidBody::Sensor))
+        .for_each(|(entity, _, trans)| {
+            commands
+                .entity(entity)
+                .insert(Interpolated::new(trans.translation));
+        });
+}
+
+pub fn shift(global: &mut GlobalTransform, offset: Vec3) {
+    let mut transform = global.compute_transform();
+    transform.translation += offset;
+    *global = GlobalTransform::from(transform);
+}
+
+fn shift_children(
+    kids: Option<&Children>,
+    children: &mut Query<&mut GlobalTransform, Without<Interpolated>>,
+    offset: Vec3,
+) {
+    if let Some(kids) = kids {
+        kids.iter().for_each(|kid| {
+            if let Ok(mut kid_global) = children.get_mut(*kid) {
+                shift(&mut kid_global, offset);
+            }
+        });
+    }
+}
+
+pub fn smooth_bodies(
+    settings: Res<Settings>,
+    gravity: Res<GravityData>,
+    clock: Res<GameClock>,
+    mut bodies: Query<(&mut Interpolated, &mut GlobalTransform, Option<&Children>)>,
+    mut children: Query<&mut GlobalTransform, Without<Interpolated>>,
+) {
+    let step = 1.0 / gravity.phys_step;
+    bodies.iter_mut().for_each(|(mut body, mut global, kids)| {
+        let position = global.translation();
+        if position != body.current {
+            let placed = position.truncate().distance(body.current.truncate()) > SNAP_DISTANCE;
+            body.previous = if placed { position } else { body.current };
+            body.current = position;
+            body.since_step = 0.0;
+        } else {
+            body.since_step += clock.delta_seconds() * gravity.phys_time;
+        }
+        if !settings.smoothing {
+            return;
+        }
+        let drawn = body
+            .previous
+            .lerp(body.current, (body.since_step / step).min(1.0));
+        let offset = (drawn - position).truncate().extend(0.0);
+        if offset == Vec3::ZERO {
+            return;
+        }
+        body.offset = offset;
+        shift(&mut global, offset);
+        shift_children(kids, &mut children, offset);
+    });
+}
+
+// puts the real positions back first thing, so physics and gameplay never see the drawn ones
+pub fn unsmooth_bodies(
+    mut bodies: Query<(&mut Interpolated, &mut GlobalTransform, Option<&Children>)>,
+    mut children: Query<&mut GlobalTransform, Without<Interpolated>>,
+) {
+    bodies
+        .iter_mut()
+        .filter(|(body, ..)| body.offset != Vec3::ZERO)
+        .for_each(|(mut body, mut global, kids)| {
+            let offset = -body.offset;
+            body.offset = Vec3::ZERO;
+            shift(&mut global, offset);
+            shift_children(kids, &mut children, offset);
+        });
+}
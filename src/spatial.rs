@@ -0,0 +1,194 @@
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::audio::{AddAudioSource, Decodable};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use rodio::{Decoder, Source};
+
+use crate::explosions::ExplosionEvent;
+use crate::loading::GameAssets;
+use crate::settings::Settings;
+use crate::{Player, SpawnEnemy};
+
+// past this far from the player a sound is at its quietest and furthest to one side
+const HEARING_RANGE: f32 = 400.0;
+const FAR_VOLUME: f32 = 0.25;
+// never all the way into one ear, a sound hard to one side still bleeds into the other
+const MAX_PAN: f32 = 0.8;
+
+// a sound that came from somewhere in the world, quieter the further it is from the player and
+// off to the side it came from
+pub struct SpatialSound {
+    pub sound: Handle<AudioSource>,
+    pub position: Vec3,
+}
+
+// bevy's playback settings only go as far as volume, so a spatial sound is played as its own
+// source with a gain for each ear. worked out when the sound starts, it doesn't follow its
+// source once it's playing
+#[derive(TypeUuid)]
+#[uuid = "82ba5d81-806d-42d3-96f9-f6af46efae8e"]
+pub struct PannedSound {
+    bytes: Arc<[u8]>,
+    // -1 is all the way left, 1 all the way right
+    pan: f32,
+}
+
+impl Decodable for PannedSound {
+    type DecoderItem = i16;
+    type Decoder = PannedDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let source = Decoder::new(Cursor::new(self.bytes.clone()))
+            .map_err(|err| warn!("could not decode a spatial sound: {}", err))
+            .ok();
+        PannedDecoder {
+            channels: source.as_ref().map_or(1, Source::channels),
+            sample_rate: source.as_ref().map_or(44100, Source::sample_rate),
+            duration: source.as_ref().and_then(Source::total_duration),
+            source,
+            gains: [(1.0 - self.pan).min(1.0), (1.0 + self.pan).min(1.0)],
+            right: None,
+        }
+    }
+}
+
+// always stereo, whatever the file is. a mono file goes to both ears, anything with more
+// channels than two keeps its first pair
+pub struct PannedDecoder {
+    source: Option<Decoder<Cursor<Arc<[u8]>>>>,
+    channels: u16,
+    sample_rate: u32,
+    duration: Option<Duration>,
+    gains: [f32; 2],
+    // the right half of the frame the left sample came from
+    right: Option<i16>,
+}
+
+fn scale(sample: i16, gain: f32) -> i16 {
+    (sample as f32 * gain) as i16
+}
+
+impl Iterator for PannedDecoder {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+        let source = self.source.as_mut()?;
+        let left = source.next()?;
+        let right = if self.channels > 1 {
+            source.next()?
+        } else {
+            left
+        };
+        // the rest of a surround frame is dropped
+        (2..self.channels).for_each(|_| {
+            source.next();
+        });
+        self.right = Some(scale(right, self.gains[1]));
+        Some(scale(left, self.gains[0]))
+    }
+}
+
+impl Source for PannedDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+}
+
+pub struct SpatialAudioPlugin;
+
+impl Plugin for SpatialAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<PannedSound>()
+            .add_event::<SpatialSound>()
+            .add_system(warning_sounds.before(play_spatial_sounds))
+            .add_system(explosion_sounds.before(play_spatial_sounds))
+            .add_system(play_spatial_sounds);
+    }
+}
+
+// how far off to the side a sound is, in the -1 to 1 of PannedSound::pan
+pub fn spatial_pan(position: Vec3, listener: Vec3) -> f32 {
+    ((position.x - listener.x) / HEARING_RANGE).clamp(-1.0, 1.0) * MAX_PAN
+}
+
+pub fn spatial_settings(position: Vec3, listener: Vec3, settings: &Settings) -> PlaybackSettings {
+    let distance = (position.x - listener.x).abs() / HEARING_RANGE;
+    let falloff = 1.0 - (1.0 - FAR_VOLUME) * distance.min(1.0);
+    PlaybackSettings {
+        repeat: false,
+        volume: settings.sfx * falloff,
+        speed: 1.0,
+    }
+}
+
+pub fn warning_sounds(
+    warnings: Query<&SpawnEnemy, Added<SpawnEnemy>>,
+    assets: Res<GameAssets>,
+    mut sounds: EventWriter<SpatialSound>,
+) {
+    warnings.iter().for_each(|warning| {
+        sounds.send(SpatialSound {
+            sound: assets.spawn_warning.clone(),
+            position: warning.location,
+        });
+    });
+}
+
+pub fn explosion_sounds(
+    mut explosions: EventReader<ExplosionEvent>,
+    assets: Res<GameAssets>,
+    mut sounds: EventWriter<SpatialSound>,
+) {
+    explosions.iter().for_each(|explosion| {
+        sounds.send(SpatialSound {
+            sound: assets.explosion.clone(),
+            position: explosion.position,
+        });
+    });
+}
+
+// a panned copy is made per sound from the loaded file's bytes, it's dropped once played
+pub fn play_spatial_sounds(
+    mut sounds: EventReader<SpatialSound>,
+    players: Query<&Player>,
+    sources: Res<Assets<AudioSource>>,
+    mut panned: ResMut<Assets<PannedSound>>,
+    audio: Res<Audio<PannedSound>>,
+    settings: Res<Settings>,
+) {
+    // with nobody to hear it from, everything sounds like it's right there
+    let listener = players.iter().next().map(|player| player.location);
+    sounds.iter().for_each(|sound| {
+        let source = match sources.get(&sound.sound) {
+            Some(source) => source,
+            None => return,
+        };
+        let listener = listener.unwrap_or(sound.position);
+        let handle = panned.add(PannedSound {
+            bytes: source.bytes.clone(),
+            pan: spatial_pan(sound.position, listener),
+        });
+        audio.play_with_settings(
+            handle,
+            spatial_settings(sound.position, listener, &settings),
+        );
+    });
+}
@@ -0,0 +1,268 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::director::Phase;
+use crate::loading::GameAssets;
+use crate::modes::GameRng;
+use crate::{Behavior, Enemy, Player, SpawnEnemy, ALL_BEHAVIORS, PLAYER_MAX_HEALTH};
+
+// the shipped table, used until the asset loads or if it fails to
+const BUILT_IN_TABLE: &str = include_str!("../assets/config/enemies.spawns.json");
+
+// tuning for how crowded the arena is allowed to get
+pub struct SpawnRules {
+    // no new warnings once live enemies plus pending warnings reach this
+    pub soft_cap: usize,
+    pub max_per_tick: i64,
+    // how much of the spawn count pressure is allowed to take away, 0 disables easing
+    pub ease: f32,
+    // enemies inside this distance of the player count towards being overwhelmed
+    pub crowd_radius: f32,
+    pub crowd_limit: usize,
+}
+
+impl Default for SpawnRules {
+    fn default() -> Self {
+        SpawnRules {
+            soft_cap: 40,
+            max_per_tick: 4,
+            ease: 0.75,
+            crowd_radius: 60.0,
+            crowd_limit: 6,
+        }
+    }
+}
+
+impl SpawnRules {
+    pub fn behavior_limit(&self, behavior: Behavior) -> usize {
+        match behavior {
+            Behavior::Walker => 12,
+            Behavior::Jumper => 10,
+            Behavior::Shooter => 3,
+            Behavior::BurstShooter => 2,
+            Behavior::Charger => 3,
+            Behavior::Summoner => 2,
+            Behavior::Shielded => 3,
+            Behavior::Bomber => 3,
+        }
+    }
+
+    // 0 when the player is comfortable, 1 when they're on their last hit or swarmed
+    pub fn pressure(&self, player: Option<&Player>, enemies: &Query<(&Enemy, &Transform)>) -> f32 {
+        let player = match player {
+            Some(player) => player,
+            None => return 0.0,
+        };
+        let hurt = 1.0 - player.health as f32 / PLAYER_MAX_HEALTH as f32;
+        let crowd = enemies
+            .iter()
+            .filter(|(_, trans)| {
+                trans
+                    .translation
+                    .truncate()
+                    .distance(player.location.truncate())
+                    < self.crowd_radius
+            })
+            .count();
+        let swarmed = crowd as f32 / self.crowd_limit as f32;
+        hurt.max(swarmed).clamp(0.0, 1.0)
+    }
+}
+
+// the odds for one stretch of difficulty, in effect until the next band starts
+#[derive(Clone, Deserialize)]
+pub struct SpawnBand {
+    from_difficulty: i64,
+    weights: HashMap<Behavior, f32>,
+    // given to any behavior the band doesn't name, so new enemies join in without a table edit
+    #[serde(default)]
+    default_weight: f32,
+}
+
+// which enemies a wave is made of, loaded from assets/config
+#[derive(Clone, Deserialize, TypeUuid)]
+#[uuid = "3c7e2a91-5d4b-4f86-a0e3-7b19c6d8f2a5"]
+pub struct SpawnTable {
+    bands: Vec<SpawnBand>,
+    // multiplies the band's weights during a director phase, unnamed behaviors keep theirs
+    #[serde(default)]
+    phase_bias: HashMap<Phase, HashMap<Behavior, f32>>,
+    // how many recent spawns are remembered, each repeat among them scales the odds by the penalty
+    memory: usize,
+    repeat_penalty: f32,
+}
+
+impl Default for SpawnTable {
+    fn default() -> Self {
+        serde_json::from_str(BUILT_IN_TABLE).unwrap_or_else(|err| {
+            warn!("the built in spawn table is broken: {}", err);
+            SpawnTable {
+                bands: Vec::new(),
+                phase_bias: HashMap::new(),
+                memory: 0,
+                repeat_penalty: 1.0,
+            }
+        })
+    }
+}
+
+impl SpawnTable {
+    fn weight(&self, behavior: Behavior, difficulty: i64, phase: Phase) -> f32 {
+        let band = self
+            .bands
+            .iter()
+            .filter(|band| band.from_difficulty <= difficulty)
+            .max_by_key(|band| band.from_difficulty);
+        // below the first band everything is equally likely rather than nothing spawning
+        let base = band.map_or(1.0, |band| {
+            band.weights
+                .get(&behavior)
+                .copied()
+                .unwrap_or(band.default_weight)
+        });
+        let bias = self
+            .phase_bias
+            .get(&phase)
+            .and_then(|bias| bias.get(&behavior))
+            .copied()
+            .unwrap_or(1.0);
+        (base * bias).max(0.0)
+    }
+
+    // a weighted roll over every behavior that still has room, none when nothing does
+    pub fn pick(
+        &self,
+        difficulty: i64,
+        phase: Phase,
+        memory: &SpawnMemory,
+        has_room: impl Fn(Behavior) -> bool,
+        rng: &mut GameRng,
+    ) -> Option<Behavior> {
+        let weights: Vec<(Behavior, f32)> = ALL_BEHAVIORS
+            .iter()
+            .filter(|behavior| has_room(**behavior))
+            .map(|behavior| {
+                let repeats = memory.0.iter().filter(|recent| *recent == behavior).count();
+                let weight = self.weight(*behavior, difficulty, phase)
+                    * self.repeat_penalty.powi(repeats as i32);
+                (*behavior, weight)
+            })
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect();
+        let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0.0..total);
+        weights
+            .iter()
+            .find(|(_, weight)| {
+                roll -= weight;
+                roll < 0.0
+            })
+            .or_else(|| weights.last())
+            .map(|(behavior, _)| *behavior)
+    }
+
+    pub fn remember(&self, memory: &mut SpawnMemory, behavior: Behavior) {
+        memory.0.push_back(behavior);
+        while memory.0.len() > self.memory {
+            memory.0.pop_front();
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SpawnTableLoader;
+
+impl AssetLoader for SpawnTableLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let table: SpawnTable = serde_json::from_slice(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(table));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["spawns.json"]
+    }
+}
+
+// the last few behaviors sent out, so the same one doesn't come up wave after wave
+#[derive(Default)]
+pub struct SpawnMemory(VecDeque<Behavior>);
+
+pub struct SpawningPlugin;
+
+impl Plugin for SpawningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SpawnTable>()
+            .init_asset_loader::<SpawnTableLoader>()
+            .init_resource::<SpawnRules>()
+            .init_resource::<SpawnTable>()
+            .init_resource::<SpawnMemory>()
+            .add_system(apply_spawn_table);
+    }
+}
+
+// the table in use is a copy of the loaded asset, swapped whenever the file changes
+pub fn apply_spawn_table(
+    mut events: EventReader<AssetEvent<SpawnTable>>,
+    tables: Res<Assets<SpawnTable>>,
+    assets: Option<Res<GameAssets>>,
+    mut table: ResMut<SpawnTable>,
+) {
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
+    events.iter().for_each(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle }
+            if *handle == assets.spawn_table =>
+        {
+            if let Some(loaded) = tables.get(handle) {
+                *table = loaded.clone();
+            }
+        }
+        _default => {}
+    });
+}
+
+// live and pending enemies per behavior, so caps also count warnings that haven't hatched
+#[derive(Default)]
+pub struct BehaviorCounts([usize; ALL_BEHAVIORS.len()]);
+
+impl BehaviorCounts {
+    pub fn new(enemies: &Query<(&Enemy, &Transform)>, warnings: &Query<&SpawnEnemy>) -> Self {
+        let mut counts = [0; ALL_BEHAVIORS.len()];
+        enemies
+            .iter()
+            .for_each(|(enemy, _)| counts[enemy.asset as usize] += 1);
+        warnings
+            .iter()
+            .for_each(|warning| counts[warning.asset as usize] += 1);
+        BehaviorCounts(counts)
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+
+    pub fn get(&self, behavior: Behavior) -> usize {
+        self.0[behavior as usize]
+    }
+
+    pub fn add(&mut self, behavior: Behavior) {
+        self.0[behavior as usize] += 1;
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::combat::{DamageEvent, DeathEvent, WeaponFiredEvent};
+use crate::spawning::BehaviorCounts;
+use crate::{GameState, ResetGame};
+
+// what happened during the current run, cleared on every reset
+#[derive(Default)]
+pub struct RunStats {
+    pub kills: BehaviorCounts,
+    pub survived: f32,
+    pub hits_taken: u32,
+    pub shots_fired: u32,
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(track_run_stats))
+            .add_system(reset_run_stats);
+    }
+}
+
+pub fn track_run_stats(
+    mut stats: ResMut<RunStats>,
+    mut deaths: EventReader<DeathEvent>,
+    mut damage: EventReader<DamageEvent>,
+    mut fired: EventReader<WeaponFiredEvent>,
+    clock: Res<GameClock>,
+) {
+    stats.survived += clock.delta_seconds();
+    deaths
+        .iter()
+        .filter_map(DeathEvent::enemy)
+        .for_each(|behavior| {
+            stats.kills.add(behavior);
+        });
+    stats.hits_taken += damage.iter().filter(|hit| hit.hit_player()).count() as u32;
+    stats.shots_fired += fired.iter().count() as u32;
+}
+
+pub fn reset_run_stats(mut stats: ResMut<RunStats>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *stats = RunStats::default();
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+
+use crate::clock::GameClock;
+use crate::combat::DeathEvent;
+use crate::depth::RenderLayer;
+use crate::loading::GameAssets;
+use crate::scoring::{ScoreEvent, ScoreSource};
+use crate::tween::{Ease, Tween, TweenTarget};
+use crate::{GameState, Player, RunEntity, Weapons};
+
+// a shotgun kill closer than this counts as point blank
+const POINT_BLANK_RANGE: f32 = 24.0;
+const MULTI_KILL_COUNT: usize = 3;
+const MULTI_KILL_SECONDS: f32 = 1.0;
+const POPUP_SECONDS: f32 = 0.9;
+// stacked popups from the same kill step up by this much
+const POPUP_SPACING: f32 = 8.0;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum StyleKind {
+    Airshot,
+    Thrown,
+    PointBlank,
+    MultiKill,
+}
+
+pub const STYLE_KINDS: [StyleKind; 4] = [
+    StyleKind::Airshot,
+    StyleKind::Thrown,
+    StyleKind::PointBlank,
+    StyleKind::MultiKill,
+];
+
+impl StyleKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            StyleKind::Airshot => "AIRSHOT",
+            StyleKind::Thrown => "THROWN",
+            StyleKind::PointBlank => "POINT BLANK",
+            StyleKind::MultiKill => "MULTI KILL",
+        }
+    }
+
+    pub fn bonus(self) -> i64 {
+        match self {
+            StyleKind::Airshot => 50,
+            StyleKind::Thrown => 40,
+            StyleKind::PointBlank => 30,
+            StyleKind::MultiKill => 100,
+        }
+    }
+}
+
+// the most one kill can earn if it somehow sets off every bonus at once
+pub fn max_style_per_kill() -> i64 {
+    STYLE_KINDS.iter().map(|kind| kind.bonus()).sum()
+}
+
+// sent by the analyzers below, each one watches the death stream for its own trick. only the
+// player's own kills count, enemies shooting each other isn't style
+pub struct StyleEvent {
+    pub kind: StyleKind,
+    pub position: Vec3,
+}
+
+#[derive(Default)]
+pub struct MultiKillWindow {
+    now: f32,
+    recent: Vec<f32>,
+}
+
+pub struct StylePlugin;
+
+impl Plugin for StylePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StyleEvent>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(detect_airshots)
+                .with_system(detect_thrown_kills)
+                .with_system(detect_point_blank)
+                .with_system(detect_multi_kills)
+                .with_system(
+                    award_style
+                        .after(detect_airshots)
+                        .after(detect_thrown_kills)
+                        .after(detect_point_blank)
+                        .after(detect_multi_kills),
+                ),
+        );
+    }
+}
+
+pub fn detect_airshots(mut deaths: EventReader<DeathEvent>, mut styles: EventWriter<StyleEvent>) {
+    deaths
+        .iter()
+        .filter(|death| death.airborne && death.player_weapon().is_some())
+        .for_each(|death| {
+            styles.send(StyleEvent {
+                kind: StyleKind::Airshot,
+                position: death.position,
+            });
+        });
+}
+
+// an empty gun tossed away that still takes someone down
+pub fn detect_thrown_kills(
+    mut deaths: EventReader<DeathEvent>,
+    mut styles: EventWriter<StyleEvent>,
+) {
+    deaths
+        .iter()
+        .filter(|death| death.thrown && death.player_weapon().is_some())
+        .for_each(|death| {
+            styles.send(StyleEvent {
+                kind: StyleKind::Thrown,
+                position: death.position,
+            });
+        });
+}
+
+pub fn detect_point_blank(
+    mut deaths: EventReader<DeathEvent>,
+    players: Query<&Player>,
+    mut styles: EventWriter<StyleEvent>,
+) {
+    let location = match players.iter().next() {
+        Some(player) => player.location,
+        None => return,
+    };
+    deaths
+        .iter()
+        .filter(|death| {
+            matches!(death.player_weapon(), Some(Weapons::Shotgun))
+                && death.position.truncate().distance(location.truncate()) < POINT_BLANK_RANGE
+        })
+        .for_each(|death| {
+            styles.send(StyleEvent {
+                kind: StyleKind::PointBlank,
+                position: death.position,
+            });
+        });
+}
+
+// pays out once the third kill lands inside the window, then starts counting again
+pub fn detect_multi_kills(
+    mut deaths: EventReader<DeathEvent>,
+    mut window: Local<MultiKillWindow>,
+    clock: Res<GameClock>,
+    mut styles: EventWriter<StyleEvent>,
+) {
+    window.now += clock.delta_seconds();
+    let now = window.now;
+    window
+        .recent
+        .retain(|killed_at| now - *killed_at <= MULTI_KILL_SECONDS);
+    deaths
+        .iter()
+        .filter(|death| death.player_weapon().is_some())
+        .for_each(|death| {
+            window.recent.push(now);
+            if window.recent.len() >= MULTI_KILL_COUNT {
+                window.recent.clear();
+                styles.send(StyleEvent {
+                    kind: StyleKind::MultiKill,
+                    position: death.position,
+                });
+            }
+        });
+}
+
+pub fn award_style(
+    mut commands: Commands,
+    mut styles: EventReader<StyleEvent>,
+    mut scores: EventWriter<ScoreEvent>,
+    assets: Res<GameAssets>,
+) {
+    styles.iter().enumerate().for_each(|(index, style)| {
+        let bonus = style.kind.bonus();
+        scores.send(ScoreEvent {
+            source: ScoreSource::Style,
+            points: bonus,
+        });
+        let position = RenderLayer::Overlay
+            .at(style.position.truncate() + Vec2::new(0.0, 12.0 + index as f32 * POPUP_SPACING));
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    format!("{} +{}", style.kind.name(), bonus),
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 32.,
+                        color: Color::GOLD,
+                    },
+                )
+                .with_alignment(TextAlignment::BOTTOM_CENTER),
+                // large font scaled down so it stays crisp at world scale
+                transform: Transform {
+                    translation: position,
+                    scale: Vec3::splat(0.25),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(
+                Tween::new(POPUP_SECONDS, Ease::OutCubic)
+                    .with(TweenTarget::Scale(Vec3::splat(0.4), Vec3::splat(0.25)))
+                    .with(TweenTarget::TextColor(
+                        Color::GOLD,
+                        Color::rgba(1.0, 0.84, 0.0, 0.0),
+                    ))
+                    .despawn_when_done(),
+            )
+            .insert(Name::new("Style Popup"))
+            .insert(RunEntity);
+    });
+}
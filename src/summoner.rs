@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::combat::DeathEvent;
+use crate::loading::AtlasRegistry;
+use crate::modes::GameRng;
+use crate::particles::spawn_burst;
+use crate::schedule::{FixedGameplayStage, FixedStep};
+use crate::tween::{Ease, Repeat, Tween, TweenTarget};
+use crate::{spawn_enemy_warning, Behavior, Enemy, GameState, Player, SpawnEnemy};
+
+// live minions and pending warnings a single summoner can have out at once
+const MAX_MINIONS: usize = 4;
+const CAST_SECONDS: f32 = 0.8;
+// summoners back off when the player gets closer than this
+const COMFORT_DISTANCE: f32 = 140.0;
+const RETREAT_SPEED: f32 = 25.0;
+const CAST_COLOR: Color = Color::rgb(0.7, 0.4, 1.0);
+
+// an enemy called in by a summoner, its warning carries this too so the cap counts it early
+#[derive(Component)]
+pub struct Minion {
+    pub summoner: Entity,
+}
+
+#[derive(Component)]
+pub struct Casting {
+    timer: Timer,
+}
+
+impl Default for Casting {
+    fn default() -> Self {
+        Casting {
+            timer: Timer::from_seconds(CAST_SECONDS, false),
+        }
+    }
+}
+
+// swells up while the cast charges
+pub fn cast_tween() -> Tween {
+    Tween::new(0.2, Ease::InOut)
+        .with(TweenTarget::Scale(Vec3::ONE, Vec3::splat(1.25)))
+        .repeat(Repeat::PingPong)
+}
+
+pub struct SummonerPlugin;
+
+impl Plugin for SummonerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set_to_stage(
+            FixedGameplayStage,
+            SystemSet::on_update(GameState::Playing)
+                .with_system(handle_casting)
+                .with_system(hang_back),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing).with_system(dismiss_orphaned_minions),
+        );
+    }
+}
+
+pub fn handle_casting(
+    mut commands: Commands,
+    mut casters: Query<(
+        Entity,
+        &mut Transform,
+        &mut Casting,
+        &mut TextureAtlasSprite,
+    )>,
+    minions: Query<&Minion>,
+    atlases: Res<AtlasRegistry>,
+    step: Res<FixedStep>,
+    mut rng: ResMut<GameRng>,
+) {
+    casters
+        .iter_mut()
+        .for_each(|(entity, mut trans, mut casting, mut sprite)| {
+            casting.timer.tick(step.delta());
+            if !casting.timer.finished() {
+                sprite.color = CAST_COLOR;
+                return;
+            }
+            sprite.color = Color::WHITE;
+            trans.scale = Vec3::ONE;
+            commands
+                .entity(entity)
+                .remove::<Casting>()
+                .remove::<Tween>();
+            let out = minions
+                .iter()
+                .filter(|minion| minion.summoner == entity)
+                .count();
+            let count = rng.gen_range(2..=3).min(MAX_MINIONS.saturating_sub(out));
+            (0..count).for_each(|_| {
+                let offset = rng.gen_range(20.0..50.0) * if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                let x = (trans.translation.x + offset).clamp(-200.0, 200.0);
+                let warning = spawn_enemy_warning(&mut commands, &atlases, Behavior::Walker, x);
+                commands.entity(warning).insert(Minion { summoner: entity });
+            });
+        });
+}
+
+// keeps its distance between casts
+pub fn hang_back(
+    mut summoners: Query<(&mut Transform, &Enemy), Without<Casting>>,
+    players: Query<&Player>,
+    step: Res<FixedStep>,
+) {
+    let player = match players.iter().next() {
+        Some(player) => player.location,
+        None => return,
+    };
+    summoners
+        .iter_mut()
+        .filter(|(_, enemy)| enemy.asset == Behavior::Summoner)
+        .for_each(|(mut trans, _)| {
+            let away = trans.translation.x - player.x;
+            if away.abs() < COMFORT_DISTANCE {
+                trans.translation.x += away.signum() * RETREAT_SPEED * step.delta_seconds();
+            }
+        });
+}
+
+// taking out the summoner unmakes everything it called in
+pub fn dismiss_orphaned_minions(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    minions: Query<(Entity, &Minion, &Transform, Option<&SpawnEnemy>)>,
+    mut rng: ResMut<GameRng>,
+) {
+    deaths.iter().for_each(|death| {
+        minions
+            .iter()
+            .filter(|(_, minion, _, _)| minion.summoner == death.entity)
+            .for_each(|(entity, _, trans, warning)| {
+                if warning.is_none() {
+                    spawn_burst(
+                        &mut commands,
+                        &mut rng,
+                        trans.translation,
+                        CAST_COLOR,
+                        6,
+                        40.0,
+                    );
+                }
+                commands.entity(entity).despawn_recursive();
+            });
+    });
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use heron::prelude::*;
+use heron::rapier_plugin::PhysicsWorld;
+
+use crate::collision::collision_layers;
+use crate::schedule::GameplaySet;
+use crate::shielded::EnemyShield;
+use crate::{Enemy, GameState, Layers};
+
+// bullets at least this fast can skip clean over an enemy between two physics steps
+pub const FAST_PROJECTILE_SPEED: f32 = 400.0;
+// bullets go through enemies, so one segment can hit a few before it stops looking
+const MAX_SWEPT_HITS: usize = 4;
+
+// a bullet that checks the stretch it covered since last frame, not just where it landed
+#[derive(Component)]
+pub struct FastProjectile {
+    last: Vec3,
+}
+
+impl FastProjectile {
+    pub fn new(position: Vec3) -> Self {
+        FastProjectile { last: position }
+    }
+}
+
+// an enemy or shield the bullet passed through without the physics step seeing it
+pub struct SweptHit {
+    pub bullet: Entity,
+    pub target: Entity,
+}
+
+pub struct SweepPlugin;
+
+impl Plugin for SweepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SweptHit>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .label(GameplaySet::Simulation)
+                .after(GameplaySet::Input)
+                .with_system(sweep_fast_projectiles),
+        );
+    }
+}
+
+// casts from last frame's position to this one, anything already touching is left to the
+// regular collision so it isn't hit twice
+pub fn sweep_fast_projectiles(
+    mut projectiles: Query<(Entity, &Transform, &Collisions, &mut FastProjectile)>,
+    targets: Query<(), Or<(With<Enemy>, With<EnemyShield>)>>,
+    physics: PhysicsWorld,
+    mut hits: EventWriter<SweptHit>,
+) {
+    projectiles
+        .iter_mut()
+        .for_each(|(bullet, trans, collisions, mut fast)| {
+            let start = fast.last;
+            let ray = trans.translation - start;
+            fast.last = trans.translation;
+            if ray.truncate().length_squared() <= f32::EPSILON {
+                return;
+            }
+            let mut found: Vec<Entity> = collisions.entities().collect();
+            let touching = found.len();
+            while found.len() - touching < MAX_SWEPT_HITS {
+                let hit = physics.ray_cast_with_filter(
+                    start,
+                    ray,
+                    true,
+                    collision_layers(Layers::Projectiles),
+                    |entity| targets.get(entity).is_ok() && !found.contains(&entity),
+                );
+                let target = match hit {
+                    Some(hit) => hit.entity,
+                    None => break,
+                };
+                found.push(target);
+                hits.send(SweptHit { bullet, target });
+            }
+        });
+}
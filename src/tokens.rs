@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::schedule::GameplaySet;
+use crate::{Enemy, GameState, ResetGame};
+
+// shooters allowed to be lining up a shot at the same time
+const ATTACK_TOKENS: usize = 2;
+
+// hands out the right to attack so a crowd can't all fire on the same frame. whoever has
+// waited longest goes next, and an attacker that's done rejoins the back of the line
+pub struct AttackTokens {
+    limit: usize,
+    holders: Vec<Entity>,
+    waiting: VecDeque<Entity>,
+}
+
+impl Default for AttackTokens {
+    fn default() -> Self {
+        AttackTokens {
+            limit: ATTACK_TOKENS,
+            holders: Vec::new(),
+            waiting: VecDeque::new(),
+        }
+    }
+}
+
+impl AttackTokens {
+    // true while this enemy may attack, otherwise it's put in line
+    pub fn request(&mut self, entity: Entity) -> bool {
+        if self.holders.contains(&entity) {
+            return true;
+        }
+        if !self.waiting.contains(&entity) {
+            self.waiting.push_back(entity);
+        }
+        false
+    }
+
+    pub fn release(&mut self, entity: Entity) {
+        self.holders.retain(|holder| *holder != entity);
+    }
+}
+
+pub struct AttackTokenPlugin;
+
+impl Plugin for AttackTokenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttackTokens>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(grant_attack_tokens.label(GameplaySet::Simulation)),
+            )
+            .add_system(reset_attack_tokens);
+    }
+}
+
+pub fn grant_attack_tokens(mut tokens: ResMut<AttackTokens>, enemies: Query<(), With<Enemy>>) {
+    // the dead give their token back
+    tokens.holders.retain(|holder| enemies.get(*holder).is_ok());
+    tokens.waiting.retain(|waiter| enemies.get(*waiter).is_ok());
+    while tokens.holders.len() < tokens.limit {
+        match tokens.waiting.pop_front() {
+            Some(next) => tokens.holders.push(next),
+            None => break,
+        }
+    }
+}
+
+pub fn reset_attack_tokens(mut tokens: ResMut<AttackTokens>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *tokens = AttackTokens::default();
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::input::touch::Touch;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use crate::actions::{read_actions, Action};
+use crate::loading::GameAssets;
+use crate::GameState;
+
+// how far the stick knob can be dragged from where the thumb landed, in ui pixels
+const STICK_RADIUS: f32 = 50.0;
+// drags shorter than this don't walk or re-aim, so a resting thumb doesn't drift
+const STICK_DEADZONE: f32 = 12.0;
+const AIM_DEADZONE: f32 = 8.0;
+const BUTTON_SIZE: f32 = 70.0;
+const BUTTON_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const BUTTON_PRESSED: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+
+// what every finger on the screen adds up to, read by the actions alongside keys and pads
+#[derive(Default)]
+pub struct TouchControls {
+    // flips on with the first touch and stays on, there's no keyboard to fall back to
+    pub enabled: bool,
+    // a finger went down this frame
+    pub touched: bool,
+    pressed: Vec<Action>,
+    // drag direction on the right half of the screen, zero while nothing is aiming
+    pub aim: Vec2,
+    stick: Vec2,
+}
+
+impl TouchControls {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+}
+
+// a ui node that holds an action down while a finger is on it
+#[derive(Component)]
+pub struct TouchButton {
+    pub action: Action,
+    // lights up while held, off for nodes that already have their own look
+    pub highlight: bool,
+}
+
+#[derive(Component)]
+pub struct TouchOverlay;
+
+#[derive(Component)]
+pub struct TouchKnob;
+
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchControls>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                read_touches.after(InputSystem).before(read_actions),
+            )
+            .add_system(create_touch_overlay)
+            .add_system(update_touch_overlay);
+    }
+}
+
+// bevy only flips touches on android and ios, browsers report them from the top like winit
+// does, so this puts them the same way up as the cursor and ui nodes
+pub fn touch_position(position: Vec2, window: &Window) -> Vec2 {
+    if cfg!(target_arch = "wasm32") {
+        Vec2::new(position.x, window.height() - position.y)
+    } else {
+        position
+    }
+}
+
+fn hit(position: Vec2, node: &Node, transform: &GlobalTransform) -> bool {
+    let center = transform.translation().truncate();
+    let extents = node.size / 2.0;
+    position.cmpge(center - extents).all() && position.cmple(center + extents).all()
+}
+
+pub fn read_touches(
+    mut controls: ResMut<TouchControls>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    buttons: Query<(&TouchButton, &Node, &GlobalTransform, &ComputedVisibility)>,
+) {
+    controls.touched = touches.iter_just_pressed().next().is_some();
+    if controls.touched {
+        controls.enabled = true;
+    }
+    controls.pressed.clear();
+    controls.aim = Vec2::ZERO;
+    controls.stick = Vec2::ZERO;
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    touches.iter().for_each(|touch: &Touch| {
+        let start = touch_position(touch.start_position(), window);
+        let drag = touch_position(touch.position(), window) - start;
+        // a finger belongs to whatever it landed on, sliding off a button doesn't let go
+        let button = buttons
+            .iter()
+            .find(|(_, node, transform, visibility)| {
+                visibility.is_visible() && hit(start, node, transform)
+            })
+            .map(|(button, ..)| button.action);
+        if let Some(action) = button {
+            controls.pressed.push(action);
+        } else if start.x < window.width() / 2.0 {
+            // the stick sits wherever the left thumb comes down
+            controls.stick = drag.clamp_length_max(STICK_RADIUS);
+            if drag.x < -STICK_DEADZONE {
+                controls.pressed.push(Action::MoveLeft);
+            } else if drag.x > STICK_DEADZONE {
+                controls.pressed.push(Action::MoveRight);
+            }
+        } else {
+            // the right thumb fires while it's down and aims the way it's dragged
+            controls.pressed.push(Action::Fire);
+            if drag.length() > AIM_DEADZONE {
+                controls.aim = drag.normalize();
+            }
+        }
+    });
+}
+
+fn spawn_touch_button(parent: &mut ChildBuilder, font: &Handle<Font>, label: &str, action: Action) {
+    parent
+        .spawn_bundle(NodeBundle {
+            color: UiColor(BUTTON_COLOR),
+            style: Style {
+                size: Size::new(Val::Px(BUTTON_SIZE), Val::Px(BUTTON_SIZE)),
+                margin: UiRect::all(Val::Px(8.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(TouchButton {
+            action,
+            highlight: true,
+        })
+        .with_children(|button| {
+            button.spawn_bundle(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 18.,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+// built the first time a finger shows up, desktop players never see it
+pub fn create_touch_overlay(
+    mut commands: Commands,
+    controls: Res<TouchControls>,
+    assets: Option<Res<GameAssets>>,
+    overlays: Query<(), With<TouchOverlay>>,
+) {
+    if !controls.enabled || !overlays.is_empty() {
+        return;
+    }
+    let font_handle = match assets {
+        Some(assets) => assets.font.clone(),
+        None => return,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            // the overlay covers everything, menu buttons underneath still need their taps
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        })
+        .insert(TouchOverlay)
+        .insert(Name::new("Touch Controls"))
+        .with_children(|overlay| {
+            // the stick's resting spot, the knob inside follows the left thumb
+            overlay
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(BUTTON_COLOR),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(40.0),
+                            bottom: Val::Px(40.0),
+                            ..default()
+                        },
+                        size: Size::new(Val::Px(STICK_RADIUS * 2.0), Val::Px(STICK_RADIUS * 2.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|base| {
+                    base.spawn_bundle(NodeBundle {
+                        color: UiColor(BUTTON_PRESSED),
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            size: Size::new(Val::Px(STICK_RADIUS), Val::Px(STICK_RADIUS)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(TouchKnob);
+                });
+            overlay
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(Color::NONE),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            right: Val::Px(30.0),
+                            bottom: Val::Px(30.0),
+                            ..default()
+                        },
+                        flex_direction: FlexDirection::ColumnReverse,
+                        ..default()
+                    },
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                })
+                .with_children(|column| {
+                    spawn_touch_button(column, &font_handle, "Jump", Action::Jump);
+                    spawn_touch_button(column, &font_handle, "Dash", Action::Dash);
+                    spawn_touch_button(column, &font_handle, "Grab", Action::Grab);
+                    spawn_touch_button(column, &font_handle, "Slow", Action::BulletTime);
+                });
+            overlay
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(Color::NONE),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            right: Val::Px(10.0),
+                            top: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                })
+                .with_children(|corner| {
+                    spawn_touch_button(corner, &font_handle, "||", Action::Pause);
+                });
+        });
+}
+
+pub fn update_touch_overlay(
+    controls: Res<TouchControls>,
+    state: Res<State<GameState>>,
+    mut overlays: Query<&mut Visibility, With<TouchOverlay>>,
+    mut knobs: Query<&mut Style, With<TouchKnob>>,
+    mut buttons: Query<(&TouchButton, &mut UiColor)>,
+) {
+    let playing = *state.current() == GameState::Playing;
+    overlays.iter_mut().for_each(|mut visibility| {
+        if visibility.is_visible != playing {
+            visibility.is_visible = playing;
+        }
+    });
+    knobs.iter_mut().for_each(|mut style| {
+        // centered in the base, nudged by the drag. ui y runs down so the drag's y flips
+        let offset = STICK_RADIUS / 2.0;
+        style.position = UiRect {
+            left: Val::Px(offset + controls.stick.x),
+            top: Val::Px(offset - controls.stick.y),
+            ..default()
+        };
+    });
+    buttons
+        .iter_mut()
+        .filter(|(button, _)| button.highlight)
+        .for_each(|(button, mut color)| {
+            color.0 = if controls.pressed(button.action) {
+                BUTTON_PRESSED
+            } else {
+                BUTTON_COLOR
+            };
+        });
+}
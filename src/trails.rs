@@ -0,0 +1,219 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::view::NoFrustumCulling;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::clock::GameClock;
+use crate::schedule::GameplaySet;
+use crate::{Dashing, GameState, Player, RunEntity, Weapons};
+
+// a new point is only laid down once the owner has moved this far from the last one
+const MIN_SPACING: f32 = 1.5;
+const MAX_POINTS: usize = 64;
+const DASH_COLOR: Color = Color::rgba(0.6, 0.85, 1.0, 0.6);
+const ROCKET_COLOR: Color = Color::rgba(1.0, 0.55, 0.2, 0.7);
+const SNIPER_COLOR: Color = Color::rgba(1.0, 1.0, 0.85, 0.8);
+
+// leaves a fading ribbon behind whatever carries it. the ribbon is its own entity, so it keeps
+// fading out after the owner is gone instead of vanishing with it
+#[derive(Component, Clone)]
+pub struct Trail {
+    pub width: f32,
+    // seconds a point lasts, the ribbon narrows and fades over this
+    pub lifetime: f32,
+    pub color: Color,
+    // only records while this is on, dashes switch it on and off
+    pub emitting: bool,
+}
+
+impl Trail {
+    pub fn new(width: f32, lifetime: f32, color: Color) -> Self {
+        Trail {
+            width,
+            lifetime,
+            color,
+            emitting: true,
+        }
+    }
+}
+
+struct TrailPoint {
+    position: Vec2,
+    age: f32,
+}
+
+#[derive(Component)]
+pub struct TrailRibbon {
+    owner: Entity,
+    trail: Trail,
+    points: VecDeque<TrailPoint>,
+}
+
+// one plain white material for every ribbon, the color comes from the vertices
+pub struct TrailMaterial(Handle<ColorMaterial>);
+
+impl FromWorld for TrailMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let mut color_materials = world.resource_mut::<Assets<ColorMaterial>>();
+        TrailMaterial(color_materials.add(ColorMaterial::from(Color::WHITE)))
+    }
+}
+
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrailMaterial>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .label(GameplaySet::Presentation)
+                .after(GameplaySet::CollisionResponse)
+                .with_system(attach_ribbons)
+                .with_system(emit_dash_trails)
+                .with_system(update_ribbons.after(attach_ribbons).after(emit_dash_trails)),
+        );
+    }
+}
+
+// the player's dash streak, only drawn while a dash is going
+pub fn dash_trail() -> Trail {
+    Trail {
+        emitting: false,
+        ..Trail::new(8.0, 0.25, DASH_COLOR)
+    }
+}
+
+pub fn projectile_trail(weapon: Weapons) -> Option<Trail> {
+    match weapon {
+        Weapons::Rocket | Weapons::HomingRocket => Some(Trail::new(3.0, 0.4, ROCKET_COLOR)),
+        Weapons::Sniper => Some(Trail::new(1.5, 0.15, SNIPER_COLOR)),
+        _default => None,
+    }
+}
+
+pub fn attach_ribbons(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    material: Res<TrailMaterial>,
+    trails: Query<(Entity, &Trail, &GlobalTransform), Added<Trail>>,
+) {
+    trails.iter().for_each(|(owner, trail, global)| {
+        commands
+            .spawn_bundle(MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(Mesh::new(PrimitiveTopology::TriangleList))),
+                material: material.0.clone(),
+                // just behind whatever leaves it
+                transform: Transform::from_xyz(0.0, 0.0, global.translation().z - 0.5),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            // the mesh is rebuilt every frame, its first bounds would cull it in the wrong place
+            .insert(NoFrustumCulling)
+            .insert(TrailRibbon {
+                owner,
+                trail: trail.clone(),
+                points: VecDeque::new(),
+            })
+            .insert(Name::new("Trail"))
+            .insert(RunEntity);
+    });
+}
+
+pub fn emit_dash_trails(mut players: Query<(&mut Trail, Option<&Dashing>), With<Player>>) {
+    players.iter_mut().for_each(|(mut trail, dashing)| {
+        if trail.emitting != dashing.is_some() {
+            trail.emitting = dashing.is_some();
+        }
+    });
+}
+
+pub fn update_ribbons(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    owners: Query<(&Trail, &GlobalTransform)>,
+    mut ribbons: Query<(Entity, &mut TrailRibbon, &Mesh2dHandle, &mut Visibility)>,
+) {
+    let delta = clock.delta_seconds();
+    ribbons
+        .iter_mut()
+        .for_each(|(entity, mut ribbon, mesh, mut visibility)| {
+            let owner = owners.get(ribbon.owner).ok();
+            if let Some((trail, _)) = owner {
+                ribbon.trail = trail.clone();
+            }
+            let lifetime = ribbon.trail.lifetime;
+            ribbon
+                .points
+                .iter_mut()
+                .for_each(|point| point.age += delta);
+            ribbon.points.retain(|point| point.age < lifetime);
+            if let Some((trail, global)) = owner {
+                let position = global.translation().truncate();
+                let moved = ribbon
+                    .points
+                    .back()
+                    .map_or(true, |last| last.position.distance(position) >= MIN_SPACING);
+                if trail.emitting && moved {
+                    ribbon.points.push_back(TrailPoint { position, age: 0.0 });
+                    if ribbon.points.len() > MAX_POINTS {
+                        ribbon.points.pop_front();
+                    }
+                }
+            } else if ribbon.points.is_empty() {
+                commands.entity(entity).despawn();
+                return;
+            }
+            let drawn = ribbon.points.len() >= 2;
+            if visibility.is_visible != drawn {
+                visibility.is_visible = drawn;
+            }
+            if !drawn {
+                return;
+            }
+            if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                *mesh = ribbon_mesh(&ribbon);
+            }
+        });
+}
+
+// a strip two vertices wide along the points, oldest end narrowest and most faded
+fn ribbon_mesh(ribbon: &TrailRibbon) -> Mesh {
+    let trail = &ribbon.trail;
+    let points: Vec<&TrailPoint> = ribbon.points.iter().collect();
+    let mut positions = Vec::with_capacity(points.len() * 2);
+    let mut colors = Vec::with_capacity(points.len() * 2);
+    points.iter().enumerate().for_each(|(index, point)| {
+        let before = points[index.saturating_sub(1)].position;
+        let after = points[(index + 1).min(points.len() - 1)].position;
+        let normal = (after - before).normalize_or_zero().perp();
+        let life = 1.0 - (point.age / trail.lifetime).min(1.0);
+        let half = normal * trail.width * 0.5 * life;
+        let mut color = trail.color;
+        color.set_a(trail.color.a() * life);
+        let color = color.as_linear_rgba_f32();
+        [point.position + half, point.position - half]
+            .iter()
+            .for_each(|corner| {
+                positions.push([corner.x, corner.y, 0.0]);
+                colors.push(color);
+            });
+    });
+    let indices: Vec<u32> = (0..points.len() as u32 - 1)
+        .flat_map(|segment| {
+            let i = segment * 2;
+            [i, i + 1, i + 2, i + 1, i + 3, i + 2]
+        })
+        .collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 0.0, 1.0]; positions.len()],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use crate::clock::RealClock;
+use crate::tween::ease_in_out;
+use crate::GameState;
+
+const FADE_SECONDS: f32 = 0.3;
+// above any depth the ui hands out, still well inside the ui camera's range
+const OVERLAY_Z: f32 = 500.0;
+
+#[derive(PartialEq, Eq)]
+enum Phase {
+    Idle,
+    // covering the screen, the state switches once it's fully black
+    Out,
+    In,
+}
+
+// drives the fullscreen fade between states, anything that changes screens goes through here
+pub struct Transition {
+    phase: Phase,
+    timer: Timer,
+    pending: Option<GameState>,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Transition {
+            phase: Phase::Idle,
+            timer: Timer::from_seconds(FADE_SECONDS, false),
+            pending: None,
+        }
+    }
+}
+
+impl Transition {
+    // a second request while one is running is dropped, so double clicks don't queue two
+    pub fn fade_to(&mut self, state: GameState) {
+        if self.phase != Phase::Idle {
+            return;
+        }
+        self.phase = Phase::Out;
+        self.timer.reset();
+        self.pending = Some(state);
+    }
+
+    fn alpha(&self) -> f32 {
+        let t = ease_in_out(self.timer.percent());
+        match self.phase {
+            Phase::Idle => 0.0,
+            Phase::Out => t,
+            Phase::In => 1.0 - t,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct FadeOverlay;
+
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Transition>()
+            .add_startup_system(create_fade_overlay)
+            .add_system(run_transition)
+            // ui layout restacks every root node each frame, so the overlay is lifted back over
+            // the menus once that and the transforms are done
+            .add_system_to_stage(CoreStage::Last, raise_fade_overlay);
+    }
+}
+
+pub fn create_fade_overlay(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(FadeOverlay)
+        .insert(Name::new("Fade Overlay"));
+}
+
+pub fn raise_fade_overlay(mut overlays: Query<&mut GlobalTransform, With<FadeOverlay>>) {
+    overlays.iter_mut().for_each(|mut global| {
+        let mut transform = global.compute_transform();
+        transform.translation.z = OVERLAY_Z;
+        *global = GlobalTransform::from(transform);
+    });
+}
+
+// the game clock is frozen in most of the screens we fade between
+pub fn run_transition(
+    mut transition: ResMut<Transition>,
+    clock: Res<RealClock>,
+    mut state: ResMut<State<GameState>>,
+    mut overlays: Query<&mut UiColor, With<FadeOverlay>>,
+) {
+    if transition.phase == Phase::Idle {
+        return;
+    }
+    transition.timer.tick(clock.delta());
+    if transition.timer.finished() {
+        match transition.phase {
+            Phase::Out => {
+                if let Some(next) = transition.pending.take() {
+                    let _ = state.replace(next);
+                }
+                transition.phase = Phase::In;
+                transition.timer.reset();
+            }
+            _ => transition.phase = Phase::Idle,
+        }
+    }
+    let alpha = transition.alpha();
+    overlays
+        .iter_mut()
+        .for_each(|mut color| color.0.set_a(alpha));
+}
@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+
+use crate::actions::{prompt, Action, Actions, Device};
+use crate::loading::GameAssets;
+use crate::menu::despawn_screen;
+use crate::modes::{GameMode, RunConfig};
+use crate::settings::Settings;
+use crate::touch::TouchButton;
+use crate::{Dashing, GameState, HeldItem, Spinning};
+
+const TODO_COLOR: Color = Color::WHITE;
+const DONE_COLOR: Color = Color::rgb(0.4, 0.9, 0.4);
+
+#[derive(Copy, Clone)]
+pub enum TutorialStep {
+    Move,
+    Jump,
+    Dash,
+    Grab,
+    Fire,
+}
+
+const STEPS: [TutorialStep; 5] = [
+    TutorialStep::Move,
+    TutorialStep::Jump,
+    TutorialStep::Dash,
+    TutorialStep::Grab,
+    TutorialStep::Fire,
+];
+
+fn step_prompt(step: TutorialStep, device: Device, versus: bool) -> String {
+    let left = prompt(Action::MoveLeft, device, versus);
+    let right = prompt(Action::MoveRight, device, versus);
+    match step {
+        TutorialStep::Move if left == right => format!("Move with {}", left),
+        TutorialStep::Move => format!("Move with {} and {}", left, right),
+        TutorialStep::Jump => format!("Jump with {}", prompt(Action::Jump, device, versus)),
+        TutorialStep::Dash if device == Device::Keyboard => {
+            format!("Double tap {} or {} to dash", left, right)
+        }
+        TutorialStep::Dash => format!("Dash with {}", prompt(Action::Dash, device, versus)),
+        TutorialStep::Grab => "Walk into a falling gun to grab it".to_string(),
+        TutorialStep::Fire => format!(
+            "{} to fire, the gun gets thrown after every shot",
+            prompt(Action::Fire, device, versus)
+        ),
+    }
+}
+
+fn skip_prompt(device: Device, versus: bool) -> String {
+    format!("{} to skip", prompt(Action::SkipTutorial, device, versus))
+}
+
+#[derive(Default)]
+pub struct Tutorial {
+    done: [bool; STEPS.len()],
+}
+
+impl Tutorial {
+    fn complete(&mut self, step: TutorialStep) {
+        self.done[step as usize] = true;
+    }
+
+    fn finished(&self) -> bool {
+        self.done.iter().all(|done| *done)
+    }
+}
+
+#[derive(Component)]
+pub struct TutorialScreen;
+
+#[derive(Component)]
+pub struct TutorialChecklist;
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing).with_system(create_tutorial_overlay),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(track_tutorial)
+                    .with_system(update_tutorial_checklist)
+                    .with_system(finish_tutorial),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Playing)
+                    .with_system(despawn_screen::<TutorialScreen>),
+            );
+    }
+}
+
+pub fn create_tutorial_overlay(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+    device: Res<Device>,
+    run_config: Res<RunConfig>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    if settings.tutorial_seen {
+        return;
+    }
+    let versus = run_config.mode == GameMode::Versus;
+    *tutorial = Tutorial::default();
+    let style = TextStyle {
+        font: assets.font.clone(),
+        font_size: 18.,
+        color: TODO_COLOR,
+    };
+    let mut sections: Vec<TextSection> = STEPS
+        .iter()
+        .map(|step| {
+            TextSection::new(
+                format!("[ ] {}\n", step_prompt(*step, *device, versus)),
+                style.clone(),
+            )
+        })
+        .collect();
+    sections.push(TextSection::new(
+        skip_prompt(*device, versus),
+        TextStyle {
+            color: Color::GRAY,
+            ..style.clone()
+        },
+    ));
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(60.0),
+                    left: Val::Px(10.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(TutorialScreen)
+        // on a phone the checklist itself is the skip button
+        .insert(TouchButton {
+            action: Action::SkipTutorial,
+            highlight: false,
+        })
+        .insert(Name::new("Tutorial"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_sections(sections),
+                    ..default()
+                })
+                .insert(TutorialChecklist);
+        });
+}
+
+pub fn track_tutorial(
+    actions: Res<Actions>,
+    mut tutorial: ResMut<Tutorial>,
+    dashes: Query<(), Added<Dashing>>,
+    grabs: Query<(), Added<HeldItem>>,
+    throws: Query<(), Added<Spinning>>,
+) {
+    if actions.pressed(Action::MoveLeft) || actions.pressed(Action::MoveRight) {
+        tutorial.complete(TutorialStep::Move);
+    }
+    if actions.just_pressed(Action::Jump) {
+        tutorial.complete(TutorialStep::Jump);
+    }
+    if !dashes.is_empty() {
+        tutorial.complete(TutorialStep::Dash);
+    }
+    if !grabs.is_empty() {
+        tutorial.complete(TutorialStep::Grab);
+    }
+    if !throws.is_empty() {
+        tutorial.complete(TutorialStep::Fire);
+    }
+}
+
+pub fn update_tutorial_checklist(
+    tutorial: Res<Tutorial>,
+    device: Res<Device>,
+    run_config: Res<RunConfig>,
+    mut checklists: Query<&mut Text, With<TutorialChecklist>>,
+) {
+    // the prompts are rewritten for a gamepad as soon as one is picked up
+    if !tutorial.is_changed() && !device.is_changed() {
+        return;
+    }
+    let versus = run_config.mode == GameMode::Versus;
+    checklists.iter_mut().for_each(|mut text| {
+        STEPS.iter().enumerate().for_each(|(index, step)| {
+            let done = tutorial.done[index];
+            let section = &mut text.sections[index];
+            section.value = format!(
+                "[{}] {}\n",
+                if done { "x" } else { " " },
+                step_prompt(*step, *device, versus)
+            );
+            section.style.color = if done { DONE_COLOR } else { TODO_COLOR };
+        });
+        if let Some(skip) = text.sections.last_mut() {
+            skip.value = skip_prompt(*device, versus);
+        }
+    });
+}
+
+pub fn finish_tutorial(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    tutorial: Res<Tutorial>,
+    mut settings: ResMut<Settings>,
+    screens: Query<Entity, With<TutorialScreen>>,
+) {
+    if screens.is_empty() || !(tutorial.finished() || actions.just_pressed(Action::SkipTutorial)) {
+        return;
+    }
+    settings.tutorial_seen = true;
+    screens.iter().for_each(|screen| {
+        commands.entity(screen).despawn_recursive();
+    });
+}
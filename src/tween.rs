@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+use crate::clock::{GameClock, RealClock};
+
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+pub fn ease_in_out(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[derive(Copy, Clone)]
+pub enum Ease {
+    Linear,
+    OutCubic,
+    InOut,
+}
+
+impl Ease {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Ease::Linear => t,
+            Ease::OutCubic => ease_out_cubic(t),
+            Ease::InOut => ease_in_out(t),
+        }
+    }
+}
+
+// the field a tween drives, from one value to the other
+#[derive(Copy, Clone)]
+pub enum TweenTarget {
+    Scale(Vec3, Vec3),
+    ScaleX(f32, f32),
+    Rotation(f32, f32),
+    SpriteAlpha(f32, f32),
+    TextColor(Color, Color),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Repeat {
+    Once,
+    Loop,
+    PingPong,
+}
+
+// declarative animation, spawn it with what should move and the system below does the rest
+#[derive(Component)]
+pub struct Tween {
+    targets: Vec<TweenTarget>,
+    ease: Ease,
+    timer: Timer,
+    repeat: Repeat,
+    forward: bool,
+    // hud and menus keep animating through pauses and bullet time
+    real_time: bool,
+    despawn_when_done: bool,
+}
+
+impl Tween {
+    pub fn new(seconds: f32, ease: Ease) -> Self {
+        Tween {
+            targets: Vec::new(),
+            ease,
+            timer: Timer::from_seconds(seconds, false),
+            repeat: Repeat::Once,
+            forward: true,
+            real_time: false,
+            despawn_when_done: false,
+        }
+    }
+
+    pub fn with(mut self, target: TweenTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.timer.set_repeating(repeat != Repeat::Once);
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn real_time(mut self) -> Self {
+        self.real_time = true;
+        self
+    }
+
+    pub fn despawn_when_done(mut self) -> Self {
+        self.despawn_when_done = true;
+        self
+    }
+
+    // starts parked at the end, for effects that only play when restarted
+    pub fn idle(mut self) -> Self {
+        let duration = self.timer.duration();
+        self.timer.tick(duration);
+        self
+    }
+
+    pub fn restart(&mut self) {
+        self.timer.reset();
+        self.forward = true;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.repeat == Repeat::Once && self.timer.finished()
+    }
+
+    fn progress(&self) -> f32 {
+        let t = if self.finished() {
+            1.0
+        } else {
+            self.timer.percent()
+        };
+        self.ease.apply(if self.forward { t } else { 1.0 - t })
+    }
+}
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(run_tweens);
+    }
+}
+
+pub fn run_tweens(
+    mut commands: Commands,
+    mut tweens: Query<(
+        Entity,
+        &mut Tween,
+        &mut Transform,
+        Option<&mut Sprite>,
+        Option<&mut Text>,
+    )>,
+    game_clock: Res<GameClock>,
+    real_clock: Res<RealClock>,
+) {
+    tweens
+        .iter_mut()
+        .for_each(|(entity, mut tween, mut trans, mut sprite, mut text)| {
+            let delta = if tween.real_time {
+                real_clock.delta()
+            } else {
+                game_clock.delta()
+            };
+            tween.timer.tick(delta);
+            if tween.repeat == Repeat::PingPong && tween.timer.just_finished() {
+                tween.forward = !tween.forward;
+            }
+            let t = tween.progress();
+            tween.targets.iter().for_each(|target| match *target {
+                TweenTarget::Scale(from, to) => trans.scale = from.lerp(to, t),
+                TweenTarget::ScaleX(from, to) => trans.scale.x = from + (to - from) * t,
+                TweenTarget::Rotation(from, to) => {
+                    trans.rotation = Quat::from_rotation_z(from + (to - from) * t)
+                }
+                TweenTarget::SpriteAlpha(from, to) => {
+                    if let Some(sprite) = sprite.as_mut() {
+                        sprite.color.set_a(from + (to - from) * t);
+                    }
+                }
+                TweenTarget::TextColor(from, to) => {
+                    if let Some(text) = text.as_mut() {
+                        let color = Vec4::from(from).lerp(Vec4::from(to), t);
+                        text.sections.iter_mut().for_each(|section| {
+                            section.style.color = Color::from(color);
+                        });
+                    }
+                }
+            });
+            if tween.despawn_when_done && tween.finished() {
+                commands.entity(entity).despawn_recursive();
+            }
+        });
+}
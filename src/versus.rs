@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use libm::atan2f;
+
+use crate::clock::GameClock;
+use crate::loading::{AtlasRegistry, GameAssets};
+use crate::{
+    aim_held_item, cursor_to_world, spawn_enemy_warning, Behavior, GameState, HeldItem, MyCamera,
+    Player, ResetGame, ALL_BEHAVIORS,
+};
+
+pub fn behavior_cost(behavior: Behavior) -> f32 {
+    match behavior {
+        Behavior::Walker => 2.0,
+        Behavior::Jumper => 3.0,
+        Behavior::Shooter => 5.0,
+        Behavior::BurstShooter => 7.0,
+        Behavior::Charger => 4.0,
+        Behavior::Summoner => 6.0,
+        Behavior::Shielded => 5.0,
+        Behavior::Bomber => 4.0,
+    }
+}
+
+pub fn behavior_name(behavior: Behavior) -> &'static str {
+    match behavior {
+        Behavior::Walker => "Walker",
+        Behavior::Jumper => "Jumper",
+        Behavior::Shooter => "Shooter",
+        Behavior::BurstShooter => "Burst",
+        Behavior::Charger => "Charger",
+        Behavior::Summoner => "Summoner",
+        Behavior::Shielded => "Shielded",
+        Behavior::Bomber => "Bomber",
+    }
+}
+
+pub struct SpawnBudget {
+    points: f32,
+    max: f32,
+    regen: f32,
+}
+
+impl Default for SpawnBudget {
+    fn default() -> Self {
+        SpawnBudget {
+            points: 10.0,
+            max: 20.0,
+            regen: 1.5,
+        }
+    }
+}
+
+pub struct SelectedBehavior(Behavior);
+
+#[derive(Component)]
+pub struct BehaviorButton(Behavior);
+
+#[derive(Component)]
+pub struct BudgetLabel;
+
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnBudget>()
+            .insert_resource(SelectedBehavior(Behavior::Walker))
+            .add_startup_system(create_spawn_bar)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(regen_budget)
+                    .with_system(select_behavior)
+                    .with_system(place_spawns)
+                    .with_system(aim_with_keys),
+            )
+            .add_system(update_budget_label)
+            .add_system(reset_budget);
+    }
+}
+
+pub fn create_spawn_bar(mut commands: Commands, assets: Res<GameAssets>) {
+    let font_handle: Handle<Font> = assets.font.clone();
+    let text_style = TextStyle {
+        font: font_handle,
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Px(50.0)),
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Name::new("Spawn Bar"))
+        .with_children(|bar| {
+            bar.spawn_bundle(TextBundle {
+                text: Text::from_section("", text_style.clone()),
+                style: Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(BudgetLabel);
+            for behavior in ALL_BEHAVIORS {
+                bar.spawn_bundle(ButtonBundle {
+                    color: UiColor(Color::rgb(0.2, 0.2, 0.2)),
+                    style: Style {
+                        margin: UiRect::all(Val::Px(5.0)),
+                        padding: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(BehaviorButton(behavior))
+                .with_children(|button| {
+                    button.spawn_bundle(TextBundle {
+                        text: Text::from_section(
+                            format!("{} ({})", behavior_name(behavior), behavior_cost(behavior)),
+                            text_style.clone(),
+                        ),
+                        ..default()
+                    });
+                });
+            }
+        });
+}
+
+pub fn regen_budget(mut budget: ResMut<SpawnBudget>, clock: Res<GameClock>) {
+    budget.points = (budget.points + budget.regen * clock.delta_seconds()).min(budget.max);
+}
+
+pub fn reset_budget(mut budget: ResMut<SpawnBudget>, mut resets: EventReader<ResetGame>) {
+    if resets.iter().count() > 0 {
+        *budget = SpawnBudget::default();
+    }
+}
+
+pub fn select_behavior(
+    mut selected: ResMut<SelectedBehavior>,
+    mut buttons: Query<(&Interaction, &BehaviorButton, &mut UiColor)>,
+) {
+    buttons
+        .iter_mut()
+        .for_each(|(interaction, button, mut color)| {
+            if *interaction == Interaction::Clicked {
+                selected.0 = button.0;
+            }
+            *color = if selected.0 == button.0 {
+                UiColor(Color::rgb(0.6, 0.2, 0.2))
+            } else {
+                UiColor(Color::rgb(0.2, 0.2, 0.2))
+            };
+        });
+}
+
+pub fn place_spawns(
+    mut commands: Commands,
+    atlases: Res<AtlasRegistry>,
+    mouse: Res<Input<MouseButton>>,
+    wnds: Res<Windows>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MyCamera>>,
+    ui_buttons: Query<&Interaction, With<BehaviorButton>>,
+    selected: Res<SelectedBehavior>,
+    mut budget: ResMut<SpawnBudget>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // clicks on the spawn bar pick a behavior rather than placing one
+    if ui_buttons
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let cost = behavior_cost(selected.0);
+    if budget.points < cost {
+        return;
+    }
+    let (camera, camera_transform) = q_camera.single();
+    if let Some(world_pos) = cursor_to_world(&wnds, camera, camera_transform) {
+        budget.points -= cost;
+        spawn_enemy_warning(
+            &mut commands,
+            &atlases,
+            selected.0,
+            world_pos.x.clamp(-200.0, 200.0),
+        );
+    }
+}
+
+pub fn update_budget_label(
+    budget: Res<SpawnBudget>,
+    mut labels: Query<&mut Text, With<BudgetLabel>>,
+) {
+    if !budget.is_changed() {
+        return;
+    }
+    labels.iter_mut().for_each(|mut label| {
+        label.sections[0].value = format!("Budget: {:.0}/{:.0}", budget.points.floor(), budget.max);
+    });
+}
+
+// player one aims with the arrow keys since player two is using the mouse
+pub fn aim_with_keys(
+    keys: Res<Input<KeyCode>>,
+    mut players: Query<&mut Player>,
+    mut held_items: Query<&mut Transform, With<HeldItem>>,
+) {
+    let mut aim = Vec2::ZERO;
+    if keys.pressed(KeyCode::Left) {
+        aim.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::Right) {
+        aim.x += 1.0;
+    }
+    if keys.pressed(KeyCode::Up) {
+        aim.y += 1.0;
+    }
+    if keys.pressed(KeyCode::Down) {
+        aim.y -= 1.0;
+    }
+    if let Some(mut player) = players.iter_mut().next() {
+        if aim != Vec2::ZERO {
+            player.looking_at = atan2f(aim.y, aim.x);
+        }
+        if let Some(mut held_item) = held_items.iter_mut().next() {
+            let angle = player.looking_at;
+            aim_held_item(&mut held_item, &player, angle, 5.0);
+        }
+    }
+}
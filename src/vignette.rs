@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use libm::sinf;
+
+use crate::clock::RealClock;
+use crate::combat::DamageEvent;
+use crate::loading::GameAssets;
+use crate::settings::Settings;
+use crate::{Player, PLAYER_MAX_HEALTH};
+
+const VIGNETTE_COLOR: Color = Color::rgb(0.8, 0.0, 0.0);
+// the pulse and heartbeat start below this much of the full bar
+const LOW_HEALTH: f32 = 0.30;
+const EDGE_SIZE: f32 = 12.0;
+
+pub struct DamageFeedback {
+    flash: Timer,
+    pulse: f32,
+    heartbeat: Option<Handle<AudioSink>>,
+}
+
+impl Default for DamageFeedback {
+    fn default() -> Self {
+        let mut flash = Timer::from_seconds(0.3, false);
+        // start finished so there's no flash on the first frame
+        flash.tick(flash.duration());
+        DamageFeedback {
+            flash,
+            pulse: 0.0,
+            heartbeat: None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct VignetteEdge;
+
+#[derive(Component)]
+pub struct Desaturate;
+
+pub struct VignettePlugin;
+
+impl Plugin for VignettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageFeedback>()
+            .add_startup_system(create_vignette)
+            .add_system(update_vignette)
+            .add_system(play_heartbeat);
+    }
+}
+
+fn edge(position: UiRect<Val>, size: Size<Val>) -> NodeBundle {
+    NodeBundle {
+        color: UiColor(Color::NONE),
+        style: Style {
+            position_type: PositionType::Absolute,
+            position,
+            size,
+            ..default()
+        },
+        ..default()
+    }
+}
+
+pub fn create_vignette(mut commands: Commands) {
+    let full = Val::Percent(100.0);
+    let thick = Val::Percent(EDGE_SIZE);
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(Color::NONE),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(full, full),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Name::new("Vignette"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(edge(UiRect::default(), Size::new(full, full)))
+                .insert(Desaturate);
+            let edges = [
+                (
+                    UiRect {
+                        top: Val::Px(0.0),
+                        ..default()
+                    },
+                    Size::new(full, thick),
+                ),
+                (
+                    UiRect {
+                        bottom: Val::Px(0.0),
+                        ..default()
+                    },
+                    Size::new(full, thick),
+                ),
+                (
+                    UiRect {
+                        left: Val::Px(0.0),
+                        ..default()
+                    },
+                    Size::new(thick, full),
+                ),
+                (
+                    UiRect {
+                        right: Val::Px(0.0),
+                        ..default()
+                    },
+                    Size::new(thick, full),
+                ),
+            ];
+            for (position, size) in edges {
+                parent
+                    .spawn_bundle(edge(position, size))
+                    .insert(VignetteEdge);
+            }
+        });
+}
+
+fn health_fraction(players: &Query<&Player>) -> Option<f32> {
+    players
+        .iter()
+        .next()
+        .map(|player| player.health as f32 / PLAYER_MAX_HEALTH as f32)
+}
+
+pub fn update_vignette(
+    mut feedback: ResMut<DamageFeedback>,
+    mut damage: EventReader<DamageEvent>,
+    players: Query<&Player>,
+    clock: Res<RealClock>,
+    settings: Res<Settings>,
+    mut edges: Query<&mut UiColor, (With<VignetteEdge>, Without<Desaturate>)>,
+    mut desaturate: Query<&mut UiColor, (With<Desaturate>, Without<VignetteEdge>)>,
+) {
+    if damage.iter().any(|hit| hit.hit_player()) {
+        feedback.flash.reset();
+    }
+    feedback.flash.tick(clock.delta());
+    let low = health_fraction(&players).map_or(false, |health| health <= LOW_HEALTH);
+    if low {
+        feedback.pulse += clock.delta_seconds();
+    } else {
+        feedback.pulse = 0.0;
+    }
+    // the accessibility slider scales the flash, the low health pulse stays as it is. the
+    // white-out on explosions is drawn by the post-process pass
+    let flash = 0.6 * settings.flash * (1.0 - feedback.flash.percent());
+    // roughly 70 bpm, one pulse per heartbeat
+    let pulse = if low {
+        0.25 + 0.15 * sinf(feedback.pulse * std::f32::consts::TAU * 1.2)
+    } else {
+        0.0
+    };
+    let mut edge_color = VIGNETTE_COLOR;
+    edge_color.set_a(flash.max(pulse));
+    edges.iter_mut().for_each(|mut color| {
+        *color = UiColor(edge_color);
+    });
+    desaturate.iter_mut().for_each(|mut color| {
+        *color = UiColor(Color::rgba(0.5, 0.5, 0.5, if low { 0.2 } else { 0.0 }));
+    });
+}
+
+pub fn play_heartbeat(
+    mut feedback: ResMut<DamageFeedback>,
+    players: Query<&Player>,
+    assets: Res<GameAssets>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    settings: Res<Settings>,
+) {
+    let low = health_fraction(&players).map_or(false, |health| health <= LOW_HEALTH);
+    match (&feedback.heartbeat, low) {
+        (None, true) => {
+            let sink = audio.play_with_settings(
+                assets.heartbeat.clone(),
+                PlaybackSettings {
+                    repeat: true,
+                    volume: settings.sfx,
+                    speed: 1.0,
+                },
+            );
+            feedback.heartbeat = Some(audio_sinks.get_handle(sink));
+        }
+        (Some(sink), false) => {
+            if let Some(sink) = audio_sinks.get(sink) {
+                sink.pause();
+            }
+            feedback.heartbeat = None;
+        }
+        _default => {}
+    }
+}
@@ -0,0 +1,351 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::accessibility::Palette;
+use crate::actions::{prompt, Action, Device};
+use crate::depth::RenderLayer;
+use crate::loading::GameAssets;
+use crate::modes::{GameMode, RunConfig};
+use crate::{GameState, HeldItem, Player, Weapon, Weapons};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+fn rarity_color(rarity: Rarity, palette: &Palette) -> Color {
+    match rarity {
+        Rarity::Common => Color::WHITE,
+        Rarity::Uncommon => palette.uncommon,
+        Rarity::Rare => palette.rare,
+        Rarity::Epic => palette.epic,
+    }
+}
+
+fn rarity_name(rarity: Rarity) -> &'static str {
+    match rarity {
+        Rarity::Common => "Common",
+        Rarity::Uncommon => "Uncommon",
+        Rarity::Rare => "Rare",
+        Rarity::Epic => "Epic",
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum FireMode {
+    // one round per click
+    SemiAuto,
+    // keeps firing while the trigger is held
+    FullAuto { rounds_per_second: f32 },
+    // a click fires a short volley
+    Burst { rounds: u8, rounds_per_second: f32 },
+    // no rounds, the beam module fires it for as long as the heat allows
+    Beam,
+    // cooked while held and thrown on release by the grenade module
+    Cooked,
+}
+
+impl FireMode {
+    fn interval(self) -> f32 {
+        match self {
+            FireMode::SemiAuto | FireMode::Beam | FireMode::Cooked => 0.0,
+            FireMode::FullAuto { rounds_per_second } => 1.0 / rounds_per_second,
+            FireMode::Burst {
+                rounds_per_second, ..
+            } => 1.0 / rounds_per_second,
+        }
+    }
+}
+
+pub struct WeaponStats {
+    pub name: &'static str,
+    pub damage: i8,
+    pub pellets: u8,
+    pub fire_style: &'static str,
+    pub rarity: Rarity,
+    pub fire_mode: FireMode,
+    // shots before the gun is empty and gets thrown
+    pub rounds: u8,
+}
+
+pub fn weapon_stats(weapon: Weapons) -> WeaponStats {
+    match weapon {
+        Weapons::Base => WeaponStats {
+            name: "Pistol",
+            damage: 1,
+            pellets: 1,
+            fire_style: "Semi-auto",
+            rarity: Rarity::Common,
+            fire_mode: FireMode::SemiAuto,
+            rounds: 1,
+        },
+        Weapons::Rocket => WeaponStats {
+            name: "Rocket",
+            damage: 3,
+            pellets: 1,
+            fire_style: "Explosive",
+            rarity: Rarity::Epic,
+            fire_mode: FireMode::SemiAuto,
+            rounds: 1,
+        },
+        Weapons::Sniper => WeaponStats {
+            name: "Sniper",
+            damage: 2,
+            pellets: 1,
+            fire_style: "Piercing",
+            rarity: Rarity::Rare,
+            fire_mode: FireMode::SemiAuto,
+            rounds: 1,
+        },
+        Weapons::Shotgun => WeaponStats {
+            name: "Shotgun",
+            damage: 1,
+            pellets: 5,
+            fire_style: "Spread",
+            rarity: Rarity::Uncommon,
+            fire_mode: FireMode::SemiAuto,
+            rounds: 1,
+        },
+        Weapons::Rock => WeaponStats {
+            name: "Rock",
+            damage: 1,
+            pellets: 1,
+            fire_style: "Thrown",
+            rarity: Rarity::Common,
+            fire_mode: FireMode::SemiAuto,
+            rounds: 1,
+        },
+        Weapons::Airplane => WeaponStats {
+            name: "Airplane",
+            damage: 2,
+            pellets: 1,
+            fire_style: "Glider",
+            rarity: Rarity::Rare,
+            fire_mode: FireMode::SemiAuto,
+            rounds: 1,
+        },
+        Weapons::EnemyGun => WeaponStats {
+            name: "Shooter's Gun",
+            damage: 1,
+            pellets: 1,
+            fire_style: "3-round burst",
+            rarity: Rarity::Uncommon,
+            fire_mode: FireMode::Burst {
+                rounds: 3,
+                rounds_per_second: 12.0,
+            },
+            rounds: 6,
+        },
+        Weapons::HomingRocket => WeaponStats {
+            name: "Homing Rocket",
+            damage: 3,
+            pellets: 1,
+            fire_style: "Locks on",
+            rarity: Rarity::Epic,
+            fire_mode: FireMode::SemiAuto,
+            rounds: 1,
+        },
+        // damage is per second of contact
+        Weapons::Laser => WeaponStats {
+            name: "Laser",
+            damage: 4,
+            pellets: 1,
+            fire_style: "Beam",
+            rarity: Rarity::Rare,
+            fire_mode: FireMode::Beam,
+            rounds: 1,
+        },
+        Weapons::Grenade => WeaponStats {
+            name: "Grenade",
+            damage: 3,
+            pellets: 1,
+            fire_style: "Hold to cook",
+            rarity: Rarity::Uncommon,
+            fire_mode: FireMode::Cooked,
+            rounds: 1,
+        },
+        Weapons::Smg => WeaponStats {
+            name: "Shooter's SMG",
+            damage: 1,
+            pellets: 1,
+            fire_style: "Full-auto",
+            rarity: Rarity::Uncommon,
+            fire_mode: FireMode::FullAuto {
+                rounds_per_second: 10.0,
+            },
+            rounds: 15,
+        },
+    }
+}
+
+// what's left in the held gun and when it can fire next
+#[derive(Component)]
+pub struct Magazine {
+    mode: FireMode,
+    rounds_left: u8,
+    burst_left: u8,
+    cooldown: Timer,
+    // practice mode's infinite ammo, shots stop counting down
+    infinite: bool,
+}
+
+impl Magazine {
+    pub fn new(weapon: Weapons) -> Self {
+        let stats = weapon_stats(weapon);
+        let mut cooldown = Timer::from_seconds(stats.fire_mode.interval(), false);
+        cooldown.tick(cooldown.duration());
+        Magazine {
+            mode: stats.fire_mode,
+            rounds_left: stats.rounds,
+            burst_left: 0,
+            cooldown,
+            infinite: false,
+        }
+    }
+
+    pub fn infinite(&self) -> bool {
+        self.infinite
+    }
+
+    pub fn set_infinite(&mut self, infinite: bool) {
+        self.infinite = infinite;
+    }
+
+    // true when a round should come out this frame
+    pub fn pull(&mut self, pressed: bool, held: bool, delta: Duration) -> bool {
+        self.cooldown.tick(delta);
+        if self.rounds_left == 0 {
+            return false;
+        }
+        let wants = match self.mode {
+            FireMode::SemiAuto => pressed,
+            FireMode::FullAuto { .. } => held,
+            FireMode::Burst { rounds, .. } => {
+                if pressed && self.burst_left == 0 {
+                    self.burst_left = rounds;
+                }
+                self.burst_left > 0
+            }
+            FireMode::Beam | FireMode::Cooked => false,
+        };
+        if !wants || !self.cooldown.finished() {
+            return false;
+        }
+        self.cooldown.reset();
+        if !self.infinite {
+            self.rounds_left -= 1;
+        }
+        self.burst_left = self.burst_left.saturating_sub(1);
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rounds_left == 0
+    }
+}
+
+fn difference(new: i32, old: Option<i32>) -> String {
+    match old {
+        Some(old) if new > old => format!(" (+{})", new - old),
+        Some(old) if new < old => format!(" ({})", new - old),
+        _default => String::new(),
+    }
+}
+
+#[derive(Component)]
+pub struct WeaponTooltip;
+
+pub struct WeaponTooltipPlugin;
+
+impl Plugin for WeaponTooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(create_weapon_tooltip)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(update_weapon_tooltip),
+            );
+    }
+}
+
+pub fn create_weapon_tooltip(mut commands: Commands, assets: Res<GameAssets>) {
+    let style = TextStyle {
+        font: assets.font.clone(),
+        font_size: 32.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_sections(vec![
+                TextSection::new("", style.clone()),
+                TextSection::new("", style),
+            ])
+            .with_alignment(TextAlignment::BOTTOM_CENTER),
+            // large font scaled down so it stays crisp at world scale
+            transform: Transform::from_scale(Vec3::splat(0.25)),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(WeaponTooltip)
+        .insert(Name::new("Weapon Tooltip"));
+}
+
+pub fn update_weapon_tooltip(
+    players: Query<(Entity, &Player)>,
+    pickups: Query<(&Weapon, &Collisions, &GlobalTransform), Without<HeldItem>>,
+    held_items: Query<&Weapon, With<HeldItem>>,
+    mut tooltips: Query<(&mut Text, &mut Transform, &mut Visibility), With<WeaponTooltip>>,
+    palette: Res<Palette>,
+    device: Res<Device>,
+    run_config: Res<RunConfig>,
+) {
+    let versus = run_config.mode == GameMode::Versus;
+    let nearby = players.iter().next().and_then(|(player, _)| {
+        pickups
+            .iter()
+            .find(|(_, collisions, _)| collisions.entities().any(|other| other == player))
+    });
+    tooltips
+        .iter_mut()
+        .for_each(|(mut text, mut trans, mut visibility)| {
+            let (weapon, _, pickup_trans) = match nearby {
+                Some(nearby) => nearby,
+                None => {
+                    visibility.is_visible = false;
+                    return;
+                }
+            };
+            let stats = weapon_stats(weapon.asset);
+            let held = held_items
+                .iter()
+                .next()
+                .map(|held| weapon_stats(held.asset));
+            visibility.is_visible = true;
+            trans.translation = RenderLayer::Overlay
+                .at(pickup_trans.translation().truncate() + Vec2::new(0.0, 12.0));
+            text.sections[0].value = format!("{} [{}]\n", stats.name, rarity_name(stats.rarity));
+            text.sections[0].style.color = rarity_color(stats.rarity, &palette);
+            text.sections[1].value = format!(
+                "DMG {}{}\nPellets {}{}\n{}\n{}",
+                stats.damage,
+                difference(
+                    stats.damage as i32,
+                    held.as_ref().map(|held| held.damage as i32)
+                ),
+                stats.pellets,
+                difference(
+                    stats.pellets as i32,
+                    held.as_ref().map(|held| held.pellets as i32)
+                ),
+                stats.fire_style,
+                if held.is_some() {
+                    format!("{} to swap", prompt(Action::Grab, *device, versus))
+                } else {
+                    "Walk over to grab".to_string()
+                },
+            );
+        });
+}
@@ -0,0 +1,16 @@
+use std::process::Command;
+
+// the game is a binary with no library to link against, so the harness lives in src/headless.rs
+// and this runs it through the built executable
+#[test]
+fn gameplay_invariants_hold_headless() {
+    let output = Command::new(env!("CARGO_BIN_EXE_game_jam_score_space_2022"))
+        .arg("--headless-check")
+        .output()
+        .expect("could not start the game binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}